@@ -0,0 +1,122 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! This file contains an example measuring the peak heap memory of the prover
+//! with and without a memory budget.
+//!
+//! Run with
+//! `cargo run --release --example prover-memory --features test-srs,std -- 16`
+//! to prove a circuit of `2^16` gates. The proving key and the circuit are
+//! allocated before the measurement starts, the peaks reported are the
+//! memory allocated on top of them.
+
+use ark_bn254::{Bn254, Fr};
+use ark_std::{
+    alloc::{GlobalAlloc, Layout, System},
+    rand::SeedableRng,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use jf_plonk::{
+    errors::PlonkError,
+    proof_system::{structs::MemoryBudget, PlonkKzgSnark, UniversalSNARK},
+    transcript::StandardTranscript,
+};
+use jf_relation::{Arithmetization, Circuit, PlonkCircuit};
+use rand_chacha::ChaCha20Rng;
+
+// A global allocator keeping track of the current and peak heap usage.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                let grown = new_size - layout.size();
+                let allocated = ALLOCATED.fetch_add(grown, Ordering::Relaxed) + grown;
+                PEAK.fetch_max(allocated, Ordering::Relaxed);
+            } else {
+                ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// Run `f` and return the peak heap usage during the call on top of the usage
+// before the call.
+fn peak_memory<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let res = f();
+    (res, PEAK.load(Ordering::Relaxed) - before)
+}
+
+fn main() -> Result<(), PlonkError> {
+    let log_num_gates: usize = std::env::args()
+        .nth(1)
+        .map(|arg| {
+            arg.parse()
+                .expect("the argument is the log of the number of gates")
+        })
+        .unwrap_or(16);
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+    // a chain of additions filling the evaluation domain
+    let mut circuit = PlonkCircuit::<Fr>::new_turbo_plonk();
+    let mut acc = circuit.create_public_variable(Fr::from(1u64))?;
+    while circuit.num_gates() + 1 < 1 << log_num_gates {
+        acc = circuit.add(acc, circuit.one())?;
+    }
+    circuit.finalize_for_arithmetization()?;
+    let n = circuit.eval_domain_size()?;
+
+    let srs = PlonkKzgSnark::<Bn254>::universal_setup_for_testing(circuit.srs_size()?, &mut rng)?;
+    let (pk, _) = PlonkKzgSnark::<Bn254>::preprocess(&srs, &circuit)?;
+    drop(srs);
+    let (_, pk_bytes) = peak_memory(|| pk.clone());
+    println!("{} gates, proving key: {} MiB", n, pk_bytes >> 20);
+
+    let (proof, peak) = peak_memory(|| {
+        PlonkKzgSnark::<Bn254>::prove::<_, _, StandardTranscript>(&mut rng, &circuit, &pk, None)
+    });
+    proof?;
+    println!("without budget: {} MiB", peak >> 20);
+    for memory_budget in [peak / 2, peak / 4, 0] {
+        let (proof, peak) = peak_memory(|| {
+            PlonkKzgSnark::<Bn254>::prove_with_memory_budget::<_, _, StandardTranscript>(
+                &mut rng,
+                &circuit,
+                &pk,
+                None,
+                MemoryBudget::new(memory_budget),
+            )
+        });
+        proof?;
+        println!("budget of {} MiB: {} MiB", memory_budget >> 20, peak >> 20);
+    }
+    Ok(())
+}
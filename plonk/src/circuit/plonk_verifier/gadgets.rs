@@ -193,6 +193,7 @@ pub(super) fn compute_challenges_vars<E, F, P>(
     public_inputs: &[&[FpElemVar<F>]],
     batch_proof: &BatchProofVar<F>,
     extra_transcript_init_msg: &Option<Vec<u8>>,
+    absorb_vk_digests: bool,
     non_native_field_info: NonNativeFieldInfo<F>,
 ) -> Result<ChallengesFpElemVar<F>, CircuitError>
 where
@@ -218,7 +219,12 @@ where
         transcript_var.append_message_vars(EXTRA_TRANSCRIPT_MSG_LABEL, &msg_vars)?;
     }
     for (&vk, &pi) in verify_keys.iter().zip(public_inputs.iter()) {
-        transcript_var.append_vk_and_pub_input_vars::<E>(circuit, vk, pi)?;
+        if absorb_vk_digests {
+            let vk_digest_var = vk.digest(circuit)?;
+            transcript_var.append_vk_digest_and_pub_input_vars(circuit, vk_digest_var, pi)?;
+        } else {
+            transcript_var.append_vk_and_pub_input_vars::<E>(circuit, vk, pi)?;
+        }
     }
     for wires_poly_comms in batch_proof.wires_poly_comms_vec.iter() {
         transcript_var.append_commitments_vars(b"witness_poly_comms", wires_poly_comms)?;
@@ -269,6 +275,7 @@ pub(super) fn prepare_pcs_info_var<E, F, P>(
     public_inputs: &[&[FpElemVar<F>]],
     batch_proof: &BatchProofVar<F>,
     extra_transcript_init_msg: &Option<Vec<u8>>,
+    absorb_vk_digests: bool,
 
     domain: Radix2EvaluationDomain<E::ScalarField>,
     non_native_field_info: NonNativeFieldInfo<F>,
@@ -314,6 +321,7 @@ where
         public_inputs,
         batch_proof,
         extra_transcript_init_msg,
+        absorb_vk_digests,
         non_native_field_info,
     )?;
 
@@ -584,6 +592,7 @@ mod test {
             &[shared_public_input_fp_elem_var_ref; 18],
             &batch_proof_vars,
             &None,
+            false,
             non_native_field_info,
         )?;
 
@@ -5,7 +5,10 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 //! Circuits for Plonk verifiers.
-use crate::proof_system::{structs::VerifyingKey, verifier::Verifier};
+use crate::proof_system::{
+    structs::{MergeKind, VerifyingKey},
+    verifier::Verifier,
+};
 use ark_ec::{
     pairing::Pairing,
     short_weierstrass::{Affine, SWCurveConfig as SWParam},
@@ -13,7 +16,7 @@ use ark_ec::{
 };
 use ark_ff::{BigInteger, PrimeField};
 use ark_std::{format, string::ToString, vec, vec::Vec};
-use jf_primitives::rescue::RescueParameter;
+use jf_primitives::{circuit::rescue::RescueNativeGadget, rescue::RescueParameter};
 use jf_relation::{
     errors::{CircuitError, CircuitError::ParameterError},
     gadgets::{
@@ -37,8 +40,11 @@ pub struct VerifyingKeyVar<E: Pairing> {
     pub(crate) sigma_comms: Vec<PointVariable>,
     /// The variables for the selector polynomial commitments.
     pub(crate) selector_comms: Vec<PointVariable>,
-    /// A flag indicating whether the key is a merged key.
-    is_merged: bool,
+    /// The variables for the Plookup polynomial commitments, empty if the key
+    /// does not support lookup.
+    pub(crate) plookup_comms: Vec<PointVariable>,
+    /// How the key is merged from other keys, if at all.
+    merge_kind: MergeKind,
 
     /// The size of the evaluation domain. Should be a power of two.
     domain_size: usize,
@@ -72,10 +78,17 @@ impl<E: Pairing> VerifyingKeyVar<E> {
             .iter()
             .map(|comm| circuit.create_point_variable(TEPoint::from(comm.0)))
             .collect::<Result<Vec<_>, CircuitError>>()?;
+        let plookup_comms = verify_key
+            .plookup_vk
+            .iter()
+            .flat_map(|vk| vk.comms())
+            .map(|comm| circuit.create_point_variable(TEPoint::from(comm.0)))
+            .collect::<Result<Vec<_>, CircuitError>>()?;
         Ok(Self {
             sigma_comms,
             selector_comms,
-            is_merged: verify_key.is_merged,
+            plookup_comms,
+            merge_kind: verify_key.merge_kind,
             domain_size: verify_key.domain_size,
             num_inputs: verify_key.num_inputs,
             k: verify_key.k.clone(),
@@ -93,9 +106,47 @@ impl<E: Pairing> VerifyingKeyVar<E> {
             res.push(selector_comm.get_x());
             res.push(selector_comm.get_y());
         }
+        for plookup_comm in self.plookup_comms.iter() {
+            res.push(plookup_comm.get_x());
+            res.push(plookup_comm.get_y());
+        }
         res
     }
 
+    /// Compute the variable for the Rescue digest of the verifying key, i.e.,
+    /// the in-circuit counterpart of [`VerifyingKey::digest`]. The domain size,
+    /// the number of inputs, the merge kind and the wire subset separators are
+    /// constants of the circuit.
+    pub fn digest<F>(&self, circuit: &mut PlonkCircuit<F>) -> Result<Variable, CircuitError>
+    where
+        F: RescueParameter,
+    {
+        let mut constants = vec![
+            F::from(self.domain_size as u64),
+            F::from(self.num_inputs as u64),
+            F::from(self.merge_kind.to_flag() as u64),
+        ];
+        constants.extend(
+            self.k
+                .iter()
+                .map(|k| F::from_le_bytes_mod_order(&k.into_bigint().to_bytes_le())),
+        );
+        let mut input_vars = constants
+            .into_iter()
+            .map(|c| circuit.create_constant_variable(c))
+            .collect::<Result<Vec<_>, CircuitError>>()?;
+        for comm in self
+            .selector_comms
+            .iter()
+            .chain(self.sigma_comms.iter())
+            .chain(self.plookup_comms.iter())
+        {
+            input_vars.push(comm.get_x());
+            input_vars.push(comm.get_y());
+        }
+        Ok(RescueNativeGadget::<F>::rescue_sponge_with_padding(circuit, &input_vars, 1)?[0])
+    }
+
     /// Merge with another Plonk verifying key variable.
     pub(crate) fn merge<F, P>(
         &self,
@@ -106,7 +157,7 @@ impl<E: Pairing> VerifyingKeyVar<E> {
         F: PrimeField,
         P: TEParam<BaseField = F>,
     {
-        if self.is_merged || other.is_merged {
+        if self.merge_kind != MergeKind::Unmerged || other.merge_kind != MergeKind::Unmerged {
             return Err(ParameterError(
                 "cannot merge a merged key again".to_string(),
             ));
@@ -121,6 +172,11 @@ impl<E: Pairing> VerifyingKeyVar<E> {
                 "cannot merge a verifying key with different public input length".to_string(),
             ));
         }
+        if !self.plookup_comms.is_empty() || !other.plookup_comms.is_empty() {
+            return Err(ParameterError(
+                "cannot merge UltraPlonk verifying keys".to_string(),
+            ));
+        }
         let sigma_comms = self
             .sigma_comms
             .iter()
@@ -136,13 +192,68 @@ impl<E: Pairing> VerifyingKeyVar<E> {
         Ok(Self {
             sigma_comms,
             selector_comms,
-            is_merged: true,
+            plookup_comms: vec![],
+            merge_kind: MergeKind::Pair,
             domain_size: self.domain_size,
             num_inputs: self.num_inputs + other.num_inputs,
             k: self.k.clone(),
         })
     }
 
+    /// Merge the verifying key variables of a group of circuits finalized for
+    /// merging, i.e., the in-circuit counterpart of
+    /// [`crate::proof_system::batch_arg::BatchArgument::aggregate_verify_key_groups`]
+    /// for a single group.
+    pub(crate) fn merge_all<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        vks: &[Self],
+    ) -> Result<Self, CircuitError>
+    where
+        F: PrimeField,
+        P: TEParam<BaseField = F>,
+    {
+        let first = vks
+            .first()
+            .ok_or_else(|| ParameterError("no verifying key to merge".to_string()))?;
+        for vk in vks.iter() {
+            if vk.merge_kind != MergeKind::Unmerged {
+                return Err(ParameterError(
+                    "cannot merge a merged key again".to_string(),
+                ));
+            }
+            if vk.domain_size != first.domain_size {
+                return Err(ParameterError(
+                    "cannot merge a verifying key with different domain size".to_string(),
+                ));
+            }
+            if !vk.plookup_comms.is_empty() {
+                return Err(ParameterError(
+                    "cannot merge UltraPlonk verifying keys".to_string(),
+                ));
+            }
+        }
+        let mut sigma_comms = first.sigma_comms.clone();
+        let mut selector_comms = first.selector_comms.clone();
+        for vk in vks.iter().skip(1) {
+            for (sum, comm) in sigma_comms
+                .iter_mut()
+                .chain(selector_comms.iter_mut())
+                .zip(vk.sigma_comms.iter().chain(vk.selector_comms.iter()))
+            {
+                *sum = circuit.ecc_add::<P>(sum, comm)?;
+            }
+        }
+        Ok(Self {
+            sigma_comms,
+            selector_comms,
+            plookup_comms: vec![],
+            merge_kind: MergeKind::Group,
+            domain_size: first.domain_size,
+            num_inputs: vks.iter().map(|vk| vk.num_inputs).sum(),
+            k: first.k.clone(),
+        })
+    }
+
     /// Circuit for partially verifying a batched proof without performing the
     /// pairing. Return the variables for the two group elements used in the
     /// final pairing.
@@ -156,6 +267,106 @@ impl<E: Pairing> VerifyingKeyVar<E> {
         batch_proof: &BatchProofVar<F>,
         blinding_factor: Variable,
     ) -> Result<(PointVariable, PointVariable), CircuitError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + TEParam,
+    {
+        // we need to copy the public input once after merging the circuit
+        let shared_public_input_vars =
+            [shared_public_input_vars, shared_public_input_vars].concat();
+        let public_inputs = vec![&shared_public_input_vars[..]; merged_vks.len()];
+        Self::partial_verify_circuit_internal(
+            circuit,
+            beta_g,
+            generator_g,
+            merged_vks,
+            &public_inputs,
+            batch_proof,
+            blinding_factor,
+            false,
+        )
+    }
+
+    /// Circuit for partially verifying a batched proof without performing the
+    /// pairing, where `public_input_vars[i]` is the public input of the `i`-th
+    /// merged instance, i.e., the in-circuit counterpart of
+    /// [`crate::proof_system::batch_arg::BatchArgument::partial_verify_merged`].
+    /// The verifying keys may be merged from a pair of keys by
+    /// [`BatchableCircuit::aggregate_verify_keys`] or from a group of keys by
+    /// [`BatchableCircuit::aggregate_verify_key_groups`]. Return the variables
+    /// for the two group elements used in the final pairing.
+    pub fn partial_verify_merged_circuit<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &TEPoint<F>,
+        generator_g: &TEPoint<F>,
+        merged_vks: &[Self],
+        public_input_vars: &[&[FpElemVar<F>]],
+        batch_proof: &BatchProofVar<F>,
+        blinding_factor: Variable,
+    ) -> Result<(PointVariable, PointVariable), CircuitError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + TEParam,
+    {
+        Self::partial_verify_circuit_internal(
+            circuit,
+            beta_g,
+            generator_g,
+            merged_vks,
+            public_input_vars,
+            batch_proof,
+            blinding_factor,
+            false,
+        )
+    }
+
+    /// Same as [`Self::partial_verify_circuit`], except that the batched proof
+    /// was generated with a
+    /// [`VkDigestTranscript`](crate::transcript::VkDigestTranscript) over
+    /// the Rescue transcript, i.e., the transcript absorbs the digests of the
+    /// merged verifying keys, which are computed in the circuit.
+    pub fn partial_verify_circuit_with_vk_digests<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &TEPoint<F>,
+        generator_g: &TEPoint<F>,
+        merged_vks: &[Self],
+        shared_public_input_vars: &[FpElemVar<F>],
+        batch_proof: &BatchProofVar<F>,
+        blinding_factor: Variable,
+    ) -> Result<(PointVariable, PointVariable), CircuitError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + TEParam,
+    {
+        let shared_public_input_vars =
+            [shared_public_input_vars, shared_public_input_vars].concat();
+        let public_inputs = vec![&shared_public_input_vars[..]; merged_vks.len()];
+        Self::partial_verify_circuit_internal(
+            circuit,
+            beta_g,
+            generator_g,
+            merged_vks,
+            &public_inputs,
+            batch_proof,
+            blinding_factor,
+            true,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn partial_verify_circuit_internal<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &TEPoint<F>,
+        generator_g: &TEPoint<F>,
+        merged_vks: &[Self],
+        public_inputs: &[&[FpElemVar<F>]],
+        batch_proof: &BatchProofVar<F>,
+        blinding_factor: Variable,
+        absorb_vk_digests: bool,
+    ) -> Result<(PointVariable, PointVariable), CircuitError>
     where
         E: Pairing<BaseField = F, G1Affine = Affine<P>>,
         F: RescueParameter + SWToTEConParam,
@@ -204,19 +415,16 @@ impl<E: Pairing> VerifyingKeyVar<E> {
         let verifier = Verifier::<E>::new(domain_size)?;
         let domain = verifier.domain;
 
-        // we need to copy the public input once after merging the circuit
-        let shared_public_input_vars =
-            [shared_public_input_vars, shared_public_input_vars].concat();
-        let public_inputs = vec![&shared_public_input_vars[..]; merged_vks.len()];
         let merged_vks_ref: Vec<&VerifyingKeyVar<E>> = merged_vks.iter().collect();
 
         // generate the PCS info
         let pcs_info_var = prepare_pcs_info_var(
             circuit,
             &merged_vks_ref,
-            &public_inputs,
+            public_inputs,
             batch_proof,
             &None,
+            absorb_vk_digests,
             domain,
             non_native_field_info,
         )?;
@@ -287,6 +495,16 @@ pub trait BatchableCircuit<F> {
     where
         E: Pairing,
         P: TEParam<BaseField = F>;
+
+    /// Aggregate the verification keys of each group of instances set up by
+    /// [`crate::proof_system::batch_arg::BatchArgument::setup_instance_group`].
+    fn aggregate_verify_key_groups<E, P>(
+        &mut self,
+        vk_group_vars: &[&[VerifyingKeyVar<E>]],
+    ) -> Result<Vec<VerifyingKeyVar<E>>, CircuitError>
+    where
+        E: Pairing,
+        P: TEParam<BaseField = F>;
 }
 
 /// Instances batching scheme related gates
@@ -316,6 +534,20 @@ where
             .map(|(vk_b, vk_d)| vk_b.merge::<F, P>(self, vk_d))
             .collect::<Result<Vec<_>, CircuitError>>()
     }
+
+    fn aggregate_verify_key_groups<E, P>(
+        &mut self,
+        vk_group_vars: &[&[VerifyingKeyVar<E>]],
+    ) -> Result<Vec<VerifyingKeyVar<E>>, CircuitError>
+    where
+        E: Pairing,
+        P: TEParam<BaseField = F>,
+    {
+        vk_group_vars
+            .iter()
+            .map(|vks| VerifyingKeyVar::merge_all::<F, P>(self, vks))
+            .collect::<Result<Vec<_>, CircuitError>>()
+    }
 }
 
 #[cfg(test)]
@@ -323,11 +555,13 @@ mod test {
     use super::*;
     use crate::{
         proof_system::{
-            batch_arg::{new_mergeable_circuit_for_test, BatchArgument},
+            batch_arg::{
+                new_group_circuit_for_test, new_mergeable_circuit_for_test, BatchArgument,
+            },
             structs::BatchProof,
             PlonkKzgSnark, UniversalSNARK,
         },
-        transcript::{PlonkTranscript, RescueTranscript},
+        transcript::{PlonkTranscript, RescueTranscript, VkDigestTranscript},
     };
     use ark_bls12_377::{g1::Config as Param377, Bls12_377, Fq as Fq377};
     use ark_ec::{short_weierstrass::SWCurveConfig, twisted_edwards::TECurveConfig, CurveGroup};
@@ -425,7 +659,7 @@ mod test {
 
         // merged keys can't be merged again.
         let mut bad_vk_vars = vk_type_a_vars.clone();
-        bad_vk_vars[0].is_merged = true;
+        bad_vk_vars[0].merge_kind = MergeKind::Pair;
         assert!(circuit
             .aggregate_verify_keys::<E, Q>(&bad_vk_vars, &vk_type_b_vars)
             .is_err());
@@ -450,7 +684,7 @@ mod test {
             let expected_comm = TEPoint::from(comm.0);
             assert_eq!(circuit.point_witness(comm_var).unwrap(), expected_comm);
         }
-        assert_eq!(vk_var.is_merged, vk.is_merged);
+        assert_eq!(vk_var.merge_kind, vk.merge_kind);
     }
 
     #[test]
@@ -544,6 +778,7 @@ mod test {
                     beta_g_ref,
                     &open_key_ref.g,
                     &blinding_factor,
+                    false,
                 )?;
 
                 assert!(
@@ -604,6 +839,7 @@ mod test {
                     beta_g_ref,
                     &open_key_ref.g,
                     &blinding_factor,
+                    false,
                 )
                 .is_err());
 
@@ -618,6 +854,7 @@ mod test {
                     beta_g_ref,
                     &open_key_ref.g,
                     &blinding_factor,
+                    false,
                 )
                 .is_err());
             }
@@ -637,6 +874,7 @@ mod test {
                     beta_g_ref,
                     &open_key_ref.g,
                     &blinding_factor,
+                    false,
                 )?;
 
                 assert!(
@@ -683,6 +921,248 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_partial_verification_circuit_with_vk_digests() -> Result<(), CircuitError> {
+        test_partial_verification_circuit_with_vk_digests_helper::<Bls12_377, _, _>()
+    }
+
+    fn test_partial_verification_circuit_with_vk_digests_helper<E, F, P>(
+    ) -> Result<(), CircuitError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F> + TECurveConfig,
+    {
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing((1 << 8) + 2, rng)?;
+
+        let shared_public_input = E::ScalarField::rand(rng);
+        let mut instances_type_a = vec![];
+        let mut instances_type_b = vec![];
+        for i in 32..36 {
+            for (circuit_type, instances) in [
+                (MergeableCircuitType::TypeA, &mut instances_type_a),
+                (MergeableCircuitType::TypeB, &mut instances_type_b),
+            ] {
+                let circuit =
+                    new_mergeable_circuit_for_test::<E>(shared_public_input, i, circuit_type)?;
+                instances.push(BatchArgument::setup_instance(&srs, circuit, circuit_type)?);
+            }
+        }
+        let batch_proof = BatchArgument::batch_prove::<_, VkDigestTranscript<RescueTranscript<F>>>(
+            rng,
+            &instances_type_a,
+            &instances_type_b,
+        )?;
+        let vks_type_a: Vec<&VerifyingKey<E>> = instances_type_a
+            .iter()
+            .map(|pred| pred.verify_key_ref())
+            .collect();
+        let vks_type_b: Vec<&VerifyingKey<E>> = instances_type_b
+            .iter()
+            .map(|pred| pred.verify_key_ref())
+            .collect();
+        let merged_vks = BatchArgument::aggregate_verify_keys(&vks_type_a, &vks_type_b)?;
+
+        let open_key_ref = &vks_type_a[0].open_key;
+        let beta_g_ref = &srs.powers_of_g[1];
+        let blinding_factor = E::ScalarField::rand(rng);
+        let (inner1, inner2) =
+            BatchArgument::partial_verify::<VkDigestTranscript<RescueTranscript<F>>>(
+                beta_g_ref,
+                &open_key_ref.g,
+                &merged_vks,
+                &[shared_public_input],
+                &batch_proof,
+                blinding_factor,
+            )?;
+        assert!(BatchArgument::decide(open_key_ref, inner1, inner2)?);
+
+        let public_inputs = [field_switching(&shared_public_input)];
+        let (circuit, partial_verify_points) = build_circuit::<E, F, P>(
+            &shared_public_input,
+            &merged_vks,
+            &batch_proof,
+            beta_g_ref,
+            &open_key_ref.g,
+            &blinding_factor,
+            true,
+        )?;
+        assert!(circuit.check_circuit_satisfiability(&public_inputs).is_ok());
+        assert_eq!(
+            circuit.point_witness(&partial_verify_points.0)?,
+            TEPoint::<F>::from(inner1.into_affine())
+        );
+        assert_eq!(
+            circuit.point_witness(&partial_verify_points.1)?,
+            TEPoint::<F>::from(inner2.into_affine())
+        );
+
+        // the circuit absorbing the keys themselves derives other challenges
+        let (circuit, partial_verify_points) = build_circuit::<E, F, P>(
+            &shared_public_input,
+            &merged_vks,
+            &batch_proof,
+            beta_g_ref,
+            &open_key_ref.g,
+            &blinding_factor,
+            false,
+        )?;
+        assert!(circuit.check_circuit_satisfiability(&public_inputs).is_ok());
+        assert_ne!(
+            circuit.point_witness(&partial_verify_points.0)?,
+            TEPoint::<F>::from(inner1.into_affine())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_verification_circuit_with_groups() -> Result<(), CircuitError> {
+        test_partial_verification_circuit_with_groups_helper::<Bls12_377, _, _, RescueTranscript<_>>(
+        )
+    }
+
+    fn test_partial_verification_circuit_with_groups_helper<E, F, P, T>() -> Result<(), CircuitError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F> + TECurveConfig,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let n = 128;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(n + 2, rng)?;
+
+        // groups of circuits with different numbers of gates and public inputs
+        let group_shapes: [&[(usize, usize)]; 2] =
+            [&[(1, 30), (2, 50), (0, 4)], &[(2, 3), (1, 20), (1, 12)]];
+        let mut instance_groups = vec![];
+        let mut public_inputs = vec![];
+        for shape in group_shapes.iter() {
+            let mut circuits = vec![];
+            let mut group_public_input = vec![];
+            for &(num_inputs, i) in shape.iter() {
+                let pub_inputs: Vec<E::ScalarField> =
+                    (0..num_inputs).map(|_| E::ScalarField::rand(rng)).collect();
+                circuits.push(new_group_circuit_for_test::<E>(&pub_inputs, i)?);
+                group_public_input.extend(pub_inputs);
+            }
+            instance_groups.push(BatchArgument::setup_instance_group(&srs, circuits, n)?);
+            public_inputs.push(group_public_input);
+        }
+        let groups_ref: Vec<&[_]> = instance_groups.iter().map(|g| &g[..]).collect();
+        let batch_proof = BatchArgument::batch_prove_merged::<_, T>(rng, &groups_ref)?;
+
+        let vk_groups: Vec<Vec<VerifyingKey<E>>> = instance_groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|pred| pred.verify_key_ref().clone())
+                    .collect()
+            })
+            .collect();
+        let vk_groups_ref: Vec<Vec<&VerifyingKey<E>>> = vk_groups
+            .iter()
+            .map(|group| group.iter().collect())
+            .collect();
+        let vk_groups_ref: Vec<&[&VerifyingKey<E>]> =
+            vk_groups_ref.iter().map(|g| &g[..]).collect();
+        let merged_vks = BatchArgument::aggregate_verify_key_groups(&vk_groups_ref)?;
+
+        let open_key_ref = &vk_groups[0][0].open_key;
+        let beta_g_ref = &srs.powers_of_g[1];
+        let blinding_factor = E::ScalarField::rand(rng);
+        let public_inputs_ref: Vec<&[E::ScalarField]> =
+            public_inputs.iter().map(|pi| &pi[..]).collect();
+        let (inner1, inner2) = BatchArgument::partial_verify_merged::<T>(
+            beta_g_ref,
+            &open_key_ref.g,
+            &merged_vks,
+            &public_inputs_ref,
+            &batch_proof,
+            blinding_factor,
+        )?;
+        assert!(BatchArgument::decide(open_key_ref, inner1, inner2)?);
+
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let m = 128;
+        let two_power_m = Some(F::from(2u8).pow([m as u64]));
+        let public_input_vars = public_inputs
+            .iter()
+            .map(|pi| {
+                pi.iter()
+                    .map(|x| {
+                        let var = circuit.create_public_variable(field_switching(x))?;
+                        FpElemVar::new_unchecked(&mut circuit, var, m, two_power_m)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let public_input_vars_ref: Vec<&[FpElemVar<F>]> =
+            public_input_vars.iter().map(|pi| &pi[..]).collect();
+
+        // the keys are merged in the circuit
+        let vk_group_vars = vk_groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|vk| VerifyingKeyVar::new(&mut circuit, vk))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let vk_group_vars_ref: Vec<&[VerifyingKeyVar<E>]> =
+            vk_group_vars.iter().map(|g| &g[..]).collect();
+        let merged_vk_vars = circuit.aggregate_verify_key_groups::<E, P>(&vk_group_vars_ref)?;
+        for (vk_var, vk) in merged_vk_vars.iter().zip(merged_vks.iter()) {
+            check_vk_equality(&circuit, vk_var, vk);
+            assert_eq!(vk_var.num_inputs, vk.num_inputs);
+        }
+        // merged keys cannot be merged again
+        assert!(circuit
+            .aggregate_verify_key_groups::<E, P>(&[&merged_vk_vars[..1]])
+            .is_err());
+        assert!(circuit.aggregate_verify_key_groups::<E, P>(&[&[]]).is_err());
+
+        let batch_proof_vars = batch_proof.create_variables(&mut circuit, m, two_power_m)?;
+        let blinding_factor_var = circuit.create_variable(field_switching(&blinding_factor))?;
+        let partial_verify_points = VerifyingKeyVar::partial_verify_merged_circuit(
+            &mut circuit,
+            &(*beta_g_ref).into(),
+            &open_key_ref.g.into(),
+            &merged_vk_vars,
+            &public_input_vars_ref,
+            &batch_proof_vars,
+            blinding_factor_var,
+        )?;
+        let circuit_public_input: Vec<F> = public_inputs
+            .iter()
+            .flatten()
+            .map(field_switching)
+            .collect();
+        assert!(circuit
+            .check_circuit_satisfiability(&circuit_public_input)
+            .is_ok());
+        assert_eq!(
+            circuit.point_witness(&partial_verify_points.0)?,
+            TEPoint::<F>::from(inner1.into_affine())
+        );
+        assert_eq!(
+            circuit.point_witness(&partial_verify_points.1)?,
+            TEPoint::<F>::from(inner2.into_affine())
+        );
+
+        // wrong public input
+        let mut wrong_public_input = circuit_public_input.clone();
+        wrong_public_input[1] = F::rand(rng);
+        assert!(circuit
+            .check_circuit_satisfiability(&wrong_public_input)
+            .is_err());
+
+        Ok(())
+    }
+
     fn build_circuit<E, F, P>(
         shared_public_input: &E::ScalarField,
         merged_vks: &[VerifyingKey<E>],
@@ -690,6 +1170,7 @@ mod test {
         beta_g_ref: &Affine<P>,
         generator_g: &Affine<P>,
         blinding_factor: &E::ScalarField,
+        absorb_vk_digests: bool,
     ) -> Result<(PlonkCircuit<F>, (PointVariable, PointVariable)), CircuitError>
     where
         E: Pairing<BaseField = F, G1Affine = Affine<P>>,
@@ -721,15 +1202,27 @@ mod test {
         let generator_g = &(*generator_g).into();
         let blinding_factor_var = circuit.create_variable(field_switching(blinding_factor))?;
 
-        let partial_verify_points = VerifyingKeyVar::partial_verify_circuit(
-            &mut circuit,
-            &beta_g,
-            generator_g,
-            &vk_vars,
-            &[shared_public_input_fp_elem_var],
-            &batch_proof_vars,
-            blinding_factor_var,
-        )?;
+        let partial_verify_points = if absorb_vk_digests {
+            VerifyingKeyVar::partial_verify_circuit_with_vk_digests(
+                &mut circuit,
+                &beta_g,
+                generator_g,
+                &vk_vars,
+                &[shared_public_input_fp_elem_var],
+                &batch_proof_vars,
+                blinding_factor_var,
+            )?
+        } else {
+            VerifyingKeyVar::partial_verify_circuit(
+                &mut circuit,
+                &beta_g,
+                generator_g,
+                &vk_vars,
+                &[shared_public_input_fp_elem_var],
+                &batch_proof_vars,
+                blinding_factor_var,
+            )?
+        };
 
         Ok((circuit, partial_verify_points))
     }
@@ -816,6 +1309,7 @@ mod test {
                 beta_g_ref,
                 &open_key_ref.g,
                 &blinding_factor,
+                false,
             )?;
 
             circuit.finalize_for_arithmetization()?;
@@ -8,11 +8,11 @@
 use super::{
     BatchProofVar, ChallengesFpElemVar, NonNativeFieldInfo, ScalarsAndBasesVar, VerifyingKeyVar,
 };
-use crate::errors::PlonkError;
+use crate::{errors::PlonkError, proof_system::structs::MergeKind};
 use ark_ec::pairing::Pairing;
 use ark_ff::PrimeField;
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
-use ark_std::{format, string::ToString, vec, vec::Vec, One};
+use ark_std::{format, vec, vec::Vec, One};
 use jf_relation::{
     constants::GATE_WIDTH,
     errors::{CircuitError, CircuitError::ParameterError},
@@ -178,7 +178,11 @@ where
 /// - v_i: A sequence of values, where v_i = g^i / n
 ///
 /// We then compute L_{i,H}(z) as `L_{i,H}(z) = Z_H(z) * v_i / (z - g^i)`
-/// The public input polynomial evaluation for the merged circuit is:
+/// The public input polynomial evaluation is:
+///
+/// \sum_{i=0..l} L_{i,H}(z) * pub_input[i].
+///
+/// For circuits merged from a type A and a type B circuit, the evaluation is:
 ///
 /// \sum_{i=0..l/2} L_{i,H}(z) * pub_input[i] +
 /// \sum_{i=0..l/2} L_{n-i,H}(z) * pub_input[l/2+i]
@@ -188,20 +192,27 @@ pub(super) fn evaluate_pi_poly_circuit<E, F>(
     pub_inputs_fp_elem_var: &[FpElemVar<F>],
     zeta_fp_elem_var: &FpElemVar<F>,
     vanish_eval_fp_elem_var: &FpElemVar<F>,
-    circuit_is_merged: bool,
+    merge_kind: MergeKind,
     non_native_field_info: NonNativeFieldInfo<F>,
 ) -> Result<FpElemVar<F>, CircuitError>
 where
     E: Pairing<BaseField = F>,
     F: PrimeField,
 {
-    // the circuit is already merged
-    if !circuit_is_merged {
-        return Err(CircuitError::ParameterError(
-            "Circuit should already been merged".to_string(),
+    if domain_size < pub_inputs_fp_elem_var.len() {
+        return Err(ParameterError(format!(
+            "{} public inputs for domain size {}",
+            pub_inputs_fp_elem_var.len(),
+            domain_size
+        )));
+    }
+    if pub_inputs_fp_elem_var.is_empty() {
+        return Ok(FpElemVar::zero(
+            circuit,
+            non_native_field_info.m,
+            non_native_field_info.two_power_m,
         ));
     }
-    let len = pub_inputs_fp_elem_var.len() >> 1;
 
     // constants
     let zeta = field_switching::<_, E::ScalarField>(&zeta_fp_elem_var.witness(circuit)?);
@@ -214,13 +225,22 @@ where
         .map(|x| domain.element(x) / E::ScalarField::from(domain_size as u64))
         .collect();
 
+    // the index of the gate of each public input: [0, l) for unmerged circuits
+    // and circuits merged from a group, [0, l/2) followed by n-1, ..., n-l/2 for
+    // circuits merged from a type A and a type B circuit
+    let gate_indices: Vec<usize> = if merge_kind.splits_public_inputs() {
+        let len = pub_inputs_fp_elem_var.len() >> 1;
+        (0..len)
+            .chain((domain_size - len..domain_size).rev())
+            .collect()
+    } else {
+        (0..pub_inputs_fp_elem_var.len()).collect()
+    };
+
     // compute L_{i,H}(zeta) = Z_H(zeta) * v_i / (zeta - g^i)
     // where Z_H(z) is the vanishing evaluation
-    // compute for both i in [0, len) and [domain_size-len, domain_size)
     let mut lagrange_eval_fp_elem_var: Vec<FpElemVar<F>> = Vec::new();
-    let range = (0..len).chain(domain_size - len..domain_size);
-
-    for i in range {
+    for &i in gate_indices.iter() {
         // compute L_{i,H}(zeta) and related values in the clear
         let v_i_fp_elem = FpElem::<F>::new(
             &field_switching(&v_i[i]),
@@ -272,23 +292,18 @@ where
         lagrange_eval_fp_elem_var.push(eval_i_fp_elem_var);
     }
 
-    // \sum_{i=0..l/2} L_{i,H}(z) * pub_input[i] + \sum_{i=0..l/2} L_{n-i,H}(z)
-    // * pub_input[l/2+i]
-    let mut res_i_fp_elem_var = Vec::new();
-    for i in 0..len {
-        let first_term = circuit.mod_mul(
-            &lagrange_eval_fp_elem_var[i],
-            &pub_inputs_fp_elem_var[i],
-            &non_native_field_info.modulus_fp_elem,
-        )?;
-        let second_term = circuit.mod_mul(
-            &lagrange_eval_fp_elem_var[(len << 1) - i - 1],
-            &pub_inputs_fp_elem_var[len + i],
-            &non_native_field_info.modulus_fp_elem,
-        )?;
-        res_i_fp_elem_var.push(first_term);
-        res_i_fp_elem_var.push(second_term);
-    }
+    // \sum_{i=0..l} L_{gate_indices[i],H}(z) * pub_input[i]
+    let res_i_fp_elem_var = lagrange_eval_fp_elem_var
+        .iter()
+        .zip(pub_inputs_fp_elem_var.iter())
+        .map(|(lagrange_eval, pub_input)| {
+            circuit.mod_mul(
+                lagrange_eval,
+                pub_input,
+                &non_native_field_info.modulus_fp_elem,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let res = circuit.mod_add_vec(&res_i_fp_elem_var, &non_native_field_info.modulus_fp_elem)?;
 
     Ok(res)
@@ -339,26 +354,6 @@ where
     let mut alpha_bases_elem_var = alpha_bases.iter();
     let mut r_0_components = Vec::new();
 
-    // making sure the public inputs are the same for all instances
-    let pi = public_inputs[0];
-    for &pi_i in public_inputs.iter().skip(1) {
-        if pi != pi_i {
-            Err(PlonkError::PublicInputsDoNotMatch)?;
-        }
-    }
-
-    // compute public inputs
-    let pi_fp_elem_var = evaluate_pi_poly_circuit::<E, F>(
-        circuit,
-        domain_size,
-        pi,
-        &zeta_fp_elem_var,
-        &evals[1],
-        true,
-        non_native_field_info,
-    )?;
-    let pi_fr = field_switching::<_, E::ScalarField>(&pi_fp_elem_var.witness(circuit)?);
-
     // L1(x)*alpha_2
     let l1_mul_alpha_2_fp_elem_var = circuit.mod_mul(
         &evals[2],
@@ -381,7 +376,34 @@ where
     // r_0[j] = alpha^{k_j} * r_plonk_j
     // where m is the number of instances, and k_j is the number of alpha power
     // terms added to the first j-1 instances.
-    for poly_evals in batch_proof.poly_evals_vec.iter() {
+    //
+    // the public input polynomial is evaluated once for consecutive instances
+    // sharing their public inputs and layout.
+    let mut last_pi: Option<(usize, FpElemVar<F>)> = None;
+    for (j, poly_evals) in batch_proof.poly_evals_vec.iter().enumerate() {
+        let (vk, pi) = (verify_keys[j], public_inputs[j]);
+        let pi_fp_elem_var = match last_pi {
+            Some((last, pi_fp_elem_var))
+                if public_inputs[last] == pi && verify_keys[last].merge_kind == vk.merge_kind =>
+            {
+                pi_fp_elem_var
+            },
+            _ => {
+                let pi_fp_elem_var = evaluate_pi_poly_circuit::<E, F>(
+                    circuit,
+                    domain_size,
+                    pi,
+                    &zeta_fp_elem_var,
+                    &evals[1],
+                    vk.merge_kind,
+                    non_native_field_info,
+                )?;
+                last_pi = Some((j, pi_fp_elem_var));
+                pi_fp_elem_var
+            },
+        };
+        let pi_fr = field_switching::<_, E::ScalarField>(&pi_fp_elem_var.witness(circuit)?);
+
         // =====================================================
         // r_plonk_j
         //  = PI - L1(x) * alpha^2 - alpha *
@@ -80,6 +80,21 @@ where
         Ok(())
     }
 
+    // append the digest of the verification key and the public input
+    pub(crate) fn append_vk_digest_and_pub_input_vars(
+        &mut self,
+        circuit: &mut PlonkCircuit<F>,
+        vk_digest_var: Variable,
+        pub_input: &[FpElemVar<F>],
+    ) -> Result<(), CircuitError> {
+        self.transcript_var.push(vk_digest_var);
+        for e in pub_input {
+            let pub_var = e.convert_to_var(circuit)?;
+            self.transcript_var.push(pub_var)
+        }
+        Ok(())
+    }
+
     // Append the variable to the transcript.
     // For efficiency purpose, label is not used for rescue FS.
     pub(crate) fn append_variable(
@@ -222,7 +237,7 @@ where
 mod tests {
     use super::*;
     use crate::{
-        proof_system::structs::VerifyingKey,
+        proof_system::structs::{MergeKind, PlookupVerifyingKey, VerifyingKey},
         transcript::{PlonkTranscript, RescueTranscript},
     };
     use ark_bls12_377::Bls12_377;
@@ -319,7 +334,7 @@ mod tests {
             selector_comms: Vec::new(),
             k: Vec::new(),
             open_key: open_key.clone(),
-            is_merged: false,
+            merge_kind: MergeKind::Unmerged,
             plookup_vk: None,
         };
 
@@ -379,7 +394,7 @@ mod tests {
                 selector_comms,
                 k,
                 open_key: open_key.clone(),
-                is_merged: false,
+                merge_kind: MergeKind::Unmerged,
                 plookup_vk: None,
             };
             let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
@@ -412,4 +427,96 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_rescue_transcript_append_vk_digest_circuit() {
+        test_rescue_transcript_append_vk_digest_circuit_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_append_vk_digest_circuit_helper<E, F, P>()
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut rng = test_rng();
+        let label = "testing".as_ref();
+
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+
+        let input: Vec<E::ScalarField> = (0..4).map(|_| E::ScalarField::rand(&mut rng)).collect();
+        let vk = VerifyingKey::<E> {
+            domain_size: 512,
+            num_inputs: input.len(),
+            sigma_comms: (0..5)
+                .map(|_| Commitment(E::G1::rand(&mut rng).into_affine()))
+                .collect(),
+            selector_comms: (0..13)
+                .map(|_| Commitment(E::G1::rand(&mut rng).into_affine()))
+                .collect(),
+            k: (0..5).map(|_| E::ScalarField::rand(&mut rng)).collect(),
+            open_key: UnivariateVerifierParam::default(),
+            merge_kind: MergeKind::Unmerged,
+            plookup_vk: None,
+        };
+        let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
+        let digest_var = vk_var.digest(&mut circuit).unwrap();
+        assert_eq!(circuit.witness(digest_var).unwrap(), vk.digest().unwrap());
+
+        transcript
+            .append_vk_digest_and_pub_input(&vk, &input)
+            .unwrap();
+        let input_fp_elem_vars: Vec<FpElemVar<F>> = input
+            .iter()
+            .map(|x| {
+                let var = circuit.create_public_variable(field_switching(x)).unwrap();
+                FpElemVar::new_unchecked(&mut circuit, var, 128, None).unwrap()
+            })
+            .collect();
+        transcript_var
+            .append_vk_digest_and_pub_input_vars(&mut circuit, digest_var, &input_fp_elem_vars)
+            .unwrap();
+
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap(),
+            field_switching(&challenge)
+        );
+        let public_input = circuit.public_input().unwrap();
+        assert!(circuit.check_circuit_satisfiability(&public_input).is_ok());
+
+        // the digest depends on the constants of the key
+        let mut other_vk = vk.clone();
+        other_vk.domain_size = 1024;
+        assert_ne!(other_vk.digest().unwrap(), vk.digest().unwrap());
+
+        // the in-circuit digest matches the native one for keys supporting
+        // lookup and for merged keys, which have distinct digests
+        let mut rand_comm = || Commitment(E::G1::rand(&mut rng).into_affine());
+        let mut ultra_vk = vk.clone();
+        ultra_vk.plookup_vk = Some(PlookupVerifyingKey {
+            range_table_comm: rand_comm(),
+            key_table_comm: rand_comm(),
+            table_dom_sep_comm: rand_comm(),
+            q_dom_sep_comm: rand_comm(),
+        });
+        let mut digests = vec![];
+        for key in [&vk, &ultra_vk] {
+            for merge_kind in [MergeKind::Unmerged, MergeKind::Pair, MergeKind::Group] {
+                let mut key = key.clone();
+                key.merge_kind = merge_kind;
+                let key_var = VerifyingKeyVar::new(&mut circuit, &key).unwrap();
+                let digest_var = key_var.digest(&mut circuit).unwrap();
+                let digest = key.digest().unwrap();
+                assert_eq!(circuit.witness(digest_var).unwrap(), digest);
+                assert!(!digests.contains(&digest));
+                digests.push(digest);
+            }
+        }
+        assert!(circuit.check_circuit_satisfiability(&public_input).is_ok());
+    }
 }
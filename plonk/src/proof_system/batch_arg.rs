@@ -28,7 +28,9 @@ use ark_std::{
     vec::Vec,
 };
 use jf_primitives::rescue::RescueParameter;
-use jf_relation::{gadgets::ecc::SWToTEConParam, Circuit, MergeableCircuitType, PlonkCircuit};
+use jf_relation::{
+    gadgets::ecc::SWToTEConParam, Circuit, MergeSlot, MergeableCircuitType, PlonkCircuit,
+};
 use jf_utils::multi_pairing;
 
 /// A batching argument.
@@ -41,7 +43,8 @@ pub struct Instance<E: Pairing> {
     // TODO: considering giving instance an ID
     prove_key: ProvingKey<E>, // the verification key can be obtained inside the proving key.
     circuit: PlonkCircuit<E::ScalarField>,
-    _circuit_type: MergeableCircuitType,
+    // `None` for instances finalized for merging with arbitrarily many others.
+    _circuit_type: Option<MergeableCircuitType>,
 }
 
 impl<E: Pairing> Instance<E> {
@@ -73,10 +76,42 @@ where
         Ok(Instance {
             prove_key,
             circuit,
-            _circuit_type: circuit_type,
+            _circuit_type: Some(circuit_type),
         })
     }
 
+    /// Setup the circuits and the proving keys for a group of instances that
+    /// are merged together when batch proving, see
+    /// [`Self::batch_prove_merged()`]. The circuits may have different numbers
+    /// of gates and public inputs; the public input of the merged instance is
+    /// the concatenation of their public inputs, in order. The merged instance
+    /// is padded to a domain of at least `min_domain_size`, which allows
+    /// batching it with other groups whose circuits are of different sizes.
+    pub fn setup_instance_group(
+        srs: &UniversalSrs<E>,
+        circuits: Vec<PlonkCircuit<E::ScalarField>>,
+        min_domain_size: usize,
+    ) -> Result<Vec<Instance<E>>, PlonkError> {
+        let sizes: Vec<(usize, usize)> = circuits
+            .iter()
+            .map(|circuit| (circuit.num_inputs(), circuit.num_gates()))
+            .collect();
+        let slots = MergeSlot::layout(&sizes, min_domain_size)?;
+        circuits
+            .into_iter()
+            .zip(slots.iter())
+            .map(|(mut circuit, slot)| {
+                circuit.finalize_for_merging(slot)?;
+                let (prove_key, _) = PlonkKzgSnark::preprocess(srs, &circuit)?;
+                Ok(Instance {
+                    prove_key,
+                    circuit,
+                    _circuit_type: None,
+                })
+            })
+            .collect()
+    }
+
     /// Prove satisfiability of multiple instances in a batch.
     pub fn batch_prove<R, T>(
         prng: &mut R,
@@ -111,6 +146,32 @@ where
         PlonkKzgSnark::batch_prove::<_, _, T>(prng, &circuits_ref, &pks_ref)
     }
 
+    /// Prove satisfiability of multiple groups of instances in a batch, where
+    /// each group is set up by [`Self::setup_instance_group()`] and merged into
+    /// a single instance.
+    pub fn batch_prove_merged<R, T>(
+        prng: &mut R,
+        instance_groups: &[&[Instance<E>]],
+    ) -> Result<BatchProof<E>, PlonkError>
+    where
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let mut pks = vec![];
+        let mut circuits = vec![];
+        for group in instance_groups.iter() {
+            let group_pks: Vec<&ProvingKey<E>> = group.iter().map(|pred| &pred.prove_key).collect();
+            let group_circuits: Vec<&PlonkCircuit<E::ScalarField>> =
+                group.iter().map(|pred| &pred.circuit).collect();
+            pks.push(ProvingKey::merge_all(&group_pks)?);
+            circuits.push(PlonkCircuit::merge_all(&group_circuits)?);
+        }
+        let pks_ref: Vec<&ProvingKey<E>> = pks.iter().collect();
+        let circuits_ref: Vec<&PlonkCircuit<E::ScalarField>> = circuits.iter().collect();
+
+        PlonkKzgSnark::batch_prove::<_, _, T>(prng, &circuits_ref, &pks_ref)
+    }
+
     /// Partially verify a batched proof without performing the pairing. Return
     /// the two group elements used in the final pairing.
     pub fn partial_verify<T>(
@@ -121,6 +182,33 @@ where
         batch_proof: &BatchProof<E>,
         blinding_factor: E::ScalarField,
     ) -> Result<(E::G1, E::G1), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        // we need to copy the public input once after merging the circuit
+        let shared_public_input = [shared_public_input, shared_public_input].concat();
+        let public_inputs = vec![&shared_public_input[..]; merged_vks.len()];
+        Self::partial_verify_merged::<T>(
+            beta_g,
+            generator_g,
+            merged_vks,
+            &public_inputs,
+            batch_proof,
+            blinding_factor,
+        )
+    }
+
+    /// Partially verify a batched proof without performing the pairing, where
+    /// `public_inputs[i]` is the public input of the `i`-th merged instance.
+    /// Return the two group elements used in the final pairing.
+    pub fn partial_verify_merged<T>(
+        beta_g: &E::G1Affine,
+        generator_g: &E::G1Affine,
+        merged_vks: &[VerifyingKey<E>],
+        public_inputs: &[&[E::ScalarField]],
+        batch_proof: &BatchProof<E>,
+        blinding_factor: E::ScalarField,
+    ) -> Result<(E::G1, E::G1), PlonkError>
     where
         T: PlonkTranscript<F>,
     {
@@ -146,12 +234,9 @@ where
             }
         }
         let verifier = Verifier::new(domain_size)?;
-        // we need to copy the public input once after merging the circuit
-        let shared_public_input = [shared_public_input, shared_public_input].concat();
-        let public_inputs = vec![&shared_public_input[..]; merged_vks.len()];
         let merged_vks_ref: Vec<&VerifyingKey<E>> = merged_vks.iter().collect();
         let pcs_info =
-            verifier.prepare_pcs_info::<T>(&merged_vks_ref, &public_inputs, batch_proof, &None)?;
+            verifier.prepare_pcs_info::<T>(&merged_vks_ref, public_inputs, batch_proof, &None)?;
 
         // inner1 = [open_proof] + u * [shifted_open_proof] + blinding_factor * [1]1
         let mut scalars_and_bases = ScalarsAndBases::<E>::new();
@@ -200,6 +285,17 @@ where
             .collect::<Result<Vec<_>, PlonkError>>()
     }
 
+    /// Aggregate the verification keys of each group of instances set up by
+    /// [`BatchArgument::setup_instance_group()`].
+    pub fn aggregate_verify_key_groups(
+        vk_groups: &[&[&VerifyingKey<E>]],
+    ) -> Result<Vec<VerifyingKey<E>>, PlonkError> {
+        vk_groups
+            .iter()
+            .map(|vks| VerifyingKey::merge_all(vks))
+            .collect::<Result<Vec<_>, PlonkError>>()
+    }
+
     /// Perform the final pairing to verify the proof.
     pub fn decide(open_key: &OpenKey<E>, inner1: E::G1, inner2: E::G1) -> Result<bool, PlonkError> {
         // check e(elem1, [beta]2) ?= e(elem2, [1]2)
@@ -231,6 +327,26 @@ pub(crate) fn new_mergeable_circuit_for_test<E: Pairing>(
     Ok(circuit)
 }
 
+// A circuit with public inputs `pub_inputs` that adds their sum to itself `i`
+// times.
+#[cfg(test)]
+pub(crate) fn new_group_circuit_for_test<E: Pairing>(
+    pub_inputs: &[E::ScalarField],
+    i: usize,
+) -> Result<PlonkCircuit<E::ScalarField>, PlonkError> {
+    let mut circuit = PlonkCircuit::new_turbo_plonk();
+    let mut sum = circuit.zero();
+    for &x in pub_inputs.iter() {
+        let x = circuit.create_public_variable(x)?;
+        sum = circuit.add(sum, x)?;
+    }
+    let mut var = sum;
+    for _ in 0..i {
+        var = circuit.add(var, sum)?;
+    }
+    Ok(circuit)
+}
+
 /// Create `num_instances` type A/B instance verifying keys and
 /// compute the corresponding batch proof. Only used for testing.
 #[allow(clippy::type_complexity)]
@@ -279,8 +395,9 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::transcript::RescueTranscript;
+    use crate::{proof_system::structs::MergeKind, transcript::RescueTranscript};
     use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_std::UniformRand;
     use jf_utils::test_rng;
 
@@ -398,4 +515,145 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_batch_argument_with_groups() -> Result<(), PlonkError> {
+        test_batch_argument_with_groups_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>()
+    }
+
+    fn test_batch_argument_with_groups_helper<E, F, P, T>() -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        // 1. Simulate universal setup
+        let rng = &mut test_rng();
+        let n = 128;
+        let max_degree = n + 2;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)?;
+
+        // 2. Setup groups of circuits with different numbers of gates and public
+        // inputs, each group is merged into a circuit with domain size `n`.
+        let group_shapes: [&[(usize, usize)]; 3] = [
+            &[(1, 30), (2, 50), (3, 4)],
+            &[(4, 90)],
+            &[(0, 7), (2, 3), (1, 20), (1, 12)],
+        ];
+        let mut instance_groups = vec![];
+        let mut public_inputs = vec![];
+        for shape in group_shapes.iter() {
+            let mut circuits = vec![];
+            let mut group_public_input = vec![];
+            for &(num_inputs, i) in shape.iter() {
+                let pub_inputs: Vec<E::ScalarField> =
+                    (0..num_inputs).map(|_| E::ScalarField::rand(rng)).collect();
+                circuits.push(new_group_circuit_for_test::<E>(&pub_inputs, i)?);
+                group_public_input.extend(pub_inputs);
+            }
+            let instances = BatchArgument::setup_instance_group(&srs, circuits, n)?;
+            assert!(instances
+                .iter()
+                .all(|instance| instance.verify_key_ref().domain_size == n));
+            instance_groups.push(instances);
+            public_inputs.push(group_public_input);
+        }
+
+        // 3. Batch Proving
+        let groups_ref: Vec<&[Instance<E>]> = instance_groups.iter().map(|g| &g[..]).collect();
+        let batch_proof = BatchArgument::batch_prove_merged::<_, T>(rng, &groups_ref)?;
+        // error path: empty group
+        assert!(BatchArgument::<E>::batch_prove_merged::<_, T>(rng, &[&[]]).is_err());
+
+        // 4. Aggregate verification keys
+        let vk_groups: Vec<Vec<&VerifyingKey<E>>> = instance_groups
+            .iter()
+            .map(|group| group.iter().map(|pred| pred.verify_key_ref()).collect())
+            .collect();
+        let vk_groups_ref: Vec<&[&VerifyingKey<E>]> = vk_groups.iter().map(|g| &g[..]).collect();
+        let merged_vks = BatchArgument::aggregate_verify_key_groups(&vk_groups_ref)?;
+        assert_eq!(merged_vks[2].num_inputs, 4);
+
+        // 5. Verification
+        let open_key_ref = &vk_groups[0][0].open_key;
+        let beta_g_ref = &srs.powers_of_g[1];
+        let blinding_factor = E::ScalarField::rand(rng);
+        let public_inputs_ref: Vec<&[E::ScalarField]> =
+            public_inputs.iter().map(|pi| &pi[..]).collect();
+        let (inner1, inner2) = BatchArgument::partial_verify_merged::<T>(
+            beta_g_ref,
+            &open_key_ref.g,
+            &merged_vks,
+            &public_inputs_ref,
+            &batch_proof,
+            blinding_factor,
+        )?;
+        assert!(BatchArgument::decide(open_key_ref, inner1, inner2)?);
+        // error paths
+        // wrong public input
+        let mut bad_public_input = public_inputs[0].clone();
+        bad_public_input[1] = E::ScalarField::rand(rng);
+        let mut bad_public_inputs_ref = public_inputs_ref.clone();
+        bad_public_inputs_ref[0] = &bad_public_input;
+        let (inner1, inner2) = BatchArgument::partial_verify_merged::<T>(
+            beta_g_ref,
+            &open_key_ref.g,
+            &merged_vks,
+            &bad_public_inputs_ref,
+            &batch_proof,
+            blinding_factor,
+        )?;
+        assert!(!BatchArgument::decide(open_key_ref, inner1, inner2)?);
+        // a group missing one of its instances
+        let bad_vk_group = &vk_groups[0][1..];
+        let mut bad_merged_vks = merged_vks.clone();
+        bad_merged_vks[0] = BatchArgument::aggregate_verify_key_groups(&[bad_vk_group])?[0].clone();
+        assert!(BatchArgument::partial_verify_merged::<T>(
+            beta_g_ref,
+            &open_key_ref.g,
+            &bad_merged_vks,
+            &public_inputs_ref,
+            &batch_proof,
+            blinding_factor,
+        )
+        .map_or(true, |(inner1, inner2)| {
+            !BatchArgument::decide(open_key_ref, inner1, inner2).unwrap()
+        }));
+        // the number of public inputs is different from the number of instances
+        assert!(BatchArgument::partial_verify_merged::<T>(
+            beta_g_ref,
+            &open_key_ref.g,
+            &merged_vks,
+            &public_inputs_ref[1..],
+            &batch_proof,
+            blinding_factor,
+        )
+        .is_err());
+        // inconsistent domain size between verification keys of a group
+        let mut bad_vk = vk_groups[1][0].clone();
+        bad_vk.domain_size /= 2;
+        assert!(
+            BatchArgument::aggregate_verify_key_groups(&[&[vk_groups[0][0], &bad_vk]]).is_err()
+        );
+        // merged keys cannot be merged again
+        assert!(merged_vks
+            .iter()
+            .all(|vk| vk.merge_kind == MergeKind::Group));
+        assert!(BatchArgument::aggregate_verify_key_groups(&[&[&merged_vks[0]]]).is_err());
+        assert!(
+            BatchArgument::aggregate_verify_key_groups(&[&[vk_groups[0][0], &merged_vks[1]]])
+                .is_err()
+        );
+        assert!(
+            BatchArgument::aggregate_verify_keys(&[&merged_vks[0]], &[vk_groups[0][0]]).is_err()
+        );
+        // including after serialization
+        let mut ser_bytes = Vec::new();
+        merged_vks[0].serialize_compressed(&mut ser_bytes)?;
+        let de = VerifyingKey::<E>::deserialize_compressed(&ser_bytes[..])?;
+        assert_eq!(de, merged_vks[0]);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,432 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A compact, versioned byte encoding of Plonk proofs.
+//!
+//! The canonical serialization of [`Proof`] prefixes every vector with its
+//! length (8 bytes) and every optional field with a tag (1 byte). The compact
+//! encoding omits all of them since they are determined by whether the proof
+//! contains a Plookup argument: a TurboPlonk proof has `w = GATE_WIDTH + 1 =
+//! 5` wire types and an UltraPlonk proof has `w = GATE_WIDTH + 2 = 6`. None of
+//! the evaluations in a proof can be derived by the verifier, hence all are
+//! kept.
+//!
+//! Version 2 also batches the two opening proofs into one, their sum, which
+//! saves a point. It is verified with a pairing more and needs `[x^2]2` in the
+//! verifying key, e.g. from
+//! [`PlonkKzgShplonkSnark::preprocess()`](crate::proof_system::PlonkKzgShplonkSnark),
+//! see [`PlonkKzgSnark::verify_compact()`](crate::proof_system::PlonkKzgSnark::verify_compact).
+//! A [`ShplonkProof`](crate::proof_system::shplonk::ShplonkProof) isn't
+//! encoded since it is larger, with a point less but two scalars more.
+//!
+//! # Byte layout (version 1)
+//!
+//! Points are G1 affine points in the compressed encoding of arkworks (the
+//! little-endian x-coordinate with the sign of y and the point-at-infinity
+//! flag in the most significant bits), `P` bytes each. Scalars are
+//! little-endian field elements, `S` bytes each.
+//!
+//! | bytes         | content                                              |
+//! |---------------|------------------------------------------------------|
+//! | 1             | version, `0x01`                                      |
+//! | 1             | flags, bit 0 set iff there is a Plookup proof, the other bits are reserved and zero |
+//! | `w * P`       | `wires_poly_comms`                                   |
+//! | `P`           | `prod_perm_poly_comm`                                |
+//! | `w * P`       | `split_quot_poly_comms`                              |
+//! | `P`           | `opening_proof`                                      |
+//! | `P`           | `shifted_opening_proof`                              |
+//! | `3 * P`       | Plookup only: the two `h_poly_comms`, `prod_lookup_poly_comm` |
+//! | `w * S`       | `wires_evals`                                        |
+//! | `(w - 1) * S` | `wire_sigma_evals`                                   |
+//! | `S`           | `perm_next_eval`                                     |
+//! | `15 * S`      | Plookup only: the evaluations at `zeta`, in the order `range_table`, `key_table`, `h_1`, `q_lookup`, `table_dom_sep`, `q_dom_sep`, then at `zeta * g`, in the order `prod`, `range_table`, `key_table`, `h_1`, `h_2`, `q_lookup`, `w_3`, `w_4`, `table_dom_sep` |
+//!
+//! # Byte layout (version 2)
+//!
+//! The same as version 1, except for the version `0x02` and a single point
+//! `opening_proof + shifted_opening_proof` instead of `opening_proof` and
+//! `shifted_opening_proof`.
+//!
+//! The layout of a version never changes, a new layout gets a new version.
+
+use crate::{
+    errors::{PlonkError, SnarkError::ParameterError},
+    proof_system::structs::{PlookupEvaluations, PlookupProof, Proof, ProofEvaluations},
+};
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use jf_primitives::pcs::prelude::Commitment;
+use jf_relation::constants::GATE_WIDTH;
+
+/// The version of the compact proof encoding with both opening proofs.
+pub const COMPACT_PROOF_VERSION: u8 = 1;
+
+/// The version of the compact proof encoding with a single batched opening
+/// proof.
+pub const BATCHED_COMPACT_PROOF_VERSION: u8 = 2;
+
+/// Flag for proofs with a Plookup argument.
+const PLOOKUP_FLAG: u8 = 1;
+
+/// The number of Plookup evaluations.
+const NUM_PLOOKUP_EVALS: usize = 15;
+
+impl<E: Pairing> Proof<E> {
+    /// Encode the proof in the compact format (version 1) described in
+    /// [`crate::proof_system::compact`].
+    pub fn to_compact_bytes(&self) -> Result<Vec<u8>, PlonkError> {
+        self.encode_compact(false)
+    }
+
+    /// Encode the proof in the compact format with a single batched opening
+    /// proof (version 2) described in [`crate::proof_system::compact`]. The
+    /// proof can't be decoded back, only verified with
+    /// [`PlonkKzgSnark::verify_compact()`](crate::proof_system::PlonkKzgSnark::verify_compact).
+    pub fn to_batched_compact_bytes(&self) -> Result<Vec<u8>, PlonkError> {
+        self.encode_compact(true)
+    }
+
+    fn encode_compact(&self, batched: bool) -> Result<Vec<u8>, PlonkError> {
+        let num_wire_types = self.wires_poly_comms.len();
+        if self.split_quot_poly_comms.len() != num_wire_types
+            || self.poly_evals.wires_evals.len() != num_wire_types
+            || self.poly_evals.wire_sigma_evals.len() + 1 != num_wire_types
+            || num_wire_types != compact_num_wire_types(self.plookup_proof.is_some())
+        {
+            return Err(ParameterError("malformed proof".to_string()).into());
+        }
+        let flags = if self.plookup_proof.is_some() {
+            PLOOKUP_FLAG
+        } else {
+            0
+        };
+        let version = if batched {
+            BATCHED_COMPACT_PROOF_VERSION
+        } else {
+            COMPACT_PROOF_VERSION
+        };
+        let mut bytes = vec![version, flags];
+
+        let mut comms: Vec<Commitment<E>> = self.wires_poly_comms.clone();
+        comms.push(self.prod_perm_poly_comm);
+        comms.extend(self.split_quot_poly_comms.iter());
+        if batched {
+            comms.push(Commitment(
+                (self.opening_proof.0 + self.shifted_opening_proof.0).into(),
+            ));
+        } else {
+            comms.push(self.opening_proof);
+            comms.push(self.shifted_opening_proof);
+        }
+        let mut evals: Vec<E::ScalarField> = self.poly_evals.clone().into();
+        if let Some(plookup_proof) = self.plookup_proof.as_ref() {
+            if plookup_proof.h_poly_comms.len() != 2 {
+                return Err(ParameterError("malformed Plookup proof".to_string()).into());
+            }
+            comms.extend(plookup_proof.h_poly_comms.iter());
+            comms.push(plookup_proof.prod_lookup_poly_comm);
+            evals.extend(plookup_proof.poly_evals.evals_vec());
+            evals.extend(plookup_proof.poly_evals.next_evals_vec());
+        }
+        for comm in comms {
+            comm.0.serialize_compressed(&mut bytes)?;
+        }
+        for eval in evals {
+            eval.serialize_compressed(&mut bytes)?;
+        }
+        Ok(bytes)
+    }
+
+    /// Decode a proof encoded in the compact format (version 1) described in
+    /// [`crate::proof_system::compact`].
+    pub fn from_compact_bytes(bytes: &[u8]) -> Result<Self, PlonkError> {
+        match Self::decode_compact(bytes)? {
+            (proof, None) => Ok(proof),
+            (_, Some(_)) => Err(ParameterError(
+                "a batched compact proof can't be decoded into a proof".to_string(),
+            )
+            .into()),
+        }
+    }
+
+    /// Decode a proof encoded in any version of the compact format. For
+    /// version 2, also return the batched opening proof, and the opening
+    /// proofs of the returned proof are zero.
+    pub(crate) fn decode_compact(
+        bytes: &[u8],
+    ) -> Result<(Self, Option<Commitment<E>>), PlonkError> {
+        if bytes.len() < 2 {
+            return Err(ParameterError("truncated compact proof".to_string()).into());
+        }
+        let batched = match bytes[0] {
+            COMPACT_PROOF_VERSION => false,
+            BATCHED_COMPACT_PROOF_VERSION => true,
+            version => {
+                return Err(ParameterError(format!(
+                    "unsupported compact proof version {}",
+                    version
+                ))
+                .into())
+            },
+        };
+        if bytes[1] & !PLOOKUP_FLAG != 0 {
+            return Err(ParameterError("reserved compact proof flags are set".to_string()).into());
+        }
+        let lookup = bytes[1] & PLOOKUP_FLAG != 0;
+        let num_wire_types = compact_num_wire_types(lookup);
+        let expected_len = compact_size::<E>(lookup, batched);
+        if bytes.len() != expected_len {
+            return Err(ParameterError(format!(
+                "compact proof of {} bytes, expected {}",
+                bytes.len(),
+                expected_len
+            ))
+            .into());
+        }
+
+        let mut reader = &bytes[2..];
+        let mut read_comms = |num: usize| -> Result<Vec<Commitment<E>>, PlonkError> {
+            (0..num)
+                .map(|_| {
+                    Ok(Commitment(E::G1Affine::deserialize_compressed(
+                        &mut reader,
+                    )?))
+                })
+                .collect()
+        };
+        let wires_poly_comms = read_comms(num_wire_types)?;
+        let prod_perm_poly_comm = read_comms(1)?[0];
+        let split_quot_poly_comms = read_comms(num_wire_types)?;
+        let (opening_proof, shifted_opening_proof, batched_opening_proof) = if batched {
+            let comm = read_comms(1)?[0];
+            (Commitment::default(), Commitment::default(), Some(comm))
+        } else {
+            (read_comms(1)?[0], read_comms(1)?[0], None)
+        };
+        let plookup_comms = read_comms(if lookup { 3 } else { 0 })?;
+
+        let mut read_evals = |num: usize| -> Result<Vec<E::ScalarField>, PlonkError> {
+            (0..num)
+                .map(|_| Ok(E::ScalarField::deserialize_compressed(&mut reader)?))
+                .collect()
+        };
+        let poly_evals = ProofEvaluations {
+            wires_evals: read_evals(num_wire_types)?,
+            wire_sigma_evals: read_evals(num_wire_types - 1)?,
+            perm_next_eval: read_evals(1)?[0],
+        };
+        let plookup_proof = if lookup {
+            let evals = read_evals(NUM_PLOOKUP_EVALS)?;
+            Some(PlookupProof {
+                h_poly_comms: plookup_comms[..2].to_vec(),
+                prod_lookup_poly_comm: plookup_comms[2],
+                poly_evals: PlookupEvaluations {
+                    range_table_eval: evals[0],
+                    key_table_eval: evals[1],
+                    h_1_eval: evals[2],
+                    q_lookup_eval: evals[3],
+                    table_dom_sep_eval: evals[4],
+                    q_dom_sep_eval: evals[5],
+                    prod_next_eval: evals[6],
+                    range_table_next_eval: evals[7],
+                    key_table_next_eval: evals[8],
+                    h_1_next_eval: evals[9],
+                    h_2_next_eval: evals[10],
+                    q_lookup_next_eval: evals[11],
+                    w_3_next_eval: evals[12],
+                    w_4_next_eval: evals[13],
+                    table_dom_sep_next_eval: evals[14],
+                },
+            })
+        } else {
+            None
+        };
+
+        Ok((
+            Self {
+                wires_poly_comms,
+                prod_perm_poly_comm,
+                split_quot_poly_comms,
+                opening_proof,
+                shifted_opening_proof,
+                poly_evals,
+                plookup_proof,
+            },
+            batched_opening_proof,
+        ))
+    }
+}
+
+/// The size in bytes of a proof in the compact encoding (version 1), with or
+/// without a Plookup argument.
+pub fn compact_proof_size<E: Pairing>(lookup: bool) -> usize {
+    compact_size::<E>(lookup, false)
+}
+
+/// The size in bytes of a proof in the compact encoding with a single batched
+/// opening proof (version 2), with or without a Plookup argument.
+pub fn batched_compact_proof_size<E: Pairing>(lookup: bool) -> usize {
+    compact_size::<E>(lookup, true)
+}
+
+// The size in bytes of a proof in the compact encoding of version 2 if
+// `batched`, of version 1 otherwise.
+fn compact_size<E: Pairing>(lookup: bool, batched: bool) -> usize {
+    let num_wire_types = compact_num_wire_types(lookup);
+    let point_size = E::G1Affine::default().compressed_size();
+    let scalar_size = E::ScalarField::default().compressed_size();
+    let (num_points, num_scalars) = if lookup {
+        (
+            2 * num_wire_types + 6,
+            2 * num_wire_types + NUM_PLOOKUP_EVALS,
+        )
+    } else {
+        (2 * num_wire_types + 3, 2 * num_wire_types)
+    };
+    let num_points = if batched { num_points - 1 } else { num_points };
+    2 + num_points * point_size + num_scalars * scalar_size
+}
+
+// The number of wire types of proofs with or without a Plookup argument.
+fn compact_num_wire_types(lookup: bool) -> usize {
+    if lookup {
+        GATE_WIDTH + 2
+    } else {
+        GATE_WIDTH + 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        proof_system::{
+            snark::test::gen_circuit_for_test, PlonkKzgShplonkSnark, PlonkKzgSnark, UniversalSNARK,
+        },
+        transcript::{PlonkTranscript, StandardTranscript},
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bls12_381::{Bls12_381, Fq as Fq381};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+    use jf_primitives::rescue::RescueParameter;
+    use jf_relation::{gadgets::ecc::SWToTEConParam, Circuit, PlonkType};
+
+    #[test]
+    fn test_compact_proof() -> Result<(), PlonkError> {
+        test_compact_proof_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_compact_proof_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::UltraPlonk)?;
+        test_compact_proof_helper::<Bls12_377, Fq377, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_compact_proof_helper::<Bls12_381, Fq381, _, StandardTranscript>(PlonkType::UltraPlonk)
+    }
+
+    fn test_compact_proof_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(80, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let proof = PlonkKzgSnark::<E>::prove::<_, _, T>(rng, &circuit, &pk, None)?;
+        let public_input = circuit.public_input()?;
+
+        let bytes = proof.to_compact_bytes()?;
+        let lookup = plonk_type == PlonkType::UltraPlonk;
+        assert_eq!(bytes.len(), compact_proof_size::<E>(lookup));
+        // The canonical encoding has 4 (resp. 5 with Plookup) vector lengths of
+        // 8 bytes and an option tag, the compact one has a 2-byte header.
+        let num_prefix_bytes = if lookup { 5 * 8 + 1 } else { 4 * 8 + 1 };
+        assert_eq!(bytes.len() + num_prefix_bytes, proof.compressed_size() + 2);
+        assert_eq!(Proof::<E>::from_compact_bytes(&bytes)?, proof);
+        assert!(PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &bytes, None).is_ok());
+
+        // error paths
+        // tampered evaluation
+        let mut bad_bytes = bytes.clone();
+        *bad_bytes.last_mut().unwrap() ^= 1;
+        assert!(
+            PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &bad_bytes, None).is_err()
+        );
+        // truncated or extended proofs
+        assert!(Proof::<E>::from_compact_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Proof::<E>::from_compact_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+        assert!(Proof::<E>::from_compact_bytes(&bytes[..1]).is_err());
+        // unknown version or flags
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[0] = COMPACT_PROOF_VERSION + 1;
+        assert!(Proof::<E>::from_compact_bytes(&bad_bytes).is_err());
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[1] |= 0x80;
+        assert!(Proof::<E>::from_compact_bytes(&bad_bytes).is_err());
+        // a proof with the wrong Plookup flag
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[1] ^= PLOOKUP_FLAG;
+        assert!(Proof::<E>::from_compact_bytes(&bad_bytes).is_err());
+
+        // batched opening proof, a point less than version 1
+        let batched_bytes = proof.to_batched_compact_bytes()?;
+        let point_size = E::G1Affine::default().compressed_size();
+        assert_eq!(batched_bytes.len(), batched_compact_proof_size::<E>(lookup));
+        assert_eq!(batched_bytes.len() + point_size, bytes.len());
+        assert_eq!(
+            proof.compressed_size() - batched_bytes.len(),
+            point_size + num_prefix_bytes - 2
+        );
+        assert!(Proof::<E>::from_compact_bytes(&batched_bytes).is_err());
+        // it needs `[x^2]2` in the verifying key
+        assert!(
+            PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &batched_bytes, None)
+                .is_err()
+        );
+        let (_, vk) = PlonkKzgShplonkSnark::<E>::preprocess(&srs, &circuit)?;
+        assert!(
+            PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &batched_bytes, None)
+                .is_ok()
+        );
+        assert!(PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &bytes, None).is_ok());
+        // wrong public input or transcript message
+        let mut bad_public_input = public_input.clone();
+        bad_public_input[0] += E::ScalarField::from(1u8);
+        assert!(PlonkKzgSnark::<E>::verify_compact::<T>(
+            &vk,
+            &bad_public_input,
+            &batched_bytes,
+            None
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::verify_compact::<T>(
+            &vk,
+            &public_input,
+            &batched_bytes,
+            Some(vec![0])
+        )
+        .is_err());
+        // tampered evaluation or opening proof
+        let mut bad_bytes = batched_bytes.clone();
+        *bad_bytes.last_mut().unwrap() ^= 1;
+        assert!(
+            PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &bad_bytes, None).is_err()
+        );
+        let mut bad_proof = proof.clone();
+        bad_proof.shifted_opening_proof = bad_proof.opening_proof;
+        let bad_bytes = bad_proof.to_batched_compact_bytes()?;
+        assert!(
+            PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &bad_bytes, None).is_err()
+        );
+        let bad_bytes = [&batched_bytes[..1], &bytes[1..]].concat();
+        assert!(
+            PlonkKzgSnark::<E>::verify_compact::<T>(&vk, &public_input, &bad_bytes, None).is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,696 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A Nova-style accumulation (folding) scheme for TurboPlonk circuits.
+//!
+//! A relaxed instance of a circuit is satisfied by a witness `w` (the values
+//! of all the circuit variables), a scalar `u` and an error vector `e` if for
+//! every gate:
+//!
+//! ```text
+//! u^4 * (q_lc0 * w0 + q_lc1 * w1 + q_lc2 * w2 + q_lc3 * w3 + pub_input - q_o * wo)
+//!   + u^3 * (q_mul0 * w0 * w1 + q_mul1 * w2 * w3)
+//!   + q_ecc * w0 * w1 * w2 * w3 * wo
+//!   + q_hash0 * w0^5 + q_hash1 * w1^5 + q_hash2 * w2^5 + q_hash3 * w3^5
+//!   + u^5 * q_c
+//!   = e
+//! ```
+//!
+//! which is the Plonk gate equation made homogeneous of degree 5. A circuit
+//! witness is a relaxed witness with `u = 1` and `e = 0`. Since the copy
+//! constraints are enforced by indexing the witness by variables, they are
+//! preserved by random linear combinations, and two relaxed instances can be
+//! folded into one with 4 cross-term commitments. Commitments are not hiding.
+//!
+//! The folding challenge binds a digest of the folding key, see
+//! [`FoldingKey::digest()`], so that a folding proof for one circuit cannot be
+//! replayed for another one.
+//!
+//! The fold verifier is native only, and the final accumulator is decided by
+//! checking its witness, see [`FoldingKey::check()`]. Without a fold verifier
+//! gadget over a cycle of curves, this accumulates instances of a circuit but
+//! does not provide incrementally verifiable computation.
+
+use crate::{
+    errors::{
+        PlonkError,
+        SnarkError::{ParameterError, SnarkLookupUnsupported},
+    },
+    proof_system::structs::UniversalSrs,
+    transcript::PlonkTranscript,
+};
+use ark_ec::{
+    pairing::Pairing,
+    scalar_mul::variable_base::VariableBaseMSM,
+    short_weierstrass::{Affine, SWCurveConfig},
+    CurveGroup,
+};
+use ark_ff::{Field, One, Zero};
+use ark_serialize::*;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use jf_primitives::pcs::prelude::Commitment;
+use jf_relation::{
+    constants::{GATE_WIDTH, N_MUL_SELECTORS},
+    Circuit, PlonkCircuit, Variable,
+};
+use jf_utils::to_bytes;
+use sha3::{Digest, Sha3_256};
+
+/// The degree of the relaxed gate equation.
+const DEGREE: usize = 5;
+
+const FOLDING_KEY_DIGEST_DOMAIN_SEPARATOR: &[u8] = b"JF-PLONK-FOLDING-KEY";
+
+/// The selectors and wire variables of a gate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GateShape<F: Field> {
+    q_lc: [F; GATE_WIDTH],
+    q_mul: [F; N_MUL_SELECTORS],
+    q_hash: [F; GATE_WIDTH],
+    q_o: F,
+    q_c: F,
+    q_ecc: F,
+    wires: [Variable; GATE_WIDTH + 1],
+    // index of the public input of an I/O gate
+    pub_input: Option<usize>,
+}
+
+/// The structure shared by all the instances folded together: the gates and
+/// wiring of a TurboPlonk circuit, and the bases used to commit to witnesses
+/// and error vectors.
+#[derive(Debug, Clone)]
+pub struct FoldingKey<E: Pairing> {
+    gates: Vec<GateShape<E::ScalarField>>,
+    num_vars: usize,
+    num_inputs: usize,
+    bases: Vec<E::G1Affine>,
+    digest: [u8; 32],
+}
+
+/// A relaxed circuit instance.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RelaxedInstance<E: Pairing> {
+    /// Commitment to the witness.
+    pub witness_comm: Commitment<E>,
+    /// Commitment to the error vector.
+    pub error_comm: Commitment<E>,
+    /// The slack scalar, one for a circuit instance.
+    pub u: E::ScalarField,
+    /// The public input.
+    pub pub_input: Vec<E::ScalarField>,
+}
+
+/// A witness of a relaxed circuit instance.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RelaxedWitness<F: Field> {
+    /// The value of every circuit variable.
+    pub witness: Vec<F>,
+    /// The error term of every gate, zero for a circuit witness.
+    pub error: Vec<F>,
+}
+
+/// A proof that a relaxed instance is the folding of two others.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct FoldingProof<E: Pairing> {
+    /// Commitments to the cross terms of degree `1..DEGREE` in the folding
+    /// challenge.
+    pub cross_term_comms: Vec<Commitment<E>>,
+}
+
+// A polynomial of degree at most `DEGREE` in the folding challenge, in
+// coefficient form.
+type FoldPoly<F> = [F; DEGREE + 1];
+
+fn poly_add<F: Field>(a: &FoldPoly<F>, b: &FoldPoly<F>) -> FoldPoly<F> {
+    let mut res = *a;
+    res.iter_mut().zip(b.iter()).for_each(|(x, y)| *x += y);
+    res
+}
+
+fn poly_scale<F: Field>(a: &FoldPoly<F>, c: F) -> FoldPoly<F> {
+    let mut res = *a;
+    res.iter_mut().for_each(|x| *x *= c);
+    res
+}
+
+// The product of two polynomials whose degrees sum to at most `DEGREE`.
+fn poly_mul<F: Field>(a: &FoldPoly<F>, b: &FoldPoly<F>) -> FoldPoly<F> {
+    let mut res = [F::zero(); DEGREE + 1];
+    for (i, x) in a.iter().enumerate().filter(|(_, x)| !x.is_zero()) {
+        for (j, y) in b.iter().take(DEGREE + 1 - i).enumerate() {
+            res[i + j] += *x * y;
+        }
+    }
+    res
+}
+
+// The polynomial `a + X * b`.
+fn poly_linear<F: Field>(a: F, b: F) -> FoldPoly<F> {
+    let mut res = [F::zero(); DEGREE + 1];
+    res[0] = a;
+    res[1] = b;
+    res
+}
+
+impl<F: Field> GateShape<F> {
+    // The left-hand side of the relaxed gate equation evaluated at
+    // `(w1 + X * w2, u1 + X * u2, pub_input1 + X * pub_input2)`.
+    fn relaxed_eval(
+        &self,
+        (w1, u1, pub_input1): (&[F], F, &[F]),
+        (w2, u2, pub_input2): (&[F], F, &[F]),
+    ) -> FoldPoly<F> {
+        let w: Vec<FoldPoly<F>> = self
+            .wires
+            .iter()
+            .map(|&var| poly_linear(w1[var], w2[var]))
+            .collect();
+        let u = poly_linear(u1, u2);
+        let u_2 = poly_mul(&u, &u);
+        let u_3 = poly_mul(&u_2, &u);
+        let u_4 = poly_mul(&u_3, &u);
+        let u_5 = poly_mul(&u_4, &u);
+
+        // degree 1 terms
+        let mut linear = poly_scale(&w[GATE_WIDTH], -self.q_o);
+        for (q, w) in self.q_lc.iter().zip(w.iter()) {
+            linear = poly_add(&linear, &poly_scale(w, *q));
+        }
+        if let Some(i) = self.pub_input {
+            linear = poly_add(&linear, &poly_linear(pub_input1[i], pub_input2[i]));
+        }
+        let mut res = poly_add(&poly_mul(&u_4, &linear), &poly_scale(&u_5, self.q_c));
+
+        // degree 2 terms
+        if !self.q_mul[0].is_zero() || !self.q_mul[1].is_zero() {
+            let quad = poly_add(
+                &poly_scale(&poly_mul(&w[0], &w[1]), self.q_mul[0]),
+                &poly_scale(&poly_mul(&w[2], &w[3]), self.q_mul[1]),
+            );
+            res = poly_add(&res, &poly_mul(&u_3, &quad));
+        }
+
+        // degree 5 terms
+        if !self.q_ecc.is_zero() {
+            let prod = w.iter().skip(1).fold(w[0], |acc, w| poly_mul(&acc, w));
+            res = poly_add(&res, &poly_scale(&prod, self.q_ecc));
+        }
+        for (q, w) in self.q_hash.iter().zip(w.iter()) {
+            if !q.is_zero() {
+                let w_2 = poly_mul(w, w);
+                let w_4 = poly_mul(&w_2, &w_2);
+                res = poly_add(&res, &poly_scale(&poly_mul(&w_4, w), *q));
+            }
+        }
+        res
+    }
+}
+
+impl<E, F, P> FoldingKey<E>
+where
+    E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+    F: Field,
+    P: SWCurveConfig<BaseField = F>,
+{
+    /// Extract the folding key of a TurboPlonk circuit, with commitment bases
+    /// taken from the universal SRS.
+    pub fn new(
+        srs: &UniversalSrs<E>,
+        circuit: &PlonkCircuit<E::ScalarField>,
+    ) -> Result<Self, PlonkError> {
+        if circuit.support_lookup() {
+            return Err(SnarkLookupUnsupported.into());
+        }
+        let num_gates = circuit.num_gates();
+        let num_vars = circuit.num_vars();
+        let size = ark_std::cmp::max(num_gates, num_vars);
+        if srs.powers_of_g.len() < size {
+            return Err(ParameterError(format!(
+                "the SRS size {} is smaller than the circuit size {}",
+                srs.powers_of_g.len(),
+                size
+            ))
+            .into());
+        }
+        let mut pub_inputs = vec![None; num_gates];
+        for (i, &gate_id) in circuit.pub_input_gate_ids().iter().enumerate() {
+            pub_inputs[gate_id] = Some(i);
+        }
+        let gates = pub_inputs
+            .into_iter()
+            .enumerate()
+            .map(|(gate_id, pub_input)| {
+                let gate = circuit.gate(gate_id)?;
+                Ok(GateShape {
+                    q_lc: gate.q_lc(),
+                    q_mul: gate.q_mul(),
+                    q_hash: gate.q_hash(),
+                    q_o: gate.q_o(),
+                    q_c: gate.q_c(),
+                    q_ecc: gate.q_ecc(),
+                    wires: circuit.gate_wire_variables(gate_id)?,
+                    pub_input,
+                })
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let num_inputs = circuit.num_inputs();
+        let bases = srs.powers_of_g[..size].to_vec();
+        let digest = Self::compute_digest(&gates, num_vars, num_inputs, &bases)?;
+        Ok(Self {
+            gates,
+            num_vars,
+            num_inputs,
+            bases,
+            digest,
+        })
+    }
+
+    /// A SHA3-256 digest of the gates, the wiring, the numbers of variables
+    /// and public inputs and the commitment bases of the key. It is bound in
+    /// the folding challenge and must be given to [`RelaxedInstance::fold()`].
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest
+    }
+
+    fn compute_digest(
+        gates: &[GateShape<E::ScalarField>],
+        num_vars: usize,
+        num_inputs: usize,
+        bases: &[E::G1Affine],
+    ) -> Result<[u8; 32], PlonkError> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(FOLDING_KEY_DIGEST_DOMAIN_SEPARATOR);
+        hasher.update((gates.len() as u64).to_le_bytes());
+        hasher.update((num_vars as u64).to_le_bytes());
+        hasher.update((num_inputs as u64).to_le_bytes());
+        for gate in gates.iter() {
+            let selectors: Vec<E::ScalarField> = gate
+                .q_lc
+                .iter()
+                .chain(gate.q_mul.iter())
+                .chain(gate.q_hash.iter())
+                .chain([&gate.q_o, &gate.q_c, &gate.q_ecc])
+                .copied()
+                .collect();
+            hasher.update(to_bytes!(&selectors)?);
+            for wire in gate.wires.iter() {
+                hasher.update((*wire as u64).to_le_bytes());
+            }
+            // `u64::MAX` for a gate without public input
+            let pub_input = gate.pub_input.map_or(u64::MAX, |i| i as u64);
+            hasher.update(pub_input.to_le_bytes());
+        }
+        hasher.update(to_bytes!(&bases.to_vec())?);
+        Ok(hasher.finalize().into())
+    }
+
+    /// The relaxed instance and witness of a circuit with the same gates and
+    /// wiring as the one of the key.
+    pub fn instance(
+        &self,
+        circuit: &PlonkCircuit<E::ScalarField>,
+    ) -> Result<(RelaxedInstance<E>, RelaxedWitness<E::ScalarField>), PlonkError> {
+        if circuit.support_lookup()
+            || circuit.num_vars() != self.num_vars
+            || circuit.num_gates() != self.gates.len()
+            || circuit.num_inputs() != self.num_inputs
+        {
+            return Err(
+                ParameterError("the circuit does not match the folding key".to_string()).into(),
+            );
+        }
+        for (gate_id, shape) in self.gates.iter().enumerate() {
+            let gate = circuit.gate(gate_id)?;
+            if circuit.gate_wire_variables(gate_id)? != shape.wires
+                || gate.q_lc() != shape.q_lc
+                || gate.q_mul() != shape.q_mul
+                || gate.q_hash() != shape.q_hash
+                || gate.q_o() != shape.q_o
+                || gate.q_c() != shape.q_c
+                || gate.q_ecc() != shape.q_ecc
+            {
+                return Err(ParameterError(format!(
+                    "the {gate_id}-th gate of the circuit does not match the folding key"
+                ))
+                .into());
+            }
+        }
+        let witness = (0..self.num_vars)
+            .map(|var| circuit.witness(var))
+            .collect::<Result<Vec<_>, _>>()?;
+        let error = vec![E::ScalarField::zero(); self.gates.len()];
+        let instance = RelaxedInstance {
+            witness_comm: self.commit(&witness),
+            error_comm: Commitment::default(),
+            u: E::ScalarField::one(),
+            pub_input: circuit.public_input()?,
+        };
+        Ok((instance, RelaxedWitness { witness, error }))
+    }
+
+    /// Fold two relaxed instances and their witnesses. Return the folded
+    /// instance and witness, and the proof that the folding is correct.
+    #[allow(clippy::type_complexity)]
+    pub fn fold<T>(
+        &self,
+        (instance1, witness1): (&RelaxedInstance<E>, &RelaxedWitness<E::ScalarField>),
+        (instance2, witness2): (&RelaxedInstance<E>, &RelaxedWitness<E::ScalarField>),
+    ) -> Result<
+        (
+            RelaxedInstance<E>,
+            RelaxedWitness<E::ScalarField>,
+            FoldingProof<E>,
+        ),
+        PlonkError,
+    >
+    where
+        T: PlonkTranscript<F>,
+    {
+        self.check_lengths(instance1, witness1)?;
+        self.check_lengths(instance2, witness2)?;
+
+        let mut cross_terms = vec![vec![]; DEGREE - 1];
+        for gate in self.gates.iter() {
+            let eval = gate.relaxed_eval(
+                (&witness1.witness, instance1.u, &instance1.pub_input),
+                (&witness2.witness, instance2.u, &instance2.pub_input),
+            );
+            for (cross_term, coeff) in cross_terms.iter_mut().zip(eval[1..DEGREE].iter()) {
+                cross_term.push(*coeff);
+            }
+        }
+        let proof = FoldingProof {
+            cross_term_comms: cross_terms.iter().map(|t| self.commit(t)).collect(),
+        };
+        let r = folding_challenge::<E, F, P, T>(&self.digest, instance1, instance2, &proof)?;
+
+        let witness = fold_vec(&witness1.witness, &witness2.witness, r);
+        // e1 + r * t1 + ... + r^4 * t4 + r^5 * e2
+        let mut error = witness2.error.clone();
+        for vec in cross_terms.iter().rev().chain([&witness1.error]) {
+            error = fold_vec(vec, &error, r);
+        }
+        let instance = instance1.fold::<T>(instance2, &proof, &self.digest)?;
+        Ok((instance, RelaxedWitness { witness, error }, proof))
+    }
+
+    /// Check that a relaxed instance is satisfied by a relaxed witness.
+    /// Return [`PlonkError::WrongProof`] if it is not.
+    pub fn check(
+        &self,
+        instance: &RelaxedInstance<E>,
+        witness: &RelaxedWitness<E::ScalarField>,
+    ) -> Result<(), PlonkError> {
+        self.check_lengths(instance, witness)?;
+        if self.commit(&witness.witness) != instance.witness_comm
+            || self.commit(&witness.error) != instance.error_comm
+        {
+            return Err(PlonkError::WrongProof);
+        }
+        let zeros = vec![E::ScalarField::zero(); self.num_vars];
+        let zero_inputs = vec![E::ScalarField::zero(); self.num_inputs];
+        for (gate, error) in self.gates.iter().zip(witness.error.iter()) {
+            let eval = gate.relaxed_eval(
+                (&witness.witness, instance.u, &instance.pub_input),
+                (&zeros, E::ScalarField::zero(), &zero_inputs),
+            );
+            if eval[0] != *error {
+                return Err(PlonkError::WrongProof);
+            }
+        }
+        Ok(())
+    }
+
+    fn commit(&self, values: &[E::ScalarField]) -> Commitment<E> {
+        Commitment(E::G1::msm_unchecked(&self.bases[..values.len()], values).into_affine())
+    }
+
+    fn check_lengths(
+        &self,
+        instance: &RelaxedInstance<E>,
+        witness: &RelaxedWitness<E::ScalarField>,
+    ) -> Result<(), PlonkError> {
+        if instance.pub_input.len() != self.num_inputs
+            || witness.witness.len() != self.num_vars
+            || witness.error.len() != self.gates.len()
+        {
+            return Err(ParameterError(
+                "the relaxed instance does not match the folding key".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl<E, F, P> RelaxedInstance<E>
+where
+    E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+    F: Field,
+    P: SWCurveConfig<BaseField = F>,
+{
+    /// Fold `self` with `other` given a folding proof and the
+    /// [`FoldingKey::digest()`] of the key of both instances. This is the
+    /// verifier side of [`FoldingKey::fold()`].
+    pub fn fold<T>(
+        &self,
+        other: &Self,
+        proof: &FoldingProof<E>,
+        key_digest: &[u8; 32],
+    ) -> Result<Self, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        if self.pub_input.len() != other.pub_input.len()
+            || proof.cross_term_comms.len() != DEGREE - 1
+        {
+            return Err(ParameterError(
+                "mismatched relaxed instances or folding proof".to_string(),
+            )
+            .into());
+        }
+        let r = folding_challenge::<E, F, P, T>(key_digest, self, other, proof)?;
+
+        let witness_comm = E::G1::from(self.witness_comm.0) + E::G1::from(other.witness_comm.0) * r;
+        // E1 + r * T1 + ... + r^4 * T4 + r^5 * E2
+        let mut error_comm = E::G1::from(other.error_comm.0);
+        for comm in proof
+            .cross_term_comms
+            .iter()
+            .rev()
+            .chain([&self.error_comm])
+        {
+            error_comm = error_comm * r + comm.0;
+        }
+        Ok(Self {
+            witness_comm: Commitment(witness_comm.into_affine()),
+            error_comm: Commitment(error_comm.into_affine()),
+            u: self.u + r * other.u,
+            pub_input: fold_vec(&self.pub_input, &other.pub_input, r),
+        })
+    }
+}
+
+// `a + r * b`
+fn fold_vec<F: Field>(a: &[F], b: &[F], r: F) -> Vec<F> {
+    a.iter().zip(b.iter()).map(|(a, b)| *a + r * b).collect()
+}
+
+fn folding_challenge<E, F, P, T>(
+    key_digest: &[u8; 32],
+    instance1: &RelaxedInstance<E>,
+    instance2: &RelaxedInstance<E>,
+    proof: &FoldingProof<E>,
+) -> Result<E::ScalarField, PlonkError>
+where
+    E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+    P: SWCurveConfig<BaseField = F>,
+    T: PlonkTranscript<F>,
+{
+    let mut transcript = T::new(b"PlonkFolding");
+    transcript.append_message(b"folding key digest", key_digest)?;
+    for instance in [instance1, instance2] {
+        transcript.append_commitment(b"witness_comm", &instance.witness_comm)?;
+        transcript.append_commitment(b"error_comm", &instance.error_comm)?;
+        transcript.append_challenge::<E>(b"u", &instance.u)?;
+        transcript.append_message(b"public input", &to_bytes!(&instance.pub_input)?)?;
+    }
+    transcript.append_commitments(b"cross_term_comms", &proof.cross_term_comms)?;
+    transcript.get_and_append_challenge::<E>(b"r")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FoldingKey, RelaxedInstance, RelaxedWitness};
+    use crate::{
+        errors::PlonkError,
+        proof_system::{structs::UniversalSrs, PlonkKzgSnark, UniversalSNARK},
+        transcript::{PlonkTranscript, RescueTranscript, StandardTranscript},
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bls12_381::{Bls12_381, Fq as Fq381};
+    use ark_ec::{
+        pairing::Pairing,
+        short_weierstrass::{Affine, SWCurveConfig},
+        twisted_edwards::{Affine as TEAffine, TECurveConfig},
+        AffineRepr,
+    };
+    use ark_ff::{Field, PrimeField};
+    use ark_std::vec;
+    use jf_primitives::pcs::prelude::Commitment;
+    use jf_primitives::rescue::RescueParameter;
+    use jf_relation::{
+        gadgets::ecc::{SWToTEConParam, TEPoint},
+        Circuit, PlonkCircuit,
+    };
+    use jf_utils::test_rng;
+
+    // A step `z -> z^11 + z^2 + 3` that also doubles a curve point, with
+    // public input `(z, z^11 + z^2 + 3)`.
+    fn step_circuit<F, P>(z: F) -> Result<PlonkCircuit<F>, PlonkError>
+    where
+        F: PrimeField,
+        P: TECurveConfig<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::new_turbo_plonk();
+        let z_var = circuit.create_public_variable(z)?;
+        let z_11 = circuit.power_11_gen(z_var)?;
+        let z_2 = circuit.mul(z_var, z_var)?;
+        let sum = circuit.add(z_11, z_2)?;
+        let out = circuit.add_constant(sum, &F::from(3u32))?;
+        circuit.set_variable_public(out)?;
+        let point: TEPoint<F> = TEAffine::<P>::generator().into();
+        let point_var = circuit.create_point_variable(point)?;
+        circuit.ecc_add::<P>(&point_var, &point_var)?;
+        Ok(circuit)
+    }
+
+    fn step<F: Field>(z: F) -> F {
+        z.pow([11]) + z.square() + F::from(3u32)
+    }
+
+    #[test]
+    fn test_folding() -> Result<(), PlonkError> {
+        test_folding_helper::<
+            Bls12_377,
+            Fq377,
+            _,
+            ark_ed_on_bls12_377::EdwardsConfig,
+            StandardTranscript,
+        >()?;
+        test_folding_helper::<
+            Bls12_377,
+            Fq377,
+            _,
+            ark_ed_on_bls12_377::EdwardsConfig,
+            RescueTranscript<_>,
+        >()?;
+        test_folding_helper::<
+            Bls12_381,
+            Fq381,
+            _,
+            ark_ed_on_bls12_381::EdwardsConfig,
+            StandardTranscript,
+        >()
+    }
+
+    fn test_folding_helper<E, F, P, Q, T>() -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        Q: TECurveConfig<BaseField = E::ScalarField>,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let mut z = E::ScalarField::from(2u32);
+        let circuit = step_circuit::<_, Q>(z)?;
+        let srs: UniversalSrs<E> = PlonkKzgSnark::<E>::universal_setup_for_testing(
+            circuit.num_vars().max(circuit.num_gates()),
+            rng,
+        )?;
+        let key = FoldingKey::new(&srs, &circuit)?;
+        let (mut acc, mut acc_witness) = key.instance(&circuit)?;
+        key.check(&acc, &acc_witness)?;
+
+        // fold the following steps into the accumulator
+        let mut fresh_instances = vec![];
+        for _ in 0..3 {
+            z = step(z);
+            let (instance, witness) = key.instance(&step_circuit::<_, Q>(z)?)?;
+            let (folded, folded_witness, proof) =
+                key.fold::<T>((&acc, &acc_witness), (&instance, &witness))?;
+            assert_eq!(acc.fold::<T>(&instance, &proof, &key.digest())?, folded);
+            key.check(&folded, &folded_witness)?;
+            fresh_instances.push((instance, witness));
+            acc = folded;
+            acc_witness = folded_witness;
+        }
+
+        // fold two accumulators
+        let (other, other_witness, _) = key.fold::<T>(
+            (&fresh_instances[0].0, &fresh_instances[0].1),
+            (&fresh_instances[1].0, &fresh_instances[1].1),
+        )?;
+        let (folded, folded_witness, proof) =
+            key.fold::<T>((&acc, &acc_witness), (&other, &other_witness))?;
+        assert_eq!(acc.fold::<T>(&other, &proof, &key.digest())?, folded);
+        key.check(&folded, &folded_witness)?;
+
+        // an unsatisfied instance makes the accumulator unsatisfied
+        let mut bad_circuit = step_circuit::<_, Q>(step(z))?;
+        *bad_circuit.witness_mut(3) += E::ScalarField::from(1u32);
+        let (bad, bad_witness) = key.instance(&bad_circuit)?;
+        let (folded, folded_witness, mut proof) =
+            key.fold::<T>((&acc, &acc_witness), (&bad, &bad_witness))?;
+        assert!(key.check(&folded, &folded_witness).is_err());
+
+        // a wrong folding proof
+        let (folded, folded_witness, good_proof) = key.fold::<T>(
+            (&acc, &acc_witness),
+            (&fresh_instances[2].0, &fresh_instances[2].1),
+        )?;
+        proof.cross_term_comms[1] = Commitment(srs.powers_of_g[0]);
+        let bad_folded = acc.fold::<T>(&fresh_instances[2].0, &proof, &key.digest())?;
+        assert!(key.check(&bad_folded, &folded_witness).is_err());
+        key.check(&folded, &folded_witness)?;
+
+        // a folding proof is bound to the key
+        let mut other_circuit = step_circuit::<_, Q>(z)?;
+        other_circuit.mul(0, 1)?;
+        let other_key = FoldingKey::new(&srs, &other_circuit)?;
+        assert_ne!(other_key.digest(), key.digest());
+        assert_eq!(FoldingKey::new(&srs, &circuit)?.digest(), key.digest());
+        assert_eq!(
+            acc.fold::<T>(&fresh_instances[2].0, &good_proof, &key.digest())?,
+            folded
+        );
+        let bad_folded = acc.fold::<T>(&fresh_instances[2].0, &good_proof, &other_key.digest())?;
+        assert!(key.check(&bad_folded, &folded_witness).is_err());
+
+        // wrong public input
+        let mut bad_acc = acc.clone();
+        bad_acc.pub_input[0] += E::ScalarField::from(1u32);
+        assert!(key.check(&bad_acc, &acc_witness).is_err());
+
+        // mismatched circuits
+        let mut circuit = step_circuit::<_, Q>(z)?;
+        circuit.mul(0, 1)?;
+        assert!(key.instance(&circuit).is_err());
+        let bad_witness = RelaxedWitness {
+            witness: acc_witness.witness[1..].to_vec(),
+            error: acc_witness.error.clone(),
+        };
+        assert!(key.check(&acc, &bad_witness).is_err());
+        let bad_acc = RelaxedInstance {
+            pub_input: vec![],
+            ..acc.clone()
+        };
+        assert!(acc.fold::<T>(&bad_acc, &proof, &key.digest()).is_err());
+        assert!(FoldingKey::new(&srs, &PlonkCircuit::new_ultra_plonk(8)).is_err());
+        Ok(())
+    }
+}
@@ -14,12 +14,20 @@ use ark_std::{
 };
 use jf_relation::Arithmetization;
 pub mod batch_arg;
+pub mod compact;
+pub mod folding;
+pub mod pcs;
 pub(crate) mod prover;
+pub mod shplonk;
 pub(crate) mod snark;
+#[cfg(feature = "std")]
+mod spill;
 pub mod structs;
 pub(crate) mod verifier;
 use crate::transcript::PlonkTranscript;
-pub use snark::PlonkKzgSnark;
+pub use pcs::PlonkPCS;
+pub use shplonk::PlonkKzgShplonkSnark;
+pub use snark::{PlonkIpaSnark, PlonkKzgSnark, PlonkSnark};
 
 // TODO: (alex) should we name it `PlonkishSNARK` instead? since we use
 // `PlonkTranscript` on prove and verify.
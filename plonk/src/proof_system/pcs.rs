@@ -0,0 +1,413 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Univariate polynomial commitment schemes that Plonk can be instantiated
+//! with.
+
+use crate::{errors::PlonkError, transcript::PlonkTranscript};
+use ark_ec::{
+    pairing::Pairing,
+    short_weierstrass::{Affine, SWCurveConfig},
+    VariableBaseMSM,
+};
+use ark_ff::One;
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    fmt::Debug,
+    format,
+    hash::Hash,
+    rand::{CryptoRng, RngCore},
+    vec,
+    vec::Vec,
+};
+use jf_primitives::pcs::{
+    prelude::{
+        Commitment, PCSError, UnivariateIpaCommitment, UnivariateIpaPCS, UnivariateIpaParams,
+        UnivariateIpaProof, UnivariateKzgPCS, UnivariateProverParam, UnivariateUniversalParams,
+        UnivariateVerifierParam,
+    },
+    PolynomialCommitmentScheme, StructuredReferenceString, UnivariatePCS,
+};
+use jf_utils::multi_pairing;
+
+/// A univariate polynomial commitment scheme that the Plonk prover and
+/// verifier can be instantiated with.
+///
+/// The commitment of a polynomial must be the multi-scalar multiplication of
+/// its coefficients with the first [`Self::commit_bases()`], so that the
+/// verifier can combine commitments linearly, and that commitments are points
+/// of `E::G1` whichever the scheme. Only the opening proofs and the keys
+/// depend on the scheme.
+pub trait PlonkPCS<E: Pairing>:
+    UnivariatePCS<
+        SRS = <Self as PlonkPCS<E>>::UniversalSrs,
+        Polynomial = DensePolynomial<E::ScalarField>,
+        Point = E::ScalarField,
+        Evaluation = E::ScalarField,
+    > + Send
+    + Sync
+{
+    /// Universal structured reference string.
+    type UniversalSrs: StructuredReferenceString<ProverParam = Self::CommitKey, VerifierParam = Self::OpenKey>
+        + Clone
+        + Debug
+        + Send
+        + Sync;
+    /// Key for committing to polynomials and computing opening proofs.
+    type CommitKey: Clone
+        + Debug
+        + PartialEq
+        + Eq
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+    /// Key for verifying opening proofs.
+    type OpenKey: Clone
+        + Debug
+        + Default
+        + PartialEq
+        + Eq
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+    /// Proof of the evaluation of a polynomial at a point.
+    type OpeningProof: Clone
+        + Debug
+        + PartialEq
+        + Eq
+        + Hash
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + Send
+        + Sync;
+
+    /// Generate the universal SRS supporting polynomials of degree up to
+    /// `max_degree`.
+    fn universal_setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSrs, PCSError>;
+
+    /// Same as [`Self::universal_setup()`], but for testing and benchmarking
+    /// code only.
+    #[cfg(any(test, feature = "test-srs"))]
+    fn universal_setup_for_testing<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSrs, PCSError>;
+
+    /// The maximal degree of the polynomials supported by `srs`.
+    fn max_degree(srs: &Self::UniversalSrs) -> usize;
+
+    /// The bases of the commitments.
+    fn commit_bases(ck: &Self::CommitKey) -> &[E::G1Affine];
+
+    /// Convert a commitment of the scheme to a Plonk commitment.
+    fn to_plonk_commitment(comm: Self::Commitment) -> Commitment<E>;
+
+    /// Compute a proof of the evaluation of `poly` at `point`. Proofs that
+    /// consist of commitments to polynomials may compute them with `commit`,
+    /// which lets the prover bound their memory.
+    #[allow(clippy::type_complexity)]
+    fn compute_opening_proof(
+        ck: &Self::CommitKey,
+        poly: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+        commit: &dyn Fn(&DensePolynomial<E::ScalarField>) -> Result<Commitment<E>, PlonkError>,
+    ) -> Result<Self::OpeningProof, PlonkError>;
+
+    /// Append `proof` to `transcript`.
+    fn append_opening_proof<F, P, T>(
+        transcript: &mut T,
+        label: &'static [u8],
+        proof: &Self::OpeningProof,
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>;
+
+    /// Verify that `proofs[i]` proves that the polynomial committed in
+    /// `comms[i]` evaluates to `evals[i]` at `points[i]`, for every `i`. The
+    /// checks may be batched with the random `combiners`.
+    fn batch_verify_opening_proofs(
+        open_key: &Self::OpenKey,
+        comms: &[Commitment<E>],
+        points: &[E::ScalarField],
+        evals: &[E::ScalarField],
+        proofs: &[&Self::OpeningProof],
+        combiners: &[E::ScalarField],
+    ) -> Result<bool, PlonkError>;
+}
+
+impl<E: Pairing> PlonkPCS<E> for UnivariateKzgPCS<E> {
+    type UniversalSrs = UnivariateUniversalParams<E>;
+    type CommitKey = UnivariateProverParam<E>;
+    type OpenKey = UnivariateVerifierParam<E>;
+    type OpeningProof = Commitment<E>;
+
+    fn universal_setup<R: RngCore + CryptoRng>(
+        _max_degree: usize,
+        _rng: &mut R,
+    ) -> Result<Self::UniversalSrs, PCSError> {
+        unimplemented!("Should load from files in practice.");
+    }
+
+    // FIXME: (alex) see <https://github.com/EspressoSystems/jellyfish/issues/249>
+    #[cfg(any(test, feature = "test-srs"))]
+    fn universal_setup_for_testing<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSrs, PCSError> {
+        use ark_ec::{scalar_mul::fixed_base::FixedBase, CurveGroup};
+        use ark_ff::{Field, PrimeField};
+        use ark_std::{end_timer, start_timer, UniformRand};
+
+        let setup_time = start_timer!(|| format!("KZG10::Setup with degree {}", max_degree));
+        let beta = E::ScalarField::rand(rng);
+        let g = E::G1::rand(rng);
+        let h = E::G2::rand(rng);
+
+        let mut powers_of_beta = vec![E::ScalarField::one()];
+
+        let mut cur = beta;
+        for _ in 0..max_degree {
+            powers_of_beta.push(cur);
+            cur *= &beta;
+        }
+
+        let window_size = FixedBase::get_mul_window_size(max_degree + 1);
+
+        let scalar_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        let g_time = start_timer!(|| "Generating powers of G");
+        // TODO: parallelization
+        let g_table = FixedBase::get_window_table(scalar_bits, window_size, g);
+        let powers_of_g =
+            FixedBase::msm::<E::G1>(scalar_bits, window_size, &g_table, &powers_of_beta);
+        end_timer!(g_time);
+
+        let powers_of_g = E::G1::normalize_batch(&powers_of_g);
+
+        let h = h.into_affine();
+        let beta_h = (h * beta).into_affine();
+        // `[beta^2]2` is needed to verify a single opening proof at two points
+        // (see `PlonkKzgShplonkSnark`).
+        let beta_2_h = (h * beta.square()).into_affine();
+
+        let pp = UnivariateUniversalParams {
+            powers_of_g,
+            h,
+            beta_h,
+            powers_of_h: vec![h, beta_h, beta_2_h],
+        };
+        end_timer!(setup_time);
+        Ok(pp)
+    }
+
+    fn max_degree(srs: &Self::UniversalSrs) -> usize {
+        srs.max_degree()
+    }
+
+    fn commit_bases(ck: &Self::CommitKey) -> &[E::G1Affine] {
+        &ck.powers_of_g
+    }
+
+    fn to_plonk_commitment(comm: Self::Commitment) -> Commitment<E> {
+        comm
+    }
+
+    /// The proof is the commitment of the witness polynomial `(poly(X) -
+    /// poly(point)) / (X - point)`.
+    fn compute_opening_proof(
+        _ck: &Self::CommitKey,
+        poly: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+        commit: &dyn Fn(&DensePolynomial<E::ScalarField>) -> Result<Commitment<E>, PlonkError>,
+    ) -> Result<Self::OpeningProof, PlonkError> {
+        let divisor = DensePolynomial::from_coefficients_vec(vec![-*point, E::ScalarField::one()]);
+        let witness_poly = poly / &divisor;
+        commit(&witness_poly)
+    }
+
+    fn append_opening_proof<F, P, T>(
+        transcript: &mut T,
+        label: &'static [u8],
+        proof: &Self::OpeningProof,
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        transcript.append_commitment(label, proof)
+    }
+
+    /// We need to verify that
+    /// - `e(Ai, [x]2) = e(Bi, [1]2) for i \in {0, .., m-1}`, where
+    /// - `Ai = [proof_i]` and
+    /// - `Bi = point_i * [proof_i] + comm_i - eval_i * [1]1`.
+    ///
+    /// By Schwartz-Zippel lemma, it's equivalent to check that
+    /// - `e(r_0 * A0 + ... + r_{m-1} * Am, [x]2) = e(r_0 * B0 + ... + r_{m-1} *
+    ///   Bm, [1]2)` for the random combiners `r_i`.
+    fn batch_verify_opening_proofs(
+        open_key: &Self::OpenKey,
+        comms: &[Commitment<E>],
+        points: &[E::ScalarField],
+        evals: &[E::ScalarField],
+        proofs: &[&Self::OpeningProof],
+        combiners: &[E::ScalarField],
+    ) -> Result<bool, PlonkError> {
+        check_openings_len(comms, points, evals, proofs.len(), combiners)?;
+
+        // Compute A := r_0 * A0 + ... + r_{m-1} * Am
+        let proof_bases: Vec<E::G1Affine> = proofs.iter().map(|proof| proof.0).collect();
+        let inner = E::G1::msm_unchecked(&proof_bases, combiners);
+        // Add (A, [x]2) to the product pairing list
+        let mut g1_elems: Vec<<E as Pairing>::G1Affine> = vec![inner.into()];
+        let mut g2_elems = vec![open_key.beta_h];
+
+        // Compute B := r_0 * B0 + ... + r_{m-1} * Bm
+        let mut bases = proof_bases;
+        let mut scalars: Vec<E::ScalarField> = combiners
+            .iter()
+            .zip(points)
+            .map(|(r, point)| *r * point)
+            .collect();
+        bases.extend(comms.iter().map(|comm| comm.0));
+        scalars.extend_from_slice(combiners);
+        let sum_evals: E::ScalarField =
+            combiners.iter().zip(evals).map(|(r, eval)| *r * eval).sum();
+        bases.push(open_key.g);
+        scalars.push(-sum_evals);
+        let inner = E::G1::msm_unchecked(&bases, &scalars);
+        // Add (-B, [1]2) to the product pairing list
+        g1_elems.push((-inner).into());
+        g2_elems.push(open_key.h);
+        // Check e(A, [x]2) ?= e(B, [1]2)
+        Ok(multi_pairing::<E>(&g1_elems, &g2_elems).0 == E::TargetField::one())
+    }
+}
+
+impl<E: Pairing> PlonkPCS<E> for UnivariateIpaPCS<E::G1> {
+    type UniversalSrs = UnivariateIpaParams<E::G1>;
+    type CommitKey = UnivariateIpaParams<E::G1>;
+    type OpenKey = UnivariateIpaParams<E::G1>;
+    type OpeningProof = UnivariateIpaProof<E::G1>;
+
+    /// The parameters are transparent, `rng` is not used.
+    fn universal_setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        _rng: &mut R,
+    ) -> Result<Self::UniversalSrs, PCSError> {
+        UnivariateIpaParams::setup(max_degree)
+    }
+
+    /// The parameters are transparent, this is the same as
+    /// [`Self::universal_setup()`].
+    #[cfg(any(test, feature = "test-srs"))]
+    fn universal_setup_for_testing<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSrs, PCSError> {
+        <Self as PlonkPCS<E>>::universal_setup(max_degree, rng)
+    }
+
+    fn max_degree(srs: &Self::UniversalSrs) -> usize {
+        srs.max_degree()
+    }
+
+    fn commit_bases(ck: &Self::CommitKey) -> &[E::G1Affine] {
+        &ck.gens
+    }
+
+    fn to_plonk_commitment(comm: Self::Commitment) -> Commitment<E> {
+        Commitment(comm.0)
+    }
+
+    fn compute_opening_proof(
+        ck: &Self::CommitKey,
+        poly: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+        _commit: &dyn Fn(&DensePolynomial<E::ScalarField>) -> Result<Commitment<E>, PlonkError>,
+    ) -> Result<Self::OpeningProof, PlonkError> {
+        Ok(<Self as PolynomialCommitmentScheme>::open(ck, poly, point)?.0)
+    }
+
+    /// The cross terms are appended as commitments and the folded coefficient
+    /// as a challenge, all under `label`.
+    fn append_opening_proof<F, P, T>(
+        transcript: &mut T,
+        label: &'static [u8],
+        proof: &Self::OpeningProof,
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        for point in proof.l_vec.iter().chain(proof.r_vec.iter()) {
+            transcript.append_commitment::<E, P>(label, &Commitment(*point))?;
+        }
+        transcript.append_challenge::<E>(label, &proof.a)
+    }
+
+    /// The proofs are verified one by one, the combiners are not used.
+    fn batch_verify_opening_proofs(
+        open_key: &Self::OpenKey,
+        comms: &[Commitment<E>],
+        points: &[E::ScalarField],
+        evals: &[E::ScalarField],
+        proofs: &[&Self::OpeningProof],
+        combiners: &[E::ScalarField],
+    ) -> Result<bool, PlonkError> {
+        check_openings_len(comms, points, evals, proofs.len(), combiners)?;
+        for (((comm, point), eval), proof) in comms.iter().zip(points).zip(evals).zip(proofs) {
+            if !<Self as PolynomialCommitmentScheme>::verify(
+                open_key,
+                &UnivariateIpaCommitment(comm.0),
+                point,
+                eval,
+                proof,
+            )? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+// Check that there are as many commitments, points, evaluations, proofs and
+// combiners.
+fn check_openings_len<E: Pairing>(
+    comms: &[Commitment<E>],
+    points: &[E::ScalarField],
+    evals: &[E::ScalarField],
+    num_proofs: usize,
+    combiners: &[E::ScalarField],
+) -> Result<(), PlonkError> {
+    let len = comms.len();
+    if points.len() != len
+        || evals.len() != len
+        || num_proofs != len
+        || combiners.len() != len
+        || len == 0
+    {
+        return Err(PCSError::InvalidParameters(format!(
+            "mismatched lengths: {} commitments, {} points, {} evaluations, {} proofs, {} combiners",
+            len,
+            points.len(),
+            evals.len(),
+            num_proofs,
+            combiners.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
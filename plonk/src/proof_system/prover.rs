@@ -6,31 +6,36 @@
 
 use core::ops::Neg;
 
+#[cfg(feature = "std")]
+use super::spill::SpilledPoly;
 use super::structs::{
-    eval_merged_lookup_witness, eval_merged_table, Challenges, Oracles, PlookupEvaluations,
-    PlookupOracles, ProofEvaluations, ProvingKey,
+    eval_merged_lookup_witness, eval_merged_table, Challenges, CosetEvals, MemoryBudget, Oracles,
+    PlookupEvaluations, PlookupOracles, ProofEvaluations, ProvingKey,
 };
 use crate::{
     constants::domain_size_ratio,
     errors::{PlonkError, SnarkError::*},
-    proof_system::structs::CommitKey,
+    proof_system::{
+        pcs::PlonkPCS,
+        structs::{CommitKey, OpeningProof},
+    },
 };
-use ark_ec::pairing::Pairing;
+use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
 use ark_ff::{FftField, Field, One, UniformRand, Zero};
 use ark_poly::{
     univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain,
     Polynomial, Radix2EvaluationDomain,
 };
 use ark_std::{
+    borrow::Cow,
+    format,
+    marker::PhantomData,
     rand::{CryptoRng, RngCore},
     string::ToString,
     vec,
     vec::Vec,
 };
-use jf_primitives::pcs::{
-    prelude::{Commitment, UnivariateKzgPCS},
-    PolynomialCommitmentScheme,
-};
+use jf_primitives::pcs::prelude::{Commitment, PCSError, UnivariateKzgPCS};
 use jf_relation::{constants::GATE_WIDTH, Arithmetization};
 use jf_utils::par_utils::parallelizable_slice_iter;
 #[cfg(feature = "parallel")]
@@ -41,13 +46,90 @@ type CommitmentsAndPolys<E> = (
     Vec<DensePolynomial<<E as Pairing>::ScalarField>>,
 );
 
+/// The maximal number of chunks of the coset of the quotient polynomial
+/// domain per point of the domain, i.e. per chunk of size `n`.
+const MAX_QUOT_CHUNKS_PER_RATIO: usize = 8;
+
 /// A Plonk IOP prover.
-pub(crate) struct Prover<E: Pairing> {
+pub(crate) struct Prover<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     domain: Radix2EvaluationDomain<E::ScalarField>,
     quot_domain: GeneralEvaluationDomain<E::ScalarField>,
+    // Memory budget, unbounded if None.
+    memory_budget: Option<MemoryBudget>,
+    // The online oracles spilled to temporary files in the third round.
+    #[cfg(feature = "std")]
+    spilled_oracles: Vec<QuotOracles<SpilledPoly<E::ScalarField>>>,
+    _pcs: PhantomData<PCS>,
+}
+
+/// The coefficients of a polynomial that is evaluated over the chunks of the
+/// coset of the quotient polynomial domain.
+pub(crate) trait QuotOracle<F: Field>: Sync {
+    /// Fold the coefficients of degree `i + k * size` into the one of degree
+    /// `i` with the factor `offset_pow_size^k`, so that the result evaluates
+    /// as the polynomial over a coset of size `size` with offset `o`, where
+    /// `o^size = offset_pow_size`.
+    fn fold_coeffs(&self, size: usize, offset_pow_size: F) -> Result<Vec<F>, PlonkError>;
 }
 
-impl<E: Pairing> Prover<E> {
+impl<F: Field> QuotOracle<F> for DensePolynomial<F> {
+    fn fold_coeffs(&self, size: usize, offset_pow_size: F) -> Result<Vec<F>, PlonkError> {
+        let mut coeffs = vec![F::zero(); size];
+        let mut factor = F::one();
+        for high_coeffs in self.coeffs.chunks(size) {
+            for (coeff, &high_coeff) in coeffs.iter_mut().zip(high_coeffs.iter()) {
+                *coeff += factor * high_coeff;
+            }
+            factor *= offset_pow_size;
+        }
+        Ok(coeffs)
+    }
+}
+
+impl<F: Field, P: QuotOracle<F>> QuotOracle<F> for &P {
+    fn fold_coeffs(&self, size: usize, offset_pow_size: F) -> Result<Vec<F>, PlonkError> {
+        (*self).fold_coeffs(size, offset_pow_size)
+    }
+}
+
+/// The online oracles of a proving instance used to compute the quotient
+/// polynomial.
+pub(crate) struct QuotOracles<P> {
+    pub(crate) wire_polys: Vec<P>,
+    pub(crate) pub_inp_poly: P,
+    pub(crate) prod_perm_poly: P,
+    pub(crate) h_polys: Vec<P>,
+    pub(crate) prod_lookup_poly: P,
+}
+
+impl<'a, F: FftField> From<&'a Oracles<F>> for QuotOracles<&'a DensePolynomial<F>> {
+    fn from(oracles: &'a Oracles<F>) -> Self {
+        Self {
+            wire_polys: oracles.wire_polys.iter().collect(),
+            pub_inp_poly: &oracles.pub_inp_poly,
+            prod_perm_poly: &oracles.prod_perm_poly,
+            h_polys: oracles.plookup_oracles.h_polys.iter().collect(),
+            prod_lookup_poly: &oracles.plookup_oracles.prod_lookup_poly,
+        }
+    }
+}
+
+/// Coset evaluations over a chunk of the polynomials of the Plookup argument
+/// that are also evaluated at the points `x * g`.
+#[derive(Clone)]
+struct PlookupChunkEvals<'a, F> {
+    w_3: &'a [F],
+    w_4: &'a [F],
+    h_1: &'a [F],
+    h_2: &'a [F],
+    prod_lookup: &'a [F],
+    range_table: &'a [F],
+    key_table: &'a [F],
+    table_dom_sep: &'a [F],
+    q_lookup: &'a [F],
+}
+
+impl<E: Pairing, PCS: PlonkPCS<E>> Prover<E, PCS> {
     /// Construct a Plonk prover that uses a domain with size `domain_size` and
     /// quotient polynomial domain with a size that is larger than the degree of
     /// the quotient polynomial.
@@ -63,9 +145,32 @@ impl<E: Pairing> Prover<E> {
         Ok(Self {
             domain,
             quot_domain,
+            memory_budget: None,
+            #[cfg(feature = "std")]
+            spilled_oracles: vec![],
+            _pcs: PhantomData,
         })
     }
 
+    /// The size of the quotient polynomial domain.
+    pub(crate) fn quot_domain_size(&self) -> usize {
+        self.quot_domain.size()
+    }
+
+    /// The `i`-th point of the coset over which the quotient polynomial is
+    /// evaluated, i.e., `g * w^i` where `w` is the generator of the quotient
+    /// polynomial domain.
+    pub(crate) fn quot_coset_element(&self, i: usize) -> E::ScalarField {
+        E::ScalarField::GENERATOR * self.quot_domain.element(i)
+    }
+
+    /// Bound the memory used by the prover by `memory_budget`, as far as
+    /// possible, see [`Self::run_3rd_round()`] and [`Self::commit()`].
+    pub(crate) fn with_memory_budget(mut self, memory_budget: Option<MemoryBudget>) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
     /// Round 1:
     /// 1. Compute and commit wire witness polynomials.
     /// 2. Compute public input polynomial.
@@ -74,7 +179,7 @@ impl<E: Pairing> Prover<E> {
     pub(crate) fn run_1st_round<C: Arithmetization<E::ScalarField>, R: CryptoRng + RngCore>(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &CommitKey<E, PCS>,
         cs: &C,
     ) -> Result<(CommitmentsAndPolys<E>, DensePolynomial<E::ScalarField>), PlonkError> {
         let wire_polys: Vec<DensePolynomial<E::ScalarField>> = cs
@@ -82,7 +187,7 @@ impl<E: Pairing> Prover<E> {
             .into_iter()
             .map(|poly| self.mask_polynomial(prng, poly, 1))
             .collect();
-        let wires_poly_comms = UnivariateKzgPCS::batch_commit(ck, &wire_polys)?;
+        let wires_poly_comms = self.batch_commit(ck, &wire_polys)?;
         let pub_input_poly = cs.compute_pub_input_polynomial()?;
         Ok(((wires_poly_comms, wire_polys), pub_input_poly))
     }
@@ -99,7 +204,7 @@ impl<E: Pairing> Prover<E> {
     >(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &CommitKey<E, PCS>,
         cs: &C,
         tau: E::ScalarField,
     ) -> Result<
@@ -116,7 +221,7 @@ impl<E: Pairing> Prover<E> {
         let h_1_poly = self.mask_polynomial(prng, h_1_poly, 2);
         let h_2_poly = self.mask_polynomial(prng, h_2_poly, 2);
         let h_polys = vec![h_1_poly, h_2_poly];
-        let h_poly_comms = UnivariateKzgPCS::batch_commit(ck, &h_polys)?;
+        let h_poly_comms = self.batch_commit(ck, &h_polys)?;
         Ok(((h_poly_comms, h_polys), sorted_vec, merged_lookup_table))
     }
 
@@ -125,7 +230,7 @@ impl<E: Pairing> Prover<E> {
     pub(crate) fn run_2nd_round<C: Arithmetization<E::ScalarField>, R: CryptoRng + RngCore>(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &CommitKey<E, PCS>,
         cs: &C,
         challenges: &Challenges<E::ScalarField>,
     ) -> Result<(Commitment<E>, DensePolynomial<E::ScalarField>), PlonkError> {
@@ -134,7 +239,7 @@ impl<E: Pairing> Prover<E> {
             cs.compute_prod_permutation_polynomial(&challenges.beta, &challenges.gamma)?,
             2,
         );
-        let prod_perm_comm = UnivariateKzgPCS::commit(ck, &prod_perm_poly)?;
+        let prod_perm_comm = self.commit(ck, &prod_perm_poly)?;
         Ok((prod_perm_comm, prod_perm_poly))
     }
 
@@ -147,7 +252,7 @@ impl<E: Pairing> Prover<E> {
     >(
         &self,
         prng: &mut R,
-        ck: &CommitKey<E>,
+        ck: &CommitKey<E, PCS>,
         cs: &C,
         challenges: &Challenges<E::ScalarField>,
         merged_lookup_table: Option<&Vec<E::ScalarField>>,
@@ -170,30 +275,111 @@ impl<E: Pairing> Prover<E> {
             )?,
             2,
         );
-        let prod_lookup_comm = UnivariateKzgPCS::commit(ck, &prod_lookup_poly)?;
+        let prod_lookup_comm = self.commit(ck, &prod_lookup_poly)?;
         Ok((prod_lookup_comm, prod_lookup_poly))
     }
 
     /// Round 3: Return the split quotient polynomials and their commitments.
     /// Note that the first `num_wire_types`-1 split quotient polynomials
     /// have degree `domain_size`+1.
+    ///
+    /// With a memory budget, the quotient polynomial is computed over chunks
+    /// of its coset small enough for the budget, and if that is not enough
+    /// the online oracles are spilled to temporary files until
+    /// [`Self::restore_oracles()`] is called.
     pub(crate) fn run_3rd_round<R: CryptoRng + RngCore>(
-        &self,
+        &mut self,
         prng: &mut R,
-        ck: &CommitKey<E>,
-        pks: &[&ProvingKey<E>],
+        ck: &CommitKey<E, PCS>,
+        pks: &[&ProvingKey<E, PCS>],
         challenges: &Challenges<E::ScalarField>,
-        online_oracles: &[Oracles<E::ScalarField>],
+        online_oracles: &mut [Oracles<E::ScalarField>],
         num_wire_types: usize,
     ) -> Result<CommitmentsAndPolys<E>, PlonkError> {
-        let quot_poly =
-            self.compute_quotient_polynomial(challenges, pks, online_oracles, num_wire_types)?;
-        let split_quot_polys = self.split_quotient_polynomial(prng, &quot_poly, num_wire_types)?;
-        let split_quot_poly_comms = UnivariateKzgPCS::batch_commit(ck, &split_quot_polys)?;
+        let (num_chunks, spill) = self.plan_quot_memory(pks, online_oracles, num_wire_types);
+        let quot_poly = if spill {
+            self.compute_spilled_quotient_polynomial(
+                challenges,
+                pks,
+                online_oracles,
+                num_wire_types,
+                num_chunks,
+            )?
+        } else {
+            let online_oracles: Vec<QuotOracles<_>> =
+                online_oracles.iter().map(QuotOracles::from).collect();
+            self.compute_quotient_polynomial(
+                challenges,
+                pks,
+                &online_oracles,
+                num_wire_types,
+                num_chunks,
+            )?
+        };
+        let split_quot_polys = self.split_quotient_polynomial(prng, quot_poly, num_wire_types)?;
+        let split_quot_poly_comms = self.batch_commit(ck, &split_quot_polys)?;
 
         Ok((split_quot_poly_comms, split_quot_polys))
     }
 
+    /// Compute the quotient polynomial with the online oracles spilled to
+    /// temporary files.
+    #[cfg(feature = "std")]
+    fn compute_spilled_quotient_polynomial(
+        &mut self,
+        challenges: &Challenges<E::ScalarField>,
+        pks: &[&ProvingKey<E, PCS>],
+        online_oracles: &mut [Oracles<E::ScalarField>],
+        num_wire_types: usize,
+        num_chunks: usize,
+    ) -> Result<DensePolynomial<E::ScalarField>, PlonkError> {
+        self.spilled_oracles = online_oracles
+            .iter_mut()
+            .map(QuotOracles::spill)
+            .collect::<Result<_, _>>()?;
+        self.compute_quotient_polynomial(
+            challenges,
+            pks,
+            &self.spilled_oracles,
+            num_wire_types,
+            num_chunks,
+        )
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn compute_spilled_quotient_polynomial(
+        &mut self,
+        _challenges: &Challenges<E::ScalarField>,
+        _pks: &[&ProvingKey<E, PCS>],
+        _online_oracles: &mut [Oracles<E::ScalarField>],
+        _num_wire_types: usize,
+        _num_chunks: usize,
+    ) -> Result<DensePolynomial<E::ScalarField>, PlonkError> {
+        Err(ParameterError("spilling to files requires the std feature".to_string()).into())
+    }
+
+    /// Restore the online oracles spilled in the third round, if any. With a
+    /// memory budget, the public input polynomials, which are not needed
+    /// after the third round, are dropped.
+    pub(crate) fn restore_oracles(
+        &mut self,
+        online_oracles: &mut [Oracles<E::ScalarField>],
+    ) -> Result<(), PlonkError> {
+        #[cfg(feature = "std")]
+        for (oracles, spilled) in online_oracles
+            .iter_mut()
+            .zip(ark_std::mem::take(&mut self.spilled_oracles))
+        {
+            spilled.restore(oracles)?;
+        }
+        if self.memory_budget.is_some() {
+            for oracles in online_oracles.iter_mut() {
+                oracles.pub_inp_poly = DensePolynomial::zero();
+            }
+        }
+        Ok(())
+    }
+
     /// Round 4: Compute linearization polynomial and evaluate polynomials to be
     /// opened.
     ///
@@ -201,7 +387,7 @@ impl<E: Pairing> Prover<E> {
     /// Return evaluations of the Plonk proof.
     pub(crate) fn compute_evaluations(
         &self,
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         challenges: &Challenges<E::ScalarField>,
         online_oracles: &Oracles<E::ScalarField>,
         num_wire_types: usize,
@@ -229,7 +415,7 @@ impl<E: Pairing> Prover<E> {
     /// polynomials
     pub(crate) fn compute_plookup_evaluations(
         &self,
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         challenges: &Challenges<E::ScalarField>,
         online_oracles: &Oracles<E::ScalarField>,
     ) -> Result<PlookupEvaluations<E::ScalarField>, PlonkError> {
@@ -296,7 +482,7 @@ impl<E: Pairing> Prover<E> {
     pub(crate) fn compute_non_quotient_component_for_lin_poly(
         &self,
         alpha_base: E::ScalarField,
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         challenges: &Challenges<E::ScalarField>,
         online_oracles: &Oracles<E::ScalarField>,
         poly_evals: &ProofEvaluations<E::ScalarField>,
@@ -309,7 +495,9 @@ impl<E: Pairing> Prover<E> {
             poly_evals,
             &online_oracles.prod_perm_poly,
         );
-        let mut lin_poly = r_circ + r_perm;
+        let mut lin_poly = r_circ;
+        lin_poly += &r_perm;
+        drop(r_perm);
         // compute Plookup contribution if support lookup
         let r_lookup = plookup_evals.map(|plookup_evals| {
             self.compute_lin_poly_plookup_contribution(
@@ -321,7 +509,7 @@ impl<E: Pairing> Prover<E> {
             )
         });
         if let Some(lookup_poly) = r_lookup {
-            lin_poly = lin_poly + lookup_poly;
+            lin_poly += &lookup_poly;
         }
 
         lin_poly = Self::mul_poly(&lin_poly, &alpha_base);
@@ -343,7 +531,7 @@ impl<E: Pairing> Prover<E> {
         let mut coeff = E::ScalarField::one();
         for poly in quot_polys.iter().skip(1) {
             coeff *= zeta_to_n_plus_2;
-            r_quot = r_quot + Self::mul_poly(poly, &coeff);
+            r_quot += (coeff, poly);
         }
         r_quot = Self::mul_poly(&r_quot, &vanish_eval.neg());
         Ok(r_quot)
@@ -351,15 +539,45 @@ impl<E: Pairing> Prover<E> {
 
     /// Compute (aggregated) polynomial opening proofs at point `zeta` and
     /// `zeta * domain_generator`. TODO: Parallelize the computation.
+    #[allow(clippy::type_complexity)]
     pub(crate) fn compute_opening_proofs(
         &self,
-        ck: &CommitKey<E>,
-        pks: &[&ProvingKey<E>],
+        ck: &CommitKey<E, PCS>,
+        pks: &[&ProvingKey<E, PCS>],
         zeta: &E::ScalarField,
         v: &E::ScalarField,
         online_oracles: &[Oracles<E::ScalarField>],
         lin_poly: &DensePolynomial<E::ScalarField>,
-    ) -> Result<(Commitment<E>, Commitment<E>), PlonkError> {
+    ) -> Result<(OpeningProof<E, PCS>, OpeningProof<E, PCS>), PlonkError> {
+        let (batch_poly, shifted_batch_poly) =
+            Self::compute_batched_polys(pks, v, online_oracles, lin_poly)?;
+        let opening_proof =
+            PCS::compute_opening_proof(ck, &batch_poly, zeta, &|poly| self.commit(ck, poly))?;
+        let shifted_opening_proof = PCS::compute_opening_proof(
+            ck,
+            &shifted_batch_poly,
+            &(self.domain.group_gen * zeta),
+            &|poly| self.commit(ck, poly),
+        )?;
+        Ok((opening_proof, shifted_opening_proof))
+    }
+
+    /// Compute the aggregations, with the powers of the random combiner `v`, of
+    /// the polynomials opened at point `zeta` and of the ones opened at point
+    /// `zeta * domain_generator`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn compute_batched_polys(
+        pks: &[&ProvingKey<E, PCS>],
+        v: &E::ScalarField,
+        online_oracles: &[Oracles<E::ScalarField>],
+        lin_poly: &DensePolynomial<E::ScalarField>,
+    ) -> Result<
+        (
+            DensePolynomial<E::ScalarField>,
+            DensePolynomial<E::ScalarField>,
+        ),
+        PlonkError,
+    > {
         if pks.is_empty() || pks.len() != online_oracles.len() {
             return Err(ParameterError(
                 "inconsistent pks/online oracles when computing opening proofs".to_string(),
@@ -384,9 +602,7 @@ impl<E: Pairing> Prover<E> {
                 polys_ref.extend(Self::plookup_open_polys_ref(oracles, pk)?);
             }
         }
-
-        let opening_proof =
-            Self::compute_batched_witness_polynomial_commitment(ck, &polys_ref, v, zeta)?;
+        let batch_poly = Self::batch_polys(&polys_ref, v);
 
         // List the polynomials to be opened at point `zeta * w`.
         let mut polys_ref = vec![];
@@ -399,26 +615,19 @@ impl<E: Pairing> Prover<E> {
                 polys_ref.extend(Self::plookup_shifted_open_polys_ref(oracles, pk)?);
             }
         }
+        let shifted_batch_poly = Self::batch_polys(&polys_ref, v);
 
-        let shifted_opening_proof = Self::compute_batched_witness_polynomial_commitment(
-            ck,
-            &polys_ref,
-            v,
-            &(self.domain.group_gen * zeta),
-        )?;
-
-        Ok((opening_proof, shifted_opening_proof))
+        Ok((batch_poly, shifted_batch_poly))
     }
 }
 
-/// Private helper methods
-impl<E: Pairing> Prover<E> {
+impl<E: Pairing, PCS: PlonkPCS<E>> Prover<E, PCS> {
     /// Return the list of plookup polynomials to be opened at point `zeta`
     /// The order should be consistent with the verifier side.
     #[inline]
     fn plookup_open_polys_ref<'a>(
         oracles: &'a Oracles<E::ScalarField>,
-        pk: &'a ProvingKey<E>,
+        pk: &'a ProvingKey<E, PCS>,
     ) -> Result<Vec<&'a DensePolynomial<E::ScalarField>>, PlonkError> {
         Ok(vec![
             &pk.plookup_pk.as_ref().unwrap().range_table_poly,
@@ -435,7 +644,7 @@ impl<E: Pairing> Prover<E> {
     #[inline]
     fn plookup_shifted_open_polys_ref<'a>(
         oracles: &'a Oracles<E::ScalarField>,
-        pk: &'a ProvingKey<E>,
+        pk: &'a ProvingKey<E, PCS>,
     ) -> Result<Vec<&'a DensePolynomial<E::ScalarField>>, PlonkError> {
         Ok(vec![
             &oracles.plookup_oracles.prod_lookup_poly,
@@ -463,35 +672,127 @@ impl<E: Pairing> Prover<E> {
         mask_poly + poly
     }
 
-    /// Return a batched opening proof given a list of polynomials `polys_ref`,
-    /// evaluation point `eval_point`, and randomized combiner `r`.
-    fn compute_batched_witness_polynomial_commitment(
-        ck: &CommitKey<E>,
+    /// Return the aggregation of the polynomials `polys_ref` with the powers of
+    /// the random combiner `r`.
+    fn batch_polys(
         polys_ref: &[&DensePolynomial<E::ScalarField>],
         r: &E::ScalarField,
-        eval_point: &E::ScalarField,
-    ) -> Result<Commitment<E>, PlonkError> {
-        // Compute the aggregated polynomial
-        let (batch_poly, _) = polys_ref.iter().fold(
-            (DensePolynomial::zero(), E::ScalarField::one()),
-            |(acc, coeff), &poly| (acc + Self::mul_poly(poly, &coeff), coeff * r),
-        );
+    ) -> DensePolynomial<E::ScalarField> {
+        let mut batch_poly = DensePolynomial::zero();
+        let mut coeff = E::ScalarField::one();
+        for &poly in polys_ref.iter() {
+            batch_poly += (coeff, poly);
+            coeff *= r;
+        }
+        batch_poly
+    }
 
-        // Compute opening witness polynomial and its commitment
-        let divisor =
-            DensePolynomial::from_coefficients_vec(vec![-*eval_point, E::ScalarField::one()]);
-        let witness_poly = &batch_poly / &divisor;
+    /// Commit to `polys`, see [`Self::commit()`].
+    fn batch_commit(
+        &self,
+        ck: &CommitKey<E, PCS>,
+        polys: &[DensePolynomial<E::ScalarField>],
+    ) -> Result<Vec<Commitment<E>>, PlonkError> {
+        if self.memory_budget.is_none() {
+            return parallelizable_slice_iter(polys)
+                .map(|poly| Ok(PCS::to_plonk_commitment(PCS::commit(ck, poly)?)))
+                .collect();
+        }
+        polys.iter().map(|poly| self.commit(ck, poly)).collect()
+    }
 
-        UnivariateKzgPCS::commit(ck, &witness_poly).map_err(PlonkError::PCSError)
+    /// Commit to `poly`. With a memory budget, the commitment is computed
+    /// over chunks of `commit_chunk_size` coefficients at a time, which
+    /// bounds the scratch space of the multi-scalar multiplications.
+    fn commit(
+        &self,
+        ck: &CommitKey<E, PCS>,
+        poly: &DensePolynomial<E::ScalarField>,
+    ) -> Result<Commitment<E>, PlonkError> {
+        let chunk_size = match self.memory_budget {
+            Some(memory_budget) => memory_budget.commit_chunk_size,
+            None => return Ok(PCS::to_plonk_commitment(PCS::commit(ck, poly)?)),
+        };
+        if chunk_size == 0 {
+            return Err(
+                ParameterError("the commit chunk size must be positive".to_string()).into(),
+            );
+        }
+        let bases = PCS::commit_bases(ck);
+        if poly.coeffs.len() > bases.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly degree {} is larger than allowed {}",
+                poly.degree(),
+                bases.len()
+            ))
+            .into());
+        }
+        let comm: E::G1 = poly
+            .coeffs
+            .chunks(chunk_size)
+            .zip(bases.chunks(chunk_size))
+            .map(|(scalars, bases)| E::G1::msm_unchecked(bases, scalars))
+            .sum();
+        Ok(Commitment(comm.into_affine()))
+    }
+
+    /// Compute the evaluations of the preprocessed polynomials of `pk` over the
+    /// coset of the quotient polynomial domain.
+    pub(crate) fn compute_coset_evals(
+        &self,
+        pk: &ProvingKey<E, PCS>,
+    ) -> CosetEvals<E::ScalarField> {
+        let coset = self
+            .quot_domain
+            .get_coset(E::ScalarField::GENERATOR)
+            .unwrap();
+        let coset_ffts = |polys: &[&DensePolynomial<E::ScalarField>]| {
+            parallelizable_slice_iter(polys)
+                .map(|poly| coset.fft(poly.coeffs()))
+                .collect::<Vec<_>>()
+        };
+        let plookup_polys = match pk.plookup_pk.as_ref() {
+            Some(plookup_pk) => vec![
+                &plookup_pk.range_table_poly,
+                &plookup_pk.key_table_poly,
+                &plookup_pk.table_dom_sep_poly,
+                &plookup_pk.q_dom_sep_poly,
+            ],
+            None => vec![],
+        };
+        CosetEvals {
+            quot_domain_size: self.quot_domain.size(),
+            selectors: coset_ffts(&pk.selectors.iter().collect::<Vec<_>>()),
+            sigmas: coset_ffts(&pk.sigmas.iter().collect::<Vec<_>>()),
+            plookup: coset_ffts(&plookup_polys),
+        }
     }
 
     /// Compute the quotient polynomial via (i)FFTs.
-    fn compute_quotient_polynomial(
+    ///
+    /// The coset of the quotient polynomial domain with size `m` is split into
+    /// `c = num_chunks` strided cosets of size `s = m / c`, the `j`-th of
+    /// which contains the points `g * w^{j + c * t}`, where `w` is the
+    /// generator of the quotient polynomial domain. The quotient polynomial is
+    /// evaluated over one chunk at a time, and the coset evaluations of the
+    /// preprocessed polynomials are computed one polynomial at a time, so that
+    /// only a few evaluation vectors of a chunk are in memory besides the `m`
+    /// coefficients of the quotient polynomial itself.
+    ///
+    /// Over the `j`-th chunk, `X^s = (g * w^j)^s`, so the evaluations of the
+    /// quotient polynomial `q(X)` are the ones of `sum_{r < s} A_{j,r} X^r`
+    /// with `A_{j,r} = sum_{k < c} q_{r + k * s} * (g^s * w^{j * s})^k`. The
+    /// `A_{j,r}` are interpolated from each chunk, and the coefficients
+    /// `q_{r + k * s}` are then interpolated from `A_{0,r}, .., A_{c-1,r}`
+    /// over the coset of size `c` with offset `g^s`, since `w^s` generates
+    /// the domain of size `c`.
+    fn compute_quotient_polynomial<P: QuotOracle<E::ScalarField>>(
         &self,
         challenges: &Challenges<E::ScalarField>,
-        pks: &[&ProvingKey<E>],
-        online_oracles: &[Oracles<E::ScalarField>],
+        pks: &[&ProvingKey<E, PCS>],
+        online_oracles: &[QuotOracles<P>],
         num_wire_types: usize,
+        num_chunks: usize,
     ) -> Result<DensePolynomial<E::ScalarField>, PlonkError> {
         if pks.is_empty() || pks.len() != online_oracles.len() {
             return Err(ParameterError(
@@ -513,208 +814,473 @@ impl<E: Pairing> Prover<E> {
             })
             .collect();
 
-        // Compute coset evaluations of the quotient polynomial.
-        let mut quot_poly_coset_evals_sum = vec![E::ScalarField::zero(); m];
-        let mut alpha_base = E::ScalarField::one();
+        // The random combiners for aggregating multiple proving instances.
         let alpha_3 = challenges.alpha.square() * challenges.alpha;
         let alpha_7 = alpha_3.square() * challenges.alpha;
-        // TODO: figure out if the unwrap is safe/map error?
-        let coset = self
-            .quot_domain
-            .get_coset(E::ScalarField::GENERATOR)
-            .unwrap();
-        // enumerate proving instances
-        for (oracles, pk) in online_oracles.iter().zip(pks.iter()) {
-            // lookup_flag = 1 if support Plookup argument.
-            let lookup_flag = pk.plookup_pk.is_some();
-
-            // Compute coset evaluations.
-            let selectors_coset_fft: Vec<Vec<E::ScalarField>> =
-                parallelizable_slice_iter(&pk.selectors)
-                    .map(|poly| coset.fft(poly.coeffs()))
-                    .collect();
-            let sigmas_coset_fft: Vec<Vec<E::ScalarField>> = parallelizable_slice_iter(&pk.sigmas)
-                .map(|poly| coset.fft(poly.coeffs()))
+        let mut alpha_base = E::ScalarField::one();
+        let alpha_bases: Vec<E::ScalarField> = pks
+            .iter()
+            .map(|pk| {
+                let base = alpha_base;
+                // lookup_flag = 1 if support Plookup argument.
+                if pk.plookup_pk.is_some() {
+                    alpha_base *= alpha_7;
+                } else {
+                    alpha_base *= alpha_3;
+                }
+                base
+            })
+            .collect();
+
+        let chunk_size = m / num_chunks;
+        let chunk_domain = Radix2EvaluationDomain::<E::ScalarField>::new(chunk_size)
+            .ok_or(PlonkError::DomainCreationError)?;
+        let chunk_coset = |chunk: usize| {
+            chunk_domain
+                .get_coset(E::ScalarField::GENERATOR * self.quot_domain.element(chunk))
+                .ok_or(PlonkError::DomainCreationError)
+        };
+        // The rows `A_{j,0}, .., A_{j,s-1}` for every chunk `j`.
+        let mut quot_poly_coeffs = Vec::with_capacity(m);
+        for chunk in 0..num_chunks {
+            let mut chunk_evals = vec![E::ScalarField::zero(); chunk_size];
+            for ((oracles, pk), alpha_base) in online_oracles
+                .iter()
+                .zip(pks.iter())
+                .zip(alpha_bases.iter())
+            {
+                let evals = self.compute_quotient_chunk(
+                    challenges,
+                    pk,
+                    oracles,
+                    num_wire_types,
+                    &z_h_inv,
+                    chunk,
+                    num_chunks,
+                    &chunk_coset,
+                )?;
+                for (sum, eval) in chunk_evals.iter_mut().zip(evals.iter()) {
+                    *sum += *alpha_base * eval;
+                }
+            }
+            chunk_coset(chunk)?.ifft_in_place(&mut chunk_evals);
+            quot_poly_coeffs.extend(chunk_evals);
+        }
+        if num_chunks > 1 {
+            let column_coset = Radix2EvaluationDomain::<E::ScalarField>::new(num_chunks)
+                .and_then(|domain| {
+                    domain.get_coset(E::ScalarField::GENERATOR.pow([chunk_size as u64]))
+                })
+                .ok_or(PlonkError::DomainCreationError)?;
+            let mut column = vec![E::ScalarField::zero(); num_chunks];
+            for r in 0..chunk_size {
+                for (k, coeff) in column.iter_mut().enumerate() {
+                    *coeff = quot_poly_coeffs[r + k * chunk_size];
+                }
+                column_coset.ifft_in_place(&mut column);
+                for (k, coeff) in column.iter().enumerate() {
+                    quot_poly_coeffs[r + k * chunk_size] = *coeff;
+                }
+            }
+        }
+        Ok(DensePolynomial::from_coefficients_vec(quot_poly_coeffs))
+    }
+
+    /// Compute the evaluations of the quotient polynomial of a proving
+    /// instance over the `chunk`-th of the `num_chunks` chunks of the coset
+    /// of the quotient polynomial domain, see
+    /// [`Self::compute_quotient_polynomial()`].
+    ///
+    /// The points `x * g`, where `g` is the generator of the domain, of the
+    /// `chunk`-th chunk are the points of the `next_chunk`-th chunk, shifted
+    /// by `next_shift`. Both chunks are the same when `num_chunks` divides the
+    /// ratio between the sizes of the quotient polynomial domain and of the
+    /// domain.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_quotient_chunk<'a, P, C>(
+        &self,
+        challenges: &Challenges<E::ScalarField>,
+        pk: &'a ProvingKey<E, PCS>,
+        oracles: &QuotOracles<P>,
+        num_wire_types: usize,
+        z_h_inv: &[E::ScalarField],
+        chunk: usize,
+        num_chunks: usize,
+        chunk_coset: &C,
+    ) -> Result<Vec<E::ScalarField>, PlonkError>
+    where
+        P: QuotOracle<E::ScalarField>,
+        C: Fn(usize) -> Result<Radix2EvaluationDomain<E::ScalarField>, PlonkError>,
+    {
+        let m = self.quot_domain.size();
+        let domain_size_ratio = z_h_inv.len();
+        let chunk_size = m / num_chunks;
+        let next_chunk = (chunk + domain_size_ratio) % num_chunks;
+        let next_shift = (chunk + domain_size_ratio) / num_chunks % chunk_size;
+        let next_in_chunk = next_chunk == chunk;
+        let cur_coset = chunk_coset(chunk)?;
+        let next_coset = chunk_coset(next_chunk)?;
+        // lookup_flag = 1 if support Plookup argument.
+        let lookup_flag = pk.plookup_pk.is_some();
+        // Coset evaluations of the preprocessed polynomials are taken from the
+        // proving key if cached.
+        let coset_evals = pk
+            .coset_evals
+            .as_ref()
+            .filter(|evals| evals.quot_domain_size == m);
+        let preprocessed_fft = |cached: Option<&'a Vec<E::ScalarField>>,
+                                poly: &DensePolynomial<E::ScalarField>,
+                                next: bool|
+         -> Result<Cow<'a, [E::ScalarField]>, PlonkError> {
+            let (chunk, coset) = if next {
+                (next_chunk, &next_coset)
+            } else {
+                (chunk, &cur_coset)
+            };
+            Ok(match cached {
+                Some(evals) => Self::coset_evals_chunk(evals, chunk, num_chunks),
+                None => Cow::Owned(Self::chunk_coset_fft(coset, poly)?),
+            })
+        };
+
+        // Compute coset evaluations of the online oracles.
+        let wire_polys_coset_fft: Vec<Vec<E::ScalarField>> =
+            parallelizable_slice_iter(&oracles.wire_polys)
+                .map(|poly| Self::chunk_coset_fft(&cur_coset, poly))
+                .collect::<Result<_, _>>()?;
+        let prod_perm_poly_coset_fft = Self::chunk_coset_fft(&cur_coset, &oracles.prod_perm_poly)?;
+        let prod_perm_poly_next_coset_fft = if next_in_chunk {
+            Cow::Borrowed(&prod_perm_poly_coset_fft[..])
+        } else {
+            Cow::Owned(Self::chunk_coset_fft(&next_coset, &oracles.prod_perm_poly)?)
+        };
+        let w = |t: usize| -> Vec<E::ScalarField> {
+            (0..num_wire_types)
+                .map(|j| wire_polys_coset_fft[j][t])
+                .collect()
+        };
+
+        // Compute the circuit part, one selector at a time, starting from the
+        // public input. The lookup selector is kept for the Plookup part.
+        let mut t_circ = Self::chunk_coset_fft(&cur_coset, &oracles.pub_inp_poly)?;
+        let mut q_lookup_coset_fft = None;
+        for (i, selector) in pk.selectors.iter().enumerate() {
+            let cached = coset_evals.map(|evals| &evals.selectors[i]);
+            let selector_coset_fft = preprocessed_fft(cached, selector, false)?;
+            if lookup_flag && i == pk.selectors.len() - 1 {
+                q_lookup_coset_fft = Some(selector_coset_fft);
+                continue;
+            }
+            t_circ = parallelizable_slice_iter(&t_circ)
+                .enumerate()
+                .map(|(t, acc)| {
+                    *acc + selector_coset_fft[t]
+                        * Self::compute_quotient_circuit_term(i, &wire_polys_coset_fft, t)
+                })
                 .collect();
-            let wire_polys_coset_fft: Vec<Vec<E::ScalarField>> =
-                parallelizable_slice_iter(&oracles.wire_polys)
-                    .map(|poly| coset.fft(poly.coeffs()))
-                    .collect();
-
-            // TODO: (binyi) we can also compute below in parallel with
-            // `wire_polys_coset_fft`.
-            let prod_perm_poly_coset_fft = coset.fft(oracles.prod_perm_poly.coeffs());
-            let pub_input_poly_coset_fft = coset.fft(oracles.pub_inp_poly.coeffs());
-
-            // Compute coset evaluations of Plookup online oracles.
-            let (
-                table_dom_sep_coset_fft,
-                q_dom_sep_coset_fft,
-                range_table_coset_fft,
-                key_table_coset_fft,
-                h_coset_ffts,
-                prod_lookup_poly_coset_fft,
-            ) = if lookup_flag {
-                let table_dom_sep_coset_fft =
-                    coset.fft(pk.plookup_pk.as_ref().unwrap().table_dom_sep_poly.coeffs());
-                let q_dom_sep_coset_fft =
-                    coset.fft(pk.plookup_pk.as_ref().unwrap().q_dom_sep_poly.coeffs());
-                let range_table_coset_fft =
-                    coset.fft(pk.plookup_pk.as_ref().unwrap().range_table_poly.coeffs()); // safe unwrap
-                let key_table_coset_fft =
-                    coset.fft(pk.plookup_pk.as_ref().unwrap().key_table_poly.coeffs()); // safe unwrap
-                let h_coset_ffts: Vec<Vec<E::ScalarField>> =
-                    parallelizable_slice_iter(&oracles.plookup_oracles.h_polys)
-                        .map(|poly| coset.fft(poly.coeffs()))
-                        .collect();
-                let prod_lookup_poly_coset_fft =
-                    coset.fft(oracles.plookup_oracles.prod_lookup_poly.coeffs());
-                (
-                    Some(table_dom_sep_coset_fft),
-                    Some(q_dom_sep_coset_fft),
-                    Some(range_table_coset_fft),
-                    Some(key_table_coset_fft),
-                    Some(h_coset_ffts),
-                    Some(prod_lookup_poly_coset_fft),
-                )
+        }
+
+        // Compute `z(xg) * prod_i [w_i(x) + beta * sigma_i(x) + gamma]` for the copy
+        // constraint part, one extended permutation polynomial at a time.
+        let mut perm_prod: Vec<E::ScalarField> = (0..chunk_size)
+            .map(|t| prod_perm_poly_next_coset_fft[(t + next_shift) % chunk_size])
+            .collect();
+        drop(prod_perm_poly_next_coset_fft);
+        for (i, sigma) in pk.sigmas.iter().enumerate() {
+            let cached = coset_evals.map(|evals| &evals.sigmas[i]);
+            let sigma_coset_fft = preprocessed_fft(cached, sigma, false)?;
+            perm_prod = parallelizable_slice_iter(&perm_prod)
+                .enumerate()
+                .map(|(t, acc)| {
+                    *acc * (wire_polys_coset_fft[i][t]
+                        + challenges.beta * sigma_coset_fft[t]
+                        + challenges.gamma)
+                })
+                .collect();
+        }
+
+        // Compute coset evaluations of Plookup preprocessed polynomials, in the
+        // order of `CosetEvals::plookup`, and online oracles, at the points `x`
+        // and `x * g`.
+        let plookup_ffts = if lookup_flag {
+            let plookup_pk = pk.plookup_pk.as_ref().unwrap(); // safe unwrap
+            let plookup_polys = [
+                &plookup_pk.range_table_poly,
+                &plookup_pk.key_table_poly,
+                &plookup_pk.table_dom_sep_poly,
+                &plookup_pk.q_dom_sep_poly,
+            ];
+            let plookup_coset_ffts = |next: bool| {
+                plookup_polys
+                    .iter()
+                    .enumerate()
+                    .map(|(i, poly)| {
+                        preprocessed_fft(coset_evals.map(|evals| &evals.plookup[i]), poly, next)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let online_coset_ffts = |coset: &Radix2EvaluationDomain<E::ScalarField>| {
+                let mut polys: Vec<&P> = oracles.h_polys.iter().collect();
+                polys.push(&oracles.prod_lookup_poly);
+                parallelizable_slice_iter(&polys)
+                    .map(|poly| Self::chunk_coset_fft(coset, *poly))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let cur = (plookup_coset_ffts(false)?, online_coset_ffts(&cur_coset)?);
+            let next = if next_in_chunk {
+                None
             } else {
-                (None, None, None, None, None, None)
+                let q_lookup = pk.selectors.last().unwrap(); // safe unwrap
+                let cached = coset_evals.and_then(|evals| evals.selectors.last());
+                let table_wires = [
+                    Self::chunk_coset_fft(&next_coset, &oracles.wire_polys[3])?,
+                    Self::chunk_coset_fft(&next_coset, &oracles.wire_polys[4])?,
+                ];
+                Some((
+                    plookup_coset_ffts(true)?,
+                    online_coset_ffts(&next_coset)?,
+                    preprocessed_fft(cached, q_lookup, true)?,
+                    table_wires,
+                ))
+            };
+            Some((cur, next))
+        } else {
+            None
+        };
+        let plookup_evals = plookup_ffts.as_ref().map(|((tables, online), next)| {
+            let q_lookup = q_lookup_coset_fft.as_ref().unwrap(); // safe unwrap
+            let cur = PlookupChunkEvals {
+                w_3: &wire_polys_coset_fft[3],
+                w_4: &wire_polys_coset_fft[4],
+                h_1: &online[0],
+                h_2: &online[1],
+                prod_lookup: &online[2],
+                range_table: &tables[0],
+                key_table: &tables[1],
+                table_dom_sep: &tables[2],
+                q_lookup,
+            };
+            let next = match next {
+                None => cur.clone(),
+                Some((tables, online, q_lookup, table_wires)) => PlookupChunkEvals {
+                    w_3: &table_wires[0],
+                    w_4: &table_wires[1],
+                    h_1: &online[0],
+                    h_2: &online[1],
+                    prod_lookup: &online[2],
+                    range_table: &tables[0],
+                    key_table: &tables[1],
+                    table_dom_sep: &tables[2],
+                    q_lookup,
+                },
             };
+            (cur, next, &tables[3])
+        });
 
-            // Compute coset evaluations of the quotient polynomial.
-            let quot_poly_coset_evals: Vec<E::ScalarField> =
-                parallelizable_slice_iter(&(0..m).collect::<Vec<_>>())
-                    .map(|&i| {
-                        let w: Vec<E::ScalarField> = (0..num_wire_types)
-                            .map(|j| wire_polys_coset_fft[j][i])
-                            .collect();
-                        let w_next: Vec<E::ScalarField> = (0..num_wire_types)
-                            .map(|j| wire_polys_coset_fft[j][(i + domain_size_ratio) % m])
-                            .collect();
-
-                        let t_circ = Self::compute_quotient_circuit_contribution(
-                            i,
+        // Compute coset evaluations of the quotient polynomial.
+        let quot_poly_coset_evals: Vec<E::ScalarField> =
+            parallelizable_slice_iter(&(0..chunk_size).collect::<Vec<_>>())
+                .map(|&t| {
+                    // index of the evaluation point in the coset and of the
+                    // next point `x * g` in the next chunk.
+                    let i = chunk + num_chunks * t;
+                    let t_next = (t + next_shift) % chunk_size;
+                    let eval_point = self.quot_domain.element(i) * E::ScalarField::GENERATOR;
+                    let w = w(t);
+
+                    let (t_perm_1, t_perm_2) = Self::compute_quotient_copy_constraint_contribution(
+                        eval_point,
+                        pk,
+                        &w,
+                        &prod_perm_poly_coset_fft[t],
+                        &perm_prod[t],
+                        challenges,
+                    );
+                    let mut t1 = t_circ[t] + t_perm_1;
+                    let mut t2 = t_perm_2;
+
+                    // add Plookup-related terms
+                    if let Some((cur, next, q_dom_sep_coset_fft)) = plookup_evals.as_ref() {
+                        let (t_lookup_1, t_lookup_2) = self.compute_quotient_plookup_contribution(
+                            t,
+                            t_next,
+                            eval_point,
+                            pk,
                             &w,
-                            &pub_input_poly_coset_fft[i],
-                            &selectors_coset_fft,
+                            q_dom_sep_coset_fft[t],
+                            cur,
+                            next,
+                            challenges,
                         );
-                        let (t_perm_1, t_perm_2) =
-                            Self::compute_quotient_copy_constraint_contribution(
-                                i,
-                                self.quot_domain.element(i) * E::ScalarField::GENERATOR,
-                                pk,
-                                &w,
-                                &prod_perm_poly_coset_fft[i],
-                                &prod_perm_poly_coset_fft[(i + domain_size_ratio) % m],
-                                challenges,
-                                &sigmas_coset_fft,
-                            );
-                        let mut t1 = t_circ + t_perm_1;
-                        let mut t2 = t_perm_2;
-
-                        // add Plookup-related terms
-                        if lookup_flag {
-                            let (t_lookup_1, t_lookup_2) = self
-                                .compute_quotient_plookup_contribution(
-                                    i,
-                                    self.quot_domain.element(i) * E::ScalarField::GENERATOR,
-                                    pk,
-                                    &w,
-                                    &w_next,
-                                    h_coset_ffts.as_ref().unwrap(),
-                                    prod_lookup_poly_coset_fft.as_ref().unwrap(),
-                                    range_table_coset_fft.as_ref().unwrap(),
-                                    key_table_coset_fft.as_ref().unwrap(),
-                                    selectors_coset_fft.last().unwrap(), /* TODO: add a method
-                                                                          * to extract
-                                                                          * q_lookup_coset_fft */
-                                    table_dom_sep_coset_fft.as_ref().unwrap(),
-                                    q_dom_sep_coset_fft.as_ref().unwrap(),
-                                    challenges,
-                                );
-                            t1 += t_lookup_1;
-                            t2 += t_lookup_2;
-                        }
-                        t1 * z_h_inv[i % domain_size_ratio] + t2
-                    })
-                    .collect();
+                        t1 += t_lookup_1;
+                        t2 += t_lookup_2;
+                    }
+                    t1 * z_h_inv[i % domain_size_ratio] + t2
+                })
+                .collect();
+        Ok(quot_poly_coset_evals)
+    }
 
-            for (a, b) in quot_poly_coset_evals_sum
-                .iter_mut()
-                .zip(quot_poly_coset_evals.iter())
-            {
-                *a += alpha_base * b;
-            }
-            // update the random combiner for aggregating multiple proving instances
-            if lookup_flag {
-                alpha_base *= alpha_7;
-            } else {
-                alpha_base *= alpha_3;
+    /// The number of chunks the coset of the quotient polynomial domain is split
+    /// into when computing the quotient polynomial, the smallest one that
+    /// keeps the memory used in the third round within the memory budget of
+    /// the prover, if any, and whether the online oracles are spilled to
+    /// temporary files meanwhile. The memory used in the third round consists
+    /// of the coefficients of the quotient polynomial, the evaluations of a
+    /// chunk, and the online oracles unless they are spilled, which is only
+    /// supported with the `std` feature. The number of chunks is a power of
+    /// two, and the chunks are at least `1 / MAX_QUOT_CHUNKS_PER_RATIO` of the
+    /// domain, since each chunk reads all the coefficients of every
+    /// polynomial.
+    fn plan_quot_memory(
+        &self,
+        pks: &[&ProvingKey<E, PCS>],
+        online_oracles: &[Oracles<E::ScalarField>],
+        num_wire_types: usize,
+    ) -> (usize, bool) {
+        let n = self.domain.size();
+        let m = self.quot_domain.size();
+        let domain_size_ratio = m / n;
+        let max_num_chunks = ark_std::cmp::min(domain_size_ratio * MAX_QUOT_CHUNKS_PER_RATIO, m);
+        let memory_budget = match self.memory_budget {
+            Some(memory_budget) => memory_budget.bytes,
+            None => return (1, false),
+        };
+        let elem_size = core::mem::size_of::<E::ScalarField>();
+        // the number of evaluation vectors of a chunk: the wires, the permutation
+        // product, the circuit part and the product of the copy constraint part
+        // being updated, the streamed preprocessed polynomial, the quotient and
+        // its sum over the instances, and the Plookup preprocessed polynomials,
+        // sorted vectors and product if any, also at the points `x * g` if they
+        // lie in another chunk.
+        let num_vecs = |num_chunks: usize| {
+            let next_in_chunk = domain_size_ratio % num_chunks == 0;
+            pks.iter()
+                .map(|pk| {
+                    let lookup_vecs = match (pk.plookup_pk.is_some(), next_in_chunk) {
+                        (false, _) => 0,
+                        (true, true) => 8,
+                        (true, false) => 17,
+                    };
+                    num_wire_types + 8 + usize::from(!next_in_chunk) + lookup_vecs
+                })
+                .max()
+                .unwrap_or(0)
+        };
+        let chunk_bytes =
+            |num_chunks: usize| elem_size * (m + num_vecs(num_chunks) * (m / num_chunks));
+        let oracles_bytes = elem_size
+            * online_oracles
+                .iter()
+                .map(|oracles| {
+                    oracles
+                        .wire_polys
+                        .iter()
+                        .chain(oracles.plookup_oracles.h_polys.iter())
+                        .chain([
+                            &oracles.pub_inp_poly,
+                            &oracles.prod_perm_poly,
+                            &oracles.plookup_oracles.prod_lookup_poly,
+                        ])
+                        .map(|poly| poly.coeffs.len())
+                        .sum::<usize>()
+                })
+                .sum::<usize>();
+        let fit = |reserved: usize| {
+            let mut num_chunks = 1;
+            while num_chunks <= max_num_chunks {
+                if chunk_bytes(num_chunks).saturating_add(reserved) <= memory_budget {
+                    return Some(num_chunks);
+                }
+                num_chunks *= 2;
             }
+            None
+        };
+        match fit(oracles_bytes) {
+            Some(num_chunks) => (num_chunks, false),
+            None => (fit(0).unwrap_or(max_num_chunks), cfg!(feature = "std")),
+        }
+    }
+
+    /// The evaluations in `coset_evals` over the `chunk`-th of the `num_chunks`
+    /// chunks of the coset of the quotient polynomial domain.
+    fn coset_evals_chunk(
+        coset_evals: &[E::ScalarField],
+        chunk: usize,
+        num_chunks: usize,
+    ) -> Cow<'_, [E::ScalarField]> {
+        if num_chunks == 1 {
+            return Cow::Borrowed(coset_evals);
         }
-        // Compute the coefficient form of the quotient polynomial
-        Ok(DensePolynomial::from_coefficients_vec(
-            coset.ifft(&quot_poly_coset_evals_sum),
-        ))
+        Cow::Owned(
+            coset_evals
+                .iter()
+                .skip(chunk)
+                .step_by(num_chunks)
+                .copied()
+                .collect(),
+        )
     }
 
-    // Compute the i-th coset evaluation of the circuit part of the quotient
-    // polynomial.
-    fn compute_quotient_circuit_contribution(
+    /// Evaluate `poly` over `chunk_coset`, which may be smaller than the
+    /// degree of `poly`.
+    fn chunk_coset_fft<P: QuotOracle<E::ScalarField> + ?Sized>(
+        chunk_coset: &Radix2EvaluationDomain<E::ScalarField>,
+        poly: &P,
+    ) -> Result<Vec<E::ScalarField>, PlonkError> {
+        let mut coeffs =
+            poly.fold_coeffs(chunk_coset.size(), chunk_coset.coset_offset_pow_size())?;
+        chunk_coset.fft_in_place(&mut coeffs);
+        Ok(coeffs)
+    }
+
+    // The monomial of the `i`-th selector, in the order q_lc, q_mul, q_hash,
+    // q_o, q_c, q_ecc, in the gate equation, given the coset evaluations of
+    // the wire polynomials at the `t`-th point.
+    fn compute_quotient_circuit_term(
         i: usize,
-        w: &[E::ScalarField],
-        pi: &E::ScalarField,
-        selectors_coset_fft: &[Vec<E::ScalarField>],
+        wire_polys_coset_fft: &[Vec<E::ScalarField>],
+        t: usize,
     ) -> E::ScalarField {
-        // Selectors
-        // The order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc
-        // TODO: (binyi) get the order from a function.
-        let q_lc: Vec<E::ScalarField> =
-            (0..GATE_WIDTH).map(|j| selectors_coset_fft[j][i]).collect();
-        let q_mul: Vec<E::ScalarField> = (GATE_WIDTH..GATE_WIDTH + 2)
-            .map(|j| selectors_coset_fft[j][i])
-            .collect();
-        let q_hash: Vec<E::ScalarField> = (GATE_WIDTH + 2..2 * GATE_WIDTH + 2)
-            .map(|j| selectors_coset_fft[j][i])
-            .collect();
-        let q_o = selectors_coset_fft[2 * GATE_WIDTH + 2][i];
-        let q_c = selectors_coset_fft[2 * GATE_WIDTH + 3][i];
-        let q_ecc = selectors_coset_fft[2 * GATE_WIDTH + 4][i];
-
-        q_c + pi
-            + q_lc[0] * w[0]
-            + q_lc[1] * w[1]
-            + q_lc[2] * w[2]
-            + q_lc[3] * w[3]
-            + q_mul[0] * w[0] * w[1]
-            + q_mul[1] * w[2] * w[3]
-            + q_ecc * w[0] * w[1] * w[2] * w[3] * w[4]
-            + q_hash[0] * w[0].pow([5])
-            + q_hash[1] * w[1].pow([5])
-            + q_hash[2] * w[2].pow([5])
-            + q_hash[3] * w[3].pow([5])
-            - q_o * w[4]
+        let w = |j: usize| wire_polys_coset_fft[j][t];
+        if i < GATE_WIDTH {
+            // q_lc
+            w(i)
+        } else if i < GATE_WIDTH + 2 {
+            // q_mul
+            let j = 2 * (i - GATE_WIDTH);
+            w(j) * w(j + 1)
+        } else if i < 2 * GATE_WIDTH + 2 {
+            // q_hash
+            w(i - GATE_WIDTH - 2).pow([5])
+        } else if i == 2 * GATE_WIDTH + 2 {
+            // q_o
+            -w(GATE_WIDTH)
+        } else if i == 2 * GATE_WIDTH + 3 {
+            // q_c
+            E::ScalarField::one()
+        } else {
+            // q_ecc
+            (0..=GATE_WIDTH).map(w).product()
+        }
     }
 
-    /// Compute the i-th coset evaluation of the copy constraint part of the
-    /// quotient polynomial.
+    /// Compute the coset evaluation of the copy constraint part of the
+    /// quotient polynomial at `eval_point`.
     /// `eval_point` - the evaluation point.
     /// `w` - the wire polynomial coset evaluations at `eval_point`.
     /// `z_x` - the permutation product polynomial evaluation at `eval_point`.
-    /// `z_xw`-  the permutation product polynomial evaluation at `eval_point *
-    /// g`, where `g` is the root of unity of the original domain.
-    #[allow(clippy::too_many_arguments)]
+    /// `z_xw_prod`- the permutation product polynomial evaluation at
+    /// `eval_point * g`, where `g` is the root of unity of the original
+    /// domain, times `prod_i [w_i + beta * sigma_i + gamma]` at `eval_point`.
     fn compute_quotient_copy_constraint_contribution(
-        i: usize,
         eval_point: E::ScalarField,
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         w: &[E::ScalarField],
         z_x: &E::ScalarField,
-        z_xw: &E::ScalarField,
+        z_xw_prod: &E::ScalarField,
         challenges: &Challenges<E::ScalarField>,
-        sigmas_coset_fft: &[Vec<E::ScalarField>],
     ) -> (E::ScalarField, E::ScalarField) {
-        let num_wire_types = w.len();
         let n = pk.domain_size();
 
         // The check that:
@@ -723,23 +1289,13 @@ impl<E: Pairing> Prover<E> {
         // on the vanishing set.
         // Delay the division of Z_H(X).
         //
-        // Extended permutation values
-        let sigmas: Vec<E::ScalarField> = (0..num_wire_types)
-            .map(|j| sigmas_coset_fft[j][i])
-            .collect();
-
         // Compute the 1st term.
         let mut result_1 = challenges.alpha
             * w.iter().enumerate().fold(*z_x, |acc, (j, &w)| {
                 acc * (w + pk.k()[j] * eval_point * challenges.beta + challenges.gamma)
             });
         // Minus the 2nd term.
-        result_1 -= challenges.alpha
-            * w.iter()
-                .zip(sigmas.iter())
-                .fold(*z_xw, |acc, (&w, &sigma)| {
-                    acc * (w + sigma * challenges.beta + challenges.gamma)
-                });
+        result_1 -= challenges.alpha * z_xw_prod;
 
         // The check that z(x) = 1 at point 1.
         // (z(x)-1) * L1(x) * alpha^2 / Z_H(x) = (z(x)-1) * alpha^2 / (n * (x - 1))
@@ -749,40 +1305,35 @@ impl<E: Pairing> Prover<E> {
         (result_1, result_2)
     }
 
-    /// Compute the i-th coset evaluation of the lookup constraint part of the
-    /// quotient polynomial.
+    /// Compute the coset evaluation of the lookup constraint part of the
+    /// quotient polynomial at the `t`-th point of a chunk.
+    /// `t_next`: the index of the coset evaluations at `eval_point * g`, where
+    /// `g` is the root of unity of the original domain, in `next`.
     /// `eval_point`: the evaluation point.
     /// `pk`: proving key.
-    /// `lookup_w`: (merged) lookup witness coset evaluations at `eval_point`.
-    /// `h_coset_ffts`: coset evaluations for the sorted lookup vector
-    /// polynomials. `prod_lookup_coset_fft`: coset evaluations for the
-    /// Plookup product polynomial. `challenges`: Fiat-shamir challenges.
+    /// `w`: the wire polynomial coset evaluations at `eval_point`.
+    /// `q_dom_sep_x`: the lookup domain separation selector at `eval_point`.
+    /// `cur`, `next`: the coset evaluations of the polynomials of the Plookup
+    /// argument over the chunk and the one of the points `x * g`.
+    /// `challenges`: Fiat-shamir challenges.
     ///
-    /// The coset evaluations should be non-empty. The proving key should be
-    /// guaranteed to support lookup.
+    /// The proving key should be guaranteed to support lookup.
     #[allow(clippy::too_many_arguments)]
     fn compute_quotient_plookup_contribution(
         &self,
-        i: usize,
+        t: usize,
+        t_next: usize,
         eval_point: E::ScalarField,
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         w: &[E::ScalarField],
-        w_next: &[E::ScalarField],
-        h_coset_ffts: &[Vec<E::ScalarField>],
-        prod_lookup_coset_fft: &[E::ScalarField],
-        range_table_coset_fft: &[E::ScalarField],
-        key_table_coset_fft: &[E::ScalarField],
-        q_lookup_coset_fft: &[E::ScalarField],
-        table_dom_sep_coset_fft: &[E::ScalarField],
-        q_dom_sep_coset_fft: &[E::ScalarField],
+        q_dom_sep_x: E::ScalarField,
+        cur: &PlookupChunkEvals<E::ScalarField>,
+        next: &PlookupChunkEvals<E::ScalarField>,
         challenges: &Challenges<E::ScalarField>,
     ) -> (E::ScalarField, E::ScalarField) {
         assert!(pk.plookup_pk.is_some());
-        assert_eq!(h_coset_ffts.len(), 2);
 
         let n = pk.domain_size();
-        let m = self.quot_domain.size();
-        let domain_size_ratio = m / n;
         let n_field = E::ScalarField::from(n as u64);
         let lagrange_n_coeff =
             self.domain.group_gen_inv / (n_field * (eval_point - self.domain.group_gen_inv));
@@ -791,36 +1342,35 @@ impl<E: Pairing> Prover<E> {
         let mut alpha_power = challenges.alpha * challenges.alpha * challenges.alpha;
 
         // extract polynomial evaluations
-        let h_1_x = h_coset_ffts[0][i];
-        let h_1_xw = h_coset_ffts[0][(i + domain_size_ratio) % m];
-        let h_2_x = h_coset_ffts[1][i];
-        let h_2_xw = h_coset_ffts[1][(i + domain_size_ratio) % m];
-        let p_x = prod_lookup_coset_fft[i];
-        let p_xw = prod_lookup_coset_fft[(i + domain_size_ratio) % m];
-        let range_table_x = range_table_coset_fft[i];
-        let key_table_x = key_table_coset_fft[i];
-        let table_dom_sep_x = table_dom_sep_coset_fft[i];
-        let q_dom_sep_x = q_dom_sep_coset_fft[i];
-
-        let range_table_xw = range_table_coset_fft[(i + domain_size_ratio) % m];
-        let key_table_xw = key_table_coset_fft[(i + domain_size_ratio) % m];
-        let table_dom_sep_xw = table_dom_sep_coset_fft[(i + domain_size_ratio) % m];
+        let h_1_x = cur.h_1[t];
+        let h_1_xw = next.h_1[t_next];
+        let h_2_x = cur.h_2[t];
+        let h_2_xw = next.h_2[t_next];
+        let p_x = cur.prod_lookup[t];
+        let p_xw = next.prod_lookup[t_next];
+        let range_table_x = cur.range_table[t];
+        let key_table_x = cur.key_table[t];
+        let table_dom_sep_x = cur.table_dom_sep[t];
+
+        let range_table_xw = next.range_table[t_next];
+        let key_table_xw = next.key_table[t_next];
+        let table_dom_sep_xw = next.table_dom_sep[t_next];
         let merged_table_x = eval_merged_table::<E>(
             challenges.tau,
             range_table_x,
             key_table_x,
-            q_lookup_coset_fft[i],
-            w[3],
-            w[4],
+            cur.q_lookup[t],
+            cur.w_3[t],
+            cur.w_4[t],
             table_dom_sep_x,
         );
         let merged_table_xw = eval_merged_table::<E>(
             challenges.tau,
             range_table_xw,
             key_table_xw,
-            q_lookup_coset_fft[(i + domain_size_ratio) % m],
-            w_next[3],
-            w_next[4],
+            next.q_lookup[t_next],
+            next.w_3[t_next],
+            next.w_4[t_next],
             table_dom_sep_xw,
         );
         let merged_lookup_x = eval_merged_lookup_witness::<E>(
@@ -829,7 +1379,7 @@ impl<E: Pairing> Prover<E> {
             w[0],
             w[1],
             w[2],
-            q_lookup_coset_fft[i],
+            cur.q_lookup[t],
             q_dom_sep_x,
         );
 
@@ -891,7 +1441,7 @@ impl<E: Pairing> Prover<E> {
     fn split_quotient_polynomial<R: CryptoRng + RngCore>(
         &self,
         prng: &mut R,
-        quot_poly: &DensePolynomial<E::ScalarField>,
+        quot_poly: DensePolynomial<E::ScalarField>,
         num_wire_types: usize,
     ) -> Result<Vec<DensePolynomial<E::ScalarField>>, PlonkError> {
         let expected_degree = quotient_polynomial_degree(self.domain.size(), num_wire_types);
@@ -900,21 +1450,21 @@ impl<E: Pairing> Prover<E> {
         }
         let n = self.domain.size();
         // compute the splitting polynomials t'_i(X) s.t. t(X) =
-        // \sum_{i=0}^{num_wire_types} X^{i*(n+2)} * t'_i(X)
-        let mut split_quot_polys: Vec<DensePolynomial<E::ScalarField>> =
-            parallelizable_slice_iter(&(0..num_wire_types).collect::<Vec<_>>())
-                .map(|&i| {
-                    let end = if i < num_wire_types - 1 {
-                        (i + 1) * (n + 2)
-                    } else {
-                        quot_poly.degree() + 1
-                    };
-                    // Degree-(n+1) polynomial has n + 2 coefficients.
-                    DensePolynomial::<E::ScalarField>::from_coefficients_slice(
-                        &quot_poly.coeffs[i * (n + 2)..end],
-                    )
-                })
-                .collect();
+        // \sum_{i=0}^{num_wire_types} X^{i*(n+2)} * t'_i(X), from the highest one
+        // so that the coefficients are moved rather than copied.
+        let mut coeffs = quot_poly.coeffs;
+        let mut split_quot_polys: Vec<DensePolynomial<E::ScalarField>> = (0..num_wire_types)
+            .rev()
+            .map(|i| {
+                // Degree-(n+1) polynomial has n + 2 coefficients, reserve one more
+                // for the masking below.
+                let mut split_coeffs = Vec::with_capacity(n + 3);
+                split_coeffs.extend(coeffs.drain(i * (n + 2)..));
+                coeffs.shrink_to_fit();
+                DensePolynomial::<E::ScalarField>::from_coefficients_vec(split_coeffs)
+            })
+            .collect();
+        split_quot_polys.reverse();
 
         // mask splitting polynomials t_i(X), for i in {0..num_wire_types}.
         // t_i(X) = t'_i(X) - b_last_i + b_now_i * X^(n+2)
@@ -941,7 +1491,7 @@ impl<E: Pairing> Prover<E> {
 
     // Compute the circuit part of the linearization polynomial
     fn compute_lin_poly_circuit_contribution(
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         w_evals: &[E::ScalarField],
     ) -> DensePolynomial<E::ScalarField> {
         // The selectors order: q_lc, q_mul, q_hash, q_o, q_c, q_ecc
@@ -955,27 +1505,27 @@ impl<E: Pairing> Prover<E> {
 
         // TODO(binyi): add polynomials in parallel.
         // Note we don't need to compute the constant term of the polynomial.
-        Self::mul_poly(&q_lc[0], &w_evals[0])
-            + Self::mul_poly(&q_lc[1], &w_evals[1])
-            + Self::mul_poly(&q_lc[2], &w_evals[2])
-            + Self::mul_poly(&q_lc[3], &w_evals[3])
-            + Self::mul_poly(&q_mul[0], &(w_evals[0] * w_evals[1]))
-            + Self::mul_poly(&q_mul[1], &(w_evals[2] * w_evals[3]))
-            + Self::mul_poly(&q_hash[0], &w_evals[0].pow([5]))
-            + Self::mul_poly(&q_hash[1], &w_evals[1].pow([5]))
-            + Self::mul_poly(&q_hash[2], &w_evals[2].pow([5]))
-            + Self::mul_poly(&q_hash[3], &w_evals[3].pow([5]))
-            + Self::mul_poly(
-                q_ecc,
-                &(w_evals[0] * w_evals[1] * w_evals[2] * w_evals[3] * w_evals[4]),
-            )
-            + Self::mul_poly(q_o, &(-w_evals[4]))
-            + q_c.clone()
+        // The terms are accumulated in place.
+        let mut r_circ = q_c.clone();
+        for (q, w_eval) in q_lc.iter().zip(w_evals.iter()) {
+            r_circ += (*w_eval, q);
+        }
+        r_circ += (w_evals[0] * w_evals[1], &q_mul[0]);
+        r_circ += (w_evals[2] * w_evals[3], &q_mul[1]);
+        for (q, w_eval) in q_hash.iter().zip(w_evals.iter()) {
+            r_circ += (w_eval.pow([5]), q);
+        }
+        r_circ += (
+            w_evals[0] * w_evals[1] * w_evals[2] * w_evals[3] * w_evals[4],
+            q_ecc,
+        );
+        r_circ += (-w_evals[4], q_o);
+        r_circ
     }
 
     // Compute the wire permutation part of the linearization polynomial
     fn compute_lin_poly_copy_constraint_contribution(
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         challenges: &Challenges<E::ScalarField>,
         poly_evals: &ProofEvaluations<E::ScalarField>,
         prod_perm_poly: &DensePolynomial<E::ScalarField>,
@@ -1009,14 +1559,14 @@ impl<E: Pairing> Prover<E> {
                     acc * (wire_eval + challenges.beta * sigma_eval + challenges.gamma)
                 },
             );
-        r_perm = r_perm + Self::mul_poly(&pk.sigmas[num_wire_types - 1], &coeff);
+        r_perm += (coeff, &pk.sigmas[num_wire_types - 1]);
         r_perm
     }
 
     // Compute the Plookup part of the linearization polynomial
     fn compute_lin_poly_plookup_contribution(
         &self,
-        pk: &ProvingKey<E>,
+        pk: &ProvingKey<E, PCS>,
         challenges: &Challenges<E::ScalarField>,
         w_evals: &[E::ScalarField],
         plookup_evals: &PlookupEvaluations<E::ScalarField>,
@@ -1086,7 +1636,7 @@ impl<E: Pairing> Prover<E> {
             * (challenges.gamma * beta_plus_one
                 + plookup_evals.h_1_eval
                 + challenges.beta * plookup_evals.h_1_next_eval);
-        r_lookup = r_lookup + Self::mul_poly(&oracles.h_polys[1], &coeff);
+        r_lookup += (coeff, &oracles.h_polys[1]);
 
         r_lookup
     }
@@ -1131,7 +1681,7 @@ mod test {
         let rng = &mut test_rng();
         let bad_quot_poly = DensePolynomial::<E::ScalarField>::rand(25, rng);
         assert!(prover
-            .split_quotient_polynomial(rng, &bad_quot_poly, GATE_WIDTH + 1)
+            .split_quotient_polynomial(rng, bad_quot_poly, GATE_WIDTH + 1)
             .is_err());
         Ok(())
     }
@@ -0,0 +1,401 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A variant of the KZG-based Plonk with a SHPLONK-style opening: a single
+//! KZG opening proof for all the polynomials at both evaluation points
+//! `zeta` and `zeta * g`, see Sec 3 of <https://eprint.iacr.org/2020/081.pdf>.
+//!
+//! Let `P_zeta` (resp. `P_zeta_g`) be the aggregation with the powers of the
+//! challenge `v` of the polynomials opened at `zeta` (resp. `zeta * g`), as
+//! in [`PlonkKzgSnark`]. The prover sends the cross evaluations
+//! `P_zeta_g(zeta)` and `P_zeta(zeta * g)`, gets a challenge `u`, and opens
+//! `P_zeta + u * P_zeta_g` at both points with
+//! [`UnivariateKzgPCS::multi_point_open()`]. The verifier knows
+//! `P_zeta(zeta)` and `P_zeta_g(zeta * g)` from the evaluations in the proof,
+//! and checks the opening with [`UnivariateKzgPCS::multi_point_verify()`].
+//!
+//! Compared with [`Proof`](crate::proof_system::structs::Proof), a
+//! [`ShplonkProof`] has a single group element for the opening instead of two,
+//! but two more field elements for the cross evaluations. The verifier
+//! computes the same two pairings, plus a multi-scalar multiplication of size
+//! 3 in G2, and the verifying key needs `[x^2]2`.
+
+use super::{
+    prover::Prover,
+    structs::{BatchProof, PlookupProof, ProofEvaluations, ProvingKey, UniversalSrs, VerifyingKey},
+    verifier::Verifier,
+    PlonkKzgSnark, UniversalSNARK,
+};
+use crate::{errors::PlonkError, transcript::PlonkTranscript};
+use ark_ec::{
+    pairing::Pairing,
+    short_weierstrass::{Affine, SWCurveConfig},
+};
+use ark_poly::{EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    vec,
+    vec::Vec,
+};
+use jf_primitives::{
+    pcs::{
+        prelude::{Commitment, UnivariateKzgPCS, UnivariateKzgProof},
+        StructuredReferenceString, UnivariatePCS,
+    },
+    rescue::RescueParameter,
+};
+use jf_relation::{gadgets::ecc::SWToTEConParam, Arithmetization};
+
+/// A Plonk proof with a single opening proof, see
+/// [`crate::proof_system::shplonk`].
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = "E: Pairing"),
+    Clone(bound = "E: Pairing"),
+    PartialEq(bound = "E: Pairing"),
+    Eq(bound = "E: Pairing"),
+    Hash(bound = "E: Pairing")
+)]
+pub struct ShplonkProof<E: Pairing> {
+    /// Wire witness polynomials commitments.
+    pub wires_poly_comms: Vec<Commitment<E>>,
+
+    /// The polynomial commitment for the wire permutation argument.
+    pub prod_perm_poly_comm: Commitment<E>,
+
+    /// Split quotient polynomial commitments.
+    pub split_quot_poly_comms: Vec<Commitment<E>>,
+
+    /// Evaluation at `zeta` of the aggregated polynomial opened at `zeta * g`.
+    pub shifted_batch_eval: E::ScalarField,
+
+    /// Evaluation at `zeta * g` of the aggregated polynomial opened at `zeta`.
+    pub batch_next_eval: E::ScalarField,
+
+    /// Proof of evaluations at challenge points `zeta` and `zeta * g`.
+    pub opening_proof: Commitment<E>,
+
+    /// Polynomial evaluations.
+    pub poly_evals: ProofEvaluations<E::ScalarField>,
+
+    /// The partial proof for Plookup argument
+    pub plookup_proof: Option<PlookupProof<E>>,
+}
+
+impl<E: Pairing> ShplonkProof<E> {
+    /// The batch proof of a single instance with the same messages, and
+    /// dummy opening proofs.
+    fn to_batch_proof(&self) -> BatchProof<E> {
+        BatchProof {
+            wires_poly_comms_vec: vec![self.wires_poly_comms.clone()],
+            prod_perm_poly_comms_vec: vec![self.prod_perm_poly_comm],
+            poly_evals_vec: vec![self.poly_evals.clone()],
+            plookup_proofs_vec: vec![self.plookup_proof.clone()],
+            split_quot_poly_comms: self.split_quot_poly_comms.clone(),
+            opening_proof: Commitment::default(),
+            shifted_opening_proof: Commitment::default(),
+        }
+    }
+}
+
+/// A Plonk instantiated with KZG PCS and a SHPLONK-style opening, see
+/// [`crate::proof_system::shplonk`].
+///
+/// It shares the SRS and the circuit-specific preprocessing with
+/// [`PlonkKzgSnark`], except for the verifying keys, which need `[x^2]2`.
+pub struct PlonkKzgShplonkSnark<E: Pairing>(PhantomData<E>);
+
+impl<E, F, P> UniversalSNARK<E> for PlonkKzgShplonkSnark<E>
+where
+    E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWCurveConfig<BaseField = F>,
+{
+    type Proof = ShplonkProof<E>;
+    type ProvingKey = ProvingKey<E>;
+    type VerifyingKey = VerifyingKey<E>;
+    type UniversalSRS = UniversalSrs<E>;
+    type Error = PlonkError;
+
+    fn universal_setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSRS, Self::Error> {
+        PlonkKzgSnark::<E>::universal_setup(max_degree, rng)
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn universal_setup_for_testing<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSRS, Self::Error> {
+        PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)
+    }
+
+    /// Same as [`PlonkKzgSnark::preprocess()`], except that the open key in
+    /// the verifying key has `[x^2]2`. Return error if `srs` doesn't have it.
+    fn preprocess<C: Arithmetization<E::ScalarField>>(
+        srs: &Self::UniversalSRS,
+        circuit: &C,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
+        let (mut pk, mut vk) = PlonkKzgSnark::<E>::preprocess(srs, circuit)?;
+        // Only the verifier parameters are used, hence the prover degree of 1.
+        let (_, open_key) = srs.trim_with_verifier_degree(1, 2)?;
+        vk.open_key = open_key;
+        pk.vk.open_key = vk.open_key.clone();
+        Ok((pk, vk))
+    }
+
+    /// Compute a Plonk proof with a single opening proof.
+    ///
+    /// `circuit` and `prove_key` has to be consistent (with the same evaluation
+    /// domain etc.), otherwise return error.
+    fn prove<C, R, T>(
+        rng: &mut R,
+        circuit: &C,
+        prove_key: &Self::ProvingKey,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Self::Proof, Self::Error>
+    where
+        C: Arithmetization<E::ScalarField>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let mut state = PlonkKzgSnark::<E>::batch_prove_until_opening::<_, _, T>(
+            rng,
+            &[circuit],
+            &[prove_key],
+            extra_transcript_init_msg,
+            None,
+        )?;
+        let (batch_poly, shifted_batch_poly) = Prover::<E>::compute_batched_polys(
+            &[prove_key],
+            &state.challenges.v,
+            &state.online_oracles,
+            &state.lin_poly,
+        )?;
+
+        let domain = Radix2EvaluationDomain::<E::ScalarField>::new(prove_key.domain_size())
+            .ok_or(PlonkError::DomainCreationError)?;
+        let zeta = state.challenges.zeta;
+        let next_zeta = zeta * domain.group_gen;
+        let shifted_batch_eval = shifted_batch_poly.evaluate(&zeta);
+        let batch_next_eval = batch_poly.evaluate(&next_zeta);
+        state
+            .transcript
+            .append_challenge::<E>(b"shifted_batch_eval", &shifted_batch_eval)?;
+        state
+            .transcript
+            .append_challenge::<E>(b"batch_next_eval", &batch_next_eval)?;
+        let u = state.transcript.get_and_append_challenge::<E>(b"u")?;
+
+        let mut opening_poly = batch_poly;
+        opening_poly += (u, &shifted_batch_poly);
+        let (opening_proof, _) = UnivariateKzgPCS::<E>::multi_point_open(
+            &prove_key.commit_key,
+            &opening_poly,
+            &[zeta, next_zeta],
+        )?;
+
+        Ok(ShplonkProof {
+            wires_poly_comms: state.wires_poly_comms_vec.pop().unwrap(),
+            prod_perm_poly_comm: state.prod_perm_poly_comms_vec[0],
+            split_quot_poly_comms: state.split_quot_poly_comms,
+            shifted_batch_eval,
+            batch_next_eval,
+            opening_proof: Commitment(opening_proof.proof),
+            poly_evals: state.poly_evals_vec.pop().unwrap(),
+            plookup_proof: state.plookup_proofs_vec.pop().unwrap(),
+        })
+    }
+
+    fn verify<T>(
+        verify_key: &Self::VerifyingKey,
+        public_input: &[E::ScalarField],
+        proof: &Self::Proof,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), Self::Error>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let verifier = Verifier::<E>::new(verify_key.domain_size)?;
+        let batch_proof = proof.to_batch_proof();
+        verifier.check_instances(&[verify_key], &[public_input], &batch_proof)?;
+
+        let (mut transcript, mut challenges) = Verifier::<E>::compute_challenges_until_v::<T>(
+            &[verify_key],
+            &[public_input],
+            &batch_proof,
+            &extra_transcript_init_msg,
+        )?;
+        transcript.append_challenge::<E>(b"shifted_batch_eval", &proof.shifted_batch_eval)?;
+        transcript.append_challenge::<E>(b"batch_next_eval", &proof.batch_next_eval)?;
+        challenges.u = transcript.get_and_append_challenge::<E>(b"u")?;
+
+        let pcs_info = verifier.prepare_pcs_info_with_challenges(
+            &[verify_key],
+            &[public_input],
+            &batch_proof,
+            &challenges,
+        )?;
+        // `pcs_info` is the claim that `P_zeta + u * P_zeta_g` is committed in
+        // `comm`, where `P_zeta(zeta) = eval - shifted_eval` and
+        // `u * P_zeta_g(zeta * g) = shifted_eval`.
+        let comm = Commitment(pcs_info.comm_scalars_and_bases.multi_scalar_mul().into());
+        let evals = [
+            pcs_info.eval - pcs_info.shifted_eval + challenges.u * proof.shifted_batch_eval,
+            proof.batch_next_eval + pcs_info.shifted_eval,
+        ];
+        if !UnivariateKzgPCS::<E>::multi_point_verify(
+            &verify_key.open_key,
+            &comm,
+            &[pcs_info.eval_point, pcs_info.next_eval_point],
+            &evals,
+            &UnivariateKzgProof {
+                proof: proof.opening_proof.0,
+            },
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        proof_system::snark::test::gen_circuit_for_test,
+        transcript::{RescueTranscript, StandardTranscript},
+    };
+    use ark_bls12_377::{Bls12_377, Fq as Fq377};
+    use ark_bn254::{Bn254, Fq as Fq254};
+    use ark_ec::AffineRepr;
+    use ark_ff::One;
+    use ark_std::format;
+    use jf_relation::{Circuit, PlonkType};
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_shplonk_proof_system() -> Result<(), PlonkError> {
+        test_shplonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_shplonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_shplonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_shplonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::UltraPlonk,
+        )
+    }
+
+    fn test_shplonk_proof_system_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut test_rng();
+        let n = 64;
+        let srs = PlonkKzgShplonkSnark::<E>::universal_setup_for_testing(n + 2, rng)?;
+        let circuits = (0..4)
+            .map(|i| gen_circuit_for_test(2 + i / 2, 1 + i % 2, plonk_type))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let (pk1, vk1) = PlonkKzgShplonkSnark::<E>::preprocess(&srs, &circuits[0])?;
+        let (pk2, vk2) = PlonkKzgShplonkSnark::<E>::preprocess(&srs, &circuits[2])?;
+
+        for (i, circuit) in circuits.iter().enumerate() {
+            let (pk, vk) = if i < 2 { (&pk1, &vk1) } else { (&pk2, &vk2) };
+            let extra_msg = Some(format!("extra message: {}", i).into_bytes());
+            let public_input = circuit.public_input()?;
+            let proof =
+                PlonkKzgShplonkSnark::<E>::prove::<_, _, T>(rng, circuit, pk, extra_msg.clone())?;
+            assert!(PlonkKzgShplonkSnark::<E>::verify::<T>(
+                vk,
+                &public_input,
+                &proof,
+                extra_msg.clone()
+            )
+            .is_ok());
+
+            // Wrong public input or transcript message
+            let mut bad_public_input = public_input.clone();
+            bad_public_input[0] += E::ScalarField::one();
+            assert!(PlonkKzgShplonkSnark::<E>::verify::<T>(
+                vk,
+                &bad_public_input,
+                &proof,
+                extra_msg.clone()
+            )
+            .is_err());
+            assert!(
+                PlonkKzgShplonkSnark::<E>::verify::<T>(vk, &public_input, &proof, None).is_err()
+            );
+
+            // Tampered cross evaluations or opening proof
+            let mut bad_proof = proof.clone();
+            bad_proof.shifted_batch_eval += E::ScalarField::one();
+            assert!(PlonkKzgShplonkSnark::<E>::verify::<T>(
+                vk,
+                &public_input,
+                &bad_proof,
+                extra_msg.clone()
+            )
+            .is_err());
+            let mut bad_proof = proof.clone();
+            bad_proof.batch_next_eval += E::ScalarField::one();
+            assert!(PlonkKzgShplonkSnark::<E>::verify::<T>(
+                vk,
+                &public_input,
+                &bad_proof,
+                extra_msg.clone()
+            )
+            .is_err());
+            let mut bad_proof = proof.clone();
+            bad_proof.opening_proof =
+                Commitment((bad_proof.opening_proof.0 + E::G1Affine::generator()).into());
+            assert!(PlonkKzgShplonkSnark::<E>::verify::<T>(
+                vk,
+                &public_input,
+                &bad_proof,
+                extra_msg.clone()
+            )
+            .is_err());
+
+            // A verifying key of `PlonkKzgSnark` doesn't have `[x^2]2`.
+            let (_, kzg_vk) = PlonkKzgSnark::<E>::preprocess(&srs, circuit)?;
+            assert!(PlonkKzgShplonkSnark::<E>::verify::<T>(
+                &kzg_vk,
+                &public_input,
+                &proof,
+                extra_msg.clone()
+            )
+            .is_err());
+
+            // Serialization, with a group element less and two field elements
+            // more than a `PlonkKzgSnark` proof.
+            let mut bytes = vec![];
+            proof.serialize_compressed(&mut bytes)?;
+            assert_eq!(
+                ShplonkProof::<E>::deserialize_compressed(&bytes[..])?,
+                proof
+            );
+            let kzg_proof =
+                PlonkKzgSnark::<E>::prove::<_, _, T>(rng, circuit, pk, extra_msg.clone())?;
+            assert_eq!(
+                bytes.len() + E::G1Affine::generator().compressed_size(),
+                kzg_proof.compressed_size() + 2 * E::ScalarField::one().compressed_size()
+            );
+        }
+        Ok(())
+    }
+}
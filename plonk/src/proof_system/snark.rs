@@ -6,10 +6,11 @@
 
 //! Instantiations of Plonk-based proof systems
 use super::{
+    pcs::PlonkPCS,
     prover::Prover,
     structs::{
-        BatchProof, Challenges, Oracles, PlookupProof, PlookupProvingKey, PlookupVerifyingKey,
-        Proof, ProvingKey, VerifyingKey,
+        BatchProof, Challenges, MemoryBudget, MergeKind, Oracles, PlookupProof, PlookupProvingKey,
+        PlookupVerifyingKey, Proof, ProofEvaluations, ProvingKey, VerifyingKey,
     },
     verifier::Verifier,
     UniversalSNARK,
@@ -17,15 +18,18 @@ use super::{
 use crate::{
     constants::EXTRA_TRANSCRIPT_MSG_LABEL,
     errors::{PlonkError, SnarkError::ParameterError},
-    proof_system::structs::UniversalSrs,
+    proof_system::structs::{CommitKey, UniversalSrs},
     transcript::*,
 };
 use ark_ec::{
     pairing::Pairing,
     short_weierstrass::{Affine, SWCurveConfig},
+    CurveGroup, VariableBaseMSM,
 };
-use ark_ff::{Field, One};
+use ark_ff::{Field, One, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Radix2EvaluationDomain};
 use ark_std::{
+    collections::{BTreeMap, BTreeSet},
     format,
     marker::PhantomData,
     rand::{CryptoRng, RngCore},
@@ -34,7 +38,10 @@ use ark_std::{
     vec::Vec,
 };
 use jf_primitives::{
-    pcs::{prelude::UnivariateKzgPCS, PolynomialCommitmentScheme, StructuredReferenceString},
+    pcs::{
+        prelude::{Commitment, UnivariateIpaPCS, UnivariateKzgPCS},
+        StructuredReferenceString,
+    },
     rescue::RescueParameter,
 };
 use jf_relation::{
@@ -44,42 +51,391 @@ use jf_utils::par_utils::parallelizable_slice_iter;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// A Plonk instantiated with the univariate PCS `PCS`
+pub struct PlonkSnark<E: Pairing, PCS: PlonkPCS<E>>(PhantomData<(E, PCS)>);
+
 /// A Plonk instantiated with KZG PCS
-pub struct PlonkKzgSnark<E: Pairing>(PhantomData<E>);
+pub type PlonkKzgSnark<E> = PlonkSnark<E, UnivariateKzgPCS<E>>;
+
+/// A Plonk instantiated with the inner-product argument PCS, which doesn't
+/// need a trusted setup. Its proofs are larger and slower to verify than
+/// with KZG PCS, and they are not zero-knowledge, since the IPA opening
+/// proofs are not hiding.
+pub type PlonkIpaSnark<E> = PlonkSnark<E, UnivariateIpaPCS<<E as Pairing>::G1>>;
+
+/// The messages and the state of the prover before the opening proofs, see
+/// [`PlonkSnark::batch_prove_until_opening()`].
+pub(crate) struct ProverState<E: Pairing, PCS: PlonkPCS<E>, T> {
+    pub(crate) prover: Prover<E, PCS>,
+    pub(crate) transcript: T,
+    pub(crate) challenges: Challenges<E::ScalarField>,
+    pub(crate) online_oracles: Vec<Oracles<E::ScalarField>>,
+    pub(crate) lin_poly: DensePolynomial<E::ScalarField>,
+    pub(crate) wires_poly_comms_vec: Vec<Vec<Commitment<E>>>,
+    pub(crate) prod_perm_poly_comms_vec: Vec<Commitment<E>>,
+    pub(crate) split_quot_poly_comms: Vec<Commitment<E>>,
+    pub(crate) poly_evals_vec: Vec<ProofEvaluations<E::ScalarField>>,
+    pub(crate) plookup_proofs_vec: Vec<Option<PlookupProof<E>>>,
+}
 
-impl<E, F, P> PlonkKzgSnark<E>
+impl<E, F, P, PCS> PlonkSnark<E, PCS>
 where
     E: Pairing<BaseField = F, G1Affine = Affine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWCurveConfig<BaseField = F>,
+    PCS: PlonkPCS<E>,
 {
     #[allow(clippy::new_without_default)]
-    /// A new Plonk KZG SNARK
+    /// A new Plonk SNARK
     pub fn new() -> Self {
         Self(PhantomData)
     }
 
+    /// Same as [`UniversalSNARK::preprocess()`], except that the returned
+    /// proving key caches the coset evaluations of the preprocessed
+    /// polynomials, which speeds up repeated proving at the cost of a larger
+    /// proving key. See [`ProvingKey::precompute_coset_evals()`].
+    #[allow(clippy::type_complexity)]
+    pub fn preprocess_with_coset_evals<C: Arithmetization<E::ScalarField>>(
+        srs: &UniversalSrs<E, PCS>,
+        circuit: &C,
+    ) -> Result<(ProvingKey<E, PCS>, VerifyingKey<E, PCS>), PlonkError> {
+        let (mut pk, vk) = Self::preprocess(srs, circuit)?;
+        pk.precompute_coset_evals()?;
+        Ok((pk, vk))
+    }
+
     /// Generate an aggregated Plonk proof for multiple instances.
     pub fn batch_prove<C, R, T>(
         prng: &mut R,
         circuits: &[&C],
-        prove_keys: &[&ProvingKey<E>],
-    ) -> Result<BatchProof<E>, PlonkError>
+        prove_keys: &[&ProvingKey<E, PCS>],
+    ) -> Result<BatchProof<E, PCS>, PlonkError>
     where
         C: Arithmetization<E::ScalarField>,
         R: CryptoRng + RngCore,
         T: PlonkTranscript<F>,
     {
         let (batch_proof, ..) =
-            Self::batch_prove_internal::<_, _, T>(prng, circuits, prove_keys, None)?;
+            Self::batch_prove_internal::<_, _, T>(prng, circuits, prove_keys, None, None)?;
         Ok(batch_proof)
     }
 
+    /// Same as [`Self::batch_prove()`], except that the prover keeps the
+    /// memory it allocates within `memory_budget.bytes` bytes as far as
+    /// possible, besides the proving keys and the circuits. The proof is the
+    /// same as the one computed by [`Self::batch_prove()`] with the same
+    /// randomness.
+    ///
+    /// The quotient polynomial is evaluated over one chunk of its coset at a
+    /// time, with the coset evaluations of a single preprocessed polynomial of
+    /// the chunk in memory at a time, the commitments are computed over chunks
+    /// of `memory_budget.commit_chunk_size` coefficients, and the intermediate
+    /// polynomials are dropped once committed or evaluated. With the `std`
+    /// feature, the online oracles are moved to temporary files while the
+    /// quotient polynomial is computed if they don't fit in the budget next to
+    /// it.
+    ///
+    /// The budget doesn't bound the memory of the proving keys and the
+    /// circuits, which are borrowed. Even with a zero budget, the prover keeps
+    /// the coefficients of the quotient polynomial, i.e. a field element per
+    /// point of the quotient polynomial domain, which is 8 times the
+    /// evaluation domain for TurboPlonk, next to the scratch space of FFTs and
+    /// multi-scalar multiplications, about 16 field elements per gate in
+    /// total. A TurboPlonk proving key holds 18 preprocessed polynomials and a
+    /// commit key of a group element per gate, and the circuit holds its
+    /// witness, wires and (extended) permutation, about 11 field elements per
+    /// gate. Over BN254, this adds up to about 1.5 KB per gate, so that
+    /// circuits of about `2^25` gates fit within 64 GB, but not circuits of
+    /// `2^26` gates.
+    pub fn batch_prove_with_memory_budget<C, R, T>(
+        prng: &mut R,
+        circuits: &[&C],
+        prove_keys: &[&ProvingKey<E, PCS>],
+        memory_budget: MemoryBudget,
+    ) -> Result<BatchProof<E, PCS>, PlonkError>
+    where
+        C: Arithmetization<E::ScalarField>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let (batch_proof, ..) = Self::batch_prove_internal::<_, _, T>(
+            prng,
+            circuits,
+            prove_keys,
+            None,
+            Some(memory_budget),
+        )?;
+        Ok(batch_proof)
+    }
+
+    /// Same as [`UniversalSNARK::prove()`], except that the prover keeps the
+    /// memory it allocates within `memory_budget.bytes` bytes as far as
+    /// possible, see [`Self::batch_prove_with_memory_budget()`].
+    pub fn prove_with_memory_budget<C, R, T>(
+        rng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<E, PCS>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        memory_budget: MemoryBudget,
+    ) -> Result<Proof<E, PCS>, PlonkError>
+    where
+        C: Arithmetization<E::ScalarField>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        Self::prove_internal::<_, _, T>(
+            rng,
+            circuit,
+            prove_key,
+            extra_transcript_init_msg,
+            Some(memory_budget),
+        )
+    }
+
+    /// Preprocess `circuit` incrementally from the proving key `prev_pk` of
+    /// another circuit over the same evaluation domain, e.g. one that only
+    /// differs from `circuit` in a few gates.
+    ///
+    /// The commitments of polynomials that didn't change are reused. The
+    /// commitments of the others are updated by the additive homomorphism of
+    /// KZG commitments: if the evaluations of a polynomial change by `d_i` at
+    /// the rows `i` of the domain, its commitment changes by `sum_i d_i *
+    /// [L_i(x)]`, where `L_i` is the `i`-th Lagrange polynomial. Computing
+    /// `[L_i(x)]` costs as much as committing to a polynomial, so the
+    /// changed polynomials are recommitted instead when they are fewer than
+    /// the changed rows. The keys are the same as the ones computed by
+    /// [`UniversalSNARK::preprocess()`].
+    ///
+    /// Only the commitments, i.e. the multi-scalar multiplications that
+    /// dominate the preprocessing time, are saved. The selector, permutation
+    /// and lookup polynomials are all interpolated from `circuit` as in
+    /// [`UniversalSNARK::preprocess()`], since `prev_pk` doesn't keep their
+    /// evaluations, and each changed polynomial costs one more FFT to find the
+    /// changed rows.
+    ///
+    /// Return error if `prev_pk` has a different domain size, Plonk type or
+    /// number of wire types, or was computed from a different SRS.
+    #[allow(clippy::type_complexity)]
+    pub fn preprocess_incremental<C: Arithmetization<E::ScalarField>>(
+        srs: &UniversalSrs<E, PCS>,
+        prev_pk: &ProvingKey<E, PCS>,
+        circuit: &C,
+    ) -> Result<(ProvingKey<E, PCS>, VerifyingKey<E, PCS>), PlonkError> {
+        let domain_size = circuit.eval_domain_size()?;
+        let srs_size = circuit.srs_size()?;
+        if PCS::max_degree(srs) < srs_size {
+            return Err(PlonkError::IndexTooLarge);
+        }
+        if prev_pk.domain_size() != domain_size {
+            return Err(ParameterError(format!(
+                "the previous proving key's domain size {} is different from {}",
+                prev_pk.domain_size(),
+                domain_size
+            ))
+            .into());
+        }
+        if prev_pk.plookup_pk.is_some() != circuit.support_lookup()
+            || prev_pk.sigmas.len() != circuit.num_wire_types()
+        {
+            return Err(ParameterError(
+                "the previous proving key is for a different Plonk type".to_string(),
+            )
+            .into());
+        }
+        let (commit_key, open_key) = srs.trim(srs_size)?;
+        if prev_pk.commit_key != commit_key {
+            return Err(ParameterError(
+                "the previous proving key is computed from a different SRS".to_string(),
+            )
+            .into());
+        }
+
+        // 1. Compute selector and permutation polynomials.
+        let selectors_polys = circuit.compute_selector_polynomials()?;
+        let sigma_polys = circuit.compute_extended_permutation_polynomials()?;
+        let plookup_pk = Self::compute_plookup_pk(circuit)?;
+
+        // 2. Update the commitments of the changed polynomials.
+        let mut polys: Vec<_> = selectors_polys.iter().chain(sigma_polys.iter()).collect();
+        let mut prev_polys: Vec<_> = prev_pk
+            .selectors
+            .iter()
+            .chain(prev_pk.sigmas.iter())
+            .collect();
+        let mut prev_comms: Vec<_> = prev_pk
+            .vk
+            .selector_comms
+            .iter()
+            .chain(prev_pk.vk.sigma_comms.iter())
+            .collect();
+        if let (Some(pk), Some(prev_plookup_pk), Some(prev_plookup_vk)) =
+            (&plookup_pk, &prev_pk.plookup_pk, &prev_pk.vk.plookup_vk)
+        {
+            polys.extend([
+                &pk.range_table_poly,
+                &pk.key_table_poly,
+                &pk.table_dom_sep_poly,
+                &pk.q_dom_sep_poly,
+            ]);
+            prev_polys.extend([
+                &prev_plookup_pk.range_table_poly,
+                &prev_plookup_pk.key_table_poly,
+                &prev_plookup_pk.table_dom_sep_poly,
+                &prev_plookup_pk.q_dom_sep_poly,
+            ]);
+            prev_comms.extend([
+                &prev_plookup_vk.range_table_comm,
+                &prev_plookup_vk.key_table_comm,
+                &prev_plookup_vk.table_dom_sep_comm,
+                &prev_plookup_vk.q_dom_sep_comm,
+            ]);
+        }
+        let domain = Radix2EvaluationDomain::<E::ScalarField>::new(domain_size)
+            .ok_or(PlonkError::DomainCreationError)?;
+        let mut comms =
+            Self::update_commitments(&commit_key, &domain, &polys, &prev_polys, &prev_comms)?
+                .into_iter();
+        let selector_comms: Vec<_> = comms.by_ref().take(selectors_polys.len()).collect();
+        let sigma_comms: Vec<_> = comms.by_ref().take(sigma_polys.len()).collect();
+        let plookup_vk = match plookup_pk {
+            None => None,
+            Some(_) => Some(PlookupVerifyingKey {
+                range_table_comm: comms.next().ok_or(PlonkError::IteratorOutOfRange)?,
+                key_table_comm: comms.next().ok_or(PlonkError::IteratorOutOfRange)?,
+                table_dom_sep_comm: comms.next().ok_or(PlonkError::IteratorOutOfRange)?,
+                q_dom_sep_comm: comms.next().ok_or(PlonkError::IteratorOutOfRange)?,
+            }),
+        };
+
+        let vk = VerifyingKey {
+            domain_size,
+            num_inputs: circuit.num_inputs(),
+            selector_comms,
+            sigma_comms,
+            k: compute_coset_representatives(circuit.num_wire_types(), Some(domain_size)),
+            open_key,
+            plookup_vk,
+            merge_kind: MergeKind::Unmerged,
+        };
+        let mut pk = ProvingKey {
+            sigmas: sigma_polys,
+            selectors: selectors_polys,
+            commit_key,
+            vk: vk.clone(),
+            plookup_pk,
+            coset_evals: None,
+        };
+        // keep the coset evaluation cache if the previous key has one
+        if prev_pk.has_coset_evals() {
+            pk.precompute_coset_evals()?;
+        }
+
+        Ok((pk, vk))
+    }
+
+    /// Compute the commitments of `polys` from the commitments `prev_comms`
+    /// of `prev_polys`, see [`Self::preprocess_incremental()`].
+    fn update_commitments(
+        commit_key: &CommitKey<E, PCS>,
+        domain: &Radix2EvaluationDomain<E::ScalarField>,
+        polys: &[&DensePolynomial<E::ScalarField>],
+        prev_polys: &[&DensePolynomial<E::ScalarField>],
+        prev_comms: &[&Commitment<E>],
+    ) -> Result<Vec<Commitment<E>>, PlonkError> {
+        // The changes of the evaluations over the domain, as (row, change) pairs.
+        let poly_pairs: Vec<_> = polys
+            .iter()
+            .copied()
+            .zip(prev_polys.iter().copied())
+            .collect();
+        let diffs: Vec<Vec<(usize, E::ScalarField)>> = parallelizable_slice_iter(&poly_pairs)
+            .map(|(poly, prev_poly)| {
+                let diff = *poly - *prev_poly;
+                if diff.is_zero() {
+                    return vec![];
+                }
+                domain
+                    .fft(&diff.coeffs)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, eval)| !eval.is_zero())
+                    .collect()
+            })
+            .collect();
+        let changed_rows: BTreeSet<usize> = diffs
+            .iter()
+            .flat_map(|diff| diff.iter().map(|(row, _)| *row))
+            .collect();
+        let num_changed_polys = diffs.iter().filter(|diff| !diff.is_empty()).count();
+
+        if changed_rows.len() >= num_changed_polys {
+            return parallelizable_slice_iter(&diffs)
+                .zip(parallelizable_slice_iter(polys))
+                .zip(parallelizable_slice_iter(prev_comms))
+                .map(|((diff, poly), prev_comm)| {
+                    if diff.is_empty() {
+                        Ok(**prev_comm)
+                    } else {
+                        Ok(PCS::to_plonk_commitment(PCS::commit(commit_key, poly)?))
+                    }
+                })
+                .collect();
+        }
+
+        // [L_i(x)] = sum_j w^{-ij} / n * [x^j]
+        let n = domain.size();
+        let bases = PCS::commit_bases(commit_key);
+        let rows: Vec<usize> = changed_rows.into_iter().collect();
+        let lagrange_bases: BTreeMap<usize, E::G1Affine> = parallelizable_slice_iter(&rows)
+            .map(|&row| {
+                let w_inv = domain.group_gen_inv.pow([row as u64]);
+                let mut coeff = domain.size_inv;
+                let coeffs: Vec<E::ScalarField> = (0..n)
+                    .map(|_| {
+                        let c = coeff;
+                        coeff *= w_inv;
+                        c
+                    })
+                    .collect();
+                (
+                    row,
+                    E::G1::msm_unchecked(&bases[..n], &coeffs).into_affine(),
+                )
+            })
+            .collect();
+        Ok(diffs
+            .iter()
+            .zip(prev_comms.iter())
+            .map(|(diff, prev_comm)| {
+                let bases: Vec<E::G1Affine> =
+                    diff.iter().map(|(row, _)| lagrange_bases[row]).collect();
+                let scalars: Vec<E::ScalarField> = diff.iter().map(|(_, d)| *d).collect();
+                Commitment((E::G1::msm_unchecked(&bases, &scalars) + prev_comm.0).into_affine())
+            })
+            .collect())
+    }
+
+    /// Compute the Plookup proving key if `circuit` supports lookup.
+    fn compute_plookup_pk<C: Arithmetization<E::ScalarField>>(
+        circuit: &C,
+    ) -> Result<Option<PlookupProvingKey<E>>, PlonkError> {
+        if !circuit.support_lookup() {
+            return Ok(None);
+        }
+        Ok(Some(PlookupProvingKey {
+            range_table_poly: circuit.compute_range_table_polynomial()?,
+            key_table_poly: circuit.compute_key_table_polynomial()?,
+            table_dom_sep_poly: circuit.compute_table_dom_sep_polynomial()?,
+            q_dom_sep_poly: circuit.compute_q_dom_sep_polynomial()?,
+        }))
+    }
+
     /// Verify a single aggregated Plonk proof.
     pub fn verify_batch_proof<T>(
-        verify_keys: &[&VerifyingKey<E>],
+        verify_keys: &[&VerifyingKey<E, PCS>],
         public_inputs: &[&[E::ScalarField]],
-        batch_proof: &BatchProof<E>,
+        batch_proof: &BatchProof<E, PCS>,
     ) -> Result<(), PlonkError>
     where
         T: PlonkTranscript<F>,
@@ -101,9 +457,9 @@ where
 
     /// Batch verify multiple SNARK proofs (w.r.t. different verifying keys).
     pub fn batch_verify<T>(
-        verify_keys: &[&VerifyingKey<E>],
+        verify_keys: &[&VerifyingKey<E, PCS>],
         public_inputs: &[&[E::ScalarField]],
-        proofs: &[&Proof<E>],
+        proofs: &[&Proof<E, PCS>],
         extra_transcript_init_msgs: &[Option<Vec<u8>>],
     ) -> Result<(), PlonkError>
     where
@@ -153,6 +509,37 @@ where
         Ok(())
     }
 
+    /// Compute a Plonk proof with an optional memory budget for the prover.
+    fn prove_internal<C, R, T>(
+        rng: &mut R,
+        circuit: &C,
+        prove_key: &ProvingKey<E, PCS>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        memory_budget: Option<MemoryBudget>,
+    ) -> Result<Proof<E, PCS>, PlonkError>
+    where
+        C: Arithmetization<E::ScalarField>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let (batch_proof, ..) = Self::batch_prove_internal::<_, _, T>(
+            rng,
+            &[circuit],
+            &[prove_key],
+            extra_transcript_init_msg,
+            memory_budget,
+        )?;
+        Ok(Proof {
+            wires_poly_comms: batch_proof.wires_poly_comms_vec[0].clone(),
+            prod_perm_poly_comm: batch_proof.prod_perm_poly_comms_vec[0],
+            split_quot_poly_comms: batch_proof.split_quot_poly_comms,
+            opening_proof: batch_proof.opening_proof,
+            shifted_opening_proof: batch_proof.shifted_opening_proof,
+            poly_evals: batch_proof.poly_evals_vec[0].clone(),
+            plookup_proof: batch_proof.plookup_proofs_vec[0].clone(),
+        })
+    }
+
     /// An internal private API for ease of testing
     ///
     /// Batchly compute a Plonk proof for multiple instances. Return the batch
@@ -165,16 +552,65 @@ where
     fn batch_prove_internal<C, R, T>(
         prng: &mut R,
         circuits: &[&C],
-        prove_keys: &[&ProvingKey<E>],
+        prove_keys: &[&ProvingKey<E, PCS>],
         extra_transcript_init_msg: Option<Vec<u8>>,
+        memory_budget: Option<MemoryBudget>,
     ) -> Result<
         (
-            BatchProof<E>,
+            BatchProof<E, PCS>,
             Vec<Oracles<E::ScalarField>>,
             Challenges<E::ScalarField>,
         ),
         PlonkError,
     >
+    where
+        C: Arithmetization<E::ScalarField>,
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let state = Self::batch_prove_until_opening::<_, _, T>(
+            prng,
+            circuits,
+            prove_keys,
+            extra_transcript_init_msg,
+            memory_budget,
+        )?;
+
+        // Round 5 (continued)
+        let (opening_proof, shifted_opening_proof) = state.prover.compute_opening_proofs(
+            &prove_keys[0].commit_key,
+            prove_keys,
+            &state.challenges.zeta,
+            &state.challenges.v,
+            &state.online_oracles,
+            &state.lin_poly,
+        )?;
+
+        Ok((
+            BatchProof {
+                wires_poly_comms_vec: state.wires_poly_comms_vec,
+                prod_perm_poly_comms_vec: state.prod_perm_poly_comms_vec,
+                poly_evals_vec: state.poly_evals_vec,
+                plookup_proofs_vec: state.plookup_proofs_vec,
+                split_quot_poly_comms: state.split_quot_poly_comms,
+                opening_proof,
+                shifted_opening_proof,
+            },
+            state.online_oracles,
+            state.challenges,
+        ))
+    }
+
+    /// Run the prover of [`Self::batch_prove_internal()`] up to the challenge
+    /// `v` of the last round, i.e. up to the opening proofs, and return its
+    /// messages and state.
+    pub(crate) fn batch_prove_until_opening<C, R, T>(
+        prng: &mut R,
+        circuits: &[&C],
+        prove_keys: &[&ProvingKey<E, PCS>],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+        memory_budget: Option<MemoryBudget>,
+    ) -> Result<ProverState<E, PCS, T>, PlonkError>
     where
         C: Arithmetization<E::ScalarField>,
         R: CryptoRng + RngCore,
@@ -240,7 +676,7 @@ where
         // Initialize verifier challenges and online polynomial oracles.
         let mut challenges = Challenges::default();
         let mut online_oracles = vec![Oracles::default(); circuits.len()];
-        let prover = Prover::new(n, num_wire_types)?;
+        let mut prover = Prover::new(n, num_wire_types)?.with_memory_budget(memory_budget);
 
         // Round 1
         let mut wires_poly_comms_vec = vec![];
@@ -313,6 +749,8 @@ where
             };
             prod_lookup_poly_comms_vec.push(prod_lookup_poly_comm);
         }
+        drop(sorted_vec_list);
+        drop(merged_table_list);
 
         // Round 3
         challenges.alpha = transcript.get_and_append_challenge::<E>(b"alpha")?;
@@ -321,13 +759,22 @@ where
             &prove_keys[0].commit_key,
             prove_keys,
             &challenges,
-            &online_oracles,
+            &mut online_oracles,
             num_wire_types,
         )?;
         transcript.append_commitments(b"quot_poly_comms", &split_quot_poly_comms)?;
 
         // Round 4
         challenges.zeta = transcript.get_and_append_challenge::<E>(b"zeta")?;
+        // The split quotient polynomials are only needed for the linearization
+        // polynomial, drop them before restoring the online oracles.
+        let mut lin_poly = Prover::<E, PCS>::compute_quotient_component_for_lin_poly(
+            n,
+            challenges.zeta,
+            &split_quot_polys,
+        )?;
+        drop(split_quot_polys);
+        prover.restore_oracles(&mut online_oracles)?;
         let mut poly_evals_vec = vec![];
         for i in 0..circuits.len() {
             let poly_evals = prover.compute_evaluations(
@@ -358,24 +805,18 @@ where
             plookup_evals_vec.push(plookup_evals);
         }
 
-        let mut lin_poly = Prover::<E>::compute_quotient_component_for_lin_poly(
-            n,
-            challenges.zeta,
-            &split_quot_polys,
-        )?;
         let mut alpha_base = E::ScalarField::one();
         let alpha_3 = challenges.alpha.square() * challenges.alpha;
         let alpha_7 = alpha_3.square() * challenges.alpha;
         for i in 0..circuits.len() {
-            lin_poly = lin_poly
-                + prover.compute_non_quotient_component_for_lin_poly(
-                    alpha_base,
-                    prove_keys[i],
-                    &challenges,
-                    &online_oracles[i],
-                    &poly_evals_vec[i],
-                    plookup_evals_vec[i].as_ref(),
-                )?;
+            lin_poly += &prover.compute_non_quotient_component_for_lin_poly(
+                alpha_base,
+                prove_keys[i],
+                &challenges,
+                &online_oracles[i],
+                &poly_evals_vec[i],
+                plookup_evals_vec[i].as_ref(),
+            )?;
             // update the alpha power term (i.e. the random combiner that aggregates
             // multiple instances)
             if plookup_evals_vec[i].is_some() {
@@ -387,14 +828,6 @@ where
 
         // Round 5
         challenges.v = transcript.get_and_append_challenge::<E>(b"v")?;
-        let (opening_proof, shifted_opening_proof) = prover.compute_opening_proofs(
-            &prove_keys[0].commit_key,
-            prove_keys,
-            &challenges.zeta,
-            &challenges.v,
-            &online_oracles,
-            &lin_poly,
-        )?;
 
         // Plookup: build Plookup argument
         let mut plookup_proofs_vec = vec![];
@@ -411,80 +844,109 @@ where
             plookup_proofs_vec.push(plookup_proof);
         }
 
-        Ok((
-            BatchProof {
-                wires_poly_comms_vec,
-                prod_perm_poly_comms_vec,
-                poly_evals_vec,
-                plookup_proofs_vec,
-                split_quot_poly_comms,
-                opening_proof,
-                shifted_opening_proof,
-            },
-            online_oracles,
+        Ok(ProverState {
+            prover,
+            transcript,
             challenges,
-        ))
+            online_oracles,
+            lin_poly,
+            wires_poly_comms_vec,
+            prod_perm_poly_comms_vec,
+            split_quot_poly_comms,
+            poly_evals_vec,
+            plookup_proofs_vec,
+        })
+    }
+}
+
+impl<E, F, P> PlonkKzgSnark<E>
+where
+    E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWCurveConfig<BaseField = F>,
+{
+    /// Verify a Plonk proof encoded in the compact format described in
+    /// [`crate::proof_system::compact`]. A proof with a batched opening proof
+    /// (version 2) needs `[x^2]2` in `verify_key`, e.g. a verifying key from
+    /// [`PlonkKzgShplonkSnark::preprocess()`](crate::proof_system::PlonkKzgShplonkSnark),
+    /// otherwise return error.
+    pub fn verify_compact<T>(
+        verify_key: &VerifyingKey<E>,
+        public_input: &[E::ScalarField],
+        compact_proof: &[u8],
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (proof, batched_opening_proof) = Proof::decode_compact(compact_proof)?;
+        let batched_opening_proof = match batched_opening_proof {
+            None => {
+                return Self::verify::<T>(
+                    verify_key,
+                    public_input,
+                    &proof,
+                    extra_transcript_init_msg,
+                )
+            },
+            Some(opening_proof) => opening_proof,
+        };
+
+        let verifier = Verifier::new(verify_key.domain_size)?;
+        let batch_proof: BatchProof<E> = proof.into();
+        verifier.check_instances(&[verify_key], &[public_input], &batch_proof)?;
+        // The challenge `u` only combines the claims at `zeta` and `zeta * g`,
+        // which are checked separately with a batched opening proof.
+        let (_, mut challenges) = Verifier::compute_challenges_until_v::<T>(
+            &[verify_key],
+            &[public_input],
+            &batch_proof,
+            &extra_transcript_init_msg,
+        )?;
+        challenges.u = E::ScalarField::one();
+        let pcs_info = verifier.prepare_pcs_info_with_challenges(
+            &[verify_key],
+            &[public_input],
+            &batch_proof,
+            &challenges,
+        )?;
+        if !Verifier::verify_batched_opening_proof(
+            &verify_key.open_key,
+            &pcs_info,
+            &batched_opening_proof,
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
     }
 }
 
-impl<E, F, P> UniversalSNARK<E> for PlonkKzgSnark<E>
+impl<E, F, P, PCS> UniversalSNARK<E> for PlonkSnark<E, PCS>
 where
     E: Pairing<BaseField = F, G1Affine = Affine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWCurveConfig<BaseField = F>,
+    PCS: PlonkPCS<E>,
 {
-    type Proof = Proof<E>;
-    type ProvingKey = ProvingKey<E>;
-    type VerifyingKey = VerifyingKey<E>;
-    type UniversalSRS = UniversalSrs<E>;
+    type Proof = Proof<E, PCS>;
+    type ProvingKey = ProvingKey<E, PCS>;
+    type VerifyingKey = VerifyingKey<E, PCS>;
+    type UniversalSRS = UniversalSrs<E, PCS>;
     type Error = PlonkError;
 
-    // FIXME: (alex) see <https://github.com/EspressoSystems/jellyfish/issues/249>
+    fn universal_setup<R: RngCore + CryptoRng>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalSRS, Self::Error> {
+        Ok(PCS::universal_setup(max_degree, rng)?)
+    }
+
     #[cfg(any(test, feature = "test-srs"))]
     fn universal_setup_for_testing<R: RngCore + CryptoRng>(
         max_degree: usize,
         rng: &mut R,
     ) -> Result<Self::UniversalSRS, Self::Error> {
-        use ark_ec::{scalar_mul::fixed_base::FixedBase, CurveGroup};
-        use ark_ff::PrimeField;
-        use ark_std::{end_timer, start_timer, UniformRand};
-
-        let setup_time = start_timer!(|| format!("KZG10::Setup with degree {}", max_degree));
-        let beta = E::ScalarField::rand(rng);
-        let g = E::G1::rand(rng);
-        let h = E::G2::rand(rng);
-
-        let mut powers_of_beta = vec![E::ScalarField::one()];
-
-        let mut cur = beta;
-        for _ in 0..max_degree {
-            powers_of_beta.push(cur);
-            cur *= &beta;
-        }
-
-        let window_size = FixedBase::get_mul_window_size(max_degree + 1);
-
-        let scalar_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
-        let g_time = start_timer!(|| "Generating powers of G");
-        // TODO: parallelization
-        let g_table = FixedBase::get_window_table(scalar_bits, window_size, g);
-        let powers_of_g =
-            FixedBase::msm::<E::G1>(scalar_bits, window_size, &g_table, &powers_of_beta);
-        end_timer!(g_time);
-
-        let powers_of_g = E::G1::normalize_batch(&powers_of_g);
-
-        let h = h.into_affine();
-        let beta_h = (h * beta).into_affine();
-
-        let pp = UniversalSrs {
-            powers_of_g,
-            h,
-            beta_h,
-            powers_of_h: vec![h, beta_h],
-        };
-        end_timer!(setup_time);
-        Ok(pp)
+        Ok(PCS::universal_setup_for_testing(max_degree, rng)?)
     }
 
     /// Input a circuit and the SRS, precompute the proving key and verification
@@ -497,7 +959,7 @@ where
         let domain_size = circuit.eval_domain_size()?;
         let srs_size = circuit.srs_size()?;
         let num_inputs = circuit.num_inputs();
-        if srs.max_degree() < circuit.srs_size()? {
+        if PCS::max_degree(srs) < srs_size {
             return Err(PlonkError::IndexTooLarge);
         }
         // 1. Compute selector and permutation polynomials.
@@ -505,54 +967,37 @@ where
         let sigma_polys = circuit.compute_extended_permutation_polynomials()?;
 
         // Compute Plookup proving key if support lookup.
-        let plookup_pk = if circuit.support_lookup() {
-            let range_table_poly = circuit.compute_range_table_polynomial()?;
-            let key_table_poly = circuit.compute_key_table_polynomial()?;
-            let table_dom_sep_poly = circuit.compute_table_dom_sep_polynomial()?;
-            let q_dom_sep_poly = circuit.compute_q_dom_sep_polynomial()?;
-            Some(PlookupProvingKey {
-                range_table_poly,
-                key_table_poly,
-                table_dom_sep_poly,
-                q_dom_sep_poly,
-            })
-        } else {
-            None
-        };
+        let plookup_pk = Self::compute_plookup_pk(circuit)?;
 
         // 2. Compute VerifyingKey
         let (commit_key, open_key) = srs.trim(srs_size)?;
         let selector_comms = parallelizable_slice_iter(&selectors_polys)
-            .map(|poly| UnivariateKzgPCS::commit(&commit_key, poly).map_err(PlonkError::PCSError))
-            .collect::<Result<Vec<_>, PlonkError>>()?
-            .into_iter()
-            .collect();
+            .map(|poly| Ok(PCS::to_plonk_commitment(PCS::commit(&commit_key, poly)?)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
         let sigma_comms = parallelizable_slice_iter(&sigma_polys)
-            .map(|poly| UnivariateKzgPCS::commit(&commit_key, poly).map_err(PlonkError::PCSError))
-            .collect::<Result<Vec<_>, PlonkError>>()?
-            .into_iter()
-            .collect();
+            .map(|poly| Ok(PCS::to_plonk_commitment(PCS::commit(&commit_key, poly)?)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
 
         // Compute Plookup verifying key if support lookup.
         let plookup_vk = match circuit.support_lookup() {
             false => None,
             true => Some(PlookupVerifyingKey {
-                range_table_comm: UnivariateKzgPCS::commit(
+                range_table_comm: PCS::to_plonk_commitment(PCS::commit(
                     &commit_key,
                     &plookup_pk.as_ref().unwrap().range_table_poly,
-                )?,
-                key_table_comm: UnivariateKzgPCS::commit(
+                )?),
+                key_table_comm: PCS::to_plonk_commitment(PCS::commit(
                     &commit_key,
                     &plookup_pk.as_ref().unwrap().key_table_poly,
-                )?,
-                table_dom_sep_comm: UnivariateKzgPCS::commit(
+                )?),
+                table_dom_sep_comm: PCS::to_plonk_commitment(PCS::commit(
                     &commit_key,
                     &plookup_pk.as_ref().unwrap().table_dom_sep_poly,
-                )?,
-                q_dom_sep_comm: UnivariateKzgPCS::commit(
+                )?),
+                q_dom_sep_comm: PCS::to_plonk_commitment(PCS::commit(
                     &commit_key,
                     &plookup_pk.as_ref().unwrap().q_dom_sep_poly,
-                )?,
+                )?),
             }),
         };
 
@@ -564,7 +1009,7 @@ where
             k: compute_coset_representatives(circuit.num_wire_types(), Some(domain_size)),
             open_key,
             plookup_vk,
-            is_merged: false,
+            merge_kind: MergeKind::Unmerged,
         };
 
         // Compute ProvingKey (which includes the VerifyingKey)
@@ -574,6 +1019,7 @@ where
             commit_key,
             vk: vk.clone(),
             plookup_pk,
+            coset_evals: None,
         };
 
         Ok((pk, vk))
@@ -595,21 +1041,7 @@ where
         R: CryptoRng + RngCore,
         T: PlonkTranscript<F>,
     {
-        let (batch_proof, ..) = Self::batch_prove_internal::<_, _, T>(
-            rng,
-            &[circuit],
-            &[prove_key],
-            extra_transcript_init_msg,
-        )?;
-        Ok(Proof {
-            wires_poly_comms: batch_proof.wires_poly_comms_vec[0].clone(),
-            prod_perm_poly_comm: batch_proof.prod_perm_poly_comms_vec[0],
-            split_quot_poly_comms: batch_proof.split_quot_poly_comms,
-            opening_proof: batch_proof.opening_proof,
-            shifted_opening_proof: batch_proof.shifted_opening_proof,
-            poly_evals: batch_proof.poly_evals_vec[0].clone(),
-            plookup_proof: batch_proof.plookup_proofs_vec[0].clone(),
-        })
+        Self::prove_internal::<_, _, T>(rng, circuit, prove_key, extra_transcript_init_msg, None)
     }
 
     fn verify<T>(
@@ -636,14 +1068,15 @@ pub mod test {
         errors::PlonkError,
         proof_system::{
             structs::{
-                eval_merged_lookup_witness, eval_merged_table, Challenges, Oracles, Proof,
-                ProvingKey, UniversalSrs, VerifyingKey,
+                eval_merged_lookup_witness, eval_merged_table, Challenges, CommitKey, CosetEvals,
+                MemoryBudget, MergeKind, OpenKey, Oracles, PlookupProvingKey, PlookupVerifyingKey,
+                Proof, ProvingKey, UniversalSrs, VerifyingKey,
             },
-            PlonkKzgSnark, UniversalSNARK,
+            PlonkIpaSnark, PlonkKzgSnark, UniversalSNARK,
         },
         transcript::{
             rescue::RescueTranscript, solidity::SolidityTranscript, standard::StandardTranscript,
-            PlonkTranscript,
+            PlonkTranscript, VkDigestTranscript,
         },
         PlonkType,
     };
@@ -660,7 +1093,7 @@ pub mod test {
         univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
         Radix2EvaluationDomain,
     };
-    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
     use ark_std::{
         format,
         rand::{CryptoRng, RngCore},
@@ -1065,6 +1498,123 @@ pub mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_ipa_plonk_proof_system() -> Result<(), PlonkError> {
+        test_ipa_plonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_ipa_plonk_proof_system_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_ipa_plonk_proof_system_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )?;
+        Ok(())
+    }
+
+    fn test_ipa_plonk_proof_system_helper<E, F, P, T>(
+        plonk_type: PlonkType,
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        // 1. Transparent setup
+        let rng = &mut test_rng();
+        let n = 64;
+        let max_degree = n + 2;
+        let srs = PlonkIpaSnark::<E>::universal_setup(max_degree, rng)?;
+
+        // 2. Create circuits
+        let circuits = (0..4)
+            .map(|i| gen_circuit_for_test(2 + i / 2, 1 + i % 2, plonk_type))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let public_inputs: Vec<Vec<E::ScalarField>> = circuits
+            .iter()
+            .map(|cs| cs.public_input())
+            .collect::<Result<Vec<Vec<E::ScalarField>>, _>>(
+        )?;
+
+        // 3. Preprocessing
+        let (pk1, vk1) = PlonkIpaSnark::<E>::preprocess(&srs, &circuits[0])?;
+        let (pk2, vk2) = PlonkIpaSnark::<E>::preprocess(&srs, &circuits[2])?;
+        let vks = [&vk1, &vk1, &vk2, &vk2];
+
+        // 4. Proving and verification
+        let mut proofs = vec![];
+        for (i, cs) in circuits.iter().enumerate() {
+            let pk_ref = if i < 2 { &pk1 } else { &pk2 };
+            let proof = PlonkIpaSnark::<E>::prove::<_, _, T>(rng, cs, pk_ref, None)?;
+            PlonkIpaSnark::<E>::verify::<T>(vks[i], &public_inputs[i], &proof, None)?;
+
+            // Inconsistent public input should fail the verification.
+            let mut bad_pub_input = public_inputs[i].clone();
+            bad_pub_input[0] = E::ScalarField::from(0u8);
+            assert!(PlonkIpaSnark::<E>::verify::<T>(vks[i], &bad_pub_input, &proof, None).is_err());
+            // Tampered opening proofs should fail the verification.
+            let mut bad_proof = proof.clone();
+            bad_proof.opening_proof.a += E::ScalarField::one();
+            assert!(
+                PlonkIpaSnark::<E>::verify::<T>(vks[i], &public_inputs[i], &bad_proof, None)
+                    .is_err()
+            );
+            let mut bad_proof = proof.clone();
+            bad_proof.shifted_opening_proof = proof.opening_proof.clone();
+            assert!(
+                PlonkIpaSnark::<E>::verify::<T>(vks[i], &public_inputs[i], &bad_proof, None)
+                    .is_err()
+            );
+
+            // The proof and the keys round-trip through serialization.
+            let mut bytes = vec![];
+            proof.serialize_compressed(&mut bytes)?;
+            assert_eq!(Proof::deserialize_compressed(&bytes[..])?, proof);
+            let mut bytes = vec![];
+            vks[i].serialize_compressed(&mut bytes)?;
+            assert_eq!(&VerifyingKey::deserialize_compressed(&bytes[..])?, vks[i]);
+            proofs.push(proof);
+        }
+
+        // 5. Batch verification
+        let public_inputs_ref: Vec<&[E::ScalarField]> =
+            public_inputs.iter().map(|pi| &pi[..]).collect();
+        let mut proofs_ref: Vec<&Proof<E, _>> = proofs.iter().collect();
+        PlonkIpaSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &[None, None, None, None],
+        )?;
+        proofs_ref.swap(0, 1);
+        assert!(PlonkIpaSnark::<E>::batch_verify::<T>(
+            &vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &[None, None, None, None],
+        )
+        .is_err());
+
+        // 6. Batch proving
+        let circuits_ref: Vec<_> = circuits.iter().collect();
+        let batch_proof =
+            PlonkIpaSnark::<E>::batch_prove::<_, _, T>(rng, &circuits_ref[..2], &[&pk1, &pk1])?;
+        PlonkIpaSnark::<E>::verify_batch_proof::<T>(
+            &vks[..2],
+            &public_inputs_ref[..2],
+            &batch_proof,
+        )?;
+        assert!(PlonkIpaSnark::<E>::verify_batch_proof::<T>(
+            &vks[..2],
+            &[public_inputs_ref[1], public_inputs_ref[0]],
+            &batch_proof,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_inconsistent_pub_input_len() -> Result<(), PlonkError> {
         // merlin transcripts
@@ -1232,8 +1782,13 @@ pub mod test {
         let (pk, _) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
 
         // 4. Proving
-        let (_, oracles, challenges) =
-            PlonkKzgSnark::<E>::batch_prove_internal::<_, _, T>(rng, &[&circuit], &[&pk], None)?;
+        let (_, oracles, challenges) = PlonkKzgSnark::<E>::batch_prove_internal::<_, _, T>(
+            rng,
+            &[&circuit],
+            &[&pk],
+            None,
+            None,
+        )?;
 
         // 5. Check that the targeted polynomials evaluate to zero on the vanishing set.
         check_plonk_prover_polynomials(plonk_type, &oracles[0], &pk, &challenges)?;
@@ -1527,6 +2082,101 @@ pub mod test {
         Ok(())
     }
 
+    // The serialization layout of the verifying key when the merge kind was a
+    // boolean flag.
+    #[derive(CanonicalSerialize, CanonicalDeserialize)]
+    struct LegacyVerifyingKey<E: Pairing> {
+        domain_size: usize,
+        num_inputs: usize,
+        sigma_comms: Vec<Commitment<E>>,
+        selector_comms: Vec<Commitment<E>>,
+        k: Vec<E::ScalarField>,
+        open_key: OpenKey<E>,
+        is_merged: bool,
+        plookup_vk: Option<PlookupVerifyingKey<E>>,
+    }
+
+    // The serialization layout of the proving key before the coset evaluations
+    // cache was added.
+    #[derive(CanonicalSerialize, CanonicalDeserialize)]
+    struct LegacyProvingKey<E: Pairing> {
+        sigmas: Vec<DensePolynomial<E::ScalarField>>,
+        selectors: Vec<DensePolynomial<E::ScalarField>>,
+        commit_key: CommitKey<E>,
+        vk: LegacyVerifyingKey<E>,
+        plookup_pk: Option<PlookupProvingKey<E>>,
+    }
+
+    #[test]
+    fn test_legacy_serde() -> Result<(), PlonkError> {
+        test_legacy_serde_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_legacy_serde_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_legacy_serde_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)?;
+        test_legacy_serde_helper::<BW6_761, Fq761, _>(PlonkType::TurboPlonk)
+    }
+
+    fn test_legacy_serde_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(80, rng)?;
+        let (pk, _) = PlonkKzgSnark::<E>::preprocess_with_coset_evals(&srs, &circuit)?;
+        let legacy_vk = |vk: &VerifyingKey<E>| LegacyVerifyingKey {
+            domain_size: vk.domain_size,
+            num_inputs: vk.num_inputs,
+            sigma_comms: vk.sigma_comms.clone(),
+            selector_comms: vk.selector_comms.clone(),
+            k: vk.k.clone(),
+            open_key: vk.open_key.clone(),
+            is_merged: vk.is_merged(),
+            plookup_vk: vk.plookup_vk.clone(),
+        };
+        let legacy_pk = LegacyProvingKey {
+            sigmas: pk.sigmas.clone(),
+            selectors: pk.selectors.clone(),
+            commit_key: pk.commit_key.clone(),
+            vk: legacy_vk(&pk.vk),
+            plookup_pk: pk.plookup_pk.clone(),
+        };
+
+        for compress in [Compress::Yes, Compress::No] {
+            // keys serialized in the legacy format are deserialized without
+            // their cache, and serialized into the same bytes
+            let mut legacy_bytes = Vec::new();
+            legacy_pk.serialize_with_mode(&mut legacy_bytes, compress)?;
+            let de =
+                ProvingKey::<E>::deserialize_with_mode(&legacy_bytes[..], compress, Validate::Yes)?;
+            assert!(!de.has_coset_evals());
+            let mut uncached_pk = pk.clone();
+            uncached_pk.clear_coset_evals();
+            assert_eq!(de, uncached_pk);
+            let mut ser_bytes = Vec::new();
+            pk.serialize_with_mode(&mut ser_bytes, compress)?;
+            assert_eq!(ser_bytes, legacy_bytes);
+            assert_eq!(pk.serialized_size(compress), legacy_bytes.len());
+
+            // including merged verifying keys
+            let mut merged_vk = pk.vk.clone();
+            merged_vk.merge_kind = MergeKind::Pair;
+            let mut legacy_bytes = Vec::new();
+            legacy_vk(&merged_vk).serialize_with_mode(&mut legacy_bytes, compress)?;
+            let de = VerifyingKey::<E>::deserialize_with_mode(
+                &legacy_bytes[..],
+                compress,
+                Validate::Yes,
+            )?;
+            assert_eq!(de, merged_vk);
+            let mut ser_bytes = Vec::new();
+            merged_vk.serialize_with_mode(&mut ser_bytes, compress)?;
+            assert_eq!(ser_bytes, legacy_bytes);
+        }
+        Ok(())
+    }
+
     fn test_serde_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
     where
         E: Pairing<BaseField = F, G1Affine = Affine<P>>,
@@ -1565,6 +2215,379 @@ pub mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_coset_evals_cache() -> Result<(), PlonkError> {
+        test_coset_evals_cache_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_coset_evals_cache_helper::<Bn254, Fq254, _, StandardTranscript>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_coset_evals_cache_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::TurboPlonk,
+        )?;
+        test_coset_evals_cache_helper::<Bls12_381, Fq381, _, SolidityTranscript>(
+            PlonkType::UltraPlonk,
+        )
+    }
+
+    fn test_coset_evals_cache_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let max_degree = 80;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)?;
+
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let (cached_pk, cached_vk) =
+            PlonkKzgSnark::<E>::preprocess_with_coset_evals(&srs, &circuit)?;
+        assert!(!pk.has_coset_evals());
+        assert!(cached_pk.has_coset_evals());
+        assert_eq!(cached_vk, vk);
+
+        // the cache doesn't change the proof.
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, T>(&mut jf_utils::test_rng(), &circuit, &pk, None)?;
+        let cached_proof = PlonkKzgSnark::<E>::prove::<_, _, T>(
+            &mut jf_utils::test_rng(),
+            &circuit,
+            &cached_pk,
+            None,
+        )?;
+        assert_eq!(cached_proof, proof);
+        let public_input = circuit.public_input()?;
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &cached_proof, None).is_ok());
+
+        // the cache is not part of the canonical serialization of the key, but
+        // is serialized on its own
+        let mut ser_bytes = Vec::new();
+        cached_pk.serialize_compressed(&mut ser_bytes)?;
+        let mut pk_bytes = Vec::new();
+        pk.serialize_compressed(&mut pk_bytes)?;
+        assert_eq!(ser_bytes, pk_bytes);
+        let mut de = ProvingKey::<E>::deserialize_compressed(&ser_bytes[..])?;
+        assert_eq!(de, pk);
+        let coset_evals = cached_pk.coset_evals().unwrap();
+        let mut ser_bytes = Vec::new();
+        coset_evals.serialize_compressed(&mut ser_bytes)?;
+        let de_coset_evals = CosetEvals::deserialize_compressed(&ser_bytes[..])?;
+        let mut bad_coset_evals = de_coset_evals.clone();
+        bad_coset_evals.sigmas.pop();
+        assert!(de.set_coset_evals(rng, bad_coset_evals).is_err());
+        let mut bad_coset_evals = de_coset_evals.clone();
+        bad_coset_evals.selectors[0].pop();
+        assert!(de.set_coset_evals(rng, bad_coset_evals).is_err());
+        // evaluations of the right shape but of other polynomials
+        let mut bad_coset_evals = de_coset_evals.clone();
+        bad_coset_evals.sigmas.swap(0, 1);
+        assert!(de.set_coset_evals(rng, bad_coset_evals.clone()).is_err());
+        assert!(!de.has_coset_evals());
+        de.set_coset_evals(rng, de_coset_evals)?;
+        assert_eq!(de, cached_pk);
+
+        // or, on demand, with the key
+        for compress in [Compress::Yes, Compress::No] {
+            for key in [&pk, &cached_pk] {
+                let mut ser_bytes = Vec::new();
+                key.serialize_with_coset_evals(&mut ser_bytes, compress)?;
+                assert_eq!(
+                    key.serialized_size_with_coset_evals(compress),
+                    ser_bytes.len()
+                );
+                let de = ProvingKey::<E>::deserialize_with_coset_evals(
+                    rng,
+                    &ser_bytes[..],
+                    compress,
+                    Validate::Yes,
+                )?;
+                assert_eq!(de, *key);
+            }
+            let mut bad_pk = pk.clone();
+            bad_pk.coset_evals = Some(bad_coset_evals.clone());
+            let mut ser_bytes = Vec::new();
+            bad_pk.serialize_with_coset_evals(&mut ser_bytes, compress)?;
+            assert!(ProvingKey::<E>::deserialize_with_coset_evals(
+                rng,
+                &ser_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .is_err());
+            let de = ProvingKey::<E>::deserialize_with_coset_evals(
+                rng,
+                &ser_bytes[..],
+                compress,
+                Validate::No,
+            )?;
+            assert_eq!(de, bad_pk);
+        }
+        let mut cached_bytes = Vec::new();
+        cached_pk.serialize_with_coset_evals(&mut cached_bytes, Compress::Yes)?;
+        assert!(cached_bytes.len() > pk_bytes.len());
+
+        let mut cleared_pk = cached_pk;
+        cleared_pk.clear_coset_evals();
+        assert_eq!(cleared_pk, pk);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_budget() -> Result<(), PlonkError> {
+        test_memory_budget_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_memory_budget_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::UltraPlonk)?;
+        test_memory_budget_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(
+            PlonkType::UltraPlonk,
+        )?;
+        test_memory_budget_helper::<BW6_761, Fq761, _, StandardTranscript>(PlonkType::TurboPlonk)
+    }
+
+    fn test_memory_budget_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let max_degree = 80;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let (cached_pk, _) = PlonkKzgSnark::<E>::preprocess_with_coset_evals(&srs, &circuit)?;
+        let public_input = circuit.public_input()?;
+
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, T>(&mut jf_utils::test_rng(), &circuit, &pk, None)?;
+        // budgets ranging from unbounded to too small for even the smallest chunks,
+        // and commit chunks from single coefficients to whole polynomials, the
+        // proof doesn't depend on the number of chunks.
+        for bytes in [usize::MAX, 1 << 16, 1 << 14, 0] {
+            for commit_chunk_size in [1, 1 << 4, MemoryBudget::DEFAULT_COMMIT_CHUNK_SIZE] {
+                for pk in [&pk, &cached_pk] {
+                    let budget_proof = PlonkKzgSnark::<E>::prove_with_memory_budget::<_, _, T>(
+                        &mut jf_utils::test_rng(),
+                        &circuit,
+                        pk,
+                        None,
+                        MemoryBudget {
+                            bytes,
+                            commit_chunk_size,
+                        },
+                    )?;
+                    assert_eq!(budget_proof, proof);
+                }
+            }
+        }
+        assert!(PlonkKzgSnark::<E>::prove_with_memory_budget::<_, _, T>(
+            &mut jf_utils::test_rng(),
+            &circuit,
+            &pk,
+            None,
+            MemoryBudget {
+                bytes: 0,
+                commit_chunk_size: 0,
+            },
+        )
+        .is_err());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_ok());
+
+        // batch proving
+        let batch_proof = PlonkKzgSnark::<E>::batch_prove::<_, _, T>(
+            &mut jf_utils::test_rng(),
+            &[&circuit, &circuit],
+            &[&pk, &cached_pk],
+        )?;
+        let budget_batch_proof = PlonkKzgSnark::<E>::batch_prove_with_memory_budget::<_, _, T>(
+            &mut jf_utils::test_rng(),
+            &[&circuit, &circuit],
+            &[&pk, &cached_pk],
+            MemoryBudget {
+                bytes: 0,
+                commit_chunk_size: 1 << 4,
+            },
+        )?;
+        assert_eq!(budget_batch_proof, batch_proof);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vk_digest() -> Result<(), PlonkError> {
+        test_vk_digest_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::TurboPlonk)?;
+        test_vk_digest_helper::<Bn254, Fq254, _, StandardTranscript>(PlonkType::UltraPlonk)?;
+        test_vk_digest_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(PlonkType::TurboPlonk)?;
+        test_vk_digest_helper::<Bls12_377, Fq377, _, RescueTranscript<_>>(PlonkType::UltraPlonk)?;
+        test_vk_digest_helper::<Bls12_381, Fq381, _, SolidityTranscript>(PlonkType::TurboPlonk)
+    }
+
+    #[test]
+    fn test_vk_rescue_digest() -> Result<(), PlonkError> {
+        // the Rescue digest requires the curve to have a Twisted Edwards form
+        let rng = &mut jf_utils::test_rng();
+        let srs = PlonkKzgSnark::<Bls12_377>::universal_setup_for_testing(80, rng)?;
+        for plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk] {
+            let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+            let other_circuit = gen_circuit_for_test(4, 4, plonk_type)?;
+            let (_, vk) = PlonkKzgSnark::<Bls12_377>::preprocess(&srs, &circuit)?;
+            let (_, other_vk) = PlonkKzgSnark::<Bls12_377>::preprocess(&srs, &other_circuit)?;
+
+            let mut ser_bytes = Vec::new();
+            vk.serialize_compressed(&mut ser_bytes)?;
+            let de = VerifyingKey::<Bls12_377>::deserialize_compressed(&ser_bytes[..])?;
+            assert_eq!(de.digest()?, vk.digest()?);
+            assert_ne!(other_vk.digest()?, vk.digest()?);
+        }
+        Ok(())
+    }
+
+    fn test_vk_digest_helper<E, F, P, T>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+        T: PlonkTranscript<F>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let circuit = gen_circuit_for_test(3, 4, plonk_type)?;
+        let other_circuit = gen_circuit_for_test(4, 4, plonk_type)?;
+        let max_degree = 80;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        let (_, other_vk) = PlonkKzgSnark::<E>::preprocess(&srs, &other_circuit)?;
+
+        // digests are stable across serialization and identify the circuit
+        let mut ser_bytes = Vec::new();
+        vk.serialize_compressed(&mut ser_bytes)?;
+        let de = VerifyingKey::<E>::deserialize_compressed(&ser_bytes[..])?;
+        assert_eq!(de.digest_bytes()?, vk.digest_bytes()?);
+        assert_ne!(other_vk.digest_bytes()?, vk.digest_bytes()?);
+
+        // proofs over a digest-absorbing transcript only verify with it
+        let public_input = circuit.public_input()?;
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, VkDigestTranscript<T>>(rng, &circuit, &pk, None)?;
+        assert!(PlonkKzgSnark::<E>::verify::<VkDigestTranscript<T>>(
+            &vk,
+            &public_input,
+            &proof,
+            None
+        )
+        .is_ok());
+        assert!(PlonkKzgSnark::<E>::verify::<T>(&vk, &public_input, &proof, None).is_err());
+        Ok(())
+    }
+
+    // A circuit computing `(..((x * y + x) * y + x)..) * y + c` (or `* c` if
+    // `mul_c`) with `len` multiplications before the last one, whose operands
+    // are swapped if `swap`.
+    fn gen_param_circuit_for_test<F: PrimeField>(
+        len: usize,
+        c: u64,
+        mul_c: bool,
+        swap: bool,
+        plonk_type: PlonkType,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut cs: PlonkCircuit<F> = match plonk_type {
+            PlonkType::TurboPlonk => PlonkCircuit::new_turbo_plonk(),
+            PlonkType::UltraPlonk => PlonkCircuit::new_ultra_plonk(4),
+        };
+        let x = cs.create_public_variable(F::from(3u8))?;
+        let y = cs.create_variable(F::from(5u8))?;
+        let mut acc = x;
+        for _ in 0..len {
+            acc = cs.mul(acc, y)?;
+            acc = cs.add(acc, x)?;
+        }
+        let out = if swap {
+            cs.mul(y, acc)?
+        } else {
+            cs.mul(acc, y)?
+        };
+        if mul_c {
+            cs.mul_constant(out, &F::from(c))?;
+        } else {
+            cs.add_constant(out, &F::from(c))?;
+        }
+        if plonk_type == PlonkType::UltraPlonk {
+            cs.add_range_check_variable(y)?;
+        }
+        cs.finalize_for_arithmetization()?;
+        Ok(cs)
+    }
+
+    #[test]
+    fn test_preprocess_incremental() -> Result<(), PlonkError> {
+        test_preprocess_incremental_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_preprocess_incremental_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_preprocess_incremental_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
+        test_preprocess_incremental_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)
+    }
+
+    fn test_preprocess_incremental_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWCurveConfig<BaseField = F>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let max_degree = 130;
+        let srs = PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)?;
+        let prev_circuit = gen_param_circuit_for_test(20, 7, false, false, plonk_type)?;
+        let (prev_pk, _) = PlonkKzgSnark::<E>::preprocess(&srs, &prev_circuit)?;
+        let (prev_cached_pk, _) =
+            PlonkKzgSnark::<E>::preprocess_with_coset_evals(&srs, &prev_circuit)?;
+
+        // unchanged circuit, changed constant, changed gate type (updated via
+        // Lagrange bases), changed wiring, changed gates (recommitted)
+        for (len, c, mul_c, swap) in [
+            (20, 7, false, false),
+            (20, 8, false, false),
+            (20, 7, true, false),
+            (20, 7, false, true),
+            (22, 9, true, true),
+        ] {
+            let circuit = gen_param_circuit_for_test(len, c, mul_c, swap, plonk_type)?;
+            assert_eq!(
+                circuit.eval_domain_size()?,
+                prev_circuit.eval_domain_size()?
+            );
+            let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+            let (incremental_pk, incremental_vk) =
+                PlonkKzgSnark::<E>::preprocess_incremental(&srs, &prev_pk, &circuit)?;
+            assert_eq!(incremental_vk, vk);
+            assert_eq!(incremental_pk, pk);
+
+            let (mut cached_pk, cached_vk) =
+                PlonkKzgSnark::<E>::preprocess_incremental(&srs, &prev_cached_pk, &circuit)?;
+            assert_eq!(cached_vk, vk);
+            assert!(cached_pk.has_coset_evals());
+            cached_pk.clear_coset_evals();
+            assert_eq!(cached_pk, pk);
+        }
+
+        // bad path: different domain size
+        let circuit = gen_param_circuit_for_test(60, 7, false, false, plonk_type)?;
+        assert!(PlonkKzgSnark::<E>::preprocess_incremental(&srs, &prev_pk, &circuit).is_err());
+        // bad path: different Plonk type
+        let other_type = match plonk_type {
+            PlonkType::TurboPlonk => PlonkType::UltraPlonk,
+            PlonkType::UltraPlonk => PlonkType::TurboPlonk,
+        };
+        let circuit = gen_param_circuit_for_test(20, 7, false, false, other_type)?;
+        assert!(PlonkKzgSnark::<E>::preprocess_incremental(&srs, &prev_pk, &circuit).is_err());
+        // bad path: different SRS
+        let other_srs = PlonkKzgSnark::<E>::universal_setup_for_testing(max_degree, rng)?;
+        assert!(
+            PlonkKzgSnark::<E>::preprocess_incremental(&other_srs, &prev_pk, &prev_circuit)
+                .is_err()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_key_aggregation_and_batch_prove() -> Result<(), PlonkError> {
         // merlin transcripts
@@ -1670,7 +2693,7 @@ pub mod test {
         // check that the merged keys are correct
         for (cs, vk) in circuits.iter().zip(vks.iter()) {
             let (_, mut expected_vk) = PlonkKzgSnark::<E>::preprocess(&srs, cs)?;
-            expected_vk.is_merged = true;
+            expected_vk.merge_kind = MergeKind::Pair;
             assert_eq!(*vk, expected_vk);
         }
 
@@ -0,0 +1,188 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Temporary files holding the polynomials that the prover doesn't need in
+//! memory for a while.
+
+use super::{
+    prover::{QuotOracle, QuotOracles},
+    structs::Oracles,
+};
+use crate::errors::PlonkError;
+use ark_ff::{FftField, Field};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::{format, marker::PhantomData, mem, vec, vec::Vec};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// The number of coefficients read at a time when loading a polynomial.
+const LOAD_BLOCK_SIZE: usize = 1 << 16;
+
+// The number of polynomials spilled by this process, to name the files.
+static NUM_SPILLED_POLYS: AtomicUsize = AtomicUsize::new(0);
+
+/// The coefficients of a polynomial written to a temporary file, which is
+/// removed when dropped.
+pub(crate) struct SpilledPoly<F> {
+    path: PathBuf,
+    len: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: Field> SpilledPoly<F> {
+    /// Write `poly` to a new file in the temporary directory.
+    pub(crate) fn new(poly: DensePolynomial<F>) -> Result<Self, PlonkError> {
+        let path = std::env::temp_dir().join(format!(
+            "jf-plonk-{}-{}.poly",
+            std::process::id(),
+            NUM_SPILLED_POLYS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        // the file is removed on error from here
+        let spilled = Self {
+            path,
+            len: poly.coeffs.len(),
+            _field: PhantomData,
+        };
+        let mut writer = BufWriter::new(file);
+        for coeff in poly.coeffs.iter() {
+            coeff.serialize_uncompressed(&mut writer)?;
+        }
+        writer.flush()?;
+        Ok(spilled)
+    }
+
+    /// Read the polynomial back and remove the file.
+    pub(crate) fn load(self) -> Result<DensePolynomial<F>, PlonkError> {
+        let mut coeffs = Vec::with_capacity(self.len);
+        self.for_each_block(LOAD_BLOCK_SIZE, |block| coeffs.extend_from_slice(block))?;
+        Ok(DensePolynomial::from_coefficients_vec(coeffs))
+    }
+
+    // Call `f` on the consecutive blocks of `block_size` coefficients, the last
+    // one being shorter.
+    fn for_each_block(&self, block_size: usize, mut f: impl FnMut(&[F])) -> Result<(), PlonkError> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut block = Vec::with_capacity(ark_std::cmp::min(block_size, self.len));
+        for i in 0..self.len {
+            block.push(F::deserialize_uncompressed_unchecked(&mut reader)?);
+            if block.len() == block_size || i + 1 == self.len {
+                f(&block);
+                block.clear();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<F> Drop for SpilledPoly<F> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl<F: Field> QuotOracle<F> for SpilledPoly<F> {
+    fn fold_coeffs(&self, size: usize, offset_pow_size: F) -> Result<Vec<F>, PlonkError> {
+        let mut coeffs = vec![F::zero(); size];
+        let mut factor = F::one();
+        self.for_each_block(size, |high_coeffs| {
+            for (coeff, &high_coeff) in coeffs.iter_mut().zip(high_coeffs.iter()) {
+                *coeff += factor * high_coeff;
+            }
+            factor *= offset_pow_size;
+        })?;
+        Ok(coeffs)
+    }
+}
+
+impl<F: FftField> QuotOracles<SpilledPoly<F>> {
+    /// Move the polynomials of `oracles` to temporary files.
+    pub(crate) fn spill(oracles: &mut Oracles<F>) -> Result<Self, PlonkError> {
+        let spill = |poly: &mut DensePolynomial<F>| SpilledPoly::new(mem::take(poly));
+        Ok(Self {
+            wire_polys: oracles
+                .wire_polys
+                .iter_mut()
+                .map(spill)
+                .collect::<Result<_, _>>()?,
+            pub_inp_poly: spill(&mut oracles.pub_inp_poly)?,
+            prod_perm_poly: spill(&mut oracles.prod_perm_poly)?,
+            h_polys: oracles
+                .plookup_oracles
+                .h_polys
+                .iter_mut()
+                .map(spill)
+                .collect::<Result<_, _>>()?,
+            prod_lookup_poly: spill(&mut oracles.plookup_oracles.prod_lookup_poly)?,
+        })
+    }
+
+    /// Move the polynomials back to `oracles`, except the public input
+    /// polynomial, which is not needed after the third round.
+    pub(crate) fn restore(self, oracles: &mut Oracles<F>) -> Result<(), PlonkError> {
+        oracles.wire_polys = self
+            .wire_polys
+            .into_iter()
+            .map(SpilledPoly::load)
+            .collect::<Result<_, _>>()?;
+        oracles.prod_perm_poly = self.prod_perm_poly.load()?;
+        oracles.plookup_oracles.h_polys = self
+            .h_polys
+            .into_iter()
+            .map(SpilledPoly::load)
+            .collect::<Result<_, _>>()?;
+        oracles.plookup_oracles.prod_lookup_poly = self.prod_lookup_poly.load()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_spilled_poly() -> Result<(), PlonkError> {
+        test_spilled_poly_helper::<Fr254>()?;
+        test_spilled_poly_helper::<Fr381>()
+    }
+
+    fn test_spilled_poly_helper<F: FftField>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        for degree in [0, 7, 100, LOAD_BLOCK_SIZE + 5] {
+            let poly = DensePolynomial::<F>::rand(degree, rng);
+            let spilled = SpilledPoly::new(poly.clone())?;
+            let path = spilled.path.clone();
+            assert!(path.exists());
+
+            let offset = F::from(5u64);
+            for size in [1, 8, 64] {
+                assert_eq!(
+                    spilled.fold_coeffs(size, offset)?,
+                    poly.fold_coeffs(size, offset)?
+                );
+            }
+            assert_eq!(spilled.load()?, poly);
+            // the file is removed once loaded
+            assert!(!path.exists());
+        }
+        // or dropped
+        let spilled = SpilledPoly::new(DensePolynomial::<F>::rand(10, rng))?;
+        let path = spilled.path.clone();
+        drop(spilled);
+        assert!(!path.exists());
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ use crate::{
         PlonkError,
         SnarkError::{self, ParameterError, SnarkLookupUnsupported},
     },
+    proof_system::{pcs::PlonkPCS, prover::Prover},
 };
 use ark_ec::{
     pairing::Pairing,
@@ -18,16 +19,15 @@ use ark_ec::{
     short_weierstrass::{Affine, SWCurveConfig},
     CurveGroup,
 };
-use ark_ff::{FftField, Field, Fp2, Fp2Config, PrimeField, Zero};
-use ark_poly::univariate::DensePolynomial;
+use ark_ff::{BigInteger, FftField, Field, Fp2, Fp2Config, PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial};
 use ark_serialize::*;
-use ark_std::{format, string::ToString, vec, vec::Vec};
+use ark_std::{format, rand::RngCore, string::ToString, vec, vec::Vec};
 use espresso_systems_common::jellyfish::tag;
 use hashbrown::HashMap;
 use jf_primitives::{
-    pcs::prelude::{
-        Commitment, UnivariateProverParam, UnivariateUniversalParams, UnivariateVerifierParam,
-    },
+    crhf::{VariableLengthRescueCRHF, CRHF},
+    pcs::prelude::{Commitment, UnivariateKzgPCS},
     rescue::RescueParameter,
 };
 use jf_relation::{
@@ -38,21 +38,33 @@ use jf_relation::{
     },
     PlonkCircuit,
 };
-use jf_utils::{field_switching, fq_to_fr, fr_to_fq};
+use jf_utils::{field_switching, fq_to_fr, fr_to_fq, to_bytes};
+use sha3::{Digest, Sha3_256};
 use tagged_base64::tagged;
 
 /// Universal StructuredReferenceString
-pub type UniversalSrs<E> = UnivariateUniversalParams<E>;
+pub type UniversalSrs<E, PCS = UnivariateKzgPCS<E>> = <PCS as PlonkPCS<E>>::UniversalSrs;
 /// Commitment key
-pub type CommitKey<E> = UnivariateProverParam<E>;
+pub type CommitKey<E, PCS = UnivariateKzgPCS<E>> = <PCS as PlonkPCS<E>>::CommitKey;
 /// Key for verifying PCS opening proof.
-pub type OpenKey<E> = UnivariateVerifierParam<E>;
+pub type OpenKey<E, PCS = UnivariateKzgPCS<E>> = <PCS as PlonkPCS<E>>::OpenKey;
+/// Proof of a PCS opening.
+pub type OpeningProof<E, PCS = UnivariateKzgPCS<E>> = <PCS as PlonkPCS<E>>::OpeningProof;
+
+/// Domain separator prepended when computing [`VerifyingKey::digest_bytes`].
+const VK_DIGEST_DOMAIN_SEPARATOR: &[u8] = b"JF-PLONK-VK";
 
 /// A Plonk SNARK proof.
 #[tagged(tag::PROOF)]
-#[derive(Debug, Clone, Eq, CanonicalSerialize, CanonicalDeserialize, Derivative)]
-#[derivative(PartialEq, Hash(bound = "E:Pairing"))]
-pub struct Proof<E: Pairing> {
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Clone(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    PartialEq(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Eq(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Hash(bound = "E: Pairing, PCS: PlonkPCS<E>")
+)]
+pub struct Proof<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     /// Wire witness polynomials commitments.
     pub wires_poly_comms: Vec<Commitment<E>>,
 
@@ -63,11 +75,11 @@ pub struct Proof<E: Pairing> {
     pub split_quot_poly_comms: Vec<Commitment<E>>,
 
     /// (Aggregated) proof of evaluations at challenge point `zeta`.
-    pub opening_proof: Commitment<E>,
+    pub opening_proof: OpeningProof<E, PCS>,
 
     /// (Aggregated) proof of evaluation at challenge point `zeta * g` where `g`
     /// is the root of unity.
-    pub shifted_opening_proof: Commitment<E>,
+    pub shifted_opening_proof: OpeningProof<E, PCS>,
 
     /// Polynomial evaluations.
     pub poly_evals: ProofEvaluations<E::ScalarField>,
@@ -223,9 +235,15 @@ pub struct PlookupProof<E: Pairing> {
 
 /// An aggregated SNARK proof that batchly proving multiple instances.
 #[tagged(tag::BATCHPROOF)]
-#[derive(Debug, Clone, Eq, CanonicalSerialize, CanonicalDeserialize, Derivative)]
-#[derivative(PartialEq, Hash(bound = "E:Pairing"))]
-pub struct BatchProof<E: Pairing> {
+#[derive(CanonicalSerialize, CanonicalDeserialize, Derivative)]
+#[derivative(
+    Debug(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Clone(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    PartialEq(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Eq(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Hash(bound = "E: Pairing, PCS: PlonkPCS<E>")
+)]
+pub struct BatchProof<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     /// The list of wire witness polynomials commitments.
     pub(crate) wires_poly_comms_vec: Vec<Vec<Commitment<E>>>,
 
@@ -242,14 +260,14 @@ pub struct BatchProof<E: Pairing> {
     pub(crate) split_quot_poly_comms: Vec<Commitment<E>>,
 
     /// (Aggregated) proof of evaluations at challenge point `zeta`.
-    pub(crate) opening_proof: Commitment<E>,
+    pub(crate) opening_proof: OpeningProof<E, PCS>,
 
     /// (Aggregated) proof of evaluation at challenge point `zeta * g` where `g`
     /// is the root of unity.
-    pub(crate) shifted_opening_proof: Commitment<E>,
+    pub(crate) shifted_opening_proof: OpeningProof<E, PCS>,
 }
 
-impl<E: Pairing> BatchProof<E> {
+impl<E: Pairing, PCS: PlonkPCS<E>> BatchProof<E, PCS> {
     /// The number of instances being proved in a batch proof.
     pub fn len(&self) -> usize {
         self.prod_perm_poly_comms_vec.len()
@@ -258,6 +276,9 @@ impl<E: Pairing> BatchProof<E> {
     pub fn is_empty(&self) -> bool {
         self.prod_perm_poly_comms_vec.is_empty()
     }
+}
+
+impl<E: Pairing> BatchProof<E> {
     /// Create a dummy batch proof over `n` TurboPlonk instances.
     pub fn dummy(n: usize) -> Self {
         let num_wire_types = GATE_WIDTH + 1;
@@ -273,8 +294,8 @@ impl<E: Pairing> BatchProof<E> {
     }
 }
 
-impl<E: Pairing> From<Proof<E>> for BatchProof<E> {
-    fn from(proof: Proof<E>) -> Self {
+impl<E: Pairing, PCS: PlonkPCS<E>> From<Proof<E, PCS>> for BatchProof<E, PCS> {
+    fn from(proof: Proof<E, PCS>) -> Self {
         Self {
             wires_poly_comms_vec: vec![proof.wires_poly_comms],
             prod_perm_poly_comms_vec: vec![proof.prod_perm_poly_comm],
@@ -537,24 +558,86 @@ impl<F: Field> PlookupEvaluations<F> {
     }
 }
 
+/// The memory budget of a prover, see
+/// [`crate::proof_system::PlonkKzgSnark::batch_prove_with_memory_budget()`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MemoryBudget {
+    /// The number of bytes the prover may allocate, besides the proving keys
+    /// and the circuits.
+    pub bytes: usize,
+
+    /// The number of coefficients committed at a time, the scratch space of a
+    /// multi-scalar multiplication being several times the size of its
+    /// scalars. Must be positive.
+    pub commit_chunk_size: usize,
+}
+
+impl MemoryBudget {
+    /// The default number of coefficients committed at a time.
+    pub const DEFAULT_COMMIT_CHUNK_SIZE: usize = 1 << 18;
+
+    /// A budget of `bytes` bytes, committing
+    /// [`Self::DEFAULT_COMMIT_CHUNK_SIZE`] coefficients at a time.
+    pub fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            commit_chunk_size: Self::DEFAULT_COMMIT_CHUNK_SIZE,
+        }
+    }
+}
+
 /// Preprocessed prover parameters used to compute Plonk proofs for a certain
 /// circuit.
-#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct ProvingKey<E: Pairing> {
+///
+/// The cached coset evaluations are not part of the canonical serialization of
+/// the key, see [`ProvingKey::serialize_with_coset_evals()`] to include them.
+#[derive(Derivative)]
+#[derivative(
+    Debug(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Clone(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    PartialEq(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Eq(bound = "E: Pairing, PCS: PlonkPCS<E>")
+)]
+pub struct ProvingKey<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     /// Extended permutation (sigma) polynomials.
     pub(crate) sigmas: Vec<DensePolynomial<E::ScalarField>>,
 
     /// Selector polynomials.
     pub(crate) selectors: Vec<DensePolynomial<E::ScalarField>>,
 
-    // KZG PCS committing key.
-    pub(crate) commit_key: CommitKey<E>,
+    // PCS committing key.
+    pub(crate) commit_key: CommitKey<E, PCS>,
 
     /// The verifying key. It is used by prover to initialize transcripts.
-    pub vk: VerifyingKey<E>,
+    pub vk: VerifyingKey<E, PCS>,
 
     /// Proving key for Plookup, None if not support lookup.
     pub(crate) plookup_pk: Option<PlookupProvingKey<E>>,
+
+    /// Cached coset evaluations of the preprocessed polynomials, None unless
+    /// precomputed by [`ProvingKey::precompute_coset_evals()`].
+    pub(crate) coset_evals: Option<CosetEvals<E::ScalarField>>,
+}
+
+/// Evaluations of the preprocessed polynomials of a proving key over the coset
+/// of the quotient polynomial domain. Caching them saves the prover from
+/// recomputing their FFTs for every proof, at the cost of a proving key that
+/// is several times larger.
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CosetEvals<F: FftField> {
+    /// The size of the quotient polynomial domain.
+    pub(crate) quot_domain_size: usize,
+
+    /// Coset evaluations of the selector polynomials.
+    pub(crate) selectors: Vec<Vec<F>>,
+
+    /// Coset evaluations of the extended permutation (sigma) polynomials.
+    pub(crate) sigmas: Vec<Vec<F>>,
+
+    /// Coset evaluations of the Plookup range table, key table, table domain
+    /// separation and lookup domain separation polynomials, in this order.
+    /// Empty if not support lookup.
+    pub(crate) plookup: Vec<Vec<F>>,
 }
 
 /// Preprocessed prover parameters used to compute Plookup proofs for a certain
@@ -574,7 +657,7 @@ pub struct PlookupProvingKey<E: Pairing> {
     pub(crate) q_dom_sep_poly: DensePolynomial<E::ScalarField>,
 }
 
-impl<E: Pairing> ProvingKey<E> {
+impl<E: Pairing, PCS: PlonkPCS<E>> ProvingKey<E, PCS> {
     /// The size of the evaluation domain. Should be a power of two.
     pub(crate) fn domain_size(&self) -> usize {
         self.vk.domain_size
@@ -597,6 +680,143 @@ impl<E: Pairing> ProvingKey<E> {
         Ok(self.selectors.last().unwrap())
     }
 
+    /// Precompute and cache the evaluations of the preprocessed polynomials
+    /// over the coset used by the prover to compute the quotient polynomial,
+    /// so that repeated proving with the key skips their FFTs. This makes the
+    /// key several times larger in memory. The cache is not part of the
+    /// canonical serialization of the key, see
+    /// [`Self::serialize_with_coset_evals()`].
+    pub fn precompute_coset_evals(&mut self) -> Result<(), PlonkError> {
+        if self.coset_evals.is_none() {
+            let prover = Prover::new(self.domain_size(), self.sigmas.len())?;
+            self.coset_evals = Some(prover.compute_coset_evals(self));
+        }
+        Ok(())
+    }
+
+    /// Drop the cached coset evaluations of the preprocessed polynomials, if
+    /// any.
+    pub fn clear_coset_evals(&mut self) {
+        self.coset_evals = None;
+    }
+
+    /// Whether the key caches the coset evaluations of the preprocessed
+    /// polynomials.
+    pub fn has_coset_evals(&self) -> bool {
+        self.coset_evals.is_some()
+    }
+
+    /// The cached coset evaluations of the preprocessed polynomials, if any.
+    /// They can be serialized on their own and restored with
+    /// [`Self::set_coset_evals()`] instead of being recomputed.
+    pub fn coset_evals(&self) -> Option<&CosetEvals<E::ScalarField>> {
+        self.coset_evals.as_ref()
+    }
+
+    /// Cache coset evaluations previously obtained from
+    /// [`Self::coset_evals()`] for this key. Return error if their number or
+    /// sizes do not match the key, or if they are not the evaluations of the
+    /// preprocessed polynomials of the key at a coset point sampled with
+    /// `rng`.
+    pub fn set_coset_evals<R: RngCore>(
+        &mut self,
+        rng: &mut R,
+        coset_evals: CosetEvals<E::ScalarField>,
+    ) -> Result<(), PlonkError> {
+        let prover = Prover::<E>::new(self.domain_size(), self.sigmas.len())?;
+        let quot_domain_size = prover.quot_domain_size();
+        let plookup_polys = match self.plookup_pk.as_ref() {
+            Some(plookup_pk) => vec![
+                &plookup_pk.range_table_poly,
+                &plookup_pk.key_table_poly,
+                &plookup_pk.table_dom_sep_poly,
+                &plookup_pk.q_dom_sep_poly,
+            ],
+            None => vec![],
+        };
+        let valid_shape = coset_evals.quot_domain_size == quot_domain_size
+            && coset_evals.selectors.len() == self.selectors.len()
+            && coset_evals.sigmas.len() == self.sigmas.len()
+            && coset_evals.plookup.len() == plookup_polys.len()
+            && coset_evals
+                .selectors
+                .iter()
+                .chain(coset_evals.sigmas.iter())
+                .chain(coset_evals.plookup.iter())
+                .all(|evals| evals.len() == quot_domain_size);
+        if !valid_shape {
+            return Err(ParameterError(
+                "coset evaluations do not match the proving key".to_string(),
+            )
+            .into());
+        }
+        // the quotient polynomial domain size is a power of two
+        let i = rng.next_u64() as usize % quot_domain_size;
+        let point = prover.quot_coset_element(i);
+        let consistent = self
+            .selectors
+            .iter()
+            .chain(self.sigmas.iter())
+            .chain(plookup_polys)
+            .zip(
+                coset_evals
+                    .selectors
+                    .iter()
+                    .chain(coset_evals.sigmas.iter())
+                    .chain(coset_evals.plookup.iter()),
+            )
+            .all(|(poly, evals)| poly.evaluate(&point) == evals[i]);
+        if !consistent {
+            return Err(ParameterError(
+                "coset evaluations are not the ones of the proving key".to_string(),
+            )
+            .into());
+        }
+        self.coset_evals = Some(coset_evals);
+        Ok(())
+    }
+
+    /// Serialize the key followed by its cached coset evaluations, if any.
+    /// Unlike the canonical serialization of the key, which excludes the
+    /// cache, this trades the size of the serialized key for the FFTs saved by
+    /// the cache when proving with the deserialized key, see
+    /// [`Self::deserialize_with_coset_evals()`].
+    pub fn serialize_with_coset_evals<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.serialize_with_mode(&mut writer, compress)?;
+        self.coset_evals.serialize_with_mode(&mut writer, compress)
+    }
+
+    /// The size of the serialization by [`Self::serialize_with_coset_evals()`].
+    pub fn serialized_size_with_coset_evals(&self, compress: Compress) -> usize {
+        self.serialized_size(compress) + self.coset_evals.serialized_size(compress)
+    }
+
+    /// Deserialize a key serialized by [`Self::serialize_with_coset_evals()`].
+    /// When validating, the cached coset evaluations are checked against the
+    /// key as in [`Self::set_coset_evals()`], at a coset point sampled with
+    /// `rng`.
+    pub fn deserialize_with_coset_evals<R: Read, G: RngCore>(
+        rng: &mut G,
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut pk = Self::deserialize_with_mode(&mut reader, compress, validate)?;
+        let coset_evals: Option<CosetEvals<E::ScalarField>> =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        match (coset_evals, validate) {
+            (Some(coset_evals), Validate::Yes) => pk
+                .set_coset_evals(rng, coset_evals)
+                .map_err(|_| SerializationError::InvalidData)?,
+            (coset_evals, _) => pk.coset_evals = coset_evals,
+        }
+        Ok(pk)
+    }
+
     /// Merge with another TurboPlonk proving key to obtain a new TurboPlonk
     /// proving key. Return error if any of the following holds:
     /// 1. the other proving key has a different domain size;
@@ -640,14 +860,110 @@ impl<E: Pairing> ProvingKey<E> {
             commit_key: self.commit_key.clone(),
             vk: self.vk.merge(&other_pk.vk)?,
             plookup_pk: None,
+            coset_evals: None,
+        })
+    }
+
+    /// Merge the TurboPlonk proving keys of circuits finalized for merging
+    /// into the proving key of the merged circuit. Return error if the keys
+    /// have different domain sizes or any of them is not a TurboPlonk key.
+    pub(crate) fn merge_all(pks: &[&Self]) -> Result<Self, PlonkError> {
+        let vks: Vec<&VerifyingKey<E, PCS>> = pks.iter().map(|pk| &pk.vk).collect();
+        let vk = VerifyingKey::merge_all(&vks)?;
+        let sum = |polys: &dyn Fn(&Self) -> &[DensePolynomial<E::ScalarField>]| {
+            let mut sums = polys(pks[0]).to_vec();
+            for pk in pks.iter().skip(1) {
+                for (sum, poly) in sums.iter_mut().zip(polys(pk).iter()) {
+                    *sum += poly;
+                }
+            }
+            sums
+        };
+
+        Ok(Self {
+            sigmas: sum(&|pk| &pk.sigmas),
+            selectors: sum(&|pk| &pk.selectors),
+            commit_key: pks[0].commit_key.clone(),
+            vk,
+            plookup_pk: None,
+            coset_evals: None,
         })
     }
 }
 
+// The cached coset evaluations are skipped, so that the canonical serialization
+// of a proving key doesn't depend on the cache.
+impl<E: Pairing, PCS: PlonkPCS<E>> CanonicalSerialize for ProvingKey<E, PCS> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.sigmas.serialize_with_mode(&mut writer, compress)?;
+        self.selectors.serialize_with_mode(&mut writer, compress)?;
+        self.commit_key.serialize_with_mode(&mut writer, compress)?;
+        self.vk.serialize_with_mode(&mut writer, compress)?;
+        self.plookup_pk.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.sigmas.serialized_size(compress)
+            + self.selectors.serialized_size(compress)
+            + self.commit_key.serialized_size(compress)
+            + self.vk.serialized_size(compress)
+            + self.plookup_pk.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing, PCS: PlonkPCS<E>> CanonicalDeserialize for ProvingKey<E, PCS> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            sigmas: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+            selectors: CanonicalDeserialize::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            commit_key: CanonicalDeserialize::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            vk: CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?,
+            plookup_pk: CanonicalDeserialize::deserialize_with_mode(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            coset_evals: None,
+        })
+    }
+}
+
+impl<E: Pairing, PCS: PlonkPCS<E>> Valid for ProvingKey<E, PCS> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.sigmas.check()?;
+        self.selectors.check()?;
+        self.commit_key.check()?;
+        self.vk.check()?;
+        self.plookup_pk.check()
+    }
+}
+
 /// Preprocessed verifier parameters used to verify Plonk proofs for a certain
 /// circuit.
-#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct VerifyingKey<E: Pairing> {
+#[derive(Derivative)]
+#[derivative(
+    Debug(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Clone(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    PartialEq(bound = "E: Pairing, PCS: PlonkPCS<E>"),
+    Eq(bound = "E: Pairing, PCS: PlonkPCS<E>")
+)]
+pub struct VerifyingKey<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     /// The size of the evaluation domain. Should be a power of two.
     pub domain_size: usize,
 
@@ -664,16 +980,142 @@ pub struct VerifyingKey<E: Pairing> {
     /// disjoint.
     pub k: Vec<E::ScalarField>,
 
-    /// KZG PCS opening key.
-    pub open_key: OpenKey<E>,
+    /// PCS opening key.
+    pub open_key: OpenKey<E, PCS>,
 
-    /// A flag indicating whether the key is a merged key.
-    pub is_merged: bool,
+    /// How the key is merged from other keys, if at all.
+    pub(crate) merge_kind: MergeKind,
 
     /// Plookup verifying key, None if not support lookup.
     pub plookup_vk: Option<PlookupVerifyingKey<E>>,
 }
 
+/// How a verifying key is merged from other keys. A merged key cannot be
+/// merged again.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub(crate) enum MergeKind {
+    /// The key is not merged.
+    #[default]
+    Unmerged,
+    /// The key is merged from a type A and a type B key, see
+    /// [`crate::proof_system::batch_arg::BatchArgument::aggregate_verify_keys`].
+    /// The first half of the public inputs occupies the first gates and the
+    /// second half the last gates of the merged circuit.
+    Pair,
+    /// The key is merged from a group of keys, see
+    /// [`crate::proof_system::batch_arg::BatchArgument::aggregate_verify_key_groups`].
+    /// The public inputs occupy the first gates in order, as for unmerged
+    /// keys.
+    Group,
+}
+
+impl MergeKind {
+    /// Whether the public inputs are split between the first and the last
+    /// gates of the circuit.
+    pub(crate) fn splits_public_inputs(self) -> bool {
+        self == Self::Pair
+    }
+
+    /// The encoding of the merge kind in serializations and digests.
+    pub(crate) fn to_flag(self) -> u8 {
+        match self {
+            Self::Unmerged => 0,
+            Self::Pair => 1,
+            Self::Group => 2,
+        }
+    }
+
+    fn from_flag(flag: u8) -> Option<Self> {
+        match flag {
+            0 => Some(Self::Unmerged),
+            1 => Some(Self::Pair),
+            2 => Some(Self::Group),
+            _ => None,
+        }
+    }
+}
+
+// The merge kind is serialized in a single byte: 0 for an unmerged key, 1 for
+// a merged key and 2 for a key merged from a group, so that unmerged and merged
+// keys serialize as with a boolean merge flag.
+impl<E: Pairing, PCS: PlonkPCS<E>> CanonicalSerialize for VerifyingKey<E, PCS> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.domain_size
+            .serialize_with_mode(&mut writer, compress)?;
+        self.num_inputs.serialize_with_mode(&mut writer, compress)?;
+        self.sigma_comms
+            .serialize_with_mode(&mut writer, compress)?;
+        self.selector_comms
+            .serialize_with_mode(&mut writer, compress)?;
+        self.k.serialize_with_mode(&mut writer, compress)?;
+        self.open_key.serialize_with_mode(&mut writer, compress)?;
+        self.merge_kind
+            .to_flag()
+            .serialize_with_mode(&mut writer, compress)?;
+        self.plookup_vk.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.domain_size.serialized_size(compress)
+            + self.num_inputs.serialized_size(compress)
+            + self.sigma_comms.serialized_size(compress)
+            + self.selector_comms.serialized_size(compress)
+            + self.k.serialized_size(compress)
+            + self.open_key.serialized_size(compress)
+            + 0u8.serialized_size(compress)
+            + self.plookup_vk.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing, PCS: PlonkPCS<E>> CanonicalDeserialize for VerifyingKey<E, PCS> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let domain_size =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let num_inputs =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let sigma_comms =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let selector_comms =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let k = CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let open_key =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let merge_kind =
+            MergeKind::from_flag(u8::deserialize_with_mode(&mut reader, compress, validate)?)
+                .ok_or(SerializationError::InvalidData)?;
+        let plookup_vk =
+            CanonicalDeserialize::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Self {
+            domain_size,
+            num_inputs,
+            sigma_comms,
+            selector_comms,
+            k,
+            open_key,
+            merge_kind,
+            plookup_vk,
+        })
+    }
+}
+
+impl<E: Pairing, PCS: PlonkPCS<E>> Valid for VerifyingKey<E, PCS> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.sigma_comms.check()?;
+        self.selector_comms.check()?;
+        self.k.check()?;
+        self.open_key.check()?;
+        self.plookup_vk.check()
+    }
+}
+
 impl<E, F, P1, P2> From<VerifyingKey<E>> for Vec<E::BaseField>
 where
     E: Pairing<G1Affine = Affine<P1>, G2Affine = Affine<P2>, TargetField = Fp2<F>>,
@@ -711,11 +1153,12 @@ where
     }
 }
 
-impl<E, F, P> VerifyingKey<E>
+impl<E, F, P, PCS> VerifyingKey<E, PCS>
 where
     E: Pairing<BaseField = F, G1Affine = Affine<P>>,
     F: SWToTEConParam,
     P: SWCurveConfig<BaseField = F>,
+    PCS: PlonkPCS<E>,
 {
     /// Convert the group elements to a list of scalars that represent the
     /// Twisted Edwards coordinates.
@@ -735,6 +1178,50 @@ where
     }
 }
 
+impl<E, F, P, PCS> VerifyingKey<E, PCS>
+where
+    E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWCurveConfig<BaseField = F>,
+    PCS: PlonkPCS<E>,
+{
+    /// Compute a Rescue digest of the key over its base field, which can be
+    /// used as a stable circuit identifier inside and outside of circuits.
+    ///
+    /// The digest binds the domain size, the number of public inputs, the
+    /// merge kind, the wire subset separators and all polynomial commitments
+    /// (in Twisted Edwards form). It does not bind the opening key, which is
+    /// determined by the SRS; use [`Self::digest_bytes`] to bind the entire
+    /// key.
+    ///
+    /// Like [`crate::transcript::RescueTranscript`], this requires the curve
+    /// to be convertible into Twisted Edwards form, e.g. BLS12-377.
+    pub fn digest(&self) -> Result<F, PlonkError> {
+        let mut input = vec![
+            F::from(self.domain_size as u64),
+            F::from(self.num_inputs as u64),
+            F::from(self.merge_kind.to_flag() as u64),
+        ];
+        input.extend(
+            self.k
+                .iter()
+                .map(|k| F::from_le_bytes_mod_order(&k.into_bigint().to_bytes_le())),
+        );
+        let plookup_comms = self.plookup_vk.iter().flat_map(|vk| vk.comms());
+        for comm in self
+            .selector_comms
+            .iter()
+            .chain(self.sigma_comms.iter())
+            .chain(plookup_comms)
+        {
+            let point: TEPoint<F> = comm.0.into();
+            input.push(point.get_x());
+            input.push(point.get_y());
+        }
+        Ok(VariableLengthRescueCRHF::<F, 1>::evaluate(input)?[0])
+    }
+}
+
 /// Preprocessed verifier parameters used to verify Plookup proofs for a certain
 /// circuit.
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -754,7 +1241,20 @@ pub struct PlookupVerifyingKey<E: Pairing> {
     pub(crate) q_dom_sep_comm: Commitment<E>,
 }
 
-impl<E: Pairing> VerifyingKey<E> {
+impl<E: Pairing> PlookupVerifyingKey<E> {
+    /// The polynomial commitments of the key, in the order in which they are
+    /// absorbed by verifying key digests.
+    pub(crate) fn comms(&self) -> [&Commitment<E>; 4] {
+        [
+            &self.range_table_comm,
+            &self.key_table_comm,
+            &self.table_dom_sep_comm,
+            &self.q_dom_sep_comm,
+        ]
+    }
+}
+
+impl<E: Pairing, PCS: PlonkPCS<E>> VerifyingKey<E, PCS> {
     /// Create a dummy TurboPlonk verification key for a circuit with
     /// `num_inputs` public inputs and domain size `domain_size`.
     pub fn dummy(num_inputs: usize, domain_size: usize) -> Self {
@@ -765,18 +1265,25 @@ impl<E: Pairing> VerifyingKey<E> {
             sigma_comms: vec![Commitment::default(); num_wire_types],
             selector_comms: vec![Commitment::default(); N_TURBO_PLONK_SELECTORS],
             k: compute_coset_representatives(num_wire_types, Some(domain_size)),
-            open_key: OpenKey::default(),
-            is_merged: false,
+            open_key: PCS::OpenKey::default(),
+            merge_kind: MergeKind::Unmerged,
             plookup_vk: None,
         }
     }
+
+    /// Whether the key is merged from other keys, in which case it cannot be
+    /// merged again.
+    pub fn is_merged(&self) -> bool {
+        self.merge_kind != MergeKind::Unmerged
+    }
+
     /// Merge with another TurboPlonk verifying key to obtain a new TurboPlonk
     /// verifying key. Return error if any of the following holds:
     /// 1. the other verifying key has a different domain size;
     /// 2. the circuit underlying the other key has different number of inputs.
     /// 3. the key or the other key is not a TurboPlonk key.
     pub(crate) fn merge(&self, other_vk: &Self) -> Result<Self, PlonkError> {
-        if self.is_merged || other_vk.is_merged {
+        if self.is_merged() || other_vk.is_merged() {
             return Err(ParameterError("cannot merge a merged key again".to_string()).into());
         }
         if self.domain_size != other_vk.domain_size {
@@ -817,10 +1324,68 @@ impl<E: Pairing> VerifyingKey<E> {
             k: self.k.clone(),
             open_key: self.open_key.clone(),
             plookup_vk: None,
-            is_merged: true,
+            merge_kind: MergeKind::Pair,
         })
     }
 
+    /// Merge the TurboPlonk verifying keys of circuits finalized for merging
+    /// into the verifying key of the merged circuit, whose public input is the
+    /// concatenation of their public inputs. Return error if the keys have
+    /// different domain sizes, or any of them is merged or not a TurboPlonk
+    /// key.
+    pub(crate) fn merge_all(vks: &[&Self]) -> Result<Self, PlonkError> {
+        let first = vks
+            .first()
+            .ok_or_else(|| ParameterError("no verifying key to merge".to_string()))?;
+        for vk in vks.iter() {
+            if vk.is_merged() {
+                return Err(ParameterError("cannot merge a merged key again".to_string()).into());
+            }
+            if vk.domain_size != first.domain_size {
+                return Err(ParameterError(
+                    "mismatched domain size when merging verifying keys".to_string(),
+                )
+                .into());
+            }
+            if vk.plookup_vk.is_some() {
+                return Err(
+                    ParameterError("cannot merge UltraPlonk verifying keys".to_string()).into(),
+                );
+            }
+        }
+        let sum = |comms: &dyn Fn(&Self) -> &[Commitment<E>]| -> Vec<Commitment<E>> {
+            (0..comms(first).len())
+                .map(|i| {
+                    let sum = vks
+                        .iter()
+                        .fold(E::G1::zero(), |sum, vk| sum + comms(vk)[i].0);
+                    Commitment(sum.into_affine())
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            domain_size: first.domain_size,
+            num_inputs: vks.iter().map(|vk| vk.num_inputs).sum(),
+            sigma_comms: sum(&|vk| &vk.sigma_comms),
+            selector_comms: sum(&|vk| &vk.selector_comms),
+            k: first.k.clone(),
+            open_key: first.open_key.clone(),
+            plookup_vk: None,
+            merge_kind: MergeKind::Group,
+        })
+    }
+
+    /// Compute a SHA3-256 digest of the canonical (compressed) serialization
+    /// of the key. Unlike [`Self::digest`], it binds every field of the key,
+    /// including the opening key.
+    pub fn digest_bytes(&self) -> Result<[u8; 32], PlonkError> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(VK_DIGEST_DOMAIN_SEPARATOR);
+        hasher.update(to_bytes!(self)?);
+        Ok(hasher.finalize().into())
+    }
+
     /// The lookup selector polynomial commitment
     pub(crate) fn q_lookup_comm(&self) -> Result<&Commitment<E>, PlonkError> {
         if self.plookup_vk.is_none() {
@@ -5,12 +5,16 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 use super::structs::{
-    BatchProof, Challenges, PlookupProof, ProofEvaluations, ScalarsAndBases, VerifyingKey,
+    BatchProof, Challenges, MergeKind, PlookupProof, ProofEvaluations, ScalarsAndBases,
+    VerifyingKey,
 };
 use crate::{
     constants::*,
     errors::{PlonkError, SnarkError::ParameterError},
-    proof_system::structs::{eval_merged_lookup_witness, eval_merged_table, OpenKey},
+    proof_system::{
+        pcs::PlonkPCS,
+        structs::{eval_merged_lookup_witness, eval_merged_table, OpenKey, OpeningProof},
+    },
     transcript::*,
 };
 use ark_ec::{
@@ -19,9 +23,12 @@ use ark_ec::{
 };
 use ark_ff::{Field, One, Zero};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
-use ark_std::{format, vec, vec::Vec};
+use ark_std::{format, marker::PhantomData, string::ToString, vec, vec::Vec};
 use core::ops::Neg;
-use jf_primitives::{pcs::prelude::Commitment, rescue::RescueParameter};
+use jf_primitives::{
+    pcs::prelude::{Commitment, UnivariateKzgPCS},
+    rescue::RescueParameter,
+};
 use jf_relation::{constants::GATE_WIDTH, gadgets::ecc::SWToTEConParam};
 use jf_utils::multi_pairing;
 
@@ -33,48 +40,81 @@ use jf_utils::multi_pairing;
 /// * `eval` - the (aggregated) polynomial evaluation value.
 /// * `comm_scalars_and_bases` - the scalars-and-bases form of the (aggregated)
 ///   polynomial commitment.
+/// * `shifted_eval` - the part of `eval` of the polynomials evaluated at point
+///   `next_eval_point`, scaled by `u`.
+/// * `shifted_comm_scalars_and_bases` - the part of `comm_scalars_and_bases` of
+///   the polynomials evaluated at point `next_eval_point`, scaled by `u`.
 /// * `opening_proof` - (aggregated) proof of evaluations at point `eval_point`.
 /// * `shifted_opening_proof` - (aggregated) proof of evaluations at point
 ///   `next_eval_point`.
-#[derive(Debug)]
-pub(crate) struct PcsInfo<E: Pairing> {
+#[derive(Derivative)]
+#[derivative(Debug(bound = "E: Pairing, PCS: PlonkPCS<E>"))]
+pub(crate) struct PcsInfo<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     pub(crate) u: E::ScalarField,
     pub(crate) eval_point: E::ScalarField,
     pub(crate) next_eval_point: E::ScalarField,
     pub(crate) eval: E::ScalarField,
     pub(crate) comm_scalars_and_bases: ScalarsAndBases<E>,
-    pub(crate) opening_proof: Commitment<E>,
-    pub(crate) shifted_opening_proof: Commitment<E>,
+    pub(crate) shifted_eval: E::ScalarField,
+    pub(crate) shifted_comm_scalars_and_bases: ScalarsAndBases<E>,
+    pub(crate) opening_proof: OpeningProof<E, PCS>,
+    pub(crate) shifted_opening_proof: OpeningProof<E, PCS>,
 }
 
-pub(crate) struct Verifier<E: Pairing> {
+pub(crate) struct Verifier<E: Pairing, PCS: PlonkPCS<E> = UnivariateKzgPCS<E>> {
     pub(crate) domain: Radix2EvaluationDomain<E::ScalarField>,
+    pub(crate) _pcs: PhantomData<PCS>,
 }
 
-impl<E, F, P> Verifier<E>
+impl<E, F, P, PCS> Verifier<E, PCS>
 where
     E: Pairing<BaseField = F, G1Affine = Affine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWCurveConfig<BaseField = F>,
+    PCS: PlonkPCS<E>,
 {
     /// Construct a Plonk verifier that uses a domain with size `domain_size`.
     pub(crate) fn new(domain_size: usize) -> Result<Self, PlonkError> {
         let domain = Radix2EvaluationDomain::<E::ScalarField>::new(domain_size)
             .ok_or(PlonkError::DomainCreationError)?;
-        Ok(Self { domain })
+        Ok(Self {
+            domain,
+            _pcs: PhantomData,
+        })
     }
 
     /// Prepare the (aggregated) polynomial commitment evaluation information.
     pub(crate) fn prepare_pcs_info<T>(
         &self,
-        verify_keys: &[&VerifyingKey<E>],
+        verify_keys: &[&VerifyingKey<E, PCS>],
         public_inputs: &[&[E::ScalarField]],
-        batch_proof: &BatchProof<E>,
+        batch_proof: &BatchProof<E, PCS>,
         extra_transcript_init_msg: &Option<Vec<u8>>,
-    ) -> Result<PcsInfo<E>, PlonkError>
+    ) -> Result<PcsInfo<E, PCS>, PlonkError>
     where
         T: PlonkTranscript<F>,
     {
+        self.check_instances(verify_keys, public_inputs, batch_proof)?;
+
+        // compute challenges and evaluations
+        let challenges = Self::compute_challenges::<T>(
+            verify_keys,
+            public_inputs,
+            batch_proof,
+            extra_transcript_init_msg,
+        )?;
+
+        self.prepare_pcs_info_with_challenges(verify_keys, public_inputs, batch_proof, &challenges)
+    }
+
+    /// Check that the verification keys, the public inputs and the instances
+    /// of `batch_proof` are consistent with each other and with the domain.
+    pub(crate) fn check_instances(
+        &self,
+        verify_keys: &[&VerifyingKey<E, PCS>],
+        public_inputs: &[&[E::ScalarField]],
+        batch_proof: &BatchProof<E, PCS>,
+    ) -> Result<(), PlonkError> {
         if verify_keys.len() != batch_proof.len()
             || verify_keys.len() != public_inputs.len()
             || verify_keys.is_empty()
@@ -110,15 +150,18 @@ where
                 .into());
             }
         }
+        Ok(())
+    }
 
-        // compute challenges and evaluations
-        let challenges = Self::compute_challenges::<T>(
-            verify_keys,
-            public_inputs,
-            batch_proof,
-            extra_transcript_init_msg,
-        )?;
-
+    /// Prepare the (aggregated) polynomial commitment evaluation information
+    /// given the verifier challenges.
+    pub(crate) fn prepare_pcs_info_with_challenges(
+        &self,
+        verify_keys: &[&VerifyingKey<E, PCS>],
+        public_inputs: &[&[E::ScalarField]],
+        batch_proof: &BatchProof<E, PCS>,
+        challenges: &Challenges<E::ScalarField>,
+    ) -> Result<PcsInfo<E, PCS>, PlonkError> {
         // pre-compute alpha related values
         let alpha_2 = challenges.alpha.square();
         let alpha_3 = alpha_2 * challenges.alpha;
@@ -147,7 +190,7 @@ where
 
         // compute the constant term of the linearization polynomial
         let lin_poly_constant = self.compute_lin_poly_constant_term(
-            &challenges,
+            challenges,
             verify_keys,
             public_inputs,
             batch_proof,
@@ -159,17 +202,18 @@ where
         )?;
 
         // build the (aggregated) polynomial commitment/evaluation instance
-        let (comm_scalars_and_bases, buffer_v_and_uv_basis) = self.aggregate_poly_commitments(
-            verify_keys,
-            &challenges,
-            &vanish_eval,
-            &lagrange_1_eval,
-            &lagrange_n_eval,
-            batch_proof,
-            &alpha_powers,
-            &alpha_bases,
-        )?;
-        let eval = Self::aggregate_evaluations(
+        let (comm_scalars_and_bases, shifted_comm_scalars_and_bases, buffer_v_and_uv_basis) = self
+            .aggregate_poly_commitments(
+                verify_keys,
+                challenges,
+                &vanish_eval,
+                &lagrange_1_eval,
+                &lagrange_n_eval,
+                batch_proof,
+                &alpha_powers,
+                &alpha_bases,
+            )?;
+        let (eval, shifted_eval) = Self::aggregate_evaluations(
             &lin_poly_constant,
             &batch_proof.poly_evals_vec,
             &batch_proof.plookup_proofs_vec,
@@ -182,23 +226,32 @@ where
             next_eval_point: challenges.zeta * self.domain.group_gen,
             comm_scalars_and_bases,
             eval,
-            opening_proof: batch_proof.opening_proof,
-            shifted_opening_proof: batch_proof.shifted_opening_proof,
+            shifted_comm_scalars_and_bases,
+            shifted_eval,
+            opening_proof: batch_proof.opening_proof.clone(),
+            shifted_opening_proof: batch_proof.shifted_opening_proof.clone(),
         })
     }
 
     /// Batchly verify multiple (aggregated) PCS opening proofs.
     ///
-    /// We need to verify that
-    /// - `e(Ai, [x]2) = e(Bi, [1]2) for i \in {0, .., m-1}`, where
+    /// Each instance `i` claims that the polynomial committed in
+    /// `comm_i - shifted_comm_i` evaluates to `eval_i - shifted_eval_i` at
+    /// `eval_point_i`, and that the one committed in `shifted_comm_i / u_i`
+    /// evaluates to `shifted_eval_i / u_i` at `next_eval_point_i`. The claims
+    /// are checked by the PCS with the random combiners `r^i` and `r^i * u_i`
+    /// respectively, for a random `r`. For KZG, this is the check that
+    /// - `e(A0 + ... + r^{m-1} * Am, [x]2) = e(B0 + ... + r^{m-1} * Bm, [1]2)`,
+    ///   where
     /// - `Ai = [open_proof_i] + u_i * [shifted_open_proof_i]` and
     /// - `Bi = eval_point_i * [open_proof_i] + u_i * next_eval_point_i *
     ///   [shifted_open_proof_i] + comm_i - eval_i * [1]1`.
-    /// By Schwartz-Zippel lemma, it's equivalent to check that for a random r:
-    /// - `e(A0 + ... + r^{m-1} * Am, [x]2) = e(B0 + ... + r^{m-1} * Bm, [1]2)`.
+    ///
+    /// See [`crate::proof_system::PlonkKzgShplonkSnark`] for the variant with a
+    /// single witness for both points.
     pub(crate) fn batch_verify_opening_proofs<T>(
-        open_key: &OpenKey<E>,
-        pcs_infos: &[PcsInfo<E>],
+        open_key: &OpenKey<E, PCS>,
+        pcs_infos: &[PcsInfo<E, PCS>],
     ) -> Result<bool, PlonkError>
     where
         T: PlonkTranscript<F>,
@@ -218,51 +271,77 @@ where
             transcript.get_and_append_challenge::<E>(b"r")?
         };
 
-        // Compute A := A0 + r * A1 + ... + r^{m-1} * Am
-        let mut inners = ScalarsAndBases::<E>::new();
+        let mut comms = vec![];
+        let mut points = vec![];
+        let mut evals = vec![];
+        let mut proofs = vec![];
+        let mut combiners = vec![];
         let mut r_base = E::ScalarField::one();
         for pcs_info in pcs_infos.iter() {
-            inners.push(r_base, pcs_info.opening_proof.0);
-            inners.push(r_base * pcs_info.u, pcs_info.shifted_opening_proof.0);
-            r_base *= r;
-        }
-        let inner = inners.multi_scalar_mul();
-        // Add (A, [x]2) to the product pairing list
-        let mut g1_elems: Vec<<E as Pairing>::G1Affine> = vec![inner.into()];
-        let mut g2_elems = vec![open_key.beta_h];
+            let u_inv = pcs_info.u.inverse().ok_or(PlonkError::WrongProof)?;
 
-        // Compute B := B0 + r * B1 + ... + r^{m-1} * Bm
-        let mut inners = ScalarsAndBases::new();
-        let mut r_base = E::ScalarField::one();
-        let mut sum_evals = E::ScalarField::zero();
-        for pcs_info in pcs_infos.iter() {
-            inners.merge(r_base, &pcs_info.comm_scalars_and_bases);
-            inners.push(r_base * pcs_info.eval_point, pcs_info.opening_proof.0);
-            inners.push(
-                r_base * pcs_info.u * pcs_info.next_eval_point,
-                pcs_info.shifted_opening_proof.0,
+            let mut scalars_and_bases = ScalarsAndBases::new();
+            scalars_and_bases.merge(E::ScalarField::one(), &pcs_info.comm_scalars_and_bases);
+            scalars_and_bases.merge(
+                -E::ScalarField::one(),
+                &pcs_info.shifted_comm_scalars_and_bases,
             );
-            sum_evals += r_base * pcs_info.eval;
+            comms.push(Commitment(scalars_and_bases.multi_scalar_mul().into()));
+            points.push(pcs_info.eval_point);
+            evals.push(pcs_info.eval - pcs_info.shifted_eval);
+            proofs.push(&pcs_info.opening_proof);
+            combiners.push(r_base);
+
+            let mut scalars_and_bases = ScalarsAndBases::new();
+            scalars_and_bases.merge(u_inv, &pcs_info.shifted_comm_scalars_and_bases);
+            comms.push(Commitment(scalars_and_bases.multi_scalar_mul().into()));
+            points.push(pcs_info.next_eval_point);
+            evals.push(pcs_info.shifted_eval * u_inv);
+            proofs.push(&pcs_info.shifted_opening_proof);
+            combiners.push(r_base * pcs_info.u);
+
             r_base *= r;
         }
-        inners.push(-sum_evals, open_key.g);
-        let inner = inners.multi_scalar_mul();
-        // Add (-B, [1]2) to the product pairing list
-        g1_elems.push(-inner.into());
-        g2_elems.push(open_key.h);
-        // Check e(A, [x]2) ?= e(B, [1]2)
-        Ok(multi_pairing::<E>(&g1_elems, &g2_elems).0 == E::TargetField::one())
+        PCS::batch_verify_opening_proofs(open_key, &comms, &points, &evals, &proofs, &combiners)
     }
 
     /// Compute verifier challenges `tau`, `beta`, `gamma`, `alpha`, `zeta`,
     /// 'v', 'u'.
     #[inline]
     pub(crate) fn compute_challenges<T>(
-        verify_keys: &[&VerifyingKey<E>],
+        verify_keys: &[&VerifyingKey<E, PCS>],
         public_inputs: &[&[E::ScalarField]],
-        batch_proof: &BatchProof<E>,
+        batch_proof: &BatchProof<E, PCS>,
         extra_transcript_init_msg: &Option<Vec<u8>>,
     ) -> Result<Challenges<E::ScalarField>, PlonkError>
+    where
+        T: PlonkTranscript<F>,
+    {
+        let (mut transcript, mut challenges) = Self::compute_challenges_until_v::<T>(
+            verify_keys,
+            public_inputs,
+            batch_proof,
+            extra_transcript_init_msg,
+        )?;
+        PCS::append_opening_proof(&mut transcript, b"open_proof", &batch_proof.opening_proof)?;
+        PCS::append_opening_proof(
+            &mut transcript,
+            b"shifted_open_proof",
+            &batch_proof.shifted_opening_proof,
+        )?;
+        challenges.u = transcript.get_and_append_challenge::<E>(b"u")?;
+        Ok(challenges)
+    }
+
+    /// Compute verifier challenges `tau`, `beta`, `gamma`, `alpha`, `zeta` and
+    /// 'v', and return them (with a zero `u`) along with the transcript. The
+    /// opening proofs of `batch_proof` are ignored.
+    pub(crate) fn compute_challenges_until_v<T>(
+        verify_keys: &[&VerifyingKey<E, PCS>],
+        public_inputs: &[&[E::ScalarField]],
+        batch_proof: &BatchProof<E, PCS>,
+        extra_transcript_init_msg: &Option<Vec<u8>>,
+    ) -> Result<(T, Challenges<E::ScalarField>), PlonkError>
     where
         T: PlonkTranscript<F>,
     {
@@ -318,18 +397,18 @@ where
         }
 
         let v = transcript.get_and_append_challenge::<E>(b"v")?;
-        transcript.append_commitment(b"open_proof", &batch_proof.opening_proof)?;
-        transcript.append_commitment(b"shifted_open_proof", &batch_proof.shifted_opening_proof)?;
-        let u = transcript.get_and_append_challenge::<E>(b"u")?;
-        Ok(Challenges {
-            tau,
-            alpha,
-            beta,
-            gamma,
-            zeta,
-            v,
-            u,
-        })
+        Ok((
+            transcript,
+            Challenges {
+                tau,
+                alpha,
+                beta,
+                gamma,
+                zeta,
+                v,
+                u: E::ScalarField::zero(),
+            },
+        ))
     }
 
     /// Compute the constant term of the linearization polynomial:
@@ -352,9 +431,9 @@ where
     pub(crate) fn compute_lin_poly_constant_term(
         &self,
         challenges: &Challenges<E::ScalarField>,
-        verify_keys: &[&VerifyingKey<E>],
+        verify_keys: &[&VerifyingKey<E, PCS>],
         public_inputs: &[&[E::ScalarField]],
-        batch_proof: &BatchProof<E>,
+        batch_proof: &BatchProof<E, PCS>,
         vanish_eval: &E::ScalarField,
         lagrange_1_eval: &E::ScalarField,
         lagrange_n_eval: &E::ScalarField,
@@ -385,8 +464,9 @@ where
                 ),
             )
         {
-            let mut tmp = self.evaluate_pi_poly(pi, &challenges.zeta, vanish_eval, vk.is_merged)?
-                - alpha_powers[0] * lagrange_1_eval;
+            let mut tmp =
+                self.evaluate_pi_poly(pi, &challenges.zeta, vanish_eval, vk.merge_kind)?
+                    - alpha_powers[0] * lagrange_1_eval;
             let num_wire_types = GATE_WIDTH
                 + 1
                 + match plookup_proof.is_some() {
@@ -430,18 +510,18 @@ where
     /// ScalarsAndBases form). Useful in batch opening.
     /// The verification key type is guaranteed to match the Plonk proof type.
     /// The returned commitment is a generalization of `[F]1` described in Sec 8.4, step 10 of https://eprint.iacr.org/2019/953.pdf
-    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     pub(crate) fn aggregate_poly_commitments(
         &self,
-        vks: &[&VerifyingKey<E>],
+        vks: &[&VerifyingKey<E, PCS>],
         challenges: &Challenges<E::ScalarField>,
         vanish_eval: &E::ScalarField,
         lagrange_1_eval: &E::ScalarField,
         lagrange_n_eval: &E::ScalarField,
-        batch_proof: &BatchProof<E>,
+        batch_proof: &BatchProof<E, PCS>,
         alpha_powers: &[E::ScalarField],
         alpha_bases: &[E::ScalarField],
-    ) -> Result<(ScalarsAndBases<E>, Vec<E::ScalarField>), PlonkError> {
+    ) -> Result<(ScalarsAndBases<E>, ScalarsAndBases<E>, Vec<E::ScalarField>), PlonkError> {
         if vks.len() != batch_proof.len() {
             return Err(ParameterError(format!(
                 "the number of verification keys {} != the number of instances {}",
@@ -468,6 +548,9 @@ where
         // the random combiner term for the polynomials evaluated at point `zeta * g`
         let mut uv_base = challenges.u;
 
+        // the part of the polynomials evaluated at point `zeta * g`
+        let mut shifted_scalars_and_bases = ScalarsAndBases::new();
+
         // return buffer for aggregate_evaluations computation
         let mut buffer_v_and_uv_basis = vec![];
 
@@ -497,6 +580,7 @@ where
 
             // Add poly commitments to be evaluated at point `zeta * g`.
             buffer_v_and_uv_basis.push(uv_base);
+            shifted_scalars_and_bases.push(uv_base, batch_proof.prod_perm_poly_comms_vec[i].0);
             Self::add_poly_comm(
                 &mut scalars_and_bases,
                 &mut uv_base,
@@ -521,12 +605,17 @@ where
                 )?;
                 for &comm in plookup_shifted_comms.iter() {
                     buffer_v_and_uv_basis.push(uv_base);
+                    shifted_scalars_and_bases.push(uv_base, comm.0);
                     Self::add_poly_comm(&mut scalars_and_bases, &mut uv_base, comm.0, challenges.v);
                 }
             }
         }
 
-        Ok((scalars_and_bases, buffer_v_and_uv_basis))
+        Ok((
+            scalars_and_bases,
+            shifted_scalars_and_bases,
+            buffer_v_and_uv_basis,
+        ))
     }
 
     /// Compute the bases and scalars in the batched polynomial commitment,
@@ -535,12 +624,12 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn linearization_scalars_and_bases(
         &self,
-        vks: &[&VerifyingKey<E>],
+        vks: &[&VerifyingKey<E, PCS>],
         challenges: &Challenges<E::ScalarField>,
         vanish_eval: &E::ScalarField,
         lagrange_1_eval: &E::ScalarField,
         lagrange_n_eval: &E::ScalarField,
-        batch_proof: &BatchProof<E>,
+        batch_proof: &BatchProof<E, PCS>,
         alpha_powers: &[E::ScalarField],
         alpha_bases: &[E::ScalarField],
     ) -> Result<ScalarsAndBases<E>, PlonkError> {
@@ -709,16 +798,18 @@ where
 
     /// Combine the polynomial evaluations into a single evaluation. Useful in
     /// batch opening.
-    /// The returned value is the scalar in `[E]1` described in Sec 8.4, step 11 of https://eprint.iacr.org/2019/953.pdf
+    /// The first returned value is the scalar in `[E]1` described in Sec 8.4, step 11 of https://eprint.iacr.org/2019/953.pdf,
+    /// the second one is its part of the evaluations at point `zeta * g`.
     pub(crate) fn aggregate_evaluations(
         lin_poly_constant: &E::ScalarField,
         poly_evals_vec: &[ProofEvaluations<E::ScalarField>],
         plookup_proofs_vec: &[Option<PlookupProof<E>>],
         buffer_v_and_uv_basis: &[E::ScalarField],
-    ) -> Result<E::ScalarField, PlonkError> {
+    ) -> Result<(E::ScalarField, E::ScalarField), PlonkError> {
         assert_eq!(poly_evals_vec.len(), plookup_proofs_vec.len());
 
         let mut result: E::ScalarField = lin_poly_constant.neg();
+        let mut shifted_result = E::ScalarField::zero();
         let mut v_and_uv_basis = buffer_v_and_uv_basis.iter();
 
         for (poly_evals, plookup_proof) in poly_evals_vec.iter().zip(plookup_proofs_vec.iter()) {
@@ -743,7 +834,7 @@ where
             }
             // evaluations at point `zeta * g`
             Self::add_pcs_eval(
-                &mut result,
+                &mut shifted_result,
                 v_and_uv_basis
                     .next()
                     .ok_or(PlonkError::IteratorOutOfRange)?,
@@ -766,7 +857,7 @@ where
                 // evaluations at point `zeta * g`
                 for &next_eval in evals.next_evals_vec().iter() {
                     Self::add_pcs_eval(
-                        &mut result,
+                        &mut shifted_result,
                         v_and_uv_basis
                             .next()
                             .ok_or(PlonkError::IteratorOutOfRange)?,
@@ -779,16 +870,80 @@ where
         if v_and_uv_basis.next().is_some() {
             return Err(PlonkError::IteratorOutOfRange);
         }
-        Ok(result)
+        Ok((result + shifted_result, shifted_result))
+    }
+}
+
+impl<E: Pairing> Verifier<E> {
+    /// Verify a single KZG opening proof `opening_proof` for both points of
+    /// `pcs_info`, the sum of the two opening proofs of a
+    /// [`Proof`](crate::proof_system::structs::Proof).
+    ///
+    /// Let `C1` and `C2` be the commitments opened to `r1` at `z1 :=
+    /// eval_point` and to `r2` at `z2 := next_eval_point` respectively, i.e.
+    /// `C1 = comm - shifted_comm`, `r1 = eval - shifted_eval`, `C2 =
+    /// shifted_comm / u` and `r2 = shifted_eval / u`. We need to verify that
+    /// `W := opening_proof` commits to `(C1 - r1) / (X - z1) + (C2 - r2) / (X -
+    /// z2)`, i.e. that, with `D1 = C1 - r1 * [1]1` and `D2 = C2 - r2 * [1]1`,
+    /// - `e(W, [(x - z1)(x - z2)]2) = e(D1, [x - z2]2) * e(D2, [x - z1]2)`,
+    ///
+    /// which holds at the roots `z1` and `z2` of `(X - z1)(X - z2)` iff both
+    /// claims hold (as in SHPLONK, see <https://eprint.iacr.org/2020/081.pdf>).
+    /// Grouped by the elements of G2, it is the check that
+    /// - `e(W, [x^2]2) * e(A, [x]2) * e(B, [1]2) = 1`, where
+    /// - `A = -(z1 + z2) * W - D1 - D2` and
+    /// - `B = z1 * z2 * W + z2 * D1 + z1 * D2`.
+    ///
+    /// It costs a pairing more than [`Self::batch_verify_opening_proofs()`],
+    /// and needs `[x^2]2` in `open_key`.
+    pub(crate) fn verify_batched_opening_proof(
+        open_key: &OpenKey<E>,
+        pcs_info: &PcsInfo<E>,
+        opening_proof: &Commitment<E>,
+    ) -> Result<bool, PlonkError> {
+        if open_key.powers_of_h.len() < 3 {
+            return Err(ParameterError(
+                "the open key doesn't support opening proofs for two points".to_string(),
+            )
+            .into());
+        }
+        let u_inv = pcs_info.u.inverse().ok_or(PlonkError::WrongProof)?;
+        let (z1, z2) = (pcs_info.eval_point, pcs_info.next_eval_point);
+        let r2 = pcs_info.shifted_eval * u_inv;
+        let r1 = pcs_info.eval - pcs_info.shifted_eval;
+
+        let mut scalars_and_bases = ScalarsAndBases::new();
+        scalars_and_bases.push(z1 + z2, opening_proof.0);
+        scalars_and_bases.merge(E::ScalarField::one(), &pcs_info.comm_scalars_and_bases);
+        scalars_and_bases.merge(
+            u_inv - E::ScalarField::one(),
+            &pcs_info.shifted_comm_scalars_and_bases,
+        );
+        scalars_and_bases.push(-(r1 + r2), open_key.g);
+        let a = -scalars_and_bases.multi_scalar_mul();
+
+        let mut scalars_and_bases = ScalarsAndBases::new();
+        scalars_and_bases.push(z1 * z2, opening_proof.0);
+        scalars_and_bases.merge(z2, &pcs_info.comm_scalars_and_bases);
+        scalars_and_bases.merge(z1 * u_inv - z2, &pcs_info.shifted_comm_scalars_and_bases);
+        scalars_and_bases.push(-(z2 * r1 + z1 * r2), open_key.g);
+        let b = scalars_and_bases.multi_scalar_mul();
+
+        Ok(multi_pairing::<E>(
+            &[opening_proof.0, a.into(), b.into()],
+            &[open_key.powers_of_h[2], open_key.powers_of_h[1], open_key.h],
+        )
+        .0 == E::TargetField::one())
     }
 }
 
 /// Private helper methods
-impl<E, F, P> Verifier<E>
+impl<E, F, P, PCS> Verifier<E, PCS>
 where
     E: Pairing<BaseField = F, G1Affine = Affine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWCurveConfig<BaseField = F>,
+    PCS: PlonkPCS<E>,
 {
     /// Merge a polynomial commitment into the aggregated polynomial commitment
     /// (in the ScalarAndBases form), update the random combiner afterward.
@@ -842,7 +997,7 @@ where
     /// The order should be consistent with the prover side.
     fn plookup_open_poly_comms(
         proof: &PlookupProof<E>,
-        vk: &VerifyingKey<E>,
+        vk: &VerifyingKey<E, PCS>,
     ) -> Result<Vec<Commitment<E>>, PlonkError> {
         Ok(vec![
             vk.plookup_vk.as_ref().unwrap().range_table_comm,
@@ -859,7 +1014,7 @@ where
     /// g`. The order should be consistent with the prover side.
     fn plookup_shifted_open_poly_comms(
         proof: &PlookupProof<E>,
-        vk: &VerifyingKey<E>,
+        vk: &VerifyingKey<E, PCS>,
         wires_poly_comms: &[Commitment<E>],
     ) -> Result<Vec<Commitment<E>>, PlonkError> {
         Ok(vec![
@@ -887,7 +1042,8 @@ where
     ///
     /// \sum_{i=0..l} L_{i,H}(z) * pub_input[i].
     ///
-    /// For merged circuits, the evaluation is:
+    /// For circuits merged from a type A and a type B circuit, the evaluation
+    /// is:
     /// \sum_{i=0..l/2} L_{i,H}(z) * pub_input[i] + \sum_{i=0..l/2} L_{n-i,H}(z)
     /// * pub_input[l/2+i]
     ///
@@ -897,13 +1053,13 @@ where
         pub_input: &[E::ScalarField],
         z: &E::ScalarField,
         vanish_eval: &E::ScalarField,
-        circuit_is_merged: bool,
+        merge_kind: MergeKind,
     ) -> Result<E::ScalarField, PlonkError> {
         // If z is a root of the vanishing polynomial, directly return zero.
         if vanish_eval.is_zero() {
             return Ok(E::ScalarField::zero());
         }
-        let len = match circuit_is_merged {
+        let len = match merge_kind.splits_public_inputs() {
             false => pub_input.len(),
             true => pub_input.len() / 2,
         };
@@ -918,7 +1074,7 @@ where
                 vanish_eval_div_n * self.domain.element(i) / (*z - self.domain.element(i));
             result += lagrange_i * val;
         }
-        if circuit_is_merged {
+        if merge_kind.splits_public_inputs() {
             let n = self.domain.size();
             for (i, val) in pub_input.iter().skip(len).enumerate() {
                 let lagrange_n_minus_i = vanish_eval_div_n * self.domain.element(n - i - 1)
@@ -26,7 +26,7 @@ use ark_ec::{
 };
 use ark_ff::Field;
 use ark_poly::Radix2EvaluationDomain;
-use ark_std::vec::Vec;
+use ark_std::{marker::PhantomData, vec::Vec};
 use hashbrown::HashMap;
 use jf_primitives::{pcs::prelude::Commitment, rescue::RescueParameter};
 use jf_relation::gadgets::ecc::SWToTEConParam;
@@ -115,6 +115,10 @@ pub struct PcsInfo<E: Pairing> {
     ///
     pub comm_scalars_and_bases: ScalarsAndBases<E>,
     ///
+    pub shifted_eval: E::ScalarField,
+    ///
+    pub shifted_comm_scalars_and_bases: ScalarsAndBases<E>,
+    ///
     pub opening_proof: Commitment<E>,
     ///
     pub shifted_opening_proof: Commitment<E>,
@@ -128,6 +132,8 @@ impl<E: Pairing> From<PcsInfo<E>> for verifier::PcsInfo<E> {
             next_eval_point: other.next_eval_point,
             eval: other.eval,
             comm_scalars_and_bases: other.comm_scalars_and_bases.into(),
+            shifted_eval: other.shifted_eval,
+            shifted_comm_scalars_and_bases: other.shifted_comm_scalars_and_bases.into(),
             opening_proof: other.opening_proof,
             shifted_opening_proof: other.shifted_opening_proof,
         }
@@ -142,6 +148,8 @@ impl<E: Pairing> From<verifier::PcsInfo<E>> for PcsInfo<E> {
             next_eval_point: other.next_eval_point,
             eval: other.eval,
             comm_scalars_and_bases: other.comm_scalars_and_bases.into(),
+            shifted_eval: other.shifted_eval,
+            shifted_comm_scalars_and_bases: other.shifted_comm_scalars_and_bases.into(),
             opening_proof: other.opening_proof,
             shifted_opening_proof: other.shifted_opening_proof,
         }
@@ -159,6 +167,7 @@ impl<E: Pairing> From<Verifier<E>> for verifier::Verifier<E> {
     fn from(other: Verifier<E>) -> Self {
         Self {
             domain: other.domain,
+            _pcs: PhantomData,
         }
     }
 }
@@ -248,7 +257,12 @@ where
         let vanish_eval = verifier.evaluate_vanishing_poly(zeta);
         let (lagrange_1_eval, lagrange_n_eval) =
             verifier.evaluate_lagrange_1_and_n(zeta, &vanish_eval);
-        let pi_eval = verifier.evaluate_pi_poly(public_input, zeta, &vanish_eval, false)?;
+        let pi_eval = verifier.evaluate_pi_poly(
+            public_input,
+            zeta,
+            &vanish_eval,
+            structs::MergeKind::Unmerged,
+        )?;
         Ok((vanish_eval, lagrange_1_eval, lagrange_n_eval, pi_eval))
     }
 
@@ -326,7 +340,7 @@ where
             alpha_powers,
             alpha_bases,
         )?;
-        Ok((res.0.into(), res.1))
+        Ok((res.0.into(), res.2))
     }
 
     /// Compute the bases and scalars in the batched polynomial commitment,
@@ -369,12 +383,13 @@ where
         plookup_proofs_vec: &[Option<PlookupProof<E>>],
         buffer_v_and_uv_basis: &[E::ScalarField],
     ) -> Result<E::ScalarField, PlonkError> {
-        verifier::Verifier::<E>::aggregate_evaluations(
+        Ok(verifier::Verifier::<E>::aggregate_evaluations(
             lin_poly_constant,
             poly_evals_vec,
             plookup_proofs_vec,
             buffer_v_and_uv_basis,
-        )
+        )?
+        .0)
     }
 }
 
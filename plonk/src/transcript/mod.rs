@@ -5,19 +5,25 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 //! This module implements three different types of transcripts that are
-//! supported.
+//! supported, and a wrapper that makes any of them absorb verification key
+//! digests.
 
 pub(crate) mod rescue;
 pub(crate) mod solidity;
 pub(crate) mod standard;
+pub(crate) mod vk_digest;
 
 pub use rescue::RescueTranscript;
 pub use solidity::SolidityTranscript;
 pub use standard::StandardTranscript;
+pub use vk_digest::VkDigestTranscript;
 
 use crate::{
     errors::PlonkError,
-    proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+    proof_system::{
+        structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+        PlonkPCS,
+    },
 };
 use ark_ec::{
     pairing::Pairing,
@@ -42,14 +48,15 @@ pub trait PlonkTranscript<F> {
     fn new(label: &'static [u8]) -> Self;
 
     /// Append the verification key and the public input to the transcript.
-    fn append_vk_and_pub_input<E, P>(
+    fn append_vk_and_pub_input<E, P, PCS>(
         &mut self,
-        vk: &VerifyingKey<E>,
+        vk: &VerifyingKey<E, PCS>,
         pub_input: &[E::ScalarField],
     ) -> Result<(), PlonkError>
     where
         E: Pairing<BaseField = F, G1Affine = Affine<P>>,
         P: SWParam<BaseField = F>,
+        PCS: PlonkPCS<E>,
     {
         <Self as PlonkTranscript<F>>::append_message(
             self,
@@ -101,6 +108,35 @@ pub trait PlonkTranscript<F> {
         Ok(())
     }
 
+    /// Append the digest of the verification key and the public input to the
+    /// transcript, instead of the verification key itself. By default, the
+    /// byte digest [`VerifyingKey::digest_bytes`] is absorbed.
+    fn append_vk_digest_and_pub_input<E, P, PCS>(
+        &mut self,
+        vk: &VerifyingKey<E, PCS>,
+        pub_input: &[E::ScalarField],
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWParam<BaseField = F>,
+        PCS: PlonkPCS<E>,
+    {
+        <Self as PlonkTranscript<F>>::append_message(
+            self,
+            b"verifying key digest",
+            &vk.digest_bytes()?,
+        )?;
+        for input in pub_input.iter() {
+            <Self as PlonkTranscript<F>>::append_message(
+                self,
+                b"public input",
+                &to_bytes!(input)?,
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Append the message to the transcript.
     fn append_message(&mut self, label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError>;
 
@@ -8,7 +8,10 @@
 use super::PlonkTranscript;
 use crate::{
     errors::PlonkError,
-    proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+    proof_system::{
+        structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+        PlonkPCS,
+    },
 };
 use ark_ec::{
     pairing::Pairing,
@@ -56,14 +59,15 @@ where
         }
     }
 
-    fn append_vk_and_pub_input<E, P>(
+    fn append_vk_and_pub_input<E, P, PCS>(
         &mut self,
-        vk: &VerifyingKey<E>,
+        vk: &VerifyingKey<E, PCS>,
         pub_input: &[E::ScalarField],
     ) -> Result<(), PlonkError>
     where
         E: Pairing<BaseField = F, G1Affine = Affine<P>>,
         P: SWParam<BaseField = F>,
+        PCS: PlonkPCS<E>,
     {
         // to enable a more efficient verifier circuit, we remove
         // the following messages (c.f. merlin transcript)
@@ -94,6 +98,26 @@ where
         Ok(())
     }
 
+    /// Append the Rescue digest [`VerifyingKey::digest`] of the verification
+    /// key and the public input to the transcript.
+    fn append_vk_digest_and_pub_input<E, P, PCS>(
+        &mut self,
+        vk: &VerifyingKey<E, PCS>,
+        pub_input: &[E::ScalarField],
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWParam<BaseField = F>,
+        PCS: PlonkPCS<E>,
+    {
+        self.transcript.push(vk.digest()?);
+        for e in pub_input {
+            self.transcript.push(field_switching(e))
+        }
+
+        Ok(())
+    }
+
     /// Append the message to the transcript. `_label` is omitted for
     /// efficiency.
     fn append_message(&mut self, _label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError> {
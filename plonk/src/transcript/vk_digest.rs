@@ -0,0 +1,129 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! This module defines a transcript wrapper that absorbs verification key
+//! digests.
+use super::PlonkTranscript;
+use crate::{
+    errors::PlonkError,
+    proof_system::{
+        structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+        PlonkPCS,
+    },
+};
+use ark_ec::{
+    pairing::Pairing,
+    short_weierstrass::{Affine, SWCurveConfig as SWParam},
+};
+use jf_primitives::pcs::prelude::Commitment;
+
+/// Transcript wrapper that absorbs the digest of the verification key rather
+/// than the key itself, and otherwise behaves exactly as the inner transcript
+/// `T`.
+///
+/// Proofs generated with `VkDigestTranscript<T>` must be verified with
+/// `VkDigestTranscript<T>` as well. The digest absorbed is the one chosen by
+/// [`PlonkTranscript::append_vk_digest_and_pub_input`] of `T`, e.g.
+/// [`VerifyingKey::digest`] for the Rescue transcript.
+pub struct VkDigestTranscript<T>(T);
+
+impl<F, T> PlonkTranscript<F> for VkDigestTranscript<T>
+where
+    T: PlonkTranscript<F>,
+{
+    fn new(label: &'static [u8]) -> Self {
+        Self(T::new(label))
+    }
+
+    fn append_vk_and_pub_input<E, P, PCS>(
+        &mut self,
+        vk: &VerifyingKey<E, PCS>,
+        pub_input: &[E::ScalarField],
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWParam<BaseField = F>,
+        PCS: PlonkPCS<E>,
+    {
+        <T as PlonkTranscript<F>>::append_vk_digest_and_pub_input(&mut self.0, vk, pub_input)
+    }
+
+    fn append_vk_digest_and_pub_input<E, P, PCS>(
+        &mut self,
+        vk: &VerifyingKey<E, PCS>,
+        pub_input: &[E::ScalarField],
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWParam<BaseField = F>,
+        PCS: PlonkPCS<E>,
+    {
+        <T as PlonkTranscript<F>>::append_vk_digest_and_pub_input(&mut self.0, vk, pub_input)
+    }
+
+    fn append_message(&mut self, label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError> {
+        <T as PlonkTranscript<F>>::append_message(&mut self.0, label, msg)
+    }
+
+    fn append_commitments<E, P>(
+        &mut self,
+        label: &'static [u8],
+        comms: &[Commitment<E>],
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWParam<BaseField = F>,
+    {
+        <T as PlonkTranscript<F>>::append_commitments(&mut self.0, label, comms)
+    }
+
+    fn append_commitment<E, P>(
+        &mut self,
+        label: &'static [u8],
+        comm: &Commitment<E>,
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F, G1Affine = Affine<P>>,
+        P: SWParam<BaseField = F>,
+    {
+        <T as PlonkTranscript<F>>::append_commitment(&mut self.0, label, comm)
+    }
+
+    fn append_challenge<E>(
+        &mut self,
+        label: &'static [u8],
+        challenge: &E::ScalarField,
+    ) -> Result<(), PlonkError>
+    where
+        E: Pairing<BaseField = F>,
+    {
+        <T as PlonkTranscript<F>>::append_challenge::<E>(&mut self.0, label, challenge)
+    }
+
+    fn append_proof_evaluations<E: Pairing>(
+        &mut self,
+        evals: &ProofEvaluations<E::ScalarField>,
+    ) -> Result<(), PlonkError> {
+        <T as PlonkTranscript<F>>::append_proof_evaluations::<E>(&mut self.0, evals)
+    }
+
+    fn append_plookup_evaluations<E: Pairing>(
+        &mut self,
+        evals: &PlookupEvaluations<E::ScalarField>,
+    ) -> Result<(), PlonkError> {
+        <T as PlonkTranscript<F>>::append_plookup_evaluations::<E>(&mut self.0, evals)
+    }
+
+    fn get_and_append_challenge<E>(
+        &mut self,
+        label: &'static [u8],
+    ) -> Result<E::ScalarField, PlonkError>
+    where
+        E: Pairing<BaseField = F>,
+    {
+        <T as PlonkTranscript<F>>::get_and_append_challenge::<E>(&mut self.0, label)
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Size and building time of the circuits of the in-circuit hashes.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_ed_on_bn254::Fq as FqEd254;
+use ark_std::UniformRand;
+use criterion::Criterion;
+use jf_primitives::circuit::{
+    blake::{Blake2sGadget, Blake3Gadget},
+    rescue::RescueNativeGadget,
+};
+use jf_relation::{gadgets::UInt8Var, Arithmetization, Circuit, PlonkCircuit};
+use std::time::Duration;
+
+const BENCH_NAME: &str = "hash_circuits";
+const RANGE_BIT_LEN: usize = 8;
+const INPUT_BYTES: [usize; 2] = [64, 1024];
+
+fn rescue_circuit(num_elems: usize) -> PlonkCircuit<FqEd254> {
+    let mut rng = jf_utils::test_rng();
+    let mut circuit = PlonkCircuit::new_turbo_plonk();
+    let input = (0..num_elems)
+        .map(|_| circuit.create_variable(FqEd254::rand(&mut rng)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    RescueNativeGadget::<FqEd254>::rescue_sponge_with_padding(&mut circuit, &input, 1).unwrap();
+    circuit
+}
+
+fn blake_circuit(num_bytes: usize, blake3: bool) -> PlonkCircuit<FqEd254> {
+    let mut circuit = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN);
+    let input = (0..num_bytes)
+        .map(|i| circuit.create_uint_variable(i as u64 % 256))
+        .collect::<Result<Vec<UInt8Var>, _>>()
+        .unwrap();
+    if blake3 {
+        circuit.blake3(&input).unwrap();
+    } else {
+        circuit.blake2s(&input).unwrap();
+    }
+    circuit
+}
+
+// Report the number of gates before and the domain size after finalization.
+fn report(name: &str, mut circuit: PlonkCircuit<FqEd254>) {
+    let num_gates = circuit.num_gates();
+    circuit.finalize_for_arithmetization().unwrap();
+    println!(
+        "{name}: {num_gates} gates, domain size {}",
+        circuit.eval_domain_size().unwrap()
+    );
+}
+
+fn hash_circuits(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    for num_bytes in INPUT_BYTES {
+        // a field element holds 31 bytes
+        let num_elems = (num_bytes + 30) / 31;
+        let name = format!("rescue_{num_bytes}_bytes");
+        report(&name, rescue_circuit(num_elems));
+        benchmark_group.bench_function(&name, |b| b.iter(|| rescue_circuit(num_elems)));
+        for (hash, blake3) in [("blake2s", false), ("blake3", true)] {
+            let name = format!("{hash}_{num_bytes}_bytes");
+            report(&name, blake_circuit(num_bytes, blake3));
+            benchmark_group.bench_function(&name, |b| b.iter(|| blake_circuit(num_bytes, blake3)));
+        }
+    }
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    hash_circuits(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of BLAKE2s-256.
+
+use super::{constant_words, digest_bytes, message_blocks, round, IV};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use jf_relation::{
+    errors::CircuitError,
+    gadgets::{UInt32Var, UInt8Var},
+    PlonkCircuit,
+};
+
+/// The message schedule of the 10 rounds.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The parameter block word of an unkeyed hash with a 32-byte digest.
+const PARAM: u32 = 0x0101_0020;
+
+/// Circuit implementation of the BLAKE2s hash function.
+pub trait Blake2sGadget<F: PrimeField> {
+    /// Compute the unkeyed BLAKE2s-256 digest of `input`.
+    /// * `input` - the message bytes, its length is fixed by the circuit
+    /// * `returns` - the 32 bytes of the digest
+    fn blake2s(&mut self, input: &[UInt8Var]) -> Result<Vec<UInt8Var>, CircuitError>;
+}
+
+impl<F: PrimeField> Blake2sGadget<F> for PlonkCircuit<F> {
+    fn blake2s(&mut self, input: &[UInt8Var]) -> Result<Vec<UInt8Var>, CircuitError> {
+        let mut iv = IV;
+        iv[0] ^= PARAM;
+        let mut h = constant_words(self, &iv)?;
+        let blocks = message_blocks(self, input)?;
+        let num_blocks = blocks.len();
+        let mut counter = 0u64;
+        for (i, (block, len)) in blocks.into_iter().enumerate() {
+            counter += len as u64;
+            h = compress(self, &h, &block, counter, i == num_blocks - 1)?;
+        }
+        digest_bytes(self, &h)
+    }
+}
+
+// The compression function, where `counter` is the number of message bytes
+// hashed so far.
+fn compress<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    h: &[UInt32Var],
+    block: &[UInt32Var],
+    counter: u64,
+    last: bool,
+) -> Result<Vec<UInt32Var>, CircuitError> {
+    let mut v = h.to_vec();
+    v.extend(constant_words(
+        circuit,
+        &[
+            IV[0],
+            IV[1],
+            IV[2],
+            IV[3],
+            IV[4] ^ counter as u32,
+            IV[5] ^ (counter >> 32) as u32,
+            if last { !IV[6] } else { IV[6] },
+            IV[7],
+        ],
+    )?);
+    for sigma in SIGMA.iter() {
+        let m: Vec<UInt32Var> = sigma.iter().map(|&j| block[j]).collect();
+        round(circuit, &mut v, &m)?;
+    }
+    (0..8)
+        .map(|i| {
+            let x = circuit.uint_xor(v[i], v[i + 8])?;
+            circuit.uint_xor(h[i], x)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, vec};
+    use blake2::{Blake2s256, Digest};
+    use jf_relation::Circuit;
+    use jf_utils::test_rng;
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 8;
+
+    #[test]
+    fn test_blake2s_circuit() -> Result<(), CircuitError> {
+        test_blake2s_circuit_helper::<FqEd254>()?;
+        test_blake2s_circuit_helper::<Fq377>()
+    }
+
+    fn test_blake2s_circuit_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let rng = &mut test_rng();
+        for len in [0, 1, 3, 63, 64, 65, 128, 200] {
+            let mut input = vec![0u8; len];
+            rng.fill_bytes(&mut input);
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            check_blake2s(&mut circuit, &input)?;
+        }
+        // the gadget is also correct on TurboPlonk circuits
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        check_blake2s(&mut circuit, b"abc")
+    }
+
+    fn check_blake2s<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        input: &[u8],
+    ) -> Result<(), CircuitError> {
+        let input_vars = input
+            .iter()
+            .map(|&b| circuit.create_uint_variable(b as u64))
+            .collect::<Result<Vec<UInt8Var>, _>>()?;
+        let digest_vars = circuit.blake2s(&input_vars)?;
+        let digest = digest_vars
+            .iter()
+            .map(|&b| circuit.uint_witness(b).map(|b| b as u8))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(digest, Blake2s256::digest(input).to_vec());
+        circuit.check_circuit_satisfiability(&[])?;
+
+        // a wrong digest byte
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(digest_vars[7].var()) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        circuit.finalize_for_arithmetization()?;
+        circuit.check_circuit_satisfiability(&[])
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of BLAKE3 with a 32-byte output.
+
+use super::{constant_words, digest_bytes, message_blocks, round, BLOCK_LEN, IV};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use jf_relation::{
+    errors::CircuitError,
+    gadgets::{UInt32Var, UInt8Var},
+    PlonkCircuit,
+};
+
+/// The size of a chunk, i.e. a leaf of the hash tree, in bytes.
+const CHUNK_LEN: usize = 1024;
+
+/// The permutation applied to the message words between rounds.
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+// Domain separation flags.
+const CHUNK_START: u32 = 1;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// Circuit implementation of the BLAKE3 hash function.
+pub trait Blake3Gadget<F: PrimeField> {
+    /// Compute the BLAKE3 digest of `input`, in the default hash mode with a
+    /// 32-byte output.
+    /// * `input` - the message bytes, its length is fixed by the circuit
+    /// * `returns` - the 32 bytes of the digest
+    fn blake3(&mut self, input: &[UInt8Var]) -> Result<Vec<UInt8Var>, CircuitError>;
+}
+
+impl<F: PrimeField> Blake3Gadget<F> for PlonkCircuit<F> {
+    fn blake3(&mut self, input: &[UInt8Var]) -> Result<Vec<UInt8Var>, CircuitError> {
+        let key = constant_words(self, &IV)?;
+        let root = if input.len() <= CHUNK_LEN {
+            chunk_chaining_value(self, &key, input, 0, ROOT)?
+        } else {
+            let chaining_values = input
+                .chunks(CHUNK_LEN)
+                .enumerate()
+                .map(|(i, chunk)| chunk_chaining_value(self, &key, chunk, i as u64, 0))
+                .collect::<Result<Vec<_>, CircuitError>>()?;
+            tree_chaining_value(self, &key, &chaining_values, ROOT)?
+        };
+        digest_bytes(self, &root)
+    }
+}
+
+// The chaining value of the `counter`-th chunk, `root_flag` is set on its last
+// block if it is the only chunk.
+fn chunk_chaining_value<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    key: &[UInt32Var],
+    chunk: &[UInt8Var],
+    counter: u64,
+    root_flag: u32,
+) -> Result<Vec<UInt32Var>, CircuitError> {
+    let blocks = message_blocks(circuit, chunk)?;
+    let num_blocks = blocks.len();
+    let mut cv = key.to_vec();
+    for (i, (block, len)) in blocks.into_iter().enumerate() {
+        let mut flags = 0;
+        if i == 0 {
+            flags |= CHUNK_START;
+        }
+        if i == num_blocks - 1 {
+            flags |= CHUNK_END | root_flag;
+        }
+        cv = compress(circuit, &cv, &block, counter, len as u32, flags)?;
+    }
+    Ok(cv)
+}
+
+// The chaining value of the subtree over the chunks of `chaining_values`. The
+// left subtree holds the largest power of two of chunks that leaves at least
+// one chunk for the right subtree.
+fn tree_chaining_value<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    key: &[UInt32Var],
+    chaining_values: &[Vec<UInt32Var>],
+    root_flag: u32,
+) -> Result<Vec<UInt32Var>, CircuitError> {
+    let num_chunks = chaining_values.len();
+    if num_chunks == 1 {
+        return Ok(chaining_values[0].clone());
+    }
+    let left_len = 1 << (usize::BITS - 1 - (num_chunks - 1).leading_zeros());
+    let mut block = tree_chaining_value(circuit, key, &chaining_values[..left_len], 0)?;
+    block.extend(tree_chaining_value(
+        circuit,
+        key,
+        &chaining_values[left_len..],
+        0,
+    )?);
+    compress(
+        circuit,
+        key,
+        &block,
+        0,
+        BLOCK_LEN as u32,
+        PARENT | root_flag,
+    )
+}
+
+// The compression function, truncated to the new chaining value.
+fn compress<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    cv: &[UInt32Var],
+    block: &[UInt32Var],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> Result<Vec<UInt32Var>, CircuitError> {
+    let mut v = cv.to_vec();
+    v.extend(constant_words(
+        circuit,
+        &[
+            IV[0],
+            IV[1],
+            IV[2],
+            IV[3],
+            counter as u32,
+            (counter >> 32) as u32,
+            block_len,
+            flags,
+        ],
+    )?);
+    let mut m = block.to_vec();
+    for i in 0..7 {
+        if i > 0 {
+            m = MSG_PERMUTATION.iter().map(|&j| m[j]).collect();
+        }
+        round(circuit, &mut v, &m)?;
+    }
+    (0..8).map(|i| circuit.uint_xor(v[i], v[i + 8])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use jf_relation::Circuit;
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 8;
+
+    // Test vectors of the reference implementation, the input of length `n`
+    // being the bytes `0, 1, ..., 250, 0, 1, ...`.
+    const TEST_VECTORS: [(usize, &str); 8] = [
+        (
+            0,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        ),
+        (
+            1,
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+        ),
+        (
+            64,
+            "4eed7141ea4a5cd4b788606bd23f46e212af9cacebacdc7d1f4c6dc7f2511b98",
+        ),
+        (
+            65,
+            "de1e5fa0be70df6d2be8fffd0e99ceaa8eb6e8c93a63f2d8d1c30ecb6b263dee",
+        ),
+        (
+            1024,
+            "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+        ),
+        (
+            1025,
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+        ),
+        (
+            2049,
+            "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+        ),
+        (
+            3073,
+            "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+        ),
+    ];
+
+    #[test]
+    fn test_blake3_circuit() -> Result<(), CircuitError> {
+        for (len, expected) in TEST_VECTORS {
+            let input: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let mut circuit = PlonkCircuit::<FqEd254>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            check_blake3(&mut circuit, &input, expected)?;
+        }
+        let mut circuit = PlonkCircuit::<Fq377>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        check_blake3(
+            &mut circuit,
+            b"abc",
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+        )?;
+        // the gadget is also correct on TurboPlonk circuits
+        let mut circuit = PlonkCircuit::<FqEd254>::new_turbo_plonk();
+        check_blake3(
+            &mut circuit,
+            b"abc",
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+        )
+    }
+
+    fn check_blake3<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        input: &[u8],
+        expected: &str,
+    ) -> Result<(), CircuitError> {
+        let input_vars = input
+            .iter()
+            .map(|&b| circuit.create_uint_variable(b as u64))
+            .collect::<Result<Vec<UInt8Var>, _>>()?;
+        let digest_vars = circuit.blake3(&input_vars)?;
+        let digest = digest_vars
+            .iter()
+            .map(|&b| circuit.uint_witness(b).map(|b| b as u8))
+            .collect::<Result<Vec<_>, _>>()?;
+        let expected: Vec<u8> = (0..32)
+            .map(|i| u8::from_str_radix(&expected[2 * i..2 * i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(digest, expected);
+        circuit.check_circuit_satisfiability(&[])?;
+
+        // a wrong digest byte
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(digest_vars[31].var()) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        // a wrong input byte
+        if let Some(byte) = input_vars.first() {
+            let mut bad_circuit = circuit.clone();
+            *bad_circuit.witness_mut(byte.var()) += F::one();
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the BLAKE2s and BLAKE3 hash functions.
+//!
+//! Both hashes work over 32-bit words and share the same mixing function, so
+//! they are built on the unsigned integer gadgets of `jf_relation`. The
+//! gadgets are designed for UltraPlonk circuits, where XORs are proven with
+//! nibble lookups and the word rotations by 16, 12 and 8 bits are free; they
+//! remain correct, but much larger, on TurboPlonk circuits.
+
+mod blake2s;
+mod blake3;
+
+pub use self::blake3::Blake3Gadget;
+pub use blake2s::Blake2sGadget;
+
+use ark_ff::PrimeField;
+use ark_std::{
+    cmp::{max, min},
+    vec::Vec,
+};
+use jf_relation::{
+    errors::CircuitError,
+    gadgets::{UInt32Var, UInt8Var},
+    PlonkCircuit,
+};
+
+/// The initialization vector shared by BLAKE2s and BLAKE3.
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// The size of a message block in bytes.
+const BLOCK_LEN: usize = 64;
+
+// The mixing function G, mixing the message words `x` and `y` into the
+// state words `a`, `b`, `c` and `d`.
+#[allow(clippy::too_many_arguments)]
+fn mix<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    v: &mut [UInt32Var],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    x: UInt32Var,
+    y: UInt32Var,
+) -> Result<(), CircuitError> {
+    v[a] = circuit.uint_wrapping_sum(&[v[a], v[b], x])?;
+    v[d] = circuit.uint_xor_rotr(v[d], v[a], 16)?;
+    v[c] = circuit.uint_wrapping_add(v[c], v[d])?;
+    v[b] = circuit.uint_xor_rotr(v[b], v[c], 12)?;
+    v[a] = circuit.uint_wrapping_sum(&[v[a], v[b], y])?;
+    v[d] = circuit.uint_xor_rotr(v[d], v[a], 8)?;
+    v[c] = circuit.uint_wrapping_add(v[c], v[d])?;
+    v[b] = circuit.uint_xor_rotr(v[b], v[c], 7)?;
+    Ok(())
+}
+
+// A round mixing the columns and then the diagonals of the state, with the
+// message words `m` already permuted for this round.
+fn round<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    v: &mut [UInt32Var],
+    m: &[UInt32Var],
+) -> Result<(), CircuitError> {
+    mix(circuit, v, 0, 4, 8, 12, m[0], m[1])?;
+    mix(circuit, v, 1, 5, 9, 13, m[2], m[3])?;
+    mix(circuit, v, 2, 6, 10, 14, m[4], m[5])?;
+    mix(circuit, v, 3, 7, 11, 15, m[6], m[7])?;
+    mix(circuit, v, 0, 5, 10, 15, m[8], m[9])?;
+    mix(circuit, v, 1, 6, 11, 12, m[10], m[11])?;
+    mix(circuit, v, 2, 7, 8, 13, m[12], m[13])?;
+    mix(circuit, v, 3, 4, 9, 14, m[14], m[15])
+}
+
+// Split the input into blocks of little-endian words, the last block being
+// padded with zeros. An empty input gives a single block of zeros. Return the
+// blocks together with the number of input bytes of each.
+fn message_blocks<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    input: &[UInt8Var],
+) -> Result<Vec<(Vec<UInt32Var>, usize)>, CircuitError> {
+    let zero: UInt8Var = circuit.create_constant_uint_variable(0)?;
+    let num_blocks = max(1, (input.len() + BLOCK_LEN - 1) / BLOCK_LEN);
+    (0..num_blocks)
+        .map(|i| {
+            let block = &input[i * BLOCK_LEN..min((i + 1) * BLOCK_LEN, input.len())];
+            let mut bytes = block.to_vec();
+            bytes.resize(BLOCK_LEN, zero);
+            let words = bytes
+                .chunks(4)
+                .map(|bytes| circuit.uint_from_bytes(bytes))
+                .collect::<Result<Vec<_>, CircuitError>>()?;
+            Ok((words, block.len()))
+        })
+        .collect()
+}
+
+// Constant variables for the words `vals`.
+fn constant_words<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    vals: &[u32],
+) -> Result<Vec<UInt32Var>, CircuitError> {
+    vals.iter()
+        .map(|&val| circuit.create_constant_uint_variable(val as u64))
+        .collect()
+}
+
+// The little-endian bytes of the words of a digest.
+fn digest_bytes<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    words: &[UInt32Var],
+) -> Result<Vec<UInt8Var>, CircuitError> {
+    let mut bytes = Vec::with_capacity(4 * words.len());
+    for &word in words.iter() {
+        bytes.extend(circuit.uint_to_bytes(word)?);
+    }
+    Ok(bytes)
+}
@@ -432,9 +432,11 @@ where
         proof_var: Merkle3AryMembershipProofVar,
         expected_root_var: Variable,
     ) -> Result<(), CircuitError> {
-        let bool_val =
-            MerkleTreeGadget::<T>::is_member(self, elem_idx_var, proof_var, expected_root_var)?;
-        self.enforce_true(bool_val.into())
+        self.profile("enforce_membership_proof", |cs| {
+            let bool_val =
+                MerkleTreeGadget::<T>::is_member(cs, elem_idx_var, proof_var, expected_root_var)?;
+            cs.enforce_true(bool_val.into())
+        })
     }
 }
 
@@ -632,4 +634,50 @@ mod test {
             assert!(circuit.check_circuit_satisfiability(&[]).is_err());
         }
     }
+
+    #[test]
+    fn test_mt_gadget_profile() {
+        test_mt_gadget_profile_helper::<FqEd254>();
+        test_mt_gadget_profile_helper::<Fq377>();
+    }
+
+    fn test_mt_gadget_profile_helper<F: RescueParameter>() {
+        let elements = (1u64..=9u64).map(|x| F::from(x)).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(Some(2), elements).unwrap();
+        let (_, proof) = mt.lookup(4).expect_ok().unwrap();
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        circuit.set_profiling(true);
+        let elem_idx_var = circuit.create_variable(F::from(4u64)).unwrap();
+        let proof_var = MerkleTreeGadget::<RescueMerkleTree<F>>::create_membership_proof_variable(
+            &mut circuit,
+            &proof,
+        )
+        .unwrap();
+        let root_var = MerkleTreeGadget::<RescueMerkleTree<F>>::create_root_variable(
+            &mut circuit,
+            mt.commitment().digest(),
+        )
+        .unwrap();
+        MerkleTreeGadget::<RescueMerkleTree<F>>::enforce_membership_proof(
+            &mut circuit,
+            elem_idx_var,
+            proof_var,
+            root_var,
+        )
+        .unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        let report = circuit.profile_report();
+        assert_eq!(report.total.num_gates, circuit.num_gates());
+        let membership = report.find("enforce_membership_proof").unwrap();
+        assert_eq!(membership.num_calls, 1);
+        // one hash for the leaf and one per level
+        let sponge = report
+            .find("enforce_membership_proof/rescue_sponge_no_padding")
+            .unwrap();
+        assert_eq!(sponge.num_calls, 3);
+        assert!(sponge.total.num_gates > 0);
+        assert!(membership.total.num_gates > sponge.total.num_gates);
+    }
 }
@@ -5,9 +5,11 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 //! Circuit implementation of various crypto primitives.
+pub mod blake;
 pub mod commitment;
 pub mod elgamal;
 pub mod merkle_tree;
+pub mod multiset;
 pub mod prf;
 pub mod rescue;
 pub mod signature;
@@ -0,0 +1,173 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Multiset equality checks with challenges derived in-circuit.
+//!
+//! The grand product checks of `jf-relation` are only sound if their
+//! challenges are unpredictable once the multisets are fixed. The gadgets
+//! below derive the challenges with the Rescue sponge over both multisets, so
+//! that a prover cannot choose the elements after seeing the challenges.
+
+use crate::rescue::RescueParameter;
+use ark_std::{format, vec::Vec};
+use jf_relation::{
+    errors::{CircuitError, CircuitError::ParameterError},
+    Circuit, PlonkCircuit, Variable,
+};
+
+use super::rescue::RescueNativeGadget;
+
+/// Circuit implementation of multiset equality with in-circuit challenges.
+pub trait MultisetGadget {
+    /// Constrain `a` to be a permutation of `b`, with the grand product
+    /// challenge computed as the Rescue sponge of `a || b`.
+    /// Return error if the lengths differ or if a variable is invalid.
+    fn enforce_multiset_equal_hashed(
+        &mut self,
+        a: &[Variable],
+        b: &[Variable],
+    ) -> Result<(), CircuitError>;
+
+    /// Constrain the tuples of `a` to be a permutation of the tuples of `b`,
+    /// with both the grand product and the folding challenges computed as the
+    /// Rescue sponge of the flattened `a || b`.
+    /// Return error if the lengths differ, if `N` is zero or if a variable is
+    /// invalid.
+    fn enforce_multiset_equal_tuples_hashed<const N: usize>(
+        &mut self,
+        a: &[[Variable; N]],
+        b: &[[Variable; N]],
+    ) -> Result<(), CircuitError>;
+}
+
+impl<F> MultisetGadget for PlonkCircuit<F>
+where
+    F: RescueParameter,
+{
+    fn enforce_multiset_equal_hashed(
+        &mut self,
+        a: &[Variable],
+        b: &[Variable],
+    ) -> Result<(), CircuitError> {
+        check_lengths(a.len(), b.len())?;
+        if a.is_empty() {
+            return Ok(());
+        }
+        let input = [a, b].concat();
+        let challenge = RescueNativeGadget::<F>::rescue_sponge_with_padding(self, &input, 1)?[0];
+        self.enforce_multiset_equal(a, b, challenge)
+    }
+
+    fn enforce_multiset_equal_tuples_hashed<const N: usize>(
+        &mut self,
+        a: &[[Variable; N]],
+        b: &[[Variable; N]],
+    ) -> Result<(), CircuitError> {
+        check_lengths(a.len(), b.len())?;
+        if N == 0 || a.is_empty() {
+            // let the relation gadget reject empty tuples
+            let zero = self.zero();
+            return self.enforce_multiset_equal_tuples(a, b, zero, zero);
+        }
+        let input: Vec<Variable> = a.iter().chain(b.iter()).flatten().copied().collect();
+        let challenges = RescueNativeGadget::<F>::rescue_sponge_with_padding(self, &input, 2)?;
+        self.enforce_multiset_equal_tuples(a, b, challenges[0], challenges[1])
+    }
+}
+
+fn check_lengths(a_len: usize, b_len: usize) -> Result<(), CircuitError> {
+    if a_len != b_len {
+        return Err(ParameterError(format!(
+            "multisets of different sizes: {} != {}",
+            a_len, b_len
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultisetGadget;
+    use crate::rescue::{sponge::RescueCRHF, RescueParameter};
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec::Vec;
+    use jf_relation::{errors::CircuitError, Circuit, PlonkCircuit, Variable};
+
+    #[test]
+    fn test_multiset_equal_hashed() -> Result<(), CircuitError> {
+        test_multiset_equal_hashed_helper::<FqEd254>()?;
+        test_multiset_equal_hashed_helper::<FqEd377>()?;
+        test_multiset_equal_hashed_helper::<FqEd381>()
+    }
+
+    fn test_multiset_equal_hashed_helper<F: RescueParameter>() -> Result<(), CircuitError> {
+        let a_vals: Vec<F> = (0..7u32).map(F::from).collect();
+        let b_vals: Vec<F> = a_vals.iter().rev().copied().collect();
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a = create_vars(&mut circuit, &a_vals)?;
+        let b = create_vars(&mut circuit, &b_vals)?;
+        let num_vars = circuit.num_vars();
+        circuit.enforce_multiset_equal_hashed(&a, &b)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // the sponge output is the challenge of the grand product
+        let expected = RescueCRHF::sponge_with_bit_padding(&[a_vals, b_vals].concat(), 1)[0];
+        assert!((num_vars..circuit.num_vars()).any(|var| circuit.witness(var).unwrap() == expected));
+
+        // not a permutation
+        *circuit.witness_mut(b[0]) = F::from(100u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // tuples
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a: Vec<[Variable; 2]> = (0..5u32)
+            .map(|i| {
+                Ok([
+                    circuit.create_variable(F::from(i))?,
+                    circuit.create_variable(F::from(i * i))?,
+                ])
+            })
+            .collect::<Result<_, CircuitError>>()?;
+        let mut b = a.clone();
+        b.rotate_left(2);
+        circuit.enforce_multiset_equal_tuples_hashed(&a, &b)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // swapping coordinates within a tuple is not a permutation of tuples
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a: Vec<[Variable; 2]> = (1..4u32)
+            .map(|i| {
+                Ok([
+                    circuit.create_variable(F::from(i))?,
+                    circuit.create_variable(F::from(i + 10))?,
+                ])
+            })
+            .collect::<Result<_, CircuitError>>()?;
+        let b: Vec<[Variable; 2]> = a.iter().map(|t| [t[1], t[0]]).collect();
+        circuit.enforce_multiset_equal_tuples_hashed(&a, &b)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // bad inputs
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a = create_vars(&mut circuit, &[F::from(1u32), F::from(2u32)])?;
+        assert!(circuit.enforce_multiset_equal_hashed(&a, &a[..1]).is_err());
+        assert!(circuit
+            .enforce_multiset_equal_tuples_hashed::<0>(&[[]], &[[]])
+            .is_err());
+        circuit.enforce_multiset_equal_hashed(&[], &[])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    fn create_vars<F: RescueParameter>(
+        circuit: &mut PlonkCircuit<F>,
+        vals: &[F],
+    ) -> Result<Vec<Variable>, CircuitError> {
+        vals.iter().map(|&v| circuit.create_variable(v)).collect()
+    }
+}
@@ -145,40 +145,42 @@ where
         data_vars: &[Variable],
         num_output: usize,
     ) -> Result<Vec<Variable>, CircuitError> {
-        if (data_vars.is_empty()) || (data_vars.len() % (STATE_SIZE - 1) != 0) {
-            return Err(ParameterError("empty data vars".to_string()));
-        }
-        let zero_var = self.zero();
-        let rate = STATE_SIZE - 1;
-
-        // ABSORB PHASE
-        let mut state_var =
-            RescueStateVar::from([data_vars[0], data_vars[1], data_vars[2], zero_var]);
-        state_var = RescueNativeGadget::<F>::rescue_permutation(self, state_var)?;
-
-        for block in data_vars[rate..].chunks_exact(rate) {
-            state_var = self.add_state(
-                &state_var,
-                &RescueStateVar::from([block[0], block[1], block[2], zero_var]),
-            )?;
-            state_var = self.rescue_permutation(state_var)?;
-        }
+        self.profile("rescue_sponge_no_padding", |cs| {
+            if (data_vars.is_empty()) || (data_vars.len() % (STATE_SIZE - 1) != 0) {
+                return Err(ParameterError("empty data vars".to_string()));
+            }
+            let zero_var = cs.zero();
+            let rate = STATE_SIZE - 1;
+
+            // ABSORB PHASE
+            let mut state_var =
+                RescueStateVar::from([data_vars[0], data_vars[1], data_vars[2], zero_var]);
+            state_var = RescueNativeGadget::<F>::rescue_permutation(cs, state_var)?;
+
+            for block in data_vars[rate..].chunks_exact(rate) {
+                state_var = cs.add_state(
+                    &state_var,
+                    &RescueStateVar::from([block[0], block[1], block[2], zero_var]),
+                )?;
+                state_var = cs.rescue_permutation(state_var)?;
+            }
 
-        // SQUEEZE PHASE
-        let mut result = vec![];
-        let mut remaining = num_output;
-        // extract current rate before calling PRP again
-        loop {
-            let extract = remaining.min(rate);
-            result.extend_from_slice(&state_var.0[0..extract]);
-            remaining -= extract;
-            if remaining == 0 {
-                break;
+            // SQUEEZE PHASE
+            let mut result = vec![];
+            let mut remaining = num_output;
+            // extract current rate before calling PRP again
+            loop {
+                let extract = remaining.min(rate);
+                result.extend_from_slice(&state_var.0[0..extract]);
+                remaining -= extract;
+                if remaining == 0 {
+                    break;
+                }
+                state_var = cs.rescue_permutation(state_var)?;
             }
-            state_var = self.rescue_permutation(state_var)?;
-        }
 
-        Ok(result)
+            Ok(result)
+        })
     }
 
     fn rescue_sponge_with_padding(
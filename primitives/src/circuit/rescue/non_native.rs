@@ -104,57 +104,59 @@ where
         data_vars: &[FpElemVar<F>],
         num_output: usize,
     ) -> Result<Vec<FpElemVar<F>>, CircuitError> {
-        if (data_vars.is_empty()) || (data_vars.len() % (STATE_SIZE - 1) != 0) {
-            return Err(ParameterError("data_vars".to_string()));
-        }
+        self.profile("rescue_sponge_no_padding", |cs| {
+            if (data_vars.is_empty()) || (data_vars.len() % (STATE_SIZE - 1) != 0) {
+                return Err(ParameterError("data_vars".to_string()));
+            }
 
-        let rate = STATE_SIZE - 1;
+            let rate = STATE_SIZE - 1;
 
-        // parameter m and 2^m
-        let m = data_vars[0].param_m();
-        let two_power_m = Some(data_vars[0].two_power_m());
+            // parameter m and 2^m
+            let m = data_vars[0].param_m();
+            let two_power_m = Some(data_vars[0].two_power_m());
 
-        let zero_var = FpElemVar::<F>::zero(self, m, two_power_m);
+            let zero_var = FpElemVar::<F>::zero(cs, m, two_power_m);
 
-        // TODO(ZZ): hmmm think of a way to pre-compute modulus in FpELem
-        // Doesn't save #constraints though
-        // move the modulus to the right field
-        let t_modulus = F::from_le_bytes_mod_order(T::MODULUS.to_bytes_le().as_ref());
-        let modulus = FpElem::new(&t_modulus, m, two_power_m)?;
+            // TODO(ZZ): hmmm think of a way to pre-compute modulus in FpELem
+            // Doesn't save #constraints though
+            // move the modulus to the right field
+            let t_modulus = F::from_le_bytes_mod_order(T::MODULUS.to_bytes_le().as_ref());
+            let modulus = FpElem::new(&t_modulus, m, two_power_m)?;
 
-        // ABSORB PHASE
-        let mut state_var = RescueNonNativeStateVar {
-            state: [data_vars[0], data_vars[1], data_vars[2], zero_var],
-            modulus,
-        };
-        state_var = RescueNonNativeGadget::<T, F>::rescue_permutation(self, state_var)?;
-
-        for block in data_vars[rate..].chunks_exact(rate) {
-            state_var = PermutationGadget::<RescueNonNativeStateVar<F>, T, F>::add_state(
-                self,
-                &state_var,
-                &RescueNonNativeStateVar {
-                    state: [block[0], block[1], block[2], zero_var],
-                    modulus,
-                },
-            )?;
-            state_var = RescueNonNativeGadget::<T, F>::rescue_permutation(self, state_var)?;
-        }
-        // SQUEEZE PHASE
-        let mut result = vec![];
-        let mut remaining = num_output;
-        // extract current rate before calling PRP again
-        loop {
-            let extract = remaining.min(rate);
-            result.extend_from_slice(&state_var.state[0..extract]);
-            remaining -= extract;
-            if remaining == 0 {
-                break;
+            // ABSORB PHASE
+            let mut state_var = RescueNonNativeStateVar {
+                state: [data_vars[0], data_vars[1], data_vars[2], zero_var],
+                modulus,
+            };
+            state_var = RescueNonNativeGadget::<T, F>::rescue_permutation(cs, state_var)?;
+
+            for block in data_vars[rate..].chunks_exact(rate) {
+                state_var = PermutationGadget::<RescueNonNativeStateVar<F>, T, F>::add_state(
+                    cs,
+                    &state_var,
+                    &RescueNonNativeStateVar {
+                        state: [block[0], block[1], block[2], zero_var],
+                        modulus,
+                    },
+                )?;
+                state_var = RescueNonNativeGadget::<T, F>::rescue_permutation(cs, state_var)?;
+            }
+            // SQUEEZE PHASE
+            let mut result = vec![];
+            let mut remaining = num_output;
+            // extract current rate before calling PRP again
+            loop {
+                let extract = remaining.min(rate);
+                result.extend_from_slice(&state_var.state[0..extract]);
+                remaining -= extract;
+                if remaining == 0 {
+                    break;
+                }
+                state_var = RescueNonNativeGadget::<T, F>::rescue_permutation(cs, state_var)?;
             }
-            state_var = RescueNonNativeGadget::<T, F>::rescue_permutation(self, state_var)?;
-        }
 
-        Ok(result)
+            Ok(result)
+        })
     }
 
     fn rescue_full_state_keyed_sponge_with_zero_padding(
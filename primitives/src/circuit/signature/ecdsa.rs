@@ -0,0 +1,474 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the ECDSA signature scheme, over curves whose
+//! base and scalar fields are both emulated, e.g. secp256k1 and P-256.
+
+use crate::signatures::ecdsa::{Signature, VerKey};
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::string::ToString;
+use jf_relation::{
+    errors::CircuitError,
+    gadgets::{
+        ecc::emulated::{EmulatedSWPointVariable, SWPoint},
+        EmulatedVariable, EmulationConfig,
+    },
+    BoolVar, Circuit, PlonkCircuit,
+};
+use num_bigint::BigUint;
+
+#[derive(Debug, Clone)]
+/// ECDSA verification key variable
+pub struct EcdsaVerKeyVar<E: PrimeField>(pub EmulatedSWPointVariable<E>);
+
+#[derive(Debug, Clone)]
+/// ECDSA signature variable
+pub struct EcdsaSignatureVar<S: PrimeField> {
+    /// r component.
+    pub r: EmulatedVariable<S>,
+    /// s component.
+    pub s: EmulatedVariable<S>,
+    /// Parity of the `y` coordinate of the nonce point.
+    pub v: BoolVar,
+}
+
+/// Plonk circuit gadget for ECDSA signatures.
+///
+/// The message is given as its digest, i.e. a scalar obtained from
+/// [`crate::signatures::ecdsa::EcdsaSignatureScheme::message_digest`].
+pub trait EcdsaSignatureGadget<F, P>
+where
+    F: PrimeField,
+    P: SWCurveConfig,
+    P::BaseField: EmulationConfig<F>,
+    P::ScalarField: EmulationConfig<F>,
+{
+    /// Create a signature variable from a signature `sig`.
+    fn create_ecdsa_signature_variable(
+        &mut self,
+        sig: &Signature<P>,
+    ) -> Result<EcdsaSignatureVar<P::ScalarField>, CircuitError>;
+
+    /// Create a signature verification key variable from a key `vk`.
+    fn create_ecdsa_vk_variable(
+        &mut self,
+        vk: &VerKey<P>,
+    ) -> Result<EcdsaVerKeyVar<P::BaseField>, CircuitError>;
+
+    /// Signature verification circuit
+    /// * `vk` - signature verification key variable.
+    /// * `digest` - digest of the message that has been signed.
+    /// * `sig` - signature variable.
+    ///
+    /// Return error if the base field modulus of the curve is not less than
+    /// twice its order, or if both fields do not have the same limb layout.
+    fn verify_ecdsa_signature(
+        &mut self,
+        vk: &EcdsaVerKeyVar<P::BaseField>,
+        digest: &EmulatedVariable<P::ScalarField>,
+        sig: &EcdsaSignatureVar<P::ScalarField>,
+    ) -> Result<(), CircuitError>;
+
+    /// Public key recovery circuit, which returns the verification key for
+    /// which `sig` is a valid signature of `digest`.
+    ///
+    /// As in the native scheme, the `x` coordinate of the nonce point should
+    /// be `r` itself. Return error if both fields do not have the same limb
+    /// layout.
+    fn recover_ecdsa_vk(
+        &mut self,
+        digest: &EmulatedVariable<P::ScalarField>,
+        sig: &EcdsaSignatureVar<P::ScalarField>,
+    ) -> Result<EcdsaVerKeyVar<P::BaseField>, CircuitError>;
+}
+
+impl<F, P> EcdsaSignatureGadget<F, P> for PlonkCircuit<F>
+where
+    F: PrimeField,
+    P: SWCurveConfig,
+    P::BaseField: EmulationConfig<F>,
+    P::ScalarField: EmulationConfig<F>,
+{
+    fn create_ecdsa_signature_variable(
+        &mut self,
+        sig: &Signature<P>,
+    ) -> Result<EcdsaSignatureVar<P::ScalarField>, CircuitError> {
+        Ok(EcdsaSignatureVar {
+            r: self.create_emulated_variable(sig.r)?,
+            s: self.create_emulated_variable(sig.s)?,
+            v: self.create_boolean_variable(sig.v)?,
+        })
+    }
+
+    fn create_ecdsa_vk_variable(
+        &mut self,
+        vk: &VerKey<P>,
+    ) -> Result<EcdsaVerKeyVar<P::BaseField>, CircuitError> {
+        Ok(EcdsaVerKeyVar(
+            self.create_emulated_sw_point_variable(vk.0.into())?,
+        ))
+    }
+
+    fn verify_ecdsa_signature(
+        &mut self,
+        vk: &EcdsaVerKeyVar<P::BaseField>,
+        digest: &EmulatedVariable<P::ScalarField>,
+        sig: &EcdsaSignatureVar<P::ScalarField>,
+    ) -> Result<(), CircuitError> {
+        let order: BigUint = P::ScalarField::MODULUS.into();
+        if P::BaseField::MODULUS.into() >= order * 2u32 {
+            return Err(CircuitError::ParameterError(
+                "ECDSA gadget requires the base field modulus to be less than twice the curve \
+                 order"
+                    .to_string(),
+            ));
+        }
+        self.enforce_false(vk.0 .2.into())?;
+        self.enforce_emulated_sw_on_curve(&vk.0, P::COEFF_A, P::COEFF_B)?;
+
+        // r and s are nonzero, the latter being enforced by the inversion
+        self.enforce_emulated_canonical(&sig.r)?;
+        let r_is_zero = self.is_emulated_var_zero(&sig.r)?;
+        self.enforce_false(r_is_zero.into())?;
+        let w = self.emulated_inverse(&sig.s)?;
+        let u1 = self.emulated_mul(digest, &w)?;
+        let u2 = self.emulated_mul(&sig.r, &w)?;
+
+        // R = u1 * G + u2 * vk should not be infinity, and its `x` coordinate
+        // reduced modulo the curve order should equal to r
+        let generator_term = self.emulated_sw_fixed_base_scalar_mul(&P::GENERATOR, &u1)?;
+        let vk_term = self.emulated_sw_variable_base_scalar_mul(&vk.0, &u2, P::COEFF_A)?;
+        let point = self.emulated_sw_ecc_add(&generator_term, &vk_term, P::COEFF_A)?;
+        self.enforce_false(point.2.into())?;
+        self.enforce_emulated_canonical(&point.0)?;
+        self.enforce_emulated_mod_reduce(&point.0, &sig.r)?;
+        Ok(())
+    }
+
+    fn recover_ecdsa_vk(
+        &mut self,
+        digest: &EmulatedVariable<P::ScalarField>,
+        sig: &EcdsaSignatureVar<P::ScalarField>,
+    ) -> Result<EcdsaVerKeyVar<P::BaseField>, CircuitError> {
+        // the nonce point R has `x = r` and a `y` coordinate of parity `v`
+        let r_val = self.emulated_witness(&sig.r)?;
+        let v_val = self.witness(sig.v.into())? == F::one();
+        let r_val: BigUint = r_val.into();
+        let point = Affine::<P>::get_ys_from_x_unchecked(P::BaseField::from(r_val.clone()))
+            .map(|(y, neg_y)| {
+                let y = if y.into_bigint().is_odd() == v_val {
+                    y
+                } else {
+                    neg_y
+                };
+                SWPoint(P::BaseField::from(r_val), y, false)
+            })
+            .unwrap_or_default();
+        let x = self.create_emulated_variable(point.0)?;
+        let y = self.create_emulated_variable(point.1)?;
+        let point = EmulatedSWPointVariable(x, y, self.false_var());
+        self.enforce_emulated_canonical(&point.0)?;
+        self.enforce_emulated_canonical(&sig.r)?;
+        let wrap = self.enforce_emulated_mod_reduce(&point.0, &sig.r)?;
+        self.enforce_false(wrap.into())?;
+        self.enforce_emulated_sw_on_curve(&point, P::COEFF_A, P::COEFF_B)?;
+        self.enforce_emulated_canonical(&point.1)?;
+        enforce_parity(self, &point.1, sig.v)?;
+
+        // vk = r^{-1} * (s * R - digest * G), where the inversion also enforces
+        // r to be nonzero
+        let r_inv = self.emulated_inverse(&sig.r)?;
+        let u1 = self.emulated_mul(digest, &r_inv)?;
+        let u2 = self.emulated_mul(&sig.s, &r_inv)?;
+        let neg_generator: Affine<P> = -P::GENERATOR;
+        let generator_term = self.emulated_sw_fixed_base_scalar_mul(&neg_generator, &u1)?;
+        let nonce_term = self.emulated_sw_variable_base_scalar_mul(&point, &u2, P::COEFF_A)?;
+        let vk = self.emulated_sw_ecc_add(&generator_term, &nonce_term, P::COEFF_A)?;
+        self.enforce_false(vk.2.into())?;
+        Ok(EcdsaVerKeyVar(vk))
+    }
+}
+
+// Constrain that the parity of a canonical `y` is `v`, by writing its lowest
+// limb as `2 * h + v` with `h` of `B - 1` bits.
+fn enforce_parity<F, E>(
+    circuit: &mut PlonkCircuit<F>,
+    y: &EmulatedVariable<E>,
+    v: BoolVar,
+) -> Result<(), CircuitError>
+where
+    F: PrimeField,
+    E: EmulationConfig<F>,
+{
+    let lowest_limb = y.native_vars()[0];
+    let lowest_limb_val: BigUint = circuit.witness(lowest_limb)?.into();
+    let h = circuit.create_variable(F::from(lowest_limb_val >> 1))?;
+    circuit.enforce_in_range(h, E::B - 1)?;
+    let zero = circuit.zero();
+    circuit.lc_gate(
+        &[h, v.into(), zero, zero, lowest_limb],
+        &[F::from(2u32), F::one(), F::zero(), F::zero()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signatures::{EcdsaSignatureScheme, SignatureScheme};
+    use ark_bn254::Fr as Fr254;
+    use ark_ec::CurveGroup;
+    use ark_std::UniformRand;
+    use jf_relation::gadgets::SerializableEmulatedStruct;
+
+    #[test]
+    fn test_ecdsa_circuit() -> Result<(), CircuitError> {
+        test_ecdsa_circuit_helper::<Fr254, ark_secp256k1::Config>()?;
+        test_ecdsa_circuit_helper::<Fr254, ark_secp256r1::Config>()
+    }
+
+    fn test_ecdsa_circuit_helper<F, P>() -> Result<(), CircuitError>
+    where
+        F: PrimeField,
+        P: SWCurveConfig,
+        P::BaseField: EmulationConfig<F>,
+        P::ScalarField: EmulationConfig<F>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let msg = b"this is a test message";
+        let (sk, vk) = EcdsaSignatureScheme::<P>::key_gen(&(), &mut rng).unwrap();
+        let (_, vk_bad) = EcdsaSignatureScheme::<P>::key_gen(&(), &mut rng).unwrap();
+        let sig = EcdsaSignatureScheme::<P>::sign(&(), &sk, msg, &mut rng).unwrap();
+        let digest = EcdsaSignatureScheme::<P>::message_digest(msg);
+        let digest_bad = EcdsaSignatureScheme::<P>::message_digest(b"this is a bad message");
+
+        // Test `verify_ecdsa_signature()`
+        // Good path
+        let circuit = build_verify_ecdsa_circuit(&vk, &digest, &sig)?;
+        let public_inputs = SWPoint::from(vk.0).serialize_to_native_elements();
+        assert!(circuit.check_circuit_satisfiability(&public_inputs).is_ok());
+        // Bad verification key
+        let circuit = build_verify_ecdsa_circuit(&vk_bad, &digest, &sig)?;
+        let public_inputs = SWPoint::from(vk_bad.0).serialize_to_native_elements();
+        assert!(circuit
+            .check_circuit_satisfiability(&public_inputs)
+            .is_err());
+        // Bad message
+        let circuit = build_verify_ecdsa_circuit(&vk, &digest_bad, &sig)?;
+        let public_inputs = SWPoint::from(vk.0).serialize_to_native_elements();
+        assert!(circuit
+            .check_circuit_satisfiability(&public_inputs)
+            .is_err());
+
+        // Test `recover_ecdsa_vk()`
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(16);
+        let digest_var = circuit.create_emulated_variable(digest)?;
+        let sig_var = circuit.create_ecdsa_signature_variable(&sig)?;
+        let vk_var = <PlonkCircuit<F> as EcdsaSignatureGadget<F, P>>::recover_ecdsa_vk(
+            &mut circuit,
+            &digest_var,
+            &sig_var,
+        )?;
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&vk_var.0)?,
+            SWPoint::from(vk.0)
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // A wrong parity bit recovers another key, which cannot be claimed to be
+        // the right one.
+        *circuit.witness_mut(sig_var.v.into()) = F::from(!sig.v);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        Ok(())
+    }
+
+    fn build_verify_ecdsa_circuit<F, P>(
+        vk: &VerKey<P>,
+        digest: &P::ScalarField,
+        sig: &Signature<P>,
+    ) -> Result<PlonkCircuit<F>, CircuitError>
+    where
+        F: PrimeField,
+        P: SWCurveConfig,
+        P::BaseField: EmulationConfig<F>,
+        P::ScalarField: EmulationConfig<F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(16);
+        let vk_var = EcdsaVerKeyVar(circuit.create_public_emulated_sw_point_variable(vk.0.into())?);
+        let digest_var = circuit.create_emulated_variable(*digest)?;
+        let sig_var = circuit.create_ecdsa_signature_variable(sig)?;
+        <PlonkCircuit<F> as EcdsaSignatureGadget<F, P>>::verify_ecdsa_signature(
+            &mut circuit,
+            &vk_var,
+            &digest_var,
+            &sig_var,
+        )?;
+        Ok(circuit)
+    }
+
+    // The term of `R = u1 * G + u2 * vk` that a cheating prover claims to be
+    // infinity.
+    #[derive(Clone, Copy)]
+    enum ForgedTerm {
+        Generator,
+        VerKey,
+    }
+
+    #[test]
+    fn test_ecdsa_circuit_forged_infinity() -> Result<(), CircuitError> {
+        for forged in [ForgedTerm::Generator, ForgedTerm::VerKey] {
+            test_ecdsa_circuit_forged_infinity_helper::<Fr254, ark_secp256k1::Config>(forged)?;
+        }
+        Ok(())
+    }
+
+    // If a term of `R` could be claimed to be infinity, then `R` would be the
+    // other term, and anybody could forge a signature for `vk` by choosing a
+    // nonce point that is a multiple of that other term.
+    fn test_ecdsa_circuit_forged_infinity_helper<F, P>(
+        forged: ForgedTerm,
+    ) -> Result<(), CircuitError>
+    where
+        F: PrimeField,
+        P: SWCurveConfig,
+        P::BaseField: EmulationConfig<F>,
+        P::ScalarField: EmulationConfig<F>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let (_, vk) = EcdsaSignatureScheme::<P>::key_gen(&(), &mut rng).unwrap();
+        let k = P::ScalarField::rand(&mut rng);
+        let x_mod_order = |p: &Affine<P>| {
+            P::ScalarField::from_le_bytes_mod_order(&p.x.into_bigint().to_bytes_le())
+        };
+        let (nonce_point, s, digest) = match forged {
+            // R = u1 * G with u1 = digest / s = k
+            ForgedTerm::VerKey => {
+                let s = P::ScalarField::rand(&mut rng);
+                ((P::GENERATOR * k).into_affine(), s, k * s)
+            },
+            // R = u2 * vk with u2 = r / s = k
+            ForgedTerm::Generator => {
+                let nonce_point = (vk.0 * k).into_affine();
+                let s = x_mod_order(&nonce_point) / k;
+                (nonce_point, s, P::ScalarField::rand(&mut rng))
+            },
+        };
+        let sig = Signature::<P> {
+            r: x_mod_order(&nonce_point),
+            s,
+            v: nonce_point.y.into_bigint().is_odd(),
+        };
+        assert!(EcdsaSignatureScheme::<P>::verify_prehashed(&vk, &digest, &sig).is_err());
+
+        // the steps of `verify_ecdsa_signature()`, where the forged term is
+        // computed as `(u - 1) * Q + Q` and the prover claims the sum to be
+        // infinity
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(16);
+        let vk_var = circuit.create_public_emulated_sw_point_variable(vk.0.into())?;
+        let digest_var = circuit.create_emulated_variable(digest)?;
+        let sig_var = circuit.create_ecdsa_signature_variable(&sig)?;
+        let w = circuit.emulated_inverse(&sig_var.s)?;
+        let u1 = circuit.emulated_mul(&digest_var, &w)?;
+        let u2 = circuit.emulated_mul(&sig_var.r, &w)?;
+        let minus_one = -P::ScalarField::from(1u64);
+        let (generator_term, vk_term) = match forged {
+            ForgedTerm::Generator => {
+                let u1_minus_one = circuit.emulated_add_constant(&u1, minus_one)?;
+                let partial =
+                    circuit.emulated_sw_fixed_base_scalar_mul(&P::GENERATOR, &u1_minus_one)?;
+                let generator =
+                    circuit.create_constant_emulated_sw_point_variable(P::GENERATOR.into())?;
+                (
+                    forged_infinity_sum::<F, P>(&mut circuit, &partial, &generator)?,
+                    circuit.emulated_sw_variable_base_scalar_mul(&vk_var, &u2, P::COEFF_A)?,
+                )
+            },
+            ForgedTerm::VerKey => {
+                let u2_minus_one = circuit.emulated_add_constant(&u2, minus_one)?;
+                let partial = circuit.emulated_sw_variable_base_scalar_mul(
+                    &vk_var,
+                    &u2_minus_one,
+                    P::COEFF_A,
+                )?;
+                (
+                    circuit.emulated_sw_fixed_base_scalar_mul(&P::GENERATOR, &u1)?,
+                    forged_infinity_sum::<F, P>(&mut circuit, &partial, &vk_var)?,
+                )
+            },
+        };
+        let point = circuit.emulated_sw_ecc_add(&generator_term, &vk_term, P::COEFF_A)?;
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&point)?,
+            SWPoint::from(nonce_point)
+        );
+        circuit.enforce_false(point.2.into())?;
+        circuit.enforce_emulated_canonical(&point.0)?;
+        circuit.enforce_emulated_mod_reduce(&point.0, &sig_var.r)?;
+        let public_inputs = SWPoint::from(vk.0).serialize_to_native_elements();
+        assert!(circuit
+            .check_circuit_satisfiability(&public_inputs)
+            .is_err());
+        Ok(())
+    }
+
+    // Constrain the sum of `p0` and `p1` to be infinity while keeping the
+    // coordinates of the actual sum.
+    fn forged_infinity_sum<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        p0: &EmulatedSWPointVariable<P::BaseField>,
+        p1: &EmulatedSWPointVariable<P::BaseField>,
+    ) -> Result<EmulatedSWPointVariable<P::BaseField>, CircuitError>
+    where
+        F: PrimeField,
+        P: SWCurveConfig,
+        P::BaseField: EmulationConfig<F>,
+    {
+        let to_affine = |p: SWPoint<P::BaseField>| Affine::<P>::new_unchecked(p.0, p.1);
+        let p0_val = to_affine(circuit.emulated_sw_point_witness(p0)?);
+        let p1_val = to_affine(circuit.emulated_sw_point_witness(p1)?);
+        let sum = (p0_val + p1_val).into_affine();
+        let forged = circuit.create_emulated_sw_point_variable(SWPoint(sum.x, sum.y, true))?;
+        circuit.emulated_sw_ecc_add_gate(p0, p1, &forged, P::COEFF_A)?;
+        Ok(forged)
+    }
+
+    #[test]
+    fn test_ecdsa_circuit_num_gates() -> Result<(), CircuitError> {
+        type P = ark_secp256k1::Config;
+        let mut rng = jf_utils::test_rng();
+        let msg = b"this is a test message";
+        let (sk, vk) = EcdsaSignatureScheme::<P>::key_gen(&(), &mut rng).unwrap();
+        let sig = EcdsaSignatureScheme::<P>::sign(&(), &sk, msg, &mut rng).unwrap();
+        let digest = EcdsaSignatureScheme::<P>::message_digest(msg);
+        let circuit = build_verify_ecdsa_circuit::<Fr254, P>(&vk, &digest, &sig)?;
+
+        // The generator term is a fixed-base scalar multiplication with
+        // precomputed tables, so that the whole verification is cheaper than a
+        // 2-base MSM with the generator as a variable base.
+        let mut msm_circuit = PlonkCircuit::<Fr254>::new_ultra_plonk(16);
+        let generator =
+            msm_circuit.create_constant_emulated_sw_point_variable(P::GENERATOR.into())?;
+        let vk_var = msm_circuit.create_emulated_sw_point_variable(vk.0.into())?;
+        let u1 = msm_circuit.create_emulated_variable(sig.r)?;
+        let u2 = msm_circuit.create_emulated_variable(sig.s)?;
+        msm_circuit.emulated_sw_msm(&[generator, vk_var], &[u1, u2], P::COEFF_A)?;
+        assert!(circuit.num_gates() < msm_circuit.num_gates());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ecdsa_circuit_bad_parameters() -> Result<(), CircuitError> {
+        // the base field of BLS12-381 is much larger than its scalar field
+        type P = ark_bls12_381::g1::Config;
+        let mut rng = jf_utils::test_rng();
+        let msg = b"this is a test message";
+        let (sk, vk) = EcdsaSignatureScheme::<P>::key_gen(&(), &mut rng).unwrap();
+        let sig = EcdsaSignatureScheme::<P>::sign(&(), &sk, msg, &mut rng).unwrap();
+        let digest = EcdsaSignatureScheme::<P>::message_digest(msg);
+        assert!(build_verify_ecdsa_circuit::<Fr254, P>(&vk, &digest, &sig).is_err());
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
 //! Circuit implementation of a signature schemes.
-//! Currently this module only implements Schnorr signature scheme over EC.
+//! Currently this module implements Schnorr signature scheme over twisted
+//! Edwards curves, and ECDSA over emulated short Weierstrass curves.
 
+pub mod ecdsa;
 pub mod schnorr;
@@ -9,6 +9,9 @@
 /// ciphersuite identifier for schnorr signature
 pub const CS_ID_SCHNORR: &str = "SCHNORR_WITH_RESCUE_HASH_v01";
 
+/// ciphersuite identifier for ECDSA signature
+pub const CS_ID_ECDSA: &str = "ECDSA_WITH_SHA-256_v01";
+
 /// ciphersuite identifier for BLS signature over BLS12_381, see:
 /// <https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-ciphersuite-format>
 pub const CS_ID_BLS_MIN_SIG: &str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
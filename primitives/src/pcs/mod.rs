@@ -11,6 +11,7 @@ mod poly;
 pub mod prelude;
 mod structs;
 pub mod transcript;
+pub(crate) mod univariate_ipa;
 pub(crate) mod univariate_kzg;
 
 use ark_ff::{FftField, Field};
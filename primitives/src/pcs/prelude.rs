@@ -13,6 +13,10 @@ pub use crate::pcs::{
         MultilinearKzgBatchProof, MultilinearKzgPCS, MultilinearKzgProof, MLE,
     },
     structs::Commitment,
+    univariate_ipa::{
+        srs::UnivariateIpaParams, UnivariateIpaBatchProof, UnivariateIpaCommitment,
+        UnivariateIpaPCS, UnivariateIpaProof,
+    },
     univariate_kzg::{
         srs::{UnivariateProverParam, UnivariateUniversalParams, UnivariateVerifierParam},
         UnivariateKzgBatchProof, UnivariateKzgPCS, UnivariateKzgProof,
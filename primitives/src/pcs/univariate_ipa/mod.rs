@@ -0,0 +1,731 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Main module for a transparent univariate polynomial commitment scheme based
+//! on the inner product argument (IPA) of
+//! [Bulletproofs](https://eprint.iacr.org/2017/1066.pdf), as used in
+//! [Halo](https://eprint.iacr.org/2019/1021.pdf).
+//!
+//! A polynomial `f(X) = sum_j a_j X^j` of degree less than `n` is committed
+//! as `C = <a, G>` for a vector of generators `G` whose discrete logarithms
+//! are unknown. An evaluation `v = f(z) = <a, b>` with `b = (1, z, z^2, ..)`
+//! is proven by `log(n)` rounds of folding, so proofs contain `2 log(n)`
+//! group elements while verification is linear in `n`.
+//!
+//! `jf-plonk` instantiates Plonk with this scheme as `PlonkIpaSnark`, which
+//! doesn't need a trusted setup. Its opening proofs are not hiding, so the
+//! resulting proofs are not zero-knowledge.
+
+use crate::pcs::{
+    poly::GeneralDensePolynomial, transcript::IOPTranscript, PCSError, PolynomialCommitmentScheme,
+    StructuredReferenceString, UnivariatePCS,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, One, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    borrow::Borrow,
+    end_timer, format,
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    start_timer,
+    string::ToString,
+    vec,
+    vec::Vec,
+};
+use srs::UnivariateIpaParams;
+
+pub(crate) mod srs;
+
+/// Transparent IPA Polynomial Commitment Scheme on univariate polynomial over
+/// the scalar field of the curve `C`.
+pub struct UnivariateIpaPCS<C> {
+    #[doc(hidden)]
+    phantom: PhantomData<C>,
+}
+
+#[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derivative(Default, Hash)]
+/// A commitment is a single group element, i.e. `<a, G>`.
+pub struct UnivariateIpaCommitment<C: CurveGroup>(
+    /// the actual commitment is an affine point.
+    pub C::Affine,
+);
+
+#[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derivative(Hash)]
+/// proof of opening
+pub struct UnivariateIpaProof<C: CurveGroup> {
+    /// Left cross terms, one per folding round
+    pub l_vec: Vec<C::Affine>,
+    /// Right cross terms, one per folding round
+    pub r_vec: Vec<C::Affine>,
+    /// The fully folded coefficient
+    pub a: C::ScalarField,
+}
+
+/// batch proof
+pub type UnivariateIpaBatchProof<C> = Vec<UnivariateIpaProof<C>>;
+
+impl<C: CurveGroup> PolynomialCommitmentScheme for UnivariateIpaPCS<C> {
+    // Config
+    type SRS = UnivariateIpaParams<C>;
+    // Polynomial and its associated types
+    type Polynomial = DensePolynomial<C::ScalarField>;
+    type Point = C::ScalarField;
+    type Evaluation = C::ScalarField;
+    // Polynomial and its associated types
+    type Commitment = UnivariateIpaCommitment<C>;
+    type BatchCommitment = Vec<Self::Commitment>;
+    type Proof = UnivariateIpaProof<C>;
+    type BatchProof = UnivariateIpaBatchProof<C>;
+
+    /// Trim the public parameters to specialize the public parameters.
+    /// Input `max_degree` for univariate.
+    /// `supported_num_vars` must be None or an error is returned.
+    fn trim(
+        srs: impl Borrow<Self::SRS>,
+        supported_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<(UnivariateIpaParams<C>, UnivariateIpaParams<C>), PCSError> {
+        if supported_num_vars.is_some() {
+            return Err(PCSError::InvalidParameters(
+                "univariate should not receive a num_var param".to_string(),
+            ));
+        }
+        srs.borrow().trim(supported_degree)
+    }
+
+    /// Generate a commitment for a polynomial
+    /// Note that the scheme is not hiding
+    fn commit(
+        prover_param: impl Borrow<UnivariateIpaParams<C>>,
+        poly: &Self::Polynomial,
+    ) -> Result<Self::Commitment, PCSError> {
+        let prover_param = prover_param.borrow();
+        check_num_coeffs(prover_param, poly)?;
+        let commitment =
+            C::msm_unchecked(&prover_param.gens[..poly.coeffs.len()], &poly.coeffs).into_affine();
+        Ok(UnivariateIpaCommitment(commitment))
+    }
+
+    /// Generate a commitment for a list of polynomials
+    fn batch_commit(
+        prover_param: impl Borrow<UnivariateIpaParams<C>>,
+        polys: &[Self::Polynomial],
+    ) -> Result<Self::BatchCommitment, PCSError> {
+        let prover_param = prover_param.borrow();
+        let commit_time = start_timer!(|| format!("batch commit {} polynomials", polys.len()));
+        let res = polys
+            .iter()
+            .map(|poly| Self::commit(prover_param, poly))
+            .collect::<Result<Vec<Self::Commitment>, PCSError>>()?;
+
+        end_timer!(commit_time);
+        Ok(res)
+    }
+
+    /// On input a polynomial `p` and a point `point`, outputs a proof for the
+    /// same.
+    fn open(
+        prover_param: impl Borrow<UnivariateIpaParams<C>>,
+        polynomial: &Self::Polynomial,
+        point: &Self::Point,
+    ) -> Result<(Self::Proof, Self::Evaluation), PCSError> {
+        let (proof, evals) = Self::open_internal(prover_param.borrow(), polynomial, &[*point])?;
+        Ok((proof, evals[0]))
+    }
+
+    /// Input a list of polynomials, and a same number of points,
+    /// compute a multi-opening for all the polynomials.
+    // This is a naive approach
+    fn batch_open(
+        prover_param: impl Borrow<UnivariateIpaParams<C>>,
+        _multi_commitment: &Self::BatchCommitment,
+        polynomials: &[Self::Polynomial],
+        points: &[Self::Point],
+    ) -> Result<(Self::BatchProof, Vec<Self::Evaluation>), PCSError> {
+        let open_time = start_timer!(|| format!("batch opening {} polynomials", polynomials.len()));
+        if polynomials.len() != points.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly length {} is different from points length {}",
+                polynomials.len(),
+                points.len()
+            )));
+        }
+        let mut batch_proof = vec![];
+        let mut evals = vec![];
+        for (poly, point) in polynomials.iter().zip(points.iter()) {
+            let (proof, eval) = Self::open(prover_param.borrow(), poly, point)?;
+            batch_proof.push(proof);
+            evals.push(eval);
+        }
+
+        end_timer!(open_time);
+        Ok((batch_proof, evals))
+    }
+
+    /// Verifies that `value` is the evaluation at `x` of the polynomial
+    /// committed inside `comm`.
+    fn verify(
+        verifier_param: &UnivariateIpaParams<C>,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &C::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<bool, PCSError> {
+        Self::verify_internal(verifier_param, commitment, &[*point], &[*value], proof)
+    }
+
+    /// Verifies that `value_i` is the evaluation at `x_i` of the polynomial
+    /// `poly_i` committed inside `comm`.
+    // This is a naive approach
+    fn batch_verify<R: RngCore + CryptoRng>(
+        verifier_param: &UnivariateIpaParams<C>,
+        multi_commitment: &Self::BatchCommitment,
+        points: &[Self::Point],
+        values: &[C::ScalarField],
+        batch_proof: &Self::BatchProof,
+        _rng: &mut R,
+    ) -> Result<bool, PCSError> {
+        let check_time =
+            start_timer!(|| format!("Checking {} evaluation proofs", multi_commitment.len()));
+        if multi_commitment.len() != points.len()
+            || multi_commitment.len() != values.len()
+            || multi_commitment.len() != batch_proof.len()
+        {
+            return Err(PCSError::InvalidParameters(format!(
+                "mismatched lengths: {} commitments, {} points, {} values, {} proofs",
+                multi_commitment.len(),
+                points.len(),
+                values.len(),
+                batch_proof.len()
+            )));
+        }
+        for (((comm, point), value), proof) in multi_commitment
+            .iter()
+            .zip(points)
+            .zip(values)
+            .zip(batch_proof)
+        {
+            if !Self::verify(verifier_param, comm, point, value, proof)? {
+                end_timer!(check_time, || "Result: false");
+                return Ok(false);
+            }
+        }
+        end_timer!(check_time, || "Result: true");
+        Ok(true)
+    }
+}
+
+impl<C: CurveGroup> UnivariatePCS for UnivariateIpaPCS<C> {
+    /// Compute the opening proofs in [`Self::multi_open_rou()`]. There is no
+    /// amortization for IPA, each point is opened separately.
+    fn multi_open_rou_proofs(
+        prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
+        polynomial: &Self::Polynomial,
+        num_points: usize,
+        domain: &Radix2EvaluationDomain<Self::Evaluation>,
+    ) -> Result<Vec<Self::Proof>, PCSError> {
+        if num_points > domain.size() {
+            return Err(PCSError::InvalidParameters(format!(
+                "cannot open at {} points of a domain of size {}",
+                num_points,
+                domain.size()
+            )));
+        }
+        domain
+            .elements()
+            .take(num_points)
+            .map(|point| {
+                Self::open(prover_param.borrow(), polynomial, &point).map(|(proof, _)| proof)
+            })
+            .collect()
+    }
+
+    /// Compute the evaluations in [`Self::multi_open_rou()`].
+    fn multi_open_rou_evals(
+        polynomial: &Self::Polynomial,
+        num_points: usize,
+        domain: &Radix2EvaluationDomain<Self::Evaluation>,
+    ) -> Result<Vec<Self::Evaluation>, PCSError> {
+        let evals = GeneralDensePolynomial::from_coeff_slice(&polynomial.coeffs)
+            .batch_evaluate_rou(domain)?
+            .into_iter()
+            .take(num_points)
+            .collect();
+        Ok(evals)
+    }
+
+    /// Input a polynomial, and multiple evaluation points,
+    /// compute a batch opening proof for the multiple points of the same
+    /// polynomial.
+    ///
+    /// The evaluation vectors of all points are combined with powers of a
+    /// random challenge, so the proof has the same size as a single opening.
+    fn multi_point_open(
+        prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
+        polynomial: &Self::Polynomial,
+        points: &[Self::Point],
+    ) -> Result<(Self::Proof, Vec<Self::Evaluation>), PCSError> {
+        if points.is_empty() {
+            return Err(PCSError::InvalidParameters(
+                "no point to evaluate and open".to_string(),
+            ));
+        }
+        Self::open_internal(prover_param.borrow(), polynomial, points)
+    }
+
+    /// Verifies that `values` are the evaluation at the `points` of the
+    /// polynomial committed inside `comm`.
+    fn multi_point_verify(
+        verifier_param: impl Borrow<<Self::SRS as StructuredReferenceString>::VerifierParam>,
+        commitment: &Self::Commitment,
+        points: &[Self::Point],
+        values: &[Self::Evaluation],
+        proof: &Self::Proof,
+    ) -> Result<bool, PCSError> {
+        if points.is_empty() {
+            return Err(PCSError::InvalidParameters(
+                "no evaluation to check".to_string(),
+            ));
+        }
+        if points.len() != values.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "the number of points {} is different from the number of evaluation values {}",
+                points.len(),
+                values.len(),
+            )));
+        }
+        Self::verify_internal(verifier_param.borrow(), commitment, points, values, proof)
+    }
+}
+
+impl<C: CurveGroup> UnivariateIpaPCS<C> {
+    // Open `polynomial` at all `points` with a single proof.
+    fn open_internal(
+        prover_param: &UnivariateIpaParams<C>,
+        polynomial: &DensePolynomial<C::ScalarField>,
+        points: &[C::ScalarField],
+    ) -> Result<(UnivariateIpaProof<C>, Vec<C::ScalarField>), PCSError> {
+        let open_time = start_timer!(|| format!(
+            "Opening polynomial of degree {} at {} points",
+            polynomial.degree(),
+            points.len()
+        ));
+        check_num_coeffs(prover_param, polynomial)?;
+        let n = prover_param.gens.len();
+
+        let evals: Vec<C::ScalarField> = points
+            .iter()
+            .map(|point| polynomial.evaluate(point))
+            .collect();
+        let commitment = Self::commit(prover_param, polynomial)?;
+        let (mut transcript, r, u) = init_transcript(prover_param, &commitment, points, &evals)?;
+
+        let mut a = polynomial.coeffs.clone();
+        a.resize(n, C::ScalarField::zero());
+        let mut b = eval_vector(points, r, n);
+        let mut gens = prover_param.gens.clone();
+        let mut l_vec = Vec::with_capacity(ark_std::log2(n) as usize);
+        let mut r_vec = Vec::with_capacity(ark_std::log2(n) as usize);
+
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let (g_lo, g_hi) = gens.split_at(half);
+
+            let l = C::msm_unchecked(g_hi, a_lo) + u * inner_product(a_lo, b_hi);
+            let r = C::msm_unchecked(g_lo, a_hi) + u * inner_product(a_hi, b_lo);
+            let lr = C::normalize_batch(&[l, r]);
+            let x = round_challenge(&mut transcript, &lr[0], &lr[1])?;
+            let x_inv = x.inverse().ok_or_else(|| {
+                PCSError::InvalidProver("zero challenge in IPA round".to_string())
+            })?;
+
+            let new_a = a_lo
+                .iter()
+                .zip(a_hi)
+                .map(|(lo, hi)| *lo * x + *hi * x_inv)
+                .collect();
+            let new_b = b_lo
+                .iter()
+                .zip(b_hi)
+                .map(|(lo, hi)| *lo * x_inv + *hi * x)
+                .collect();
+            let new_gens: Vec<C> = g_lo
+                .iter()
+                .zip(g_hi)
+                .map(|(lo, hi)| *lo * x_inv + *hi * x)
+                .collect();
+            a = new_a;
+            b = new_b;
+            gens = C::normalize_batch(&new_gens);
+            l_vec.push(lr[0]);
+            r_vec.push(lr[1]);
+        }
+
+        end_timer!(open_time);
+        Ok((
+            UnivariateIpaProof {
+                l_vec,
+                r_vec,
+                a: a[0],
+            },
+            evals,
+        ))
+    }
+
+    // Verify a (possibly multi-point) opening proof.
+    fn verify_internal(
+        verifier_param: &UnivariateIpaParams<C>,
+        commitment: &UnivariateIpaCommitment<C>,
+        points: &[C::ScalarField],
+        values: &[C::ScalarField],
+        proof: &UnivariateIpaProof<C>,
+    ) -> Result<bool, PCSError> {
+        let check_time = start_timer!(|| "Checking evaluation");
+        let n = verifier_param.gens.len();
+        if !n.is_power_of_two() {
+            return Err(PCSError::InvalidParameters(format!(
+                "number of generators {} is not a power of two",
+                n
+            )));
+        }
+        let num_rounds = ark_std::log2(n) as usize;
+        if proof.l_vec.len() != num_rounds || proof.r_vec.len() != num_rounds {
+            return Err(PCSError::InvalidProof(format!(
+                "expect {} rounds, got {} left and {} right terms",
+                num_rounds,
+                proof.l_vec.len(),
+                proof.r_vec.len()
+            )));
+        }
+
+        let (mut transcript, r, u) = init_transcript(verifier_param, commitment, points, values)?;
+        let mut challenges = Vec::with_capacity(num_rounds);
+        let mut challenges_inv = Vec::with_capacity(num_rounds);
+        for (l, r) in proof.l_vec.iter().zip(proof.r_vec.iter()) {
+            let x = round_challenge(&mut transcript, l, r)?;
+            let x_inv = x
+                .inverse()
+                .ok_or_else(|| PCSError::InvalidProof("zero challenge in IPA round".to_string()))?;
+            challenges.push(x);
+            challenges_inv.push(x_inv);
+        }
+
+        // `s[j]` is the coefficient of `G_j` in the folded generator, the
+        // first round decides the most significant bit of `j`.
+        let mut s = vec![C::ScalarField::one()];
+        for (x, x_inv) in challenges.iter().zip(challenges_inv.iter()) {
+            s = s.iter().flat_map(|s| [*s * x_inv, *s * x]).collect();
+        }
+
+        // The folded evaluation vector in closed form: at round `k` the halves
+        // differ by a factor `z^(n / 2^(k+1))`.
+        let mut b_final = C::ScalarField::zero();
+        let mut r_power = C::ScalarField::one();
+        for point in points {
+            let mut z_powers = Vec::with_capacity(num_rounds);
+            let mut z_power = *point;
+            for _ in 0..num_rounds {
+                z_powers.push(z_power);
+                z_power.square_in_place();
+            }
+            let folded = challenges
+                .iter()
+                .zip(challenges_inv.iter())
+                .zip(z_powers.iter().rev())
+                .fold(C::ScalarField::one(), |acc, ((x, x_inv), z_power)| {
+                    acc * (*x_inv + *x * z_power)
+                });
+            b_final += r_power * folded;
+            r_power *= r;
+        }
+        let value = combine(values, r);
+
+        // a * <s, G> + a * b_final * U - C - v * U - sum(x^2 L + x^-2 R) == 0
+        let mut bases = verifier_param.gens.clone();
+        let mut scalars: Vec<C::ScalarField> = s.iter().map(|s| *s * proof.a).collect();
+        bases.push(u.into_affine());
+        scalars.push(proof.a * b_final - value);
+        bases.push(commitment.0);
+        scalars.push(-C::ScalarField::one());
+        for (((l, r), x), x_inv) in proof
+            .l_vec
+            .iter()
+            .zip(proof.r_vec.iter())
+            .zip(challenges.iter())
+            .zip(challenges_inv.iter())
+        {
+            bases.push(*l);
+            scalars.push(-x.square());
+            bases.push(*r);
+            scalars.push(-x_inv.square());
+        }
+        let res = C::msm_unchecked(&bases, &scalars).is_zero();
+
+        end_timer!(check_time, || format!("Result: {res}"));
+        Ok(res)
+    }
+}
+
+// Make sure that `poly` fits in the generators of `param`.
+fn check_num_coeffs<C: CurveGroup>(
+    param: &UnivariateIpaParams<C>,
+    poly: &DensePolynomial<C::ScalarField>,
+) -> Result<(), PCSError> {
+    if poly.coeffs.len() > param.gens.len() {
+        return Err(PCSError::InvalidParameters(format!(
+            "poly degree {} is larger than allowed {}",
+            poly.degree(),
+            param.max_degree()
+        )));
+    }
+    Ok(())
+}
+
+// Bind the statement to a fresh transcript, and derive the challenge `r` for
+// combining multiple points as well as the evaluation generator `U`.
+#[allow(clippy::type_complexity)]
+fn init_transcript<C: CurveGroup>(
+    param: &UnivariateIpaParams<C>,
+    commitment: &UnivariateIpaCommitment<C>,
+    points: &[C::ScalarField],
+    values: &[C::ScalarField],
+) -> Result<(IOPTranscript<C::ScalarField>, C::ScalarField, C), PCSError> {
+    let mut transcript = IOPTranscript::new(b"jf-ipa-pcs");
+    transcript.append_serializable_element(b"num_gens", &(param.gens.len() as u64))?;
+    transcript.append_serializable_element(b"commitment", &commitment.0)?;
+    transcript.append_serializable_element(b"points", &points.to_vec())?;
+    transcript.append_serializable_element(b"values", &values.to_vec())?;
+    let r = transcript.get_and_append_challenge(b"r")?;
+    let xi = transcript.get_and_append_challenge(b"xi")?;
+    Ok((transcript, r, param.u * xi))
+}
+
+// Absorb the cross terms of one folding round and derive its challenge.
+fn round_challenge<C: AffineRepr>(
+    transcript: &mut IOPTranscript<C::ScalarField>,
+    l: &C,
+    r: &C,
+) -> Result<C::ScalarField, PCSError> {
+    transcript.append_serializable_element(b"L", l)?;
+    transcript.append_serializable_element(b"R", r)?;
+    Ok(transcript.get_and_append_challenge(b"x")?)
+}
+
+// `sum_i r^i * (1, z_i, z_i^2, .., z_i^(n-1))`
+fn eval_vector<F: Field>(points: &[F], r: F, n: usize) -> Vec<F> {
+    let mut res = vec![F::zero(); n];
+    let mut r_power = F::one();
+    for point in points {
+        let mut z_power = r_power;
+        for coeff in res.iter_mut() {
+            *coeff += z_power;
+            z_power *= point;
+        }
+        r_power *= r;
+    }
+    res
+}
+
+// `sum_i r^i * values_i`
+fn combine<F: Field>(values: &[F], r: F) -> F {
+    values
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, value| acc * r + value)
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::DenseUVPolynomial;
+    use ark_std::UniformRand;
+    use jf_utils::test_rng;
+
+    fn end_to_end_test_template<C: CurveGroup>() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        for _ in 0..10 {
+            let mut degree = 0;
+            while degree <= 1 {
+                degree = usize::rand(rng) % 40;
+            }
+            let pp = UnivariateIpaPCS::<C>::gen_srs_for_testing(rng, degree)?;
+            let (ck, vk) = pp.trim(degree)?;
+            let p = DensePolynomial::<C::ScalarField>::rand(degree, rng);
+            let comm = UnivariateIpaPCS::<C>::commit(&ck, &p)?;
+            let point = C::ScalarField::rand(rng);
+            let (proof, value) = UnivariateIpaPCS::<C>::open(&ck, &p, &point)?;
+            assert_eq!(value, p.evaluate(&point));
+            assert!(
+                UnivariateIpaPCS::<C>::verify(&vk, &comm, &point, &value, &proof)?,
+                "proof was incorrect for max_degree = {}, polynomial_degree = {}",
+                degree,
+                p.degree(),
+            );
+
+            // bad paths
+            let wrong_value = value + C::ScalarField::one();
+            assert!(!UnivariateIpaPCS::<C>::verify(
+                &vk,
+                &comm,
+                &point,
+                &wrong_value,
+                &proof
+            )?);
+            let wrong_point = point + C::ScalarField::one();
+            assert!(!UnivariateIpaPCS::<C>::verify(
+                &vk,
+                &comm,
+                &wrong_point,
+                &value,
+                &proof
+            )?);
+            let other_comm = UnivariateIpaPCS::<C>::commit(
+                &ck,
+                &DensePolynomial::<C::ScalarField>::rand(degree, rng),
+            )?;
+            assert!(!UnivariateIpaPCS::<C>::verify(
+                &vk,
+                &other_comm,
+                &point,
+                &value,
+                &proof
+            )?);
+            let mut bad_proof = proof.clone();
+            bad_proof.a += C::ScalarField::one();
+            assert!(!UnivariateIpaPCS::<C>::verify(
+                &vk, &comm, &point, &value, &bad_proof
+            )?);
+            let mut bad_proof = proof.clone();
+            bad_proof.l_vec.pop();
+            assert!(UnivariateIpaPCS::<C>::verify(&vk, &comm, &point, &value, &bad_proof).is_err());
+        }
+
+        // polynomials of smaller degree are supported
+        let pp = UnivariateIpaPCS::<C>::gen_srs_for_testing(rng, 16)?;
+        let (ck, vk) = pp.trim(16)?;
+        let p = DensePolynomial::<C::ScalarField>::rand(3, rng);
+        let comm = UnivariateIpaPCS::<C>::commit(&ck, &p)?;
+        let point = C::ScalarField::rand(rng);
+        let (proof, value) = UnivariateIpaPCS::<C>::open(&ck, &p, &point)?;
+        assert!(UnivariateIpaPCS::<C>::verify(
+            &vk, &comm, &point, &value, &proof
+        )?);
+        // but not larger ones
+        let p = DensePolynomial::<C::ScalarField>::rand(32, rng);
+        assert!(UnivariateIpaPCS::<C>::commit(&ck, &p).is_err());
+        assert!(UnivariateIpaPCS::<C>::open(&ck, &p, &point).is_err());
+        assert!(pp.trim(32).is_err());
+        Ok(())
+    }
+
+    fn batch_check_test_template<C: CurveGroup>() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let degree = 20;
+        let pp = UnivariateIpaPCS::<C>::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = UnivariateIpaPCS::<C>::trim(&pp, degree, None)?;
+        let polys: Vec<_> = (0..5)
+            .map(|_| DensePolynomial::<C::ScalarField>::rand(degree, rng))
+            .collect();
+        let points: Vec<_> = (0..5).map(|_| C::ScalarField::rand(rng)).collect();
+        let comms = UnivariateIpaPCS::<C>::batch_commit(&ck, &polys)?;
+        let (proofs, mut values) = UnivariateIpaPCS::<C>::batch_open(&ck, &comms, &polys, &points)?;
+        assert!(UnivariateIpaPCS::<C>::batch_verify(
+            &vk, &comms, &points, &values, &proofs, rng
+        )?);
+        values[2] += C::ScalarField::one();
+        assert!(!UnivariateIpaPCS::<C>::batch_verify(
+            &vk, &comms, &points, &values, &proofs, rng
+        )?);
+        Ok(())
+    }
+
+    fn multi_point_open_test_template<C: CurveGroup>() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let degree = 20;
+        let pp = UnivariateIpaPCS::<C>::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        for _ in 0..5 {
+            let p = DensePolynomial::<C::ScalarField>::rand(degree, rng);
+            let comm = UnivariateIpaPCS::<C>::commit(&ck, &p)?;
+            let points: Vec<_> = (0..5).map(|_| C::ScalarField::rand(rng)).collect();
+            let (proof, mut values) = UnivariateIpaPCS::<C>::multi_point_open(&ck, &p, &points)?;
+            assert!(UnivariateIpaPCS::<C>::multi_point_verify(
+                &vk, &comm, &points, &values, &proof
+            )?);
+            values[4] += C::ScalarField::one();
+            assert!(!UnivariateIpaPCS::<C>::multi_point_verify(
+                &vk, &comm, &points, &values, &proof
+            )?);
+        }
+
+        let p = DensePolynomial::<C::ScalarField>::rand(degree, rng);
+        let domain = UnivariateIpaPCS::<C>::multi_open_rou_eval_domain(degree, 4)?;
+        let (proofs, evals) = UnivariateIpaPCS::<C>::multi_open_rou(&ck, &p, 4, &domain)?;
+        let comm = UnivariateIpaPCS::<C>::commit(&ck, &p)?;
+        for (i, (proof, eval)) in proofs.iter().zip(evals.iter()).enumerate() {
+            let point = domain.element(i);
+            assert_eq!(*eval, p.evaluate(&point));
+            assert!(UnivariateIpaPCS::<C>::verify(
+                &vk, &comm, &point, eval, proof
+            )?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn end_to_end_test() {
+        end_to_end_test_template::<ark_bls12_381::G1Projective>()
+            .expect("test failed for bls12-381");
+        end_to_end_test_template::<ark_bn254::G1Projective>().expect("test failed for bn254");
+        end_to_end_test_template::<ark_pallas::Projective>().expect("test failed for pallas");
+    }
+
+    #[test]
+    fn batch_check_test() {
+        batch_check_test_template::<ark_bls12_381::G1Projective>()
+            .expect("test failed for bls12-381");
+        batch_check_test_template::<ark_pallas::Projective>().expect("test failed for pallas");
+    }
+
+    #[test]
+    fn multi_point_open_test() {
+        multi_point_open_test_template::<ark_bls12_381::G1Projective>()
+            .expect("test failed for bls12-381");
+        multi_point_open_test_template::<ark_pallas::Projective>().expect("test failed for pallas");
+    }
+
+    #[test]
+    fn setup_is_deterministic() -> Result<(), PCSError> {
+        let pp = UnivariateIpaParams::<ark_pallas::Projective>::setup(10)?;
+        assert_eq!(pp.gens.len(), 16);
+        assert_eq!(pp.max_degree(), 15);
+        assert_eq!(
+            pp,
+            UnivariateIpaParams::<ark_pallas::Projective>::setup(10)?
+        );
+        let (ck, _) = pp.trim(5)?;
+        assert_eq!(ck.gens[..], pp.gens[..8]);
+        assert_eq!(ck.u, pp.u);
+        // generators are distinct and not the identity
+        for (i, g) in pp.gens.iter().enumerate() {
+            assert!(!g.is_zero());
+            assert!(g.is_on_curve() && g.is_in_correct_subgroup_assuming_on_curve());
+            assert!(pp.gens[i + 1..].iter().all(|h| h != g));
+            assert_ne!(*g, pp.u);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implementing transparent public parameters for univariate polynomial IPA
+
+use crate::pcs::{PCSError, StructuredReferenceString};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{format, vec::Vec};
+use sha3::{Digest, Sha3_512};
+
+/// Domain separator for deriving the generators.
+const GENERATORS_DOMAIN_SEPARATOR: &[u8] = b"JF-IPA-PCS-GENERATORS";
+
+/// `UnivariateIpaParams` are the public parameters of the IPA scheme: a vector
+/// of generators whose length is a power of two, and an extra generator `u`
+/// for binding evaluations.
+///
+/// They are derived by hashing to the curve, so no trapdoor is known and no
+/// trusted setup is needed. The same struct is used as the prover and
+/// verifier parameters, since the verifier needs all generators.
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize, Default)]
+pub struct UnivariateIpaParams<C: CurveGroup> {
+    /// Generators for the coefficients.
+    pub gens: Vec<C::Affine>,
+    /// Generator for evaluations.
+    pub u: C::Affine,
+}
+
+impl<C: CurveGroup> UnivariateIpaParams<C> {
+    /// Derive the parameters supporting polynomials of degree up to
+    /// `max_degree`. The generators are derived deterministically, so every
+    /// party can derive the same parameters.
+    pub fn setup(max_degree: usize) -> Result<Self, PCSError> {
+        let num_gens = max_degree
+            .checked_add(1)
+            .and_then(usize::checked_next_power_of_two)
+            .ok_or_else(|| {
+                PCSError::InvalidParameters(format!("degree {} is too large", max_degree))
+            })?;
+        let gens = (0..num_gens)
+            .map(|i| hash_to_curve::<C>(i as u64))
+            .collect();
+        let u = hash_to_curve::<C>(num_gens as u64);
+        Ok(Self { gens, u })
+    }
+
+    /// Returns the maximum supported degree
+    pub fn max_degree(&self) -> usize {
+        self.gens.len().saturating_sub(1)
+    }
+
+    // Keep the first `(supported_degree + 1).next_power_of_two()` generators,
+    // the caller should make sure that there are enough of them.
+    fn trim_to(&self, supported_degree: usize) -> Self {
+        let num_gens = (supported_degree + 1).next_power_of_two();
+        Self {
+            gens: self.gens[..num_gens].to_vec(),
+            u: self.u,
+        }
+    }
+}
+
+impl<C: CurveGroup> StructuredReferenceString for UnivariateIpaParams<C> {
+    type ProverParam = UnivariateIpaParams<C>;
+    type VerifierParam = UnivariateIpaParams<C>;
+
+    /// Extract the prover parameters from the public parameters.
+    fn extract_prover_param(&self, supported_degree: usize) -> Self::ProverParam {
+        self.trim_to(supported_degree)
+    }
+
+    /// Extract the verifier parameters from the public parameters.
+    fn extract_verifier_param(&self, supported_degree: usize) -> Self::VerifierParam {
+        self.trim_to(supported_degree)
+    }
+
+    /// Trim the public parameters to the given `prover_supported_degree`. The
+    /// verifier needs the same generators as the prover, so
+    /// `verifier_supported_degree` is ignored.
+    fn trim_with_verifier_degree(
+        &self,
+        prover_supported_degree: usize,
+        _verifier_supported_degree: usize,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        self.trim(prover_supported_degree)
+    }
+
+    /// Trim the public parameters to the given `supported_degree`, rounded up
+    /// so that the number of generators is a power of two.
+    fn trim(
+        &self,
+        supported_degree: usize,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        let num_gens = supported_degree
+            .checked_add(1)
+            .and_then(usize::checked_next_power_of_two);
+        if num_gens.map_or(true, |num_gens| num_gens > self.gens.len()) {
+            return Err(PCSError::InvalidParameters(format!(
+                "Largest supported degree by the parameters is: {}, but requested: {}",
+                self.max_degree(),
+                supported_degree,
+            )));
+        }
+        let pp = self.trim_to(supported_degree);
+        Ok((pp.clone(), pp))
+    }
+
+    /// The parameters are transparent, `rng` is not used.
+    #[cfg(any(test, feature = "test-srs"))]
+    fn gen_srs_for_testing<R>(_rng: &mut R, max_degree: usize) -> Result<Self, PCSError>
+    where
+        R: ark_std::rand::RngCore + ark_std::rand::CryptoRng,
+    {
+        Self::setup(max_degree)
+    }
+
+    /// The parameters are transparent, `rng` is not used.
+    #[cfg(any(test, feature = "test-srs"))]
+    fn gen_srs_for_testing_with_verifier_degree<
+        R: ark_std::rand::prelude::RngCore + ark_std::rand::prelude::CryptoRng,
+    >(
+        _rng: &mut R,
+        prover_supported_degree: usize,
+        _verifier_supported_degree: usize,
+    ) -> Result<Self, PCSError> {
+        Self::setup(prover_supported_degree)
+    }
+}
+
+// Try-and-increment hashing of `index` to a point of the prime order subgroup.
+fn hash_to_curve<C: CurveGroup>(index: u64) -> C::Affine {
+    let num_bytes = C::Affine::zero().compressed_size();
+    let mut counter = 0u64;
+    loop {
+        let mut bytes = Vec::with_capacity(num_bytes + 64);
+        let mut block = 0u64;
+        while bytes.len() < num_bytes {
+            let mut hasher = Sha3_512::new();
+            hasher.update(GENERATORS_DOMAIN_SEPARATOR);
+            hasher.update(index.to_le_bytes());
+            hasher.update(counter.to_le_bytes());
+            hasher.update(block.to_le_bytes());
+            bytes.extend_from_slice(&hasher.finalize());
+            block += 1;
+        }
+        if let Some(point) = C::Affine::from_random_bytes(&bytes[..num_bytes]) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+        counter += 1;
+    }
+}
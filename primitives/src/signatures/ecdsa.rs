@@ -0,0 +1,381 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! This module implements the ECDSA signature scheme over short Weierstrass
+//! curves with prime order, e.g. secp256k1 and P-256, using SHA-256 as the
+//! message digest.
+//!
+//! Signatures also carry the parity of the `y` coordinate of the nonce point,
+//! so that the verification key can be recovered from a message and its
+//! signature.
+
+use super::SignatureScheme;
+use crate::{
+    constants::CS_ID_ECDSA,
+    errors::PrimitivesError::{self, ParameterError, VerificationError},
+};
+use ark_ec::{
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::*;
+use ark_std::{
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+    UniformRand, Zero,
+};
+use jf_utils::canonical;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// ECDSA signature scheme.
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), PartialEq(bound = ""))]
+pub struct EcdsaSignatureScheme<P> {
+    curve_param: PhantomData<P>,
+}
+
+impl<P> SignatureScheme for EcdsaSignatureScheme<P>
+where
+    P: SWCurveConfig,
+    P::BaseField: PrimeField,
+{
+    const CS_ID: &'static str = CS_ID_ECDSA;
+
+    /// Signing key.
+    type SigningKey = SignKey<P::ScalarField>;
+
+    /// Verification key
+    type VerificationKey = VerKey<P>;
+
+    /// Public Parameter
+    type PublicParameter = ();
+
+    /// Signature
+    type Signature = Signature<P>;
+
+    /// A message is &\[MessageUnit\]
+    type MessageUnit = u8;
+
+    /// generate public parameters from RNG.
+    fn param_gen<R: CryptoRng + RngCore>(
+        _prng: Option<&mut R>,
+    ) -> Result<Self::PublicParameter, PrimitivesError> {
+        Ok(())
+    }
+
+    /// Sample a pair of keys.
+    fn key_gen<R: CryptoRng + RngCore>(
+        _pp: &Self::PublicParameter,
+        prng: &mut R,
+    ) -> Result<(Self::SigningKey, Self::VerificationKey), PrimitivesError> {
+        let sk = loop {
+            let sk = P::ScalarField::rand(prng);
+            if !sk.is_zero() {
+                break sk;
+            }
+        };
+        let vk = VerKey((P::GENERATOR * sk).into_affine());
+        Ok((SignKey(sk), vk))
+    }
+
+    /// Sign a message with the signing key
+    fn sign<R: CryptoRng + RngCore, M: AsRef<[Self::MessageUnit]>>(
+        _pp: &Self::PublicParameter,
+        sk: &Self::SigningKey,
+        msg: M,
+        prng: &mut R,
+    ) -> Result<Self::Signature, PrimitivesError> {
+        Self::sign_prehashed(sk, &Self::message_digest(msg.as_ref()), prng)
+    }
+
+    /// Verify a signature.
+    fn verify<M: AsRef<[Self::MessageUnit]>>(
+        _pp: &Self::PublicParameter,
+        vk: &Self::VerificationKey,
+        msg: M,
+        sig: &Self::Signature,
+    ) -> Result<(), PrimitivesError> {
+        Self::verify_prehashed(vk, &Self::message_digest(msg.as_ref()), sig)
+    }
+}
+
+impl<P> EcdsaSignatureScheme<P>
+where
+    P: SWCurveConfig,
+    P::BaseField: PrimeField,
+{
+    /// Compute the digest of a message as a scalar: the leftmost
+    /// `P::ScalarField::MODULUS_BIT_SIZE` bits of its SHA-256 hash, reduced
+    /// modulo the group order.
+    pub fn message_digest(msg: &[u8]) -> P::ScalarField {
+        let mut digest = BigUint::from_bytes_be(&Sha256::digest(msg));
+        let digest_bits = 8 * <Sha256 as Digest>::output_size();
+        let order_bits = P::ScalarField::MODULUS_BIT_SIZE as usize;
+        if order_bits < digest_bits {
+            digest >>= digest_bits - order_bits;
+        }
+        P::ScalarField::from(digest)
+    }
+
+    /// Sign a message digest obtained from [`Self::message_digest`].
+    ///
+    /// The returned `s` is always in the lower half of the scalar field.
+    pub fn sign_prehashed<R: CryptoRng + RngCore>(
+        sk: &SignKey<P::ScalarField>,
+        digest: &P::ScalarField,
+        prng: &mut R,
+    ) -> Result<Signature<P>, PrimitivesError> {
+        loop {
+            let k = P::ScalarField::rand(prng);
+            if k.is_zero() {
+                continue;
+            }
+            let point = (P::GENERATOR * k).into_affine();
+            let r = P::ScalarField::from(point.x.into_bigint().into());
+            if r.is_zero() {
+                continue;
+            }
+            // k is nonzero
+            let s = k.inverse().unwrap() * (*digest + r * sk.0);
+            if s.is_zero() {
+                continue;
+            }
+            let v = point.y.into_bigint().is_odd();
+            // (r, -s) is the signature of the nonce -k, whose point is -R
+            return Ok(if s.into_bigint() > (-s).into_bigint() {
+                Signature { r, s: -s, v: !v }
+            } else {
+                Signature { r, s, v }
+            });
+        }
+    }
+
+    /// Verify a signature on a message digest obtained from
+    /// [`Self::message_digest`].
+    pub fn verify_prehashed(
+        vk: &VerKey<P>,
+        digest: &P::ScalarField,
+        sig: &Signature<P>,
+    ) -> Result<(), PrimitivesError> {
+        if vk.0.is_zero() || !vk.0.is_on_curve() {
+            return Err(VerificationError("Invalid verification key".to_string()));
+        }
+        if sig.r.is_zero() || sig.s.is_zero() {
+            return Err(VerificationError("Invalid signature".to_string()));
+        }
+        // s is nonzero
+        let w = sig.s.inverse().unwrap();
+        let point = (P::GENERATOR * (*digest * w) + vk.0 * (sig.r * w)).into_affine();
+        if point.is_zero() || P::ScalarField::from(point.x.into_bigint().into()) != sig.r {
+            return Err(VerificationError(
+                "Signature verification error".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Recover the verification key from a message and its signature.
+    ///
+    /// The `x` coordinate of the nonce point is assumed to be `r` itself,
+    /// which fails with negligible probability over curves such as secp256k1
+    /// and P-256, where the base field modulus is close to the group order.
+    pub fn recover_vk<M: AsRef<[u8]>>(
+        msg: M,
+        sig: &Signature<P>,
+    ) -> Result<VerKey<P>, PrimitivesError> {
+        Self::recover_vk_prehashed(&Self::message_digest(msg.as_ref()), sig)
+    }
+
+    /// Recover the verification key from a message digest obtained from
+    /// [`Self::message_digest`] and its signature.
+    pub fn recover_vk_prehashed(
+        digest: &P::ScalarField,
+        sig: &Signature<P>,
+    ) -> Result<VerKey<P>, PrimitivesError> {
+        let r: BigUint = sig.r.into();
+        if sig.r.is_zero() || sig.s.is_zero() || r >= P::BaseField::MODULUS.into() {
+            return Err(ParameterError("Invalid signature".to_string()));
+        }
+        let x = P::BaseField::from(r);
+        let (y, neg_y) = Affine::<P>::get_ys_from_x_unchecked(x)
+            .ok_or_else(|| ParameterError("Invalid signature".to_string()))?;
+        let y = if y.into_bigint().is_odd() == sig.v {
+            y
+        } else {
+            neg_y
+        };
+        let point = Affine::<P>::new_unchecked(x, y);
+        // r is nonzero
+        let r_inv = sig.r.inverse().unwrap();
+        let vk = (point * (sig.s * r_inv) - P::GENERATOR * (*digest * r_inv)).into_affine();
+        if vk.is_zero() {
+            return Err(ParameterError("Invalid signature".to_string()));
+        }
+        Ok(VerKey(vk))
+    }
+}
+
+// =====================================================
+// Signing key
+// =====================================================
+/// Signing key for ECDSA signature.
+#[derive(
+    Clone,
+    Default,
+    Zeroize,
+    Eq,
+    PartialEq,
+    CanonicalSerialize,
+    CanonicalDeserialize,
+    Serialize,
+    Deserialize,
+    Debug,
+)]
+pub struct SignKey<F: PrimeField>(#[serde(with = "canonical")] pub(crate) F);
+
+impl<F: PrimeField> Drop for SignKey<F> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<F: PrimeField> From<F> for SignKey<F> {
+    fn from(sk: F) -> Self {
+        Self(sk)
+    }
+}
+
+// =====================================================
+// Verification key
+// =====================================================
+/// Signature public verification key
+#[derive(CanonicalSerialize, CanonicalDeserialize, Serialize, Deserialize, Derivative)]
+#[derivative(
+    Debug(bound = "P: SWCurveConfig"),
+    Clone(bound = "P: SWCurveConfig"),
+    PartialEq(bound = "P: SWCurveConfig"),
+    Eq(bound = "P: SWCurveConfig"),
+    Hash(bound = "P: SWCurveConfig")
+)]
+pub struct VerKey<P: SWCurveConfig>(#[serde(with = "canonical")] pub(crate) Affine<P>);
+
+impl<P: SWCurveConfig> VerKey<P> {
+    /// Convert the verification key into the affine form.
+    pub fn to_affine(&self) -> Affine<P> {
+        self.0
+    }
+}
+
+impl<P: SWCurveConfig> From<Affine<P>> for VerKey<P> {
+    fn from(point: Affine<P>) -> Self {
+        VerKey(point)
+    }
+}
+
+// =====================================================
+// Signature
+// =====================================================
+/// The signature of ECDSA signature scheme
+#[derive(CanonicalSerialize, CanonicalDeserialize, Serialize, Deserialize, Derivative)]
+#[derivative(
+    Debug(bound = "P: SWCurveConfig"),
+    Clone(bound = "P: SWCurveConfig"),
+    PartialEq(bound = "P: SWCurveConfig"),
+    Eq(bound = "P: SWCurveConfig"),
+    Hash(bound = "P: SWCurveConfig")
+)]
+#[serde(bound = "P: SWCurveConfig")]
+pub struct Signature<P: SWCurveConfig> {
+    /// `x` coordinate of the nonce point, reduced modulo the group order.
+    #[serde(with = "canonical")]
+    pub(crate) r: P::ScalarField,
+    #[serde(with = "canonical")]
+    pub(crate) s: P::ScalarField,
+    /// Parity of the `y` coordinate of the nonce point.
+    pub(crate) v: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signatures::tests::{failed_verification, sign_and_verify};
+    use ark_std::vec;
+
+    #[test]
+    fn test_ecdsa_sign_and_verify() {
+        let message = b"this is a test message";
+        let message_bad = b"this is a wrong message";
+        sign_and_verify::<EcdsaSignatureScheme<ark_secp256k1::Config>>(message.as_ref());
+        sign_and_verify::<EcdsaSignatureScheme<ark_secp256r1::Config>>(message.as_ref());
+        failed_verification::<EcdsaSignatureScheme<ark_secp256k1::Config>>(
+            message.as_ref(),
+            message_bad.as_ref(),
+        );
+        failed_verification::<EcdsaSignatureScheme<ark_secp256r1::Config>>(
+            message.as_ref(),
+            message_bad.as_ref(),
+        );
+    }
+
+    #[test]
+    fn test_ecdsa_recover_vk() {
+        test_ecdsa_recover_vk_helper::<ark_secp256k1::Config>();
+        test_ecdsa_recover_vk_helper::<ark_secp256r1::Config>();
+    }
+
+    fn test_ecdsa_recover_vk_helper<P>()
+    where
+        P: SWCurveConfig,
+        P::BaseField: PrimeField,
+    {
+        let mut rng = jf_utils::test_rng();
+        for i in 0..10u8 {
+            let msg = vec![i; 32];
+            let (sk, vk) = EcdsaSignatureScheme::<P>::key_gen(&(), &mut rng).unwrap();
+            let sig = EcdsaSignatureScheme::<P>::sign(&(), &sk, &msg, &mut rng).unwrap();
+            assert!(sig.s.into_bigint() <= (-sig.s).into_bigint());
+            assert_eq!(
+                EcdsaSignatureScheme::<P>::recover_vk(&msg, &sig).unwrap(),
+                vk
+            );
+
+            // a different message or parity recovers a different key
+            let recovered = EcdsaSignatureScheme::<P>::recover_vk([i + 1; 32], &sig).unwrap();
+            assert_ne!(recovered, vk);
+            let mut bad_sig = sig.clone();
+            bad_sig.v = !bad_sig.v;
+            let recovered = EcdsaSignatureScheme::<P>::recover_vk(&msg, &bad_sig).unwrap();
+            assert_ne!(recovered, vk);
+            assert!(EcdsaSignatureScheme::<P>::verify(&(), &recovered, &msg, &sig).is_ok());
+
+            // the high-s variant of a signature is also valid
+            let mut high_s_sig = sig.clone();
+            high_s_sig.s = -high_s_sig.s;
+            assert!(EcdsaSignatureScheme::<P>::verify(&(), &vk, &msg, &high_s_sig).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_serde() {
+        let mut rng = jf_utils::test_rng();
+        let (sk, vk) =
+            EcdsaSignatureScheme::<ark_secp256k1::Config>::key_gen(&(), &mut rng).unwrap();
+        let sig = EcdsaSignatureScheme::<ark_secp256k1::Config>::sign(&(), &sk, b"msg", &mut rng)
+            .unwrap();
+        let bytes = bincode::serialize(&vk).unwrap();
+        assert_eq!(bincode::deserialize::<VerKey<_>>(&bytes).unwrap(), vk);
+        let bytes = bincode::serialize(&sig).unwrap();
+        assert_eq!(bincode::deserialize::<Signature<_>>(&bytes).unwrap(), sig);
+        let bytes = bincode::serialize(&sk).unwrap();
+        assert_eq!(bincode::deserialize::<SignKey<_>>(&bytes).unwrap(), sk);
+    }
+}
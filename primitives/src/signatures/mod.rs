@@ -5,10 +5,12 @@ use ark_std::rand::{CryptoRng, RngCore};
 
 pub mod bls_over_bls12381;
 pub mod bls_over_bn254;
+pub mod ecdsa;
 pub mod schnorr;
 
 pub use bls_over_bls12381::BLSSignatureScheme;
 use core::fmt::Debug;
+pub use ecdsa::EcdsaSignatureScheme;
 pub use schnorr::SchnorrSignatureScheme;
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
@@ -8,6 +8,7 @@
 use crate::{
     constants::{compute_coset_representatives, GATE_WIDTH, N_MUL_SELECTORS},
     errors::{CircuitError, CircuitError::*},
+    gadgets::ultraplonk::{DeferredTable, Memory},
     gates::*,
 };
 use ark_ff::{FftField, Field, PrimeField};
@@ -15,12 +16,29 @@ use ark_poly::{
     domain::Radix2EvaluationDomain, univariate::DensePolynomial, DenseUVPolynomial,
     EvaluationDomain,
 };
-use ark_std::{boxed::Box, cmp::max, format, string::ToString, vec, vec::Vec};
-use hashbrown::{HashMap, HashSet};
+use ark_std::{
+    boxed::Box,
+    cmp::max,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use hashbrown::HashMap;
 use jf_utils::par_utils::parallelizable_slice_iter;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+mod debug;
+mod optimizer;
+mod profile;
+use debug::NamespaceTable;
+pub use debug::{
+    ConstraintKind, NamespaceId, UnsatisfiedGate, WireValue, NAMESPACE_SEPARATOR, ROOT_NAMESPACE,
+};
+pub use optimizer::OptimizationReport;
+pub use profile::{CircuitProfile, ProfileStats};
+
 /// An index to a gate in circuit.
 pub type GateId = usize;
 /// An index to the type of gate wires.
@@ -69,6 +87,71 @@ pub enum MergeableCircuitType {
     TypeB,
 }
 
+/// The placement of a circuit among several TurboPlonk circuits merged into a
+/// single circuit, see [`PlonkCircuit::finalize_for_merging()`].
+///
+/// The public input gates of all the merged circuits come first, in order, so
+/// that the public input of the merged circuit is the concatenation of their
+/// public inputs. The other gates of each circuit follow in a contiguous
+/// block; the last circuit also owns the padding gates of the merged circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MergeSlot {
+    /// The domain size of the merged circuit.
+    pub domain_size: usize,
+    /// The index of the first public input gate of the circuit.
+    pub pub_input_offset: usize,
+    /// The number of public inputs of the circuit.
+    pub num_inputs: usize,
+    /// The index of the first other gate of the circuit.
+    pub gate_offset: usize,
+    /// The number of other gates of the circuit, including padding gates.
+    pub num_gates: usize,
+}
+
+impl MergeSlot {
+    /// Compute the slots of circuits with `(num_inputs, num_gates)` given in
+    /// merging order, where `num_gates` includes the public input gates. The
+    /// merged circuit is padded to at least `min_domain_size` gates so that it
+    /// can be batched with other merged circuits.
+    pub fn layout(
+        sizes: &[(usize, usize)],
+        min_domain_size: usize,
+    ) -> Result<Vec<Self>, CircuitError> {
+        if sizes.is_empty() {
+            return Err(ParameterError("no circuit to merge".to_string()));
+        }
+        if let Some((num_inputs, num_gates)) = sizes.iter().find(|(i, g)| i > g) {
+            return Err(ParameterError(format!(
+                "{num_inputs} public inputs for {num_gates} gates"
+            )));
+        }
+        let total_inputs: usize = sizes.iter().map(|(num_inputs, _)| num_inputs).sum();
+        let total_gates: usize = sizes.iter().map(|(_, num_gates)| num_gates).sum();
+        let domain_size = max(total_gates, min_domain_size).next_power_of_two();
+        let mut pub_input_offset = 0;
+        let mut gate_offset = total_inputs;
+        let mut slots: Vec<Self> = sizes
+            .iter()
+            .map(|&(num_inputs, num_gates)| {
+                let slot = Self {
+                    domain_size,
+                    pub_input_offset,
+                    num_inputs,
+                    gate_offset,
+                    num_gates: num_gates - num_inputs,
+                };
+                pub_input_offset += num_inputs;
+                gate_offset += slot.num_gates;
+                slot
+            })
+            .collect();
+        if let Some(last) = slots.last_mut() {
+            last.num_gates += domain_size - total_gates;
+        }
+        Ok(slots)
+    }
+}
+
 /// An interface for Plonk constraint systems.
 pub trait Circuit<F: Field> {
     /// The number of constraints.
@@ -393,6 +476,24 @@ where
     /// For each inserted table, the 1st value is the start id of the table,
     /// the 2nd values is the length of the table.
     table_gate_ids: Vec<(GateId, usize)>,
+    /// The lookup tables whose lookups are collected until the circuit is
+    /// finalized, e.g. the ROMs and the nibble tables of bitwise gadgets.
+    deferred_tables: Vec<DeferredTable>,
+    /// The access traces of the RAMs, checked when the circuit is finalized.
+    memories: Vec<Memory>,
+
+    /// The hierarchical namespaces used to label gates and variables.
+    namespaces: NamespaceTable,
+    /// The namespace each gate was created in.
+    gate_namespaces: Vec<NamespaceId>,
+    /// The namespace each variable was created in.
+    var_namespaces: Vec<NamespaceId>,
+    /// The namespace each range check was created in.
+    range_namespaces: Vec<NamespaceId>,
+    /// The names attached to variables for debugging.
+    var_labels: HashMap<Variable, String>,
+    /// Whether gadgets record their constraints in profiling namespaces.
+    profiling: bool,
 }
 
 impl<F: FftField> Default for PlonkCircuit<F> {
@@ -427,6 +528,14 @@ impl<F: FftField> PlonkCircuit<F> {
             plonk_params,
             num_table_elems: 0,
             table_gate_ids: vec![],
+            deferred_tables: vec![],
+            memories: vec![],
+            namespaces: NamespaceTable::default(),
+            gate_namespaces: vec![],
+            var_namespaces: vec![ROOT_NAMESPACE; 2],
+            range_namespaces: vec![],
+            var_labels: HashMap::new(),
+            profiling: false,
         };
         // Constrain variables `0`/`1` to have value 0/1.
         circuit.enforce_constant(0, zero).unwrap(); // safe unwrap
@@ -465,6 +574,7 @@ impl<F: FftField> PlonkCircuit<F> {
         }
 
         self.gates.push(gate);
+        self.gate_namespaces.push(self.namespaces.current());
         Ok(())
     }
 
@@ -476,6 +586,7 @@ impl<F: FftField> PlonkCircuit<F> {
         self.check_finalize_flag(false)?;
         self.check_var_bound(var)?;
         self.wire_variables[RANGE_WIRE_ID].push(var);
+        self.range_namespaces.push(self.namespaces.current());
         Ok(())
     }
 
@@ -511,11 +622,60 @@ impl<F: FftField> PlonkCircuit<F> {
         &mut self.witness[idx]
     }
 
+    /// The `gate_id`-th gate.
+    pub fn gate(&self, gate_id: GateId) -> Result<&dyn Gate<F>, CircuitError> {
+        self.gates
+            .get(gate_id)
+            .map(|gate| gate.as_ref())
+            .ok_or(IndexError)
+    }
+
+    /// The variables of the wires `0..=GATE_WIDTH` of the `gate_id`-th gate.
+    pub fn gate_wire_variables(
+        &self,
+        gate_id: GateId,
+    ) -> Result<[Variable; GATE_WIDTH + 1], CircuitError> {
+        if gate_id >= self.gates.len() {
+            return Err(IndexError);
+        }
+        let mut wires = [0; GATE_WIDTH + 1];
+        for (i, wire) in wires.iter_mut().enumerate() {
+            *wire = self.wire_variables[i][gate_id];
+        }
+        Ok(wires)
+    }
+
+    /// The ids of the public input gates, in the order of the public inputs.
+    pub fn pub_input_gate_ids(&self) -> &[GateId] {
+        &self.pub_input_gate_ids
+    }
+
     /// Get the mutable reference of the inserted table ids.
     pub(crate) fn table_gate_ids_mut(&mut self) -> &mut Vec<(GateId, usize)> {
         &mut self.table_gate_ids
     }
 
+    /// Get the tables whose lookups are deferred to the finalization.
+    pub(crate) fn deferred_tables(&self) -> &[DeferredTable] {
+        &self.deferred_tables
+    }
+
+    /// Get the mutable reference of the tables whose lookups are deferred to
+    /// the finalization.
+    pub(crate) fn deferred_tables_mut(&mut self) -> &mut Vec<DeferredTable> {
+        &mut self.deferred_tables
+    }
+
+    /// Get the access traces of the RAMs.
+    pub(crate) fn memories(&self) -> &[Memory] {
+        &self.memories
+    }
+
+    /// Get the mutable reference of the access traces of the RAMs.
+    pub(crate) fn memories_mut(&mut self) -> &mut Vec<Memory> {
+        &mut self.memories
+    }
+
     /// Get the mutable reference of the number of inserted table elements.
     pub(crate) fn num_table_elems_mut(&mut self) -> &mut usize {
         &mut self.num_table_elems
@@ -582,70 +742,10 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
     }
 
     fn check_circuit_satisfiability(&self, pub_input: &[F]) -> Result<(), CircuitError> {
-        if pub_input.len() != self.num_inputs() {
-            return Err(PubInputLenMismatch(
-                pub_input.len(),
-                self.pub_input_gate_ids.len(),
-            ));
-        }
-        // Check public I/O gates
-        for (i, gate_id) in self.pub_input_gate_ids.iter().enumerate() {
-            let pi = pub_input[i];
-            self.check_gate(*gate_id, &pi)?;
-        }
-        // Check rest of the gates
-        for gate_id in 0..self.num_gates() {
-            if !self.is_io_gate(gate_id) {
-                let pi = F::zero();
-                self.check_gate(gate_id, &pi)?;
-            }
-        }
-        // Check range/lookup gates if the circuit supports lookup
-        if self.plonk_params.plonk_type == PlonkType::UltraPlonk {
-            // range gates
-            for idx in 0..self.wire_variables[RANGE_WIRE_ID].len() {
-                self.check_range_gate(idx)?
-            }
-            // key-value map lookup gates
-            let mut key_val_table = HashSet::new();
-            key_val_table.insert((F::zero(), F::zero(), F::zero(), F::zero()));
-            let q_lookup_vec = self.q_lookup();
-            let q_dom_sep_vec = self.q_dom_sep();
-            let table_key_vec = self.table_key_vec();
-            let table_dom_sep_vec = self.table_dom_sep_vec();
-            // insert table elements
-            for (gate_id, ((&q_lookup, &table_dom_sep), &table_key)) in q_lookup_vec
-                .iter()
-                .zip(table_dom_sep_vec.iter())
-                .zip(table_key_vec.iter())
-                .enumerate()
-            {
-                if q_lookup != F::zero() {
-                    let val0 = self.witness(self.wire_variable(TABLE_VAL_1_WIRE_ID, gate_id))?;
-                    let val1 = self.witness(self.wire_variable(TABLE_VAL_2_WIRE_ID, gate_id))?;
-                    key_val_table.insert((table_dom_sep, table_key, val0, val1));
-                }
-            }
-            // check lookups
-            for (gate_id, (&q_lookup, &q_dom_sep)) in
-                q_lookup_vec.iter().zip(q_dom_sep_vec.iter()).enumerate()
-            {
-                if q_lookup != F::zero() {
-                    let key = self.witness(self.wire_variable(LOOKUP_KEY_WIRE_ID, gate_id))?;
-                    let val0 = self.witness(self.wire_variable(LOOKUP_VAL_1_WIRE_ID, gate_id))?;
-                    let val1 = self.witness(self.wire_variable(LOOKUP_VAL_2_WIRE_ID, gate_id))?;
-                    if !key_val_table.contains(&(q_dom_sep, key, val0, val1)) {
-                        return Err(GateCheckFailure(
-                            gate_id,
-                            format!(
-                                "Lookup gate failed: ({q_dom_sep}, {key}, {val0}, {val1}) not in the table",
-                            ),
-                        ));
-                    }
-                }
-            }
+        match self.unsatisfied_gates(pub_input, 1)?.pop() {
+            Some(report) => Err(GateCheckFailure(report.index, report.to_string())),
+            None => Ok(()),
         }
-        Ok(())
     }
 
     fn create_constant_variable(&mut self, val: F) -> Result<Variable, CircuitError> {
@@ -657,6 +757,7 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
     fn create_variable(&mut self, val: F) -> Result<Variable, CircuitError> {
         self.check_finalize_flag(false)?;
         self.witness.push(val);
+        self.var_namespaces.push(self.namespaces.current());
         self.num_vars += 1;
         // the index is from `0` to `num_vars - 1`
         Ok(self.num_vars - 1)
@@ -797,26 +898,6 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
 
 /// Private helper methods
 impl<F: FftField> PlonkCircuit<F> {
-    /// Check correctness of the idx-th range gate. Return an error if the
-    /// circuit does not support lookup.
-    fn check_range_gate(&self, idx: usize) -> Result<(), CircuitError> {
-        self.check_plonk_type(PlonkType::UltraPlonk)?;
-        if idx >= self.wire_variables[RANGE_WIRE_ID].len() {
-            return Err(IndexError);
-        }
-        let range_size = self.range_size()?;
-        if self.witness[self.wire_variables[RANGE_WIRE_ID][idx]] >= F::from(range_size as u32) {
-            return Err(GateCheckFailure(
-                idx,
-                format!(
-                    "Range gate failed: {} >= {}",
-                    self.witness[self.wire_variables[RANGE_WIRE_ID][idx]], range_size
-                ),
-            ));
-        }
-        Ok(())
-    }
-
     fn is_finalized(&self) -> bool {
         self.eval_domain.size() != 1
     }
@@ -833,6 +914,7 @@ impl<F: FftField> PlonkCircuit<F> {
             if *io_gate_id > gate_id {
                 // Swap gate types
                 self.gates.swap(gate_id, *io_gate_id);
+                self.gate_namespaces.swap(gate_id, *io_gate_id);
                 // Swap wire variables
                 for i in 0..GATE_WIDTH + 1 {
                     self.wire_variables[i].swap(gate_id, *io_gate_id);
@@ -852,6 +934,7 @@ impl<F: FftField> PlonkCircuit<F> {
                     if gate_id < cur_gate_id {
                         // Swap gate types
                         self.gates.swap(gate_id, cur_gate_id);
+                        self.gate_namespaces.swap(gate_id, cur_gate_id);
                         // Swap wire variables
                         for j in 0..GATE_WIDTH + 1 {
                             self.wire_variables[j].swap(gate_id, cur_gate_id);
@@ -876,70 +959,24 @@ impl<F: FftField> PlonkCircuit<F> {
         for _ in self.num_gates()..n {
             self.gates.push(Box::new(PaddingGate));
         }
+        self.gate_namespaces.resize(n, ROOT_NAMESPACE);
         for wire_id in 0..self.num_wire_types() {
             self.wire_variables[wire_id].resize(n, self.zero());
         }
         Ok(())
     }
 
-    /// Check that the `gate_id`-th gate is satisfied by the circuit's witness
-    /// and the public input value `pub_input`. `gate_id` is guaranteed to
-    /// be in the range. The gate equation:
-    /// qo * wo = pub_input + q_c +
-    ///           q_mul0 * w0 * w1 + q_mul1 * w2 * w3 +
-    ///           q_lc0 * w0 + q_lc1 * w1 + q_lc2 * w2 + q_lc3 * w3 +
-    ///           q_hash0 * w0 + q_hash1 * w1 + q_hash2 * w2 + q_hash3 * w3 +
-    ///           q_ecc * w0 * w1 * w2 * w3 * wo
-    fn check_gate(&self, gate_id: Variable, pub_input: &F) -> Result<(), CircuitError> {
-        // Compute wire values
-
-        let w_vals: Vec<F> = (0..GATE_WIDTH + 1)
-            .map(|i| self.witness[self.wire_variables[i][gate_id]])
-            .collect();
-        // Compute selector values.
-        let q_lc: [F; GATE_WIDTH] = self.gates[gate_id].q_lc();
-        let q_mul: [F; N_MUL_SELECTORS] = self.gates[gate_id].q_mul();
-        let q_hash: [F; GATE_WIDTH] = self.gates[gate_id].q_hash();
-        let q_c = self.gates[gate_id].q_c();
-        let q_o = self.gates[gate_id].q_o();
-        let q_ecc = self.gates[gate_id].q_ecc();
-
-        // Compute the gate output
-        let expected_gate_output = *pub_input
-            + q_lc[0] * w_vals[0]
-            + q_lc[1] * w_vals[1]
-            + q_lc[2] * w_vals[2]
-            + q_lc[3] * w_vals[3]
-            + q_mul[0] * w_vals[0] * w_vals[1]
-            + q_mul[1] * w_vals[2] * w_vals[3]
-            + q_ecc * w_vals[0] * w_vals[1] * w_vals[2] * w_vals[3] * w_vals[4]
-            + q_hash[0] * w_vals[0].pow([5])
-            + q_hash[1] * w_vals[1].pow([5])
-            + q_hash[2] * w_vals[2].pow([5])
-            + q_hash[3] * w_vals[3].pow([5])
-            + q_c;
-        let gate_output = q_o * w_vals[4];
-        if expected_gate_output != gate_output {
-            return Err(
-                GateCheckFailure(
-                    gate_id,
-                    format!(
-                        "gate: {:?}, wire values: {:?}, pub_input: {}, expected_gate_output: {}, gate_output: {}",
-                        self.gates[gate_id],
-                        w_vals,
-                        pub_input,
-                        expected_gate_output,
-                        gate_output
-                    )
-                ));
-        }
-        Ok(())
-    }
-
     // Compute the permutation over wires.
     // The circuit is guaranteed to be padded before calling the method.
     #[inline]
     fn compute_wire_permutation(&mut self) {
+        let n = self.eval_domain.size();
+        self.compute_wire_permutation_over(0..n);
+    }
+
+    // Compute the permutation over the wires of the given gates. The wires of
+    // the other gates are mapped to the undefined value `(num_wire_types, 0)`.
+    fn compute_wire_permutation_over(&mut self, gate_ids: impl Iterator<Item = GateId> + Clone) {
         assert!(self.is_finalized());
         let n = self.eval_domain.size();
         let m = self.num_vars();
@@ -957,13 +994,13 @@ impl<F: FftField> PlonkCircuit<F> {
             .take(self.num_wire_types())
             .enumerate()
         {
-            for (gate_id, &var) in variables.iter().enumerate() {
-                variable_wires_map[var].push((gate_wire_id, gate_id));
+            for gate_id in gate_ids.clone() {
+                variable_wires_map[variables[gate_id]].push((gate_wire_id, gate_id));
             }
         }
 
         // Compute the wire permutation
-        self.wire_permutation = vec![(0usize, 0usize); self.num_wire_types * n];
+        self.wire_permutation = vec![(self.num_wire_types, 0usize); self.num_wire_types * n];
         for wires_vec in variable_wires_map.iter_mut() {
             // The list of wires that map to the same variable forms a cycle.
             if !wires_vec.is_empty() {
@@ -1167,6 +1204,8 @@ impl<F: PrimeField> PlonkCircuit<F> {
         if self.is_finalized() {
             return Ok(());
         }
+        self.finalize_memories()?;
+        self.insert_deferred_tables()?;
         let num_slots_needed = match self.support_lookup() {
             false => self.num_gates(),
             true => max(
@@ -1204,6 +1243,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         for _ in 0..n {
             self.gates.push(Box::new(PaddingGate));
         }
+        self.gate_namespaces.resize(2 * n, ROOT_NAMESPACE);
         for wire_id in 0..self.num_wire_types() {
             self.wire_variables[wire_id].resize(2 * n, self.zero());
         }
@@ -1219,6 +1259,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         } else {
             // reverse the gate indices.
             self.gates.reverse();
+            self.gate_namespaces.reverse();
             for wire_id in 0..self.num_wire_types() {
                 self.wire_variables[wire_id].reverse();
             }
@@ -1242,6 +1283,222 @@ impl<F: PrimeField> PlonkCircuit<F> {
         Ok(())
     }
 
+    /// Finalize the setup of a circuit to be merged with arbitrarily many other
+    /// circuits, occupying the slots given by `slot` in the merged circuit.
+    /// Compute the slots with [`MergeSlot::layout()`] and merge the finalized
+    /// circuits with [`Self::merge_all()`].
+    /// The method only supports TurboPlonk circuits.
+    pub fn finalize_for_merging(&mut self, slot: &MergeSlot) -> Result<(), CircuitError> {
+        self.check_finalize_flag(false)?;
+        self.check_plonk_type(PlonkType::TurboPlonk)?;
+        if slot.num_inputs != self.num_inputs() {
+            return Err(ParameterError(format!(
+                "the slot has {} public inputs while the circuit has {}",
+                slot.num_inputs,
+                self.num_inputs()
+            )));
+        }
+        if slot.num_gates + slot.num_inputs < self.num_gates() {
+            return Err(ParameterError(format!(
+                "the slot has {} gates while the circuit has {}",
+                slot.num_gates + slot.num_inputs,
+                self.num_gates()
+            )));
+        }
+        if slot.pub_input_offset + slot.num_inputs > slot.gate_offset
+            || slot.gate_offset + slot.num_gates > slot.domain_size
+        {
+            return Err(ParameterError(
+                "the slot does not fit in the merged domain".to_string(),
+            ));
+        }
+        self.eval_domain = Radix2EvaluationDomain::new(slot.domain_size)
+            .ok_or(CircuitError::DomainCreationError)?;
+        let n = self.eval_domain.size();
+        if n != slot.domain_size {
+            return Err(ParameterError(format!(
+                "the domain size {} is not a power of two",
+                slot.domain_size
+            )));
+        }
+
+        // io gates go to `[pub_input_offset, pub_input_offset + num_inputs)`, the
+        // other gates to `[gate_offset, gate_offset + num_gates)` and the rest of
+        // the slots hold dummy gates/wires.
+        let mut is_io_gate = vec![false; self.num_gates()];
+        for &gate_id in self.pub_input_gate_ids.iter() {
+            is_io_gate[gate_id] = true;
+        }
+        let mut new_ids = vec![0usize; self.num_gates()];
+        let (mut io_gate_id, mut gate_id) = (slot.pub_input_offset, slot.gate_offset);
+        for (old_id, new_id) in new_ids.iter_mut().enumerate() {
+            if is_io_gate[old_id] {
+                *new_id = io_gate_id;
+                io_gate_id += 1;
+            } else {
+                *new_id = gate_id;
+                gate_id += 1;
+            }
+        }
+        let mut gates: Vec<Box<dyn Gate<F>>> = (0..n)
+            .map(|_| Box::new(PaddingGate) as Box<dyn Gate<F>>)
+            .collect();
+        let mut gate_namespaces = vec![ROOT_NAMESPACE; n];
+        for (old_id, gate) in self.gates.drain(..).enumerate() {
+            gates[new_ids[old_id]] = gate;
+            gate_namespaces[new_ids[old_id]] = self.gate_namespaces[old_id];
+        }
+        self.gates = gates;
+        self.gate_namespaces = gate_namespaces;
+        for wire_id in 0..self.num_wire_types() {
+            let mut wire_vars = vec![self.zero(); n];
+            for (old_id, &var) in self.wire_variables[wire_id].iter().enumerate() {
+                wire_vars[new_ids[old_id]] = var;
+            }
+            self.wire_variables[wire_id] = wire_vars;
+        }
+        self.pub_input_gate_ids =
+            (slot.pub_input_offset..slot.pub_input_offset + slot.num_inputs).collect();
+
+        self.compute_wire_permutation_over(
+            (slot.pub_input_offset..slot.pub_input_offset + slot.num_inputs)
+                .chain(slot.gate_offset..slot.gate_offset + slot.num_gates),
+        );
+        self.compute_extended_id_permutation();
+        Ok(())
+    }
+
+    /// Merge circuits finalized by [`Self::finalize_for_merging()`] with the
+    /// slots computed by a single call to [`MergeSlot::layout()`], in the same
+    /// order. The public input of the merged circuit is the concatenation of
+    /// the public inputs of the circuits.
+    /// The method only supports TurboPlonk circuits.
+    pub fn merge_all(circuits: &[&Self]) -> Result<Self, CircuitError> {
+        let first = circuits
+            .first()
+            .ok_or_else(|| ParameterError("no circuit to merge".to_string()))?;
+        let n = first.eval_domain_size()?;
+        let num_wire_types = first.num_wire_types;
+        for circuit in circuits.iter() {
+            circuit.check_finalize_flag(true)?;
+            if circuit.plonk_params.plonk_type != PlonkType::TurboPlonk {
+                return Err(ParameterError(
+                    "do not support merging non-TurboPlonk circuits.".to_string(),
+                ));
+            }
+            if circuit.eval_domain_size()? != n {
+                return Err(ParameterError(format!(
+                    "cannot merge circuits with different domain sizes: {}, {}",
+                    n,
+                    circuit.eval_domain_size()?
+                )));
+            }
+        }
+        let pub_input_gate_ids: Vec<GateId> = circuits
+            .iter()
+            .flat_map(|circuit| circuit.pub_input_gate_ids.iter().copied())
+            .collect();
+        if pub_input_gate_ids
+            .iter()
+            .enumerate()
+            .any(|(i, &gate_id)| i != gate_id)
+        {
+            return Err(ParameterError(
+                "the circuits are not finalized with the merging slots in order".to_string(),
+            ));
+        }
+
+        // the owner of a gate is the circuit whose permutation is defined over
+        // the gate, unowned gates are padded with dummy gates/wires of the first
+        // circuit.
+        let mut owners = vec![0usize; n];
+        for (j, owner) in owners.iter_mut().enumerate() {
+            let mut found = false;
+            for (k, circuit) in circuits.iter().enumerate() {
+                if circuit.wire_permutation[j].0 < num_wire_types {
+                    if found {
+                        return Err(ParameterError(format!(
+                            "gate {j} is owned by more than one circuit"
+                        )));
+                    }
+                    *owner = k;
+                    found = true;
+                }
+            }
+        }
+
+        let mut var_offsets = Vec::with_capacity(circuits.len());
+        let mut num_vars = 0;
+        for circuit in circuits.iter() {
+            var_offsets.push(num_vars);
+            num_vars += circuit.num_vars;
+        }
+        let witness: Vec<F> = circuits
+            .iter()
+            .flat_map(|circuit| circuit.witness.iter().copied())
+            .collect();
+
+        // merge namespaces and labels, the variables of each circuit are shifted
+        // by the number of variables of the circuits before it.
+        let mut namespaces = NamespaceTable::default();
+        let namespace_ids: Vec<Vec<NamespaceId>> = circuits
+            .iter()
+            .map(|circuit| namespaces.import(&circuit.namespaces))
+            .collect();
+        let var_namespaces: Vec<NamespaceId> = circuits
+            .iter()
+            .zip(namespace_ids.iter())
+            .flat_map(|(circuit, ids)| circuit.var_namespaces.iter().map(move |&id| ids[id]))
+            .collect();
+        let mut var_labels = HashMap::new();
+        for (circuit, &offset) in circuits.iter().zip(var_offsets.iter()) {
+            var_labels.extend(
+                circuit
+                    .var_labels
+                    .iter()
+                    .map(|(&var, label)| (var + offset, label.clone())),
+            );
+        }
+
+        // merge gates, wire variables and the wire permutation
+        let mut gates = Vec::with_capacity(n);
+        let mut gate_namespaces = Vec::with_capacity(n);
+        let mut wire_variables = [vec![], vec![], vec![], vec![], vec![], vec![]];
+        let mut wire_permutation = vec![(num_wire_types, 0usize); num_wire_types * n];
+        for (j, &k) in owners.iter().enumerate() {
+            let circuit = circuits[k];
+            gates.push(circuit.gates[j].clone());
+            gate_namespaces.push(namespace_ids[k][circuit.gate_namespaces[j]]);
+            for (i, wire_vars) in wire_variables.iter_mut().enumerate().take(num_wire_types) {
+                wire_vars.push(circuit.wire_variable(i, j) + var_offsets[k]);
+                wire_permutation[i * n + j] = circuit.wire_permutation[i * n + j];
+            }
+        }
+
+        Ok(Self {
+            num_vars,
+            witness,
+            gates,
+            wire_variables,
+            pub_input_gate_ids,
+            wire_permutation,
+            extended_id_permutation: first.extended_id_permutation.clone(),
+            num_wire_types,
+            eval_domain: first.eval_domain,
+            plonk_params: first.plonk_params,
+            num_table_elems: 0,
+            table_gate_ids: vec![],
+            deferred_tables: vec![],
+            memories: vec![],
+            namespaces,
+            gate_namespaces,
+            var_namespaces,
+            range_namespaces: vec![],
+            var_labels,
+            profiling: first.profiling,
+        })
+    }
+
     /// Merge a type A circuit with a type B circuit.
     /// Both circuits should have been finalized before.
     /// The method only supports TurboPlonk circuits.
@@ -1315,6 +1572,38 @@ impl<F: PrimeField> PlonkCircuit<F> {
             }
         }
 
+        // merge namespaces and labels, the variables of the second circuit are
+        // shifted by `self.num_vars`.
+        let mut namespaces = self.namespaces.clone();
+        let other_namespace_ids = namespaces.import(&other.namespaces);
+        let gate_namespaces: Vec<NamespaceId> = self.gate_namespaces[..n]
+            .iter()
+            .copied()
+            .chain(
+                other.gate_namespaces[n..]
+                    .iter()
+                    .map(|&id| other_namespace_ids[id]),
+            )
+            .collect();
+        let var_namespaces: Vec<NamespaceId> = self
+            .var_namespaces
+            .iter()
+            .copied()
+            .chain(
+                other
+                    .var_namespaces
+                    .iter()
+                    .map(|&id| other_namespace_ids[id]),
+            )
+            .collect();
+        let mut var_labels = self.var_labels.clone();
+        var_labels.extend(
+            other
+                .var_labels
+                .iter()
+                .map(|(&var, label)| (var + self.num_vars, label.clone())),
+        );
+
         // merge wire_permutation
         let mut wire_permutation = vec![(0usize, 0usize); self.num_wire_types * 2 * n];
         for i in 0..self.num_wire_types {
@@ -1337,6 +1626,14 @@ impl<F: PrimeField> PlonkCircuit<F> {
             plonk_params: self.plonk_params,
             num_table_elems: 0,
             table_gate_ids: vec![],
+            deferred_tables: vec![],
+            memories: vec![],
+            namespaces,
+            gate_namespaces,
+            var_namespaces,
+            range_namespaces: vec![],
+            var_labels,
+            profiling: self.profiling,
         })
     }
 }
@@ -1688,7 +1985,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
 
 #[cfg(test)]
 pub(crate) mod test {
-    use super::{Arithmetization, Circuit, PlonkCircuit};
+    use super::{Arithmetization, Circuit, MergeSlot, PlonkCircuit};
     use crate::{constants::compute_coset_representatives, errors::CircuitError};
     use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
@@ -2256,6 +2553,112 @@ pub(crate) mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_all() -> Result<(), CircuitError> {
+        test_merge_all_helper::<FqEd254>()?;
+        test_merge_all_helper::<FqEd377>()?;
+        test_merge_all_helper::<FqEd381>()?;
+        test_merge_all_helper::<Fq377>()
+    }
+
+    // A circuit with `num_inputs` public inputs whose product is enforced by a
+    // chain of `num_inputs + extra` multiplications.
+    fn create_merge_instance<F: PrimeField>(
+        num_inputs: usize,
+        extra: usize,
+        seed: u32,
+    ) -> Result<(PlonkCircuit<F>, Vec<F>), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let pub_inputs: Vec<F> = (0..num_inputs as u32)
+            .map(|i| F::from(seed + i + 2))
+            .collect();
+        let mut acc = circuit.create_variable(F::from(seed))?;
+        for &x in pub_inputs.iter() {
+            let x = circuit.create_public_variable(x)?;
+            acc = circuit.mul(acc, x)?;
+        }
+        for _ in 0..extra {
+            acc = circuit.add(acc, circuit.one())?;
+        }
+        Ok((circuit, pub_inputs))
+    }
+
+    fn test_merge_all_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut instances = [
+            create_merge_instance::<F>(2, 3, 5)?,
+            create_merge_instance::<F>(0, 20, 7)?,
+            create_merge_instance::<F>(1, 0, 11)?,
+            create_merge_instance::<F>(3, 9, 13)?,
+        ];
+        let sizes: Vec<(usize, usize)> = instances
+            .iter()
+            .map(|(circuit, _)| (circuit.num_inputs(), circuit.num_gates()))
+            .collect();
+        let slots = MergeSlot::layout(&sizes, 0)?;
+        let n = slots[0].domain_size;
+        assert_eq!(n, 64);
+        assert_eq!(MergeSlot::layout(&sizes, 100)?[0].domain_size, 128);
+        for ((circuit, pub_inputs), slot) in instances.iter_mut().zip(slots.iter()) {
+            circuit.finalize_for_merging(slot)?;
+            assert_eq!(circuit.eval_domain_size()?, n);
+            assert!(circuit.check_circuit_satisfiability(pub_inputs).is_ok());
+        }
+        // a finalized circuit can't be refinalized for merging
+        assert!(instances[0].0.finalize_for_merging(&slots[0]).is_err());
+
+        let circuits: Vec<&PlonkCircuit<F>> =
+            instances.iter().map(|(circuit, _)| circuit).collect();
+        let merged = PlonkCircuit::merge_all(&circuits)?;
+        let pub_inputs: Vec<F> = instances
+            .iter()
+            .flat_map(|(_, pub_inputs)| pub_inputs.iter().copied())
+            .collect();
+        assert_eq!(merged.num_inputs(), 6);
+        assert_eq!(merged.eval_domain_size()?, n);
+        assert!(merged.check_circuit_satisfiability(&pub_inputs).is_ok());
+        let mut bad_pub_inputs = pub_inputs.clone();
+        bad_pub_inputs[3] = F::one();
+        assert!(merged
+            .check_circuit_satisfiability(&bad_pub_inputs)
+            .is_err());
+
+        // the selectors and the extended permutation of the merged circuit are the
+        // sums of those of the circuits, which is what merging the keys relies on.
+        let mut selectors = vec![vec![F::zero(); n]; merged.all_selectors().len()];
+        let mut extended_perm = vec![F::zero(); merged.num_wire_types() * n];
+        for circuit in circuits.iter() {
+            for (sum, evals) in selectors.iter_mut().zip(circuit.all_selectors().iter()) {
+                sum.iter_mut().zip(evals.iter()).for_each(|(a, b)| *a += b);
+            }
+            extended_perm
+                .iter_mut()
+                .zip(circuit.compute_extended_permutation()?.iter())
+                .for_each(|(a, b)| *a += b);
+        }
+        assert_eq!(selectors, merged.all_selectors());
+        assert_eq!(extended_perm, merged.compute_extended_permutation()?);
+
+        // the grand product of the merged circuit wraps around to one.
+        let rng = &mut test_rng();
+        let beta = F::rand(rng);
+        let gamma = F::rand(rng);
+        let mut prod = F::one();
+        for j in 0..n {
+            for i in 0..merged.num_wire_types() {
+                let wire_value = merged.witness[merged.wire_variable(i, j)];
+                prod *= wire_value + beta * merged.extended_id_permutation[i * n + j] + gamma;
+                prod /= wire_value + beta * extended_perm[i * n + j] + gamma;
+            }
+        }
+        assert_eq!(prod, F::one());
+        test_arithmetization_for_circuit(merged, pub_inputs)?;
+
+        // circuits merged out of order are rejected.
+        assert!(PlonkCircuit::merge_all(&[circuits[3], circuits[0]]).is_err());
+        assert!(MergeSlot::layout(&[], 0).is_err());
+        Ok(())
+    }
+
     // Check that the polynomial `poly` is consistent with the evaluations `evals`
     // over the domain.
     fn check_polynomial<F: PrimeField>(poly: &DensePolynomial<F>, evals: &[F]) {
@@ -2379,7 +2782,7 @@ pub(crate) mod test {
         // Check public input polynomial
         let pi_poly = circuit.compute_pub_input_polynomial()?;
         let mut pi_evals = pub_inputs;
-        pi_evals.extend(vec![F::zero(); n - 2]);
+        pi_evals.resize(n, F::zero());
         check_polynomial(&pi_poly, &pi_evals);
 
         // Check extended permutation polynomials
@@ -0,0 +1,758 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Debugging utilities for Plonk circuits: hierarchical namespaces for gates
+//! and variables, and detailed reports on unsatisfied constraints.
+
+use super::{
+    GateId, PlonkCircuit, PlonkType, Variable, LOOKUP_KEY_WIRE_ID, LOOKUP_VAL_1_WIRE_ID,
+    LOOKUP_VAL_2_WIRE_ID, RANGE_WIRE_ID, TABLE_VAL_1_WIRE_ID, TABLE_VAL_2_WIRE_ID,
+};
+use crate::{
+    constants::GATE_WIDTH,
+    errors::{CircuitError, CircuitError::*},
+    gadgets::ultraplonk::DeferredTable,
+    Circuit,
+};
+use ark_ff::{FftField, Field};
+use ark_std::{
+    fmt, format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use hashbrown::{HashMap, HashSet};
+
+/// An index to a namespace of a circuit.
+pub type NamespaceId = usize;
+
+/// The id of the root namespace, which every circuit starts in.
+pub const ROOT_NAMESPACE: NamespaceId = 0;
+
+/// The separator between the levels of a namespace path.
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// An interned table of hierarchical namespaces.
+///
+/// Each namespace is identified by its full path (e.g. `merkle/level3`), the
+/// root namespace has the empty path. Interning a namespace also interns all
+/// its ancestors.
+#[derive(Debug, Clone)]
+pub(crate) struct NamespaceTable {
+    /// Full paths of the namespaces, indexed by `NamespaceId`.
+    paths: Vec<String>,
+    /// Reverse map from full paths to namespace ids.
+    ids: HashMap<String, NamespaceId>,
+    /// Number of times each namespace has been entered.
+    num_entries: Vec<usize>,
+    /// The stack of currently entered namespaces.
+    stack: Vec<NamespaceId>,
+}
+
+impl Default for NamespaceTable {
+    fn default() -> Self {
+        let mut ids = HashMap::new();
+        ids.insert(String::new(), ROOT_NAMESPACE);
+        Self {
+            paths: vec![String::new()],
+            ids,
+            num_entries: vec![0],
+            stack: vec![],
+        }
+    }
+}
+
+impl NamespaceTable {
+    /// The namespace new gates and variables are currently created in.
+    pub(crate) fn current(&self) -> NamespaceId {
+        self.stack.last().copied().unwrap_or(ROOT_NAMESPACE)
+    }
+
+    /// The full path of namespace `id`.
+    pub(crate) fn path(&self, id: NamespaceId) -> &str {
+        &self.paths[id]
+    }
+
+    /// The number of namespaces, including the root namespace.
+    pub(crate) fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// The parent of namespace `id`, `None` for the root namespace.
+    pub(crate) fn parent(&self, id: NamespaceId) -> Option<NamespaceId> {
+        if id == ROOT_NAMESPACE {
+            return None;
+        }
+        let path = self.path(id);
+        Some(match path.rfind(NAMESPACE_SEPARATOR) {
+            Some(pos) => self.ids[&path[..pos]],
+            None => ROOT_NAMESPACE,
+        })
+    }
+
+    /// The number of times namespace `id` has been entered.
+    pub(crate) fn num_entries(&self, id: NamespaceId) -> usize {
+        self.num_entries[id]
+    }
+
+    /// Return the id of the namespace with full path `path`, creating it and
+    /// all its ancestors if needed.
+    pub(crate) fn intern(&mut self, path: &str) -> NamespaceId {
+        if let Some(&id) = self.ids.get(path) {
+            return id;
+        }
+        if let Some(pos) = path.rfind(NAMESPACE_SEPARATOR) {
+            self.intern(&path[..pos]);
+        }
+        let id = self.paths.len();
+        self.paths.push(path.to_string());
+        self.ids.insert(path.to_string(), id);
+        self.num_entries.push(0);
+        id
+    }
+
+    /// Intern all namespaces of `other` into `self`, adding up their entry
+    /// counts. Return the map from namespace ids of `other` to namespace ids
+    /// of `self`.
+    pub(crate) fn import(&mut self, other: &Self) -> Vec<NamespaceId> {
+        other
+            .paths
+            .iter()
+            .zip(other.num_entries.iter())
+            .map(|(path, &entries)| {
+                let id = self.intern(path);
+                self.num_entries[id] += entries;
+                id
+            })
+            .collect()
+    }
+
+    /// Enter the child namespace `name` of the current namespace.
+    fn push(&mut self, name: &str) -> Result<(), CircuitError> {
+        if name
+            .split(NAMESPACE_SEPARATOR)
+            .any(|component| component.is_empty())
+        {
+            return Err(ParameterError(format!("invalid namespace name: `{name}`")));
+        }
+        let current = self.path(self.current());
+        let path = if current.is_empty() {
+            name.to_string()
+        } else {
+            format!("{current}{NAMESPACE_SEPARATOR}{name}")
+        };
+        let id = self.intern(&path);
+        self.num_entries[id] += 1;
+        self.stack.push(id);
+        Ok(())
+    }
+
+    /// Leave the current namespace.
+    fn pop(&mut self) -> Result<(), CircuitError> {
+        self.stack
+            .pop()
+            .map(|_| ())
+            .ok_or_else(|| ParameterError("cannot leave the root namespace".to_string()))
+    }
+}
+
+/// The kind of constraint reported in an [`UnsatisfiedGate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstraintKind {
+    /// An algebraic gate (including public I/O gates).
+    Gate,
+    /// An UltraPlonk range check.
+    Range,
+    /// An UltraPlonk variable-table lookup.
+    Lookup,
+    /// A read of an UltraPlonk RAM not finalized yet.
+    Memory,
+}
+
+/// The variable and witness value assigned to a wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireValue<F: Field> {
+    /// The variable connected to the wire.
+    pub var: Variable,
+    /// The label of the variable, see [`PlonkCircuit::variable_label()`].
+    pub label: String,
+    /// The witness value of the variable.
+    pub value: F,
+}
+
+/// A detailed report of a constraint that is not satisfied by the witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedGate<F: Field> {
+    /// The kind of the failing constraint.
+    pub kind: ConstraintKind,
+    /// The gate index for algebraic and lookup gates, the index of the range
+    /// check for range gates.
+    pub index: usize,
+    /// The name of the failing gate.
+    pub gate_name: &'static str,
+    /// The full namespace path the constraint was created in.
+    pub namespace: String,
+    /// The values of the wires involved in the constraint.
+    pub wires: Vec<WireValue<F>>,
+    /// The non-zero selector values of the gate.
+    pub selectors: Vec<(&'static str, F)>,
+    /// Why the constraint is not satisfied.
+    pub reason: String,
+}
+
+impl<F: Field> fmt::Display for UnsatisfiedGate<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let namespace = if self.namespace.is_empty() {
+            "<root>"
+        } else {
+            self.namespace.as_str()
+        };
+        write!(
+            f,
+            "{} #{} ({:?}) in namespace `{}`: {}",
+            self.gate_name, self.index, self.kind, namespace, self.reason
+        )?;
+        for (i, wire) in self.wires.iter().enumerate() {
+            write!(
+                f,
+                "\n  w{} = {} (var {}, `{}`)",
+                i, wire.value, wire.var, wire.label
+            )?;
+        }
+        for (name, value) in self.selectors.iter() {
+            write!(f, "\n  {name} = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FftField> PlonkCircuit<F> {
+    /// Enter the child namespace `name` of the current namespace. Gates and
+    /// variables created afterwards are labeled with the new namespace until
+    /// the matching [`Self::pop_namespace()`].
+    ///
+    /// `name` may contain several levels separated by `/`, e.g.
+    /// `merkle/level3`. Return an error if any level is empty.
+    pub fn push_namespace(&mut self, name: &str) -> Result<(), CircuitError> {
+        self.namespaces.push(name)
+    }
+
+    /// Leave the namespace entered by the last [`Self::push_namespace()`].
+    /// Return an error if the circuit is in the root namespace.
+    pub fn pop_namespace(&mut self) -> Result<(), CircuitError> {
+        self.namespaces.pop()
+    }
+
+    /// Run `f` inside the child namespace `name` of the current namespace.
+    /// The namespace is left afterwards, even if `f` returns an error.
+    pub fn namespace<T, G>(&mut self, name: &str, f: G) -> Result<T, CircuitError>
+    where
+        G: FnOnce(&mut Self) -> Result<T, CircuitError>,
+    {
+        self.push_namespace(name)?;
+        let res = f(self);
+        self.pop_namespace()?;
+        res
+    }
+
+    /// The full path of the current namespace, empty for the root namespace.
+    pub fn current_namespace(&self) -> &str {
+        self.namespaces.path(self.namespaces.current())
+    }
+
+    /// The full path of the namespace the `gate_id`-th gate was created in.
+    pub fn gate_namespace(&self, gate_id: GateId) -> Result<&str, CircuitError> {
+        let id = self.gate_namespaces.get(gate_id).ok_or(IndexError)?;
+        Ok(self.namespaces.path(*id))
+    }
+
+    /// Attach a human-readable name to a variable. The name is prefixed with
+    /// the current namespace.
+    pub fn label_variable(&mut self, var: Variable, name: &str) -> Result<(), CircuitError> {
+        self.check_var_bound(var)?;
+        let namespace = self.current_namespace();
+        let label = if namespace.is_empty() {
+            name.to_string()
+        } else {
+            format!("{namespace}{NAMESPACE_SEPARATOR}{name}")
+        };
+        self.var_labels.insert(var, label);
+        Ok(())
+    }
+
+    /// The label of a variable: the name given with
+    /// [`Self::label_variable()`] if any, otherwise the namespace it was
+    /// created in followed by its index.
+    pub fn variable_label(&self, var: Variable) -> Result<String, CircuitError> {
+        self.check_var_bound(var)?;
+        if let Some(label) = self.var_labels.get(&var) {
+            return Ok(label.clone());
+        }
+        let namespace = self.namespaces.path(self.var_namespaces[var]);
+        Ok(if namespace.is_empty() {
+            format!("v{var}")
+        } else {
+            format!("{namespace}{NAMESPACE_SEPARATOR}v{var}")
+        })
+    }
+
+    /// Check the circuit against a public input and return reports of at
+    /// most `limit` unsatisfied constraints, in the same order
+    /// [`Circuit::check_circuit_satisfiability()`] checks them. Return an
+    /// empty vector if the circuit is satisfied.
+    pub fn unsatisfied_gates(
+        &self,
+        pub_input: &[F],
+        limit: usize,
+    ) -> Result<Vec<UnsatisfiedGate<F>>, CircuitError> {
+        if pub_input.len() != self.num_inputs() {
+            return Err(PubInputLenMismatch(
+                pub_input.len(),
+                self.pub_input_gate_ids.len(),
+            ));
+        }
+        let mut reports = vec![];
+        macro_rules! report {
+            ($report:expr) => {
+                if reports.len() >= limit {
+                    return Ok(reports);
+                }
+                if let Some(report) = $report {
+                    reports.push(report);
+                }
+            };
+        }
+        // Check public I/O gates
+        for (i, gate_id) in self.pub_input_gate_ids.iter().enumerate() {
+            report!(self.gate_report(*gate_id, &pub_input[i])?);
+        }
+        // Check rest of the gates
+        for gate_id in 0..self.num_gates() {
+            if !self.is_io_gate(gate_id) {
+                report!(self.gate_report(gate_id, &F::zero())?);
+            }
+        }
+        // Check range/lookup gates if the circuit supports lookup
+        if self.plonk_params.plonk_type == PlonkType::UltraPlonk {
+            // range gates
+            for idx in 0..self.wire_variables[RANGE_WIRE_ID].len() {
+                report!(self.range_gate_report(idx)?);
+            }
+            // key-value map lookup gates
+            let mut key_val_table = HashSet::new();
+            key_val_table.insert((F::zero(), F::zero(), F::zero(), F::zero()));
+            let q_lookup_vec = self.q_lookup();
+            let q_dom_sep_vec = self.q_dom_sep();
+            let table_key_vec = self.table_key_vec();
+            let table_dom_sep_vec = self.table_dom_sep_vec();
+            // insert table elements
+            for (gate_id, ((&q_lookup, &table_dom_sep), &table_key)) in q_lookup_vec
+                .iter()
+                .zip(table_dom_sep_vec.iter())
+                .zip(table_key_vec.iter())
+                .enumerate()
+            {
+                if q_lookup != F::zero() {
+                    let val0 = self.witness(self.wire_variable(TABLE_VAL_1_WIRE_ID, gate_id))?;
+                    let val1 = self.witness(self.wire_variable(TABLE_VAL_2_WIRE_ID, gate_id))?;
+                    key_val_table.insert((table_dom_sep, table_key, val0, val1));
+                }
+            }
+            // check lookups
+            for (gate_id, (&q_lookup, &q_dom_sep)) in
+                q_lookup_vec.iter().zip(q_dom_sep_vec.iter()).enumerate()
+            {
+                if q_lookup != F::zero() {
+                    let key = self.witness(self.wire_variable(LOOKUP_KEY_WIRE_ID, gate_id))?;
+                    let val0 = self.witness(self.wire_variable(LOOKUP_VAL_1_WIRE_ID, gate_id))?;
+                    let val1 = self.witness(self.wire_variable(LOOKUP_VAL_2_WIRE_ID, gate_id))?;
+                    if !key_val_table.contains(&(q_dom_sep, key, val0, val1)) {
+                        report!(Some(UnsatisfiedGate {
+                            kind: ConstraintKind::Lookup,
+                            index: gate_id,
+                            gate_name: self.gates[gate_id].name(),
+                            namespace: self.gate_namespace(gate_id)?.to_string(),
+                            wires: self.wire_values(
+                                &[
+                                    LOOKUP_KEY_WIRE_ID,
+                                    LOOKUP_VAL_1_WIRE_ID,
+                                    LOOKUP_VAL_2_WIRE_ID
+                                ],
+                                gate_id
+                            )?,
+                            selectors: vec![("q_lookup", q_lookup), ("q_dom_sep", q_dom_sep)],
+                            reason: format!(
+                                "lookup ({q_dom_sep}, {key}, {val0}, {val1}) not in the table"
+                            ),
+                        }));
+                    }
+                }
+            }
+            // deferred lookups not yet inserted
+            for table in self.deferred_tables.iter() {
+                let entries: HashSet<_> = table
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(val0, val1))| {
+                        (F::from(i as u64), self.witness[val0], self.witness[val1])
+                    })
+                    .collect();
+                for idx in 0..table.lookups.len() {
+                    report!(self.deferred_lookup_report(table, &entries, idx)?);
+                }
+            }
+            // RAMs not yet finalized
+            for memory in self.memories.iter() {
+                let two_inv = F::from(2u32).inverse().ok_or(FieldAlgebraError(
+                    "the field has characteristic two".to_string(),
+                ))?;
+                let entries = &self.deferred_tables[memory.table].entries;
+                let mut contents = HashMap::new();
+                for (time, (&(key, val), &is_write)) in
+                    entries.iter().zip(memory.writes.iter()).enumerate()
+                {
+                    let addr = (self.witness[key] - F::from(is_write as u32)) * two_inv;
+                    let value = self.witness[val];
+                    if is_write {
+                        contents.insert(addr, value);
+                        continue;
+                    }
+                    let expected = contents.get(&addr).copied();
+                    if expected == Some(value) {
+                        continue;
+                    }
+                    report!(Some(UnsatisfiedGate {
+                        kind: ConstraintKind::Memory,
+                        index: time,
+                        gate_name: "RAM Access",
+                        namespace: self.namespaces.path(self.var_namespaces[val]).to_string(),
+                        wires: vec![WireValue {
+                            var: val,
+                            label: self.variable_label(val)?,
+                            value,
+                        }],
+                        selectors: vec![],
+                        reason: match expected {
+                            Some(expected) => {
+                                format!("read {value} at address {addr}, last written {expected}")
+                            },
+                            None => format!("read at address {addr} out of bounds"),
+                        },
+                    }));
+                }
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Check that the `gate_id`-th gate is satisfied by the circuit's witness
+    /// and the public input value `pub_input`. Return a report if it is not.
+    /// The gate equation:
+    /// qo * wo = pub_input + q_c +
+    ///           q_mul0 * w0 * w1 + q_mul1 * w2 * w3 +
+    ///           q_lc0 * w0 + q_lc1 * w1 + q_lc2 * w2 + q_lc3 * w3 +
+    ///           q_hash0 * w0 + q_hash1 * w1 + q_hash2 * w2 + q_hash3 * w3 +
+    ///           q_ecc * w0 * w1 * w2 * w3 * wo
+    pub(crate) fn gate_report(
+        &self,
+        gate_id: GateId,
+        pub_input: &F,
+    ) -> Result<Option<UnsatisfiedGate<F>>, CircuitError> {
+        // Compute wire values
+        let w_vals: Vec<F> = (0..GATE_WIDTH + 1)
+            .map(|i| self.witness[self.wire_variables[i][gate_id]])
+            .collect();
+        // Compute selector values.
+        let gate = &self.gates[gate_id];
+        let q_lc = gate.q_lc();
+        let q_mul = gate.q_mul();
+        let q_hash = gate.q_hash();
+        let q_c = gate.q_c();
+        let q_o = gate.q_o();
+        let q_ecc = gate.q_ecc();
+
+        // Compute the gate output
+        let expected_gate_output = *pub_input
+            + q_lc[0] * w_vals[0]
+            + q_lc[1] * w_vals[1]
+            + q_lc[2] * w_vals[2]
+            + q_lc[3] * w_vals[3]
+            + q_mul[0] * w_vals[0] * w_vals[1]
+            + q_mul[1] * w_vals[2] * w_vals[3]
+            + q_ecc * w_vals[0] * w_vals[1] * w_vals[2] * w_vals[3] * w_vals[4]
+            + q_hash[0] * w_vals[0].pow([5])
+            + q_hash[1] * w_vals[1].pow([5])
+            + q_hash[2] * w_vals[2].pow([5])
+            + q_hash[3] * w_vals[3].pow([5])
+            + q_c;
+        let gate_output = q_o * w_vals[4];
+        if expected_gate_output == gate_output {
+            return Ok(None);
+        }
+
+        let selectors = [
+            ("q_lc0", q_lc[0]),
+            ("q_lc1", q_lc[1]),
+            ("q_lc2", q_lc[2]),
+            ("q_lc3", q_lc[3]),
+            ("q_mul0", q_mul[0]),
+            ("q_mul1", q_mul[1]),
+            ("q_hash0", q_hash[0]),
+            ("q_hash1", q_hash[1]),
+            ("q_hash2", q_hash[2]),
+            ("q_hash3", q_hash[3]),
+            ("q_o", q_o),
+            ("q_c", q_c),
+            ("q_ecc", q_ecc),
+        ]
+        .into_iter()
+        .filter(|(_, q)| !q.is_zero())
+        .collect();
+        Ok(Some(UnsatisfiedGate {
+            kind: ConstraintKind::Gate,
+            index: gate_id,
+            gate_name: gate.name(),
+            namespace: self.gate_namespace(gate_id)?.to_string(),
+            wires: self.wire_values(&[0, 1, 2, 3, 4], gate_id)?,
+            selectors,
+            reason: format!(
+                "pub_input: {pub_input}, expected_gate_output: {expected_gate_output}, gate_output: {gate_output}"
+            ),
+        }))
+    }
+
+    /// Check the correctness of the `idx`-th range gate and return a report if
+    /// it fails. Return an error if the circuit does not support lookup.
+    pub(crate) fn range_gate_report(
+        &self,
+        idx: usize,
+    ) -> Result<Option<UnsatisfiedGate<F>>, CircuitError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        let var = *self.wire_variables[RANGE_WIRE_ID]
+            .get(idx)
+            .ok_or(IndexError)?;
+        let range_size = self.range_size()?;
+        let value = self.witness[var];
+        if value < F::from(range_size as u32) {
+            return Ok(None);
+        }
+        let namespace = self
+            .range_namespaces
+            .get(idx)
+            .map(|id| self.namespaces.path(*id).to_string())
+            .unwrap_or_default();
+        Ok(Some(UnsatisfiedGate {
+            kind: ConstraintKind::Range,
+            index: idx,
+            gate_name: "Range Gate",
+            namespace,
+            wires: vec![WireValue {
+                var,
+                label: self.variable_label(var)?,
+                value,
+            }],
+            selectors: vec![],
+            reason: format!("{value} >= {range_size}"),
+        }))
+    }
+
+    /// Check that the `idx`-th pending lookup into a deferred table is
+    /// satisfied by the circuit's witness, `entries` being the values of the
+    /// table. Return a report if it is not.
+    pub(crate) fn deferred_lookup_report(
+        &self,
+        table: &DeferredTable,
+        entries: &HashSet<(F, F, F)>,
+        idx: usize,
+    ) -> Result<Option<UnsatisfiedGate<F>>, CircuitError> {
+        let vars = *table.lookups.get(idx).ok_or(IndexError)?;
+        let [key, val0, val1] = vars.map(|var| self.witness[var]);
+        if entries.contains(&(key, val0, val1)) {
+            return Ok(None);
+        }
+        Ok(Some(UnsatisfiedGate {
+            kind: ConstraintKind::Lookup,
+            index: idx,
+            gate_name: table.name,
+            namespace: self
+                .namespaces
+                .path(self.var_namespaces[vars[0]])
+                .to_string(),
+            wires: vars
+                .iter()
+                .map(|&var| {
+                    Ok(WireValue {
+                        var,
+                        label: self.variable_label(var)?,
+                        value: self.witness[var],
+                    })
+                })
+                .collect::<Result<_, CircuitError>>()?,
+            selectors: vec![],
+            reason: format!("lookup ({key}, {val0}, {val1}) not in the table"),
+        }))
+    }
+
+    // The variables and values of the wires `wire_ids` of gate `gate_id`.
+    fn wire_values(
+        &self,
+        wire_ids: &[usize],
+        gate_id: GateId,
+    ) -> Result<Vec<WireValue<F>>, CircuitError> {
+        wire_ids
+            .iter()
+            .map(|&wire_id| {
+                let var = self.wire_variable(wire_id, gate_id);
+                Ok(WireValue {
+                    var,
+                    label: self.variable_label(var)?,
+                    value: self.witness(var)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConstraintKind, ROOT_NAMESPACE};
+    use crate::{errors::CircuitError, Circuit, PlonkCircuit};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+    use ark_std::{format, vec};
+
+    #[test]
+    fn test_namespaces() -> Result<(), CircuitError> {
+        test_namespaces_helper::<FqEd254>()?;
+        test_namespaces_helper::<FqEd377>()?;
+        test_namespaces_helper::<FqEd381>()?;
+        test_namespaces_helper::<Fq377>()
+    }
+
+    fn test_namespaces_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        assert_eq!(circuit.current_namespace(), "");
+        assert!(circuit.pop_namespace().is_err());
+        assert!(circuit.push_namespace("").is_err());
+        assert!(circuit.push_namespace("merkle//level").is_err());
+
+        let a = circuit.create_variable(F::from(3u32))?;
+        let (b, c) = circuit.namespace("merkle/level3", |cs| {
+            assert_eq!(cs.current_namespace(), "merkle/level3");
+            let b = cs.create_variable(F::from(4u32))?;
+            cs.label_variable(b, "sibling")?;
+            let c = cs.namespace("hash", |cs| {
+                assert_eq!(cs.current_namespace(), "merkle/level3/hash");
+                cs.add(a, b)
+            })?;
+            Ok((b, c))
+        })?;
+        assert_eq!(circuit.current_namespace(), "");
+        // the namespace is left even if the closure fails
+        assert!(circuit
+            .namespace("failing", |cs| cs.add(a, cs.num_vars()))
+            .is_err());
+        assert_eq!(circuit.current_namespace(), "");
+
+        assert_eq!(circuit.variable_label(a)?, format!("v{a}"));
+        assert_eq!(circuit.variable_label(b)?, "merkle/level3/sibling");
+        assert_eq!(
+            circuit.variable_label(c)?,
+            format!("merkle/level3/hash/v{c}")
+        );
+        assert!(circuit.variable_label(circuit.num_vars()).is_err());
+        assert_eq!(circuit.gate_namespace(0)?, "");
+        assert_eq!(
+            circuit.gate_namespace(circuit.num_gates() - 1)?,
+            "merkle/level3/hash"
+        );
+        assert!(circuit.gate_namespace(circuit.num_gates()).is_err());
+
+        // intermediate levels are interned as well
+        assert!(circuit.namespaces.ids.contains_key("merkle"));
+        assert!(circuit.namespaces.ids.contains_key("merkle/level3"));
+        assert_eq!(circuit.namespaces.ids[""], ROOT_NAMESPACE);
+
+        // labels survive finalization
+        let d = circuit.namespace("io", |cs| cs.create_public_variable(F::from(7u32)))?;
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.gate_namespace(0)?, "io");
+        assert_eq!(circuit.variable_label(d)?, format!("io/v{d}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsatisfied_gates() -> Result<(), CircuitError> {
+        test_unsatisfied_gates_helper::<FqEd254>()?;
+        test_unsatisfied_gates_helper::<FqEd377>()?;
+        test_unsatisfied_gates_helper::<FqEd381>()?;
+        test_unsatisfied_gates_helper::<Fq377>()
+    }
+
+    fn test_unsatisfied_gates_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let x = circuit.create_public_variable(F::from(2u32))?;
+        let (y, z) = circuit.namespace("square", |cs| {
+            let y = cs.mul(x, x)?;
+            let z = cs.mul(y, y)?;
+            Ok((y, z))
+        })?;
+        circuit.namespace("range", |cs| cs.add_range_check_variable(y))?;
+        circuit.enforce_constant(z, F::from(16u32))?;
+        assert!(circuit.unsatisfied_gates(&[], 10).is_err());
+        assert!(circuit.unsatisfied_gates(&[F::from(2u32)], 10)?.is_empty());
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(2u32)])
+            .is_ok());
+
+        // break the squaring: y = 5 != x * x, and y * y = 25 != z
+        *circuit.witness_mut(y) = F::from(5u32);
+        let reports = circuit.unsatisfied_gates(&[F::from(2u32)], 10)?;
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].kind, ConstraintKind::Gate);
+        assert_eq!(reports[0].namespace, "square");
+        assert_eq!(reports[0].gate_name, "Multiplication Gate");
+        assert_eq!(reports[0].wires[4].var, y);
+        assert_eq!(reports[0].wires[4].value, F::from(5u32));
+        assert_eq!(
+            reports[0].selectors,
+            vec![("q_mul0", F::one()), ("q_o", F::one())]
+        );
+        assert_eq!(reports[1].namespace, "square");
+        assert_eq!(reports[1].wires[0].var, y);
+        // only return the first `limit` failures
+        assert_eq!(
+            circuit.unsatisfied_gates(&[F::from(2u32)], 1)?,
+            reports[..1]
+        );
+
+        // the satisfiability check reports the first failure in detail
+        match circuit.check_circuit_satisfiability(&[F::from(2u32)]) {
+            Err(CircuitError::GateCheckFailure(gate_id, msg)) => {
+                assert_eq!(gate_id, reports[0].index);
+                assert!(msg.contains("square"));
+            },
+            _ => panic!("should fail the gate check"),
+        }
+
+        // range check failure
+        *circuit.witness_mut(y) = F::from(16u32);
+        *circuit.witness_mut(z) = F::from(256u32);
+        *circuit.witness_mut(x) = F::from(4u32);
+        let reports = circuit.unsatisfied_gates(&[F::from(4u32)], usize::MAX)?;
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].kind, ConstraintKind::Gate);
+        assert_eq!(reports[0].gate_name, "Constant Gate");
+        assert_eq!(reports[1].kind, ConstraintKind::Range);
+        assert_eq!(reports[1].namespace, "range");
+        assert_eq!(reports[1].wires[0].var, y);
+        Ok(())
+    }
+}
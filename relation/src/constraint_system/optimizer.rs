@@ -0,0 +1,616 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An optional optimization pass over a Plonk circuit, to be run before
+//! finalization.
+
+use super::{GateId, PlonkCircuit, Variable, RANGE_WIRE_ID};
+use crate::{
+    constants::GATE_WIDTH,
+    errors::{CircuitError, CircuitError::*},
+    gates::{ConstantGate, EqualityGate, Gate, IoGate, LinCombGate, QuadPolyGate},
+    Circuit,
+};
+use ark_ff::FftField;
+use ark_std::{boxed::Box, fmt, format, string::ToString, vec, vec::Vec};
+use hashbrown::{hash_map::Entry, HashMap, HashSet};
+
+/// What the optimization pass of [`PlonkCircuit::optimize()`] saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizationReport {
+    /// Number of gates before the optimization.
+    pub num_gates_before: usize,
+    /// Number of gates after the optimization.
+    pub num_gates_after: usize,
+    /// Number of variables before the optimization.
+    pub num_vars_before: usize,
+    /// Number of variables after the optimization.
+    pub num_vars_after: usize,
+    /// Number of removed constant gates that constrained a value already
+    /// held by another constant variable.
+    pub num_constants_deduplicated: usize,
+    /// Number of removed equality gates.
+    pub num_equalities_removed: usize,
+    /// Number of linear gates merged into the gate consuming their output.
+    pub num_gates_merged: usize,
+    /// The new index of every variable of the original circuit, `None` if the
+    /// variable was unreferenced and has been removed.
+    var_map: Vec<Option<Variable>>,
+}
+
+impl OptimizationReport {
+    /// The index in the optimized circuit of the variable `var` of the
+    /// original circuit. Variables merged by the optimization share the same
+    /// new index. Return `None` if `var` was removed.
+    pub fn new_variable(&self, var: Variable) -> Option<Variable> {
+        self.var_map.get(var).copied().flatten()
+    }
+}
+
+impl fmt::Display for OptimizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "gates: {} -> {}, variables: {} -> {} ({} constants deduplicated, {} equalities \
+             removed, {} linear gates merged)",
+            self.num_gates_before,
+            self.num_gates_after,
+            self.num_vars_before,
+            self.num_vars_after,
+            self.num_constants_deduplicated,
+            self.num_equalities_removed,
+            self.num_gates_merged
+        )
+    }
+}
+
+impl<F: FftField> PlonkCircuit<F> {
+    /// Optimize the circuit before finalization:
+    /// 1. variables constrained to the same constant are merged, and the
+    ///    duplicate constant gates removed;
+    /// 2. variables constrained to be equal are merged, and the equality gates
+    ///    removed;
+    /// 3. a linear gate whose output is used by a single other gate is merged
+    ///    into that gate, if the result still fits in a `LinCombGate` or a
+    ///    `QuadPolyGate`;
+    /// 4. variables not referenced by any gate are removed.
+    ///
+    /// Variables are renumbered: use [`OptimizationReport::new_variable()`]
+    /// to translate variables of the original circuit. The public input gates
+    /// keep their relative order. Return an error if the circuit is
+    /// finalized, or if the witness is inconsistent with the constraints
+    /// being merged in a way that would change the public input.
+    pub fn optimize(&mut self) -> Result<OptimizationReport, CircuitError> {
+        self.check_finalize_flag(false)?;
+        let num_gates_before = self.num_gates();
+        let num_vars_before = self.num_vars;
+        let mut gates = self.gates.clone();
+        let mut wires = self.wire_variables.clone();
+        let mut keep = vec![true; num_gates_before];
+
+        // merge variables constrained to the same constant or to each other
+        let mut parent: Vec<Variable> = (0..num_vars_before).collect();
+        let mut constants = HashMap::new();
+        for (gate_id, gate) in gates.iter().enumerate() {
+            if gate.as_any().is::<ConstantGate<F>>() {
+                let var = wires[GATE_WIDTH][gate_id];
+                match constants.entry(gate.q_c()) {
+                    Entry::Occupied(entry) => union(&mut parent, *entry.get(), var),
+                    Entry::Vacant(entry) => {
+                        entry.insert(var);
+                    },
+                }
+            } else if gate.as_any().is::<EqualityGate>() {
+                union(&mut parent, wires[0][gate_id], wires[1][gate_id]);
+            }
+        }
+        for wire in wires.iter_mut() {
+            for var in wire.iter_mut() {
+                *var = find(&mut parent, *var);
+            }
+        }
+        let mut num_constants_deduplicated = 0;
+        let mut num_equalities_removed = 0;
+        let mut seen_constants = HashSet::new();
+        for (gate_id, gate) in gates.iter().enumerate() {
+            if gate.as_any().is::<ConstantGate<F>>() {
+                if !seen_constants.insert((wires[GATE_WIDTH][gate_id], gate.q_c())) {
+                    keep[gate_id] = false;
+                    num_constants_deduplicated += 1;
+                }
+            } else if gate.as_any().is::<EqualityGate>() {
+                keep[gate_id] = false;
+                num_equalities_removed += 1;
+            }
+        }
+
+        // merge linear gates into the only gate using their output
+        let mut count = vec![0usize; num_vars_before];
+        for (wire_id, wire) in wires.iter().enumerate() {
+            for (gate_id, &var) in wire.iter().enumerate() {
+                if wire_id == RANGE_WIRE_ID || keep[gate_id] {
+                    count[var] += 1;
+                }
+            }
+        }
+        let deferred_vars = self.deferred_tables.iter().flat_map(|table| {
+            let entries = table.entries.iter().flat_map(|&(val0, val1)| [val0, val1]);
+            entries.chain(table.lookups.iter().flatten().copied())
+        });
+        let memory_vars = self
+            .memories
+            .iter()
+            .flat_map(|memory| memory.contents.iter().copied());
+        for var in deferred_vars.chain(memory_vars) {
+            count[find(&mut parent, var)] += 1;
+        }
+        let mut producers = vec![None; num_vars_before];
+        for (gate_id, gate) in gates.iter().enumerate() {
+            if keep[gate_id] && is_linear(gate.as_ref()) {
+                producers[wires[GATE_WIDTH][gate_id]] = Some(gate_id);
+            }
+        }
+        let mut num_gates_merged = 0;
+        for consumer_id in 0..num_gates_before {
+            if !keep[consumer_id] || !is_consumer(gates[consumer_id].as_ref()) {
+                continue;
+            }
+            let mut wire_id = 0;
+            while wire_id < GATE_WIDTH {
+                let var = wires[wire_id][consumer_id];
+                wire_id += 1;
+                if var < 2 || count[var] != 2 {
+                    continue;
+                }
+                let producer_id = match producers[var] {
+                    Some(id) if id != consumer_id && keep[id] => id,
+                    _ => continue,
+                };
+                let producer_wires = gate_wires(&wires, producer_id);
+                let consumer_wires = gate_wires(&wires, consumer_id);
+                if producer_wires[GATE_WIDTH] != var || !is_linear(gates[producer_id].as_ref()) {
+                    continue;
+                }
+                if let Some((gate, merged_wires)) = merge_linear_gate(
+                    gates[producer_id].as_ref(),
+                    &producer_wires,
+                    gates[consumer_id].as_ref(),
+                    &consumer_wires,
+                ) {
+                    for &var in producer_wires.iter().chain(consumer_wires.iter()) {
+                        count[var] -= 1;
+                    }
+                    for (wire, &var) in wires.iter_mut().zip(merged_wires.iter()) {
+                        wire[consumer_id] = var;
+                        count[var] += 1;
+                    }
+                    gates[consumer_id] = gate;
+                    keep[producer_id] = false;
+                    num_gates_merged += 1;
+                    // the merged gate may absorb another producer
+                    wire_id = 0;
+                }
+            }
+        }
+
+        // remove unreferenced variables
+        let mut var_ids = vec![None; num_vars_before];
+        let mut num_vars_after = 0;
+        for (var, new_id) in var_ids.iter_mut().enumerate() {
+            if var < 2 || count[var] > 0 {
+                *new_id = Some(num_vars_after);
+                num_vars_after += 1;
+            }
+        }
+        let mut gate_ids = vec![None; num_gates_before];
+        let mut num_gates_after = 0;
+        for (gate_id, new_id) in gate_ids.iter_mut().enumerate() {
+            if keep[gate_id] {
+                *new_id = Some(num_gates_after);
+                num_gates_after += 1;
+            }
+        }
+        let new_var = |var: Variable| {
+            var_ids[var].ok_or_else(|| InternalError(format!("variable {var} was removed")))
+        };
+        let new_gate = |gate_id: GateId| {
+            gate_ids[gate_id].ok_or_else(|| InternalError(format!("gate {gate_id} was removed")))
+        };
+
+        let mut new_wires: [Vec<Variable>; GATE_WIDTH + 2] = Default::default();
+        for (wire_id, wire) in wires.iter().enumerate() {
+            new_wires[wire_id] = wire
+                .iter()
+                .enumerate()
+                .filter(|&(gate_id, _)| wire_id == RANGE_WIRE_ID || keep[gate_id])
+                .map(|(_, &var)| new_var(var))
+                .collect::<Result<_, _>>()?;
+        }
+        let new_witness: Vec<F> = (0..num_vars_before)
+            .filter(|&var| var_ids[var].is_some())
+            .map(|var| self.witness[var])
+            .collect();
+        let pub_input_gate_ids = self
+            .pub_input_gate_ids
+            .iter()
+            .map(|&gate_id| new_gate(gate_id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // the public input must be left untouched
+        let pub_input = self.public_input()?;
+        if pub_input_gate_ids.len() != pub_input.len()
+            || pub_input_gate_ids
+                .iter()
+                .zip(pub_input.iter())
+                .any(|(&gate_id, val)| new_witness[new_wires[GATE_WIDTH][gate_id]] != *val)
+        {
+            return Err(ParameterError(
+                "optimization would change the public input, the witness does not satisfy the \
+                 circuit"
+                    .to_string(),
+            ));
+        }
+
+        let mut deferred_tables = self.deferred_tables.clone();
+        for table in deferred_tables.iter_mut() {
+            for (val0, val1) in table.entries.iter_mut() {
+                *val0 = new_var(find(&mut parent, *val0))?;
+                *val1 = new_var(find(&mut parent, *val1))?;
+            }
+            for var in table.lookups.iter_mut().flatten() {
+                *var = new_var(find(&mut parent, *var))?;
+            }
+        }
+        let mut memories = self.memories.clone();
+        for var in memories
+            .iter_mut()
+            .flat_map(|memory| memory.contents.iter_mut())
+        {
+            *var = new_var(find(&mut parent, *var))?;
+        }
+        let var_map = (0..num_vars_before)
+            .map(|var| var_ids[find(&mut parent, var)])
+            .collect();
+        self.table_gate_ids = self
+            .table_gate_ids
+            .iter()
+            .map(|&(gate_id, size)| Ok((new_gate(gate_id)?, size)))
+            .collect::<Result<_, CircuitError>>()?;
+        self.gates = gates
+            .into_iter()
+            .zip(keep.iter())
+            .filter_map(|(gate, &keep)| keep.then_some(gate))
+            .collect();
+        self.gate_namespaces = self
+            .gate_namespaces
+            .iter()
+            .zip(keep.iter())
+            .filter_map(|(&id, &keep)| keep.then_some(id))
+            .collect();
+        self.var_namespaces = (0..num_vars_before)
+            .filter(|&var| var_ids[var].is_some())
+            .map(|var| self.var_namespaces[var])
+            .collect();
+        self.var_labels = self
+            .var_labels
+            .drain()
+            .filter_map(|(var, label)| var_ids[var].map(|var| (var, label)))
+            .collect();
+        self.wire_variables = new_wires;
+        self.deferred_tables = deferred_tables;
+        self.memories = memories;
+        self.witness = new_witness;
+        self.pub_input_gate_ids = pub_input_gate_ids;
+        self.num_vars = num_vars_after;
+
+        Ok(OptimizationReport {
+            num_gates_before,
+            num_gates_after,
+            num_vars_before,
+            num_vars_after,
+            num_constants_deduplicated,
+            num_equalities_removed,
+            num_gates_merged,
+            var_map,
+        })
+    }
+}
+
+fn find(parent: &mut [Variable], mut var: Variable) -> Variable {
+    while parent[var] != var {
+        parent[var] = parent[parent[var]];
+        var = parent[var];
+    }
+    var
+}
+
+// The smaller variable becomes the representative, so that the constant
+// variables `0` and `1` are never replaced.
+fn union(parent: &mut [Variable], a: Variable, b: Variable) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a < b {
+        parent[b] = a;
+    } else {
+        parent[a] = b;
+    }
+}
+
+fn gate_wires(wires: &[Vec<Variable>], gate_id: GateId) -> [Variable; GATE_WIDTH + 1] {
+    let mut res = [0; GATE_WIDTH + 1];
+    for (var, wire) in res.iter_mut().zip(wires.iter()) {
+        *var = wire[gate_id];
+    }
+    res
+}
+
+// A gate whose output is a linear combination of its inputs plus a constant.
+fn is_linear<F: FftField>(gate: &dyn Gate<F>) -> bool {
+    is_consumer(gate) && gate.q_mul().iter().all(|q| q.is_zero()) && !gate.q_o().is_zero()
+}
+
+// A gate into which a linear gate can be merged.
+fn is_consumer<F: FftField>(gate: &dyn Gate<F>) -> bool {
+    !gate.as_any().is::<IoGate>()
+        && gate.q_hash().iter().all(|q| q.is_zero())
+        && gate.q_ecc().is_zero()
+        && gate.q_lookup().is_zero()
+}
+
+// Substitute the output of the linear gate `producer` into the input wire of
+// `consumer` holding it. Return `None` if the resulting gate has too many
+// inputs.
+fn merge_linear_gate<F: FftField>(
+    producer: &dyn Gate<F>,
+    producer_wires: &[Variable; GATE_WIDTH + 1],
+    consumer: &dyn Gate<F>,
+    consumer_wires: &[Variable; GATE_WIDTH + 1],
+) -> Option<(Box<dyn Gate<F>>, [Variable; GATE_WIDTH + 1])> {
+    let var = producer_wires[GATE_WIDTH];
+    let pos = consumer_wires[..GATE_WIDTH]
+        .iter()
+        .position(|&wire| wire == var)?;
+    let q_lc = consumer.q_lc();
+    let q_mul = consumer.q_mul();
+    if !q_mul[pos / 2].is_zero() {
+        return None;
+    }
+
+    // var = (producer_lc * producer_wires + producer_c) / producer_o
+    let scale = q_lc[pos] / producer.q_o();
+    let mut terms: Vec<(Variable, F)> = vec![];
+    let inputs = (0..GATE_WIDTH)
+        .filter(|&i| i != pos)
+        .map(|i| (consumer_wires[i], q_lc[i]))
+        .chain(
+            producer_wires[..GATE_WIDTH]
+                .iter()
+                .zip(producer.q_lc())
+                .map(|(&wire, coeff)| (wire, scale * coeff)),
+        );
+    // terms on the zero variable vanish
+    for (wire, coeff) in inputs.filter(|&(wire, coeff)| wire != 0 && !coeff.is_zero()) {
+        match terms.iter_mut().find(|(var, _)| *var == wire) {
+            Some((_, acc)) => *acc += coeff,
+            None => terms.push((wire, coeff)),
+        }
+    }
+    let q_c = consumer.q_c() + scale * producer.q_c();
+
+    // the multiplication terms pin their wires, the linear terms use the
+    // wire holding the same variable or a free one
+    let mut wires = [0; GATE_WIDTH + 1];
+    let mut coeffs = [F::zero(); GATE_WIDTH];
+    let mut pinned = [false; GATE_WIDTH];
+    for (i, q) in q_mul.iter().enumerate() {
+        if !q.is_zero() {
+            for j in 2 * i..2 * i + 2 {
+                wires[j] = consumer_wires[j];
+                pinned[j] = true;
+            }
+        }
+    }
+    for (var, coeff) in terms.into_iter().filter(|(_, coeff)| !coeff.is_zero()) {
+        let slot = (0..GATE_WIDTH)
+            .find(|&i| pinned[i] && wires[i] == var)
+            .or_else(|| (0..GATE_WIDTH).find(|&i| !pinned[i]))?;
+        wires[slot] = var;
+        coeffs[slot] = coeff;
+        pinned[slot] = true;
+    }
+    wires[GATE_WIDTH] = consumer_wires[GATE_WIDTH];
+
+    let q_o = consumer.q_o();
+    let gate: Box<dyn Gate<F>> =
+        if q_mul.iter().all(|q| q.is_zero()) && q_c.is_zero() && q_o.is_one() {
+            Box::new(LinCombGate { coeffs })
+        } else {
+            Box::new(QuadPolyGate {
+                q_lc: coeffs,
+                q_mul,
+                q_o,
+                q_c,
+            })
+        };
+    Some((gate, wires))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{errors::CircuitError, gadgets::UInt32Var, Circuit, PlonkCircuit};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+    use ark_std::{vec, vec::Vec};
+
+    #[test]
+    fn test_optimize() -> Result<(), CircuitError> {
+        test_optimize_helper::<FqEd254>()?;
+        test_optimize_helper::<FqEd377>()?;
+        test_optimize_helper::<FqEd381>()?;
+        test_optimize_helper::<Fq377>()
+    }
+
+    fn test_optimize_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_public_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(5u32))?;
+        // duplicate constants
+        let c1 = circuit.create_constant_variable(F::from(7u32))?;
+        let c2 = circuit.create_constant_variable(F::from(7u32))?;
+        let one = circuit.create_constant_variable(F::one())?;
+        // an equality chain
+        let d = circuit.create_variable(F::from(5u32))?;
+        let e = circuit.create_variable(F::from(5u32))?;
+        circuit.enforce_equal(b, d)?;
+        circuit.enforce_equal(d, e)?;
+        // a linear chain ending in a multiplication
+        let s1 = circuit.add(a, c1)?;
+        let s2 = circuit.add_constant(s1, &F::from(2u32))?;
+        let s3 = circuit.lc(
+            &[s2, e, c2, one],
+            &[F::one(), F::from(2u32), F::one(), F::one()],
+        )?;
+        let m = circuit.mul(s3, b)?;
+        let out = circuit.create_public_variable(circuit.witness(m)?)?;
+        circuit.enforce_equal(m, out)?;
+        let unused = circuit.create_variable(F::from(42u32))?;
+
+        let pub_input = circuit.public_input()?;
+        let num_gates = circuit.num_gates();
+        let num_vars = circuit.num_vars();
+        circuit.check_circuit_satisfiability(&pub_input)?;
+
+        let report = circuit.optimize()?;
+        assert_eq!(report.num_gates_before, num_gates);
+        assert_eq!(report.num_vars_before, num_vars);
+        assert_eq!(report.num_gates_after, circuit.num_gates());
+        assert_eq!(report.num_vars_after, circuit.num_vars());
+        assert_eq!(report.num_constants_deduplicated, 2);
+        assert_eq!(report.num_equalities_removed, 3);
+        assert!(report.num_gates_merged >= 2);
+        assert!(circuit.num_gates() < num_gates);
+        assert!(circuit.num_vars() < num_vars);
+
+        // the public input is preserved, in order
+        assert_eq!(circuit.public_input()?, pub_input);
+        assert_eq!(circuit.num_inputs(), 2);
+        circuit.check_circuit_satisfiability(&pub_input)?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(3u32), F::one()])
+            .is_err());
+
+        // variable translation
+        assert_eq!(report.new_variable(b), report.new_variable(e));
+        assert_eq!(report.new_variable(c1), report.new_variable(c2));
+        assert_eq!(report.new_variable(one), Some(circuit.one()));
+        assert_eq!(report.new_variable(m), report.new_variable(out));
+        assert_eq!(report.new_variable(unused), None);
+        assert_eq!(report.new_variable(s1), None);
+        let new_m = report.new_variable(m).unwrap();
+        assert_eq!(circuit.witness(new_m)?, pub_input[1]);
+
+        // a tampered witness is caught
+        let new_b = report.new_variable(b).unwrap();
+        *circuit.witness_mut(new_b) = F::from(6u32);
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_err());
+
+        // the circuit can still be finalized and is not optimized twice
+        *circuit.witness_mut(new_b) = F::from(5u32);
+        circuit.finalize_for_arithmetization()?;
+        assert!(circuit.optimize().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_gadgets() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<Fq377> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_public_variable(Fq377::from(1234u32))?;
+        circuit.enforce_in_range(a, 16)?;
+        let elems = (0..10u32)
+            .map(|i| circuit.create_variable(Fq377::from(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sum = circuit.sum(&elems)?;
+        let eq = circuit.is_equal(sum, a)?;
+        circuit.enforce_false(eq.into())?;
+        let num_gates = circuit.num_gates();
+
+        let report = circuit.optimize()?;
+        assert!(report.num_gates_merged > 0);
+        assert!(circuit.num_gates() < num_gates);
+        circuit.check_circuit_satisfiability(&[Fq377::from(1234u32)])?;
+        let new_sum = report.new_variable(sum).unwrap();
+        assert_eq!(circuit.witness(new_sum)?, Fq377::from(45u32));
+        *circuit.witness_mut(new_sum) = Fq377::from(46u32);
+        assert!(circuit
+            .check_circuit_satisfiability(&[Fq377::from(1234u32)])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_inconsistent_witness() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<FqEd254> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(FqEd254::from(3u32))?;
+        let b = circuit.create_public_variable(FqEd254::from(4u32))?;
+        circuit.enforce_equal(a, b)?;
+        let num_gates = circuit.num_gates();
+        assert!(circuit.optimize().is_err());
+        // the circuit is left untouched
+        assert_eq!(circuit.num_gates(), num_gates);
+        assert_eq!(circuit.public_input()?, vec![FqEd254::from(4u32)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_ultra_plonk() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<FqEd254> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(FqEd254::from(3u32))?;
+        let b = circuit.create_variable(FqEd254::from(3u32))?;
+        circuit.enforce_equal(a, b)?;
+        circuit.add_range_check_variable(b)?;
+        let key = circuit.create_variable(FqEd254::from(1u32))?;
+        let val = circuit.create_variable(FqEd254::from(8u32))?;
+        let t0 = circuit.create_variable(FqEd254::from(7u32))?;
+        let t1 = circuit.create_variable(FqEd254::from(8u32))?;
+        let zero = circuit.zero();
+        circuit
+            .create_table_and_lookup_variables(&[(key, val, zero)], &[(t0, zero), (t1, zero)])?;
+        let sum = circuit.add(a, val)?;
+        circuit.enforce_constant(sum, FqEd254::from(11u32))?;
+
+        let report = circuit.optimize()?;
+        assert_eq!(report.num_equalities_removed, 1);
+        circuit.check_circuit_satisfiability(&[])?;
+        circuit.finalize_for_arithmetization()?;
+        circuit.check_circuit_satisfiability(&[])
+    }
+
+    #[test]
+    fn test_optimize_nibble_lookups() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<FqEd254> = PlonkCircuit::new_ultra_plonk(8);
+        let a: UInt32Var = circuit.create_uint_variable(0xdead_beef)?;
+        let b: UInt32Var = circuit.create_uint_variable(0x0bad_cafe)?;
+        let b_copy = circuit.create_variable(FqEd254::from(0x0bad_cafe_u32))?;
+        circuit.enforce_equal(b.var(), b_copy)?;
+        let b_copy = circuit.uint_from_variable::<32>(b_copy)?;
+        let xor = circuit.uint_xor(a, b_copy)?;
+
+        let report = circuit.optimize()?;
+        circuit.check_circuit_satisfiability(&[])?;
+        let new_xor = report.new_variable(xor.var()).unwrap();
+        assert_eq!(
+            circuit.witness(new_xor)?,
+            FqEd254::from(0xdead_beef_u32 ^ 0x0bad_cafe)
+        );
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(new_xor) += FqEd254::from(1u32);
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        circuit.finalize_for_arithmetization()?;
+        circuit.check_circuit_satisfiability(&[])
+    }
+}
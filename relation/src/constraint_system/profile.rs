@@ -0,0 +1,279 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Constraint counts of a Plonk circuit broken down by gadget.
+//!
+//! When profiling is enabled with [`PlonkCircuit::set_profiling()`], gadgets
+//! wrapped in [`PlonkCircuit::profile()`] create their gates, variables and
+//! range checks in a namespace named after the gadget. The resulting tree of
+//! namespaces is reported by [`PlonkCircuit::profile_report()`]. With the
+//! `serde` feature, the report can be serialized, e.g. to JSON.
+
+use super::{NamespaceId, PlonkCircuit, NAMESPACE_SEPARATOR, ROOT_NAMESPACE};
+use crate::errors::CircuitError;
+use ark_ff::FftField;
+use ark_std::{
+    fmt,
+    ops::AddAssign,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Constraint counts of a region of a circuit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProfileStats {
+    /// Number of gates, including lookup gates.
+    pub num_gates: usize,
+    /// Number of variables.
+    pub num_vars: usize,
+    /// Number of UltraPlonk range checks.
+    pub num_range_checks: usize,
+    /// Number of UltraPlonk lookup gates.
+    pub num_lookup_gates: usize,
+}
+
+impl AddAssign for ProfileStats {
+    fn add_assign(&mut self, other: Self) {
+        self.num_gates += other.num_gates;
+        self.num_vars += other.num_vars;
+        self.num_range_checks += other.num_range_checks;
+        self.num_lookup_gates += other.num_lookup_gates;
+    }
+}
+
+impl fmt::Display for ProfileStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} gates, {} vars, {} range checks, {} lookup gates",
+            self.num_gates, self.num_vars, self.num_range_checks, self.num_lookup_gates
+        )
+    }
+}
+
+/// A node of the profiling report of a circuit, see
+/// [`PlonkCircuit::profile_report()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CircuitProfile {
+    /// The last level of the namespace path, empty for the root.
+    pub name: String,
+    /// The full namespace path, empty for the root.
+    pub path: String,
+    /// Number of times the namespace has been entered.
+    pub num_calls: usize,
+    /// Constraints created directly in this namespace.
+    pub own: ProfileStats,
+    /// Constraints created in this namespace and all its descendants.
+    pub total: ProfileStats,
+    /// Child namespaces, sorted by decreasing total number of gates.
+    pub children: Vec<CircuitProfile>,
+}
+
+impl CircuitProfile {
+    /// Find the node of the namespace with full path `path`.
+    pub fn find(&self, path: &str) -> Option<&Self> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(path))
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let name = if self.name.is_empty() {
+            "<root>"
+        } else {
+            &self.name
+        };
+        writeln!(
+            f,
+            "{:indent$}{} (calls: {}): {} (own: {} gates)",
+            "",
+            name,
+            self.num_calls,
+            self.total,
+            self.own.num_gates,
+            indent = 2 * depth
+        )?;
+        self.children
+            .iter()
+            .try_for_each(|child| child.fmt_indented(f, depth + 1))
+    }
+}
+
+impl fmt::Display for CircuitProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl<F: FftField> PlonkCircuit<F> {
+    /// Enable or disable the recording of gadget constraints in profiling
+    /// namespaces. Profiling is disabled by default.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+    }
+
+    /// Whether profiling is enabled.
+    pub fn is_profiling(&self) -> bool {
+        self.profiling
+    }
+
+    /// Run the gadget `f`. If profiling is enabled, `f` is run inside the
+    /// child namespace `name` of the current namespace, see
+    /// [`Self::namespace()`]; otherwise `f` is simply run on `self`.
+    pub fn profile<T, G>(&mut self, name: &str, f: G) -> Result<T, CircuitError>
+    where
+        G: FnOnce(&mut Self) -> Result<T, CircuitError>,
+    {
+        if self.profiling {
+            self.namespace(name, f)
+        } else {
+            f(self)
+        }
+    }
+
+    /// Report the constraint counts of every namespace of the circuit as a
+    /// tree rooted at the root namespace.
+    ///
+    /// Gates added when finalizing the circuit (e.g. padding) are counted in
+    /// the root namespace.
+    pub fn profile_report(&self) -> CircuitProfile {
+        let num_namespaces = self.namespaces.len();
+        let mut own = vec![ProfileStats::default(); num_namespaces];
+        for (gate, &id) in self.gates.iter().zip(self.gate_namespaces.iter()) {
+            own[id].num_gates += 1;
+            if !gate.q_lookup().is_zero() {
+                own[id].num_lookup_gates += 1;
+            }
+        }
+        for &id in self.var_namespaces.iter() {
+            own[id].num_vars += 1;
+        }
+        for &id in self.range_namespaces.iter() {
+            own[id].num_range_checks += 1;
+        }
+
+        let mut children = vec![vec![]; num_namespaces];
+        for id in 0..num_namespaces {
+            if let Some(parent) = self.namespaces.parent(id) {
+                children[parent].push(id);
+            }
+        }
+        self.profile_node(ROOT_NAMESPACE, &own, &children)
+    }
+
+    fn profile_node(
+        &self,
+        id: NamespaceId,
+        own: &[ProfileStats],
+        children: &[Vec<NamespaceId>],
+    ) -> CircuitProfile {
+        let mut nodes: Vec<CircuitProfile> = children[id]
+            .iter()
+            .map(|&child| self.profile_node(child, own, children))
+            .collect();
+        nodes.sort_by(|a, b| {
+            b.total
+                .num_gates
+                .cmp(&a.total.num_gates)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        let mut total = own[id];
+        for node in nodes.iter() {
+            total += node.total;
+        }
+        let path = self.namespaces.path(id);
+        let name = path.rsplit(NAMESPACE_SEPARATOR).next().unwrap_or_default();
+        CircuitProfile {
+            name: name.to_string(),
+            path: path.to_string(),
+            num_calls: self.namespaces.num_entries(id),
+            own: own[id],
+            total,
+            children: nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{errors::CircuitError, Circuit, PlonkCircuit};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+
+    #[test]
+    fn test_profile_report() -> Result<(), CircuitError> {
+        test_profile_report_helper::<FqEd254>()?;
+        test_profile_report_helper::<FqEd377>()?;
+        test_profile_report_helper::<FqEd381>()?;
+        test_profile_report_helper::<Fq377>()
+    }
+
+    fn test_profile_report_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(8);
+        let a = circuit.create_variable(F::from(3u32))?;
+
+        // nothing is recorded while profiling is disabled
+        circuit.profile("disabled", |cs| cs.add(a, a))?;
+        assert!(circuit.profile_report().children.is_empty());
+
+        circuit.set_profiling(true);
+        assert!(circuit.is_profiling());
+        for _ in 0..2 {
+            circuit.profile("outer", |cs| {
+                let b = cs.mul(a, a)?;
+                cs.profile("inner", |cs| {
+                    cs.add_range_check_variable(b)?;
+                    cs.add(a, b)
+                })
+            })?;
+        }
+        circuit.profile("small", |cs| cs.add_constant(a, &F::one()))?;
+
+        let report = circuit.profile_report();
+        assert_eq!(report.total.num_gates, circuit.num_gates());
+        assert_eq!(report.total.num_vars, circuit.num_vars());
+        assert_eq!(report.total.num_range_checks, 2);
+        assert_eq!(report.num_calls, 0);
+
+        assert_eq!(report.children.len(), 2);
+        let outer = &report.children[0];
+        assert_eq!(outer.name, "outer");
+        assert_eq!(outer.num_calls, 2);
+        assert_eq!(outer.own.num_gates, 2);
+        assert_eq!(outer.own.num_vars, 2);
+        assert_eq!(outer.total.num_gates, 4);
+        assert_eq!(outer.total.num_range_checks, 2);
+        let inner = report.find("outer/inner").unwrap();
+        assert_eq!(inner.name, "inner");
+        assert_eq!(inner.num_calls, 2);
+        assert_eq!(inner.own.num_gates, 2);
+        assert_eq!(inner.own.num_range_checks, 2);
+        assert!(inner.children.is_empty());
+        assert_eq!(report.children[1].name, "small");
+        assert_eq!(report.children[1].total.num_gates, 1);
+        assert!(report.find("disabled").is_none());
+
+        // the report survives a JSON roundtrip
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&report).unwrap();
+            assert_eq!(
+                serde_json::from_str::<super::CircuitProfile>(&json).unwrap(),
+                report
+            );
+        }
+        Ok(())
+    }
+}
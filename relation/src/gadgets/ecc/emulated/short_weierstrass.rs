@@ -4,17 +4,23 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-//! Short Weierstrass curve point addition
+//! Short Weierstrass curve point addition and scalar multiplication
 
 use crate::{
     errors::CircuitError,
     gadgets::{from_emulated_field, EmulatedVariable, EmulationConfig, SerializableEmulatedStruct},
     BoolVar, Circuit, PlonkCircuit,
 };
-use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::{
+    short_weierstrass::{Affine, Projective, SWCurveConfig},
+    AffineRepr, CurveGroup,
+};
 use ark_ff::PrimeField;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{vec, vec::Vec};
+use ark_std::{format, vec, vec::Vec, Zero};
+
+/// Window size in bits used by [`PlonkCircuit::emulated_sw_msm`].
+pub const EMULATED_MSM_WINDOW_SIZE: usize = 4;
 
 /// An elliptic curve point in short Weierstrass affine form (x, y, infinity).
 #[derive(Debug, Eq, PartialEq, Copy, Clone, CanonicalSerialize, CanonicalDeserialize)]
@@ -167,15 +173,19 @@ impl<F: PrimeField> PlonkCircuit<F> {
     ///     - x2 = s^2 - x0 - x1
     ///     - y2 = s(x0 - x2) - y0
     /// The first case is equivalent to the following:
-    /// - inf0 == 1 || inf1 == 1 || x0 != x1 || y0 != y1 || y0 != 0 || inf2 == 0
+    /// - inf0 == 1 || inf1 == 1 || x0 != x1 || y0 != y1 || inf2 == (y0 == 0)
     /// - (x0 + x1 + x2) * (y0 + y0)^2 == (3 * x0^2 + a)^2
     /// - (y2 + y0) * (y0 + y0) == (3 * x0^2 + a) (x0 - x2)
+    ///
     /// The second case is equivalent to the following:
-    /// - inf0 == 1 || inf1 == 1 || x0 != x1 || y0 == y1 || inf2 == 0
+    /// - inf0 == 1 || inf1 == 1 || y0 == y1 || inf2 == (x0 == x1)
     /// - (x0 - x1)^2 (x0 + x1 + x2) == (y0 - y1)^2
     /// - (x0 - x2) (y0 - y1) == (y0 + y2) (x0 - x1)
+    ///
     /// First check in both cases can be combined into the following:
-    /// inf0 == 1 || inf1 == 1 || inf2 == 0 || x0 != x1 || (y0 == y1 && y0 != 0)
+    /// inf0 == 1 || inf1 == 1 || inf2 == (x0 == x1 && (y0 != y1 || y0 == 0))
+    /// and an infinity p2 must be (0, 0, 1).
+    ///
     /// For the rest equality checks,
     ///   - Both LHS and RHS must be multiplied with an indicator variable
     ///     (!inf0 && !inf1). So that if either p0 or p1 is infinity, those
@@ -205,17 +215,22 @@ impl<F: PrimeField> PlonkCircuit<F> {
         let non_infinity_mark = self.logic_neg(infinity_mark)?;
 
         // Case 2: p2 is infinity, while p0 and p1 are not.
-        // inf0 == 1 || inf1 == 1 || inf2 == 0 || x0 != x1 || (y0 == y1 && y0 != 0)
-        let non_inf_p2 = self.logic_neg(p2.2)?;
+        // inf0 == 1 || inf1 == 1 || inf2 == (x0 == x1 && (y0 != y1 || y0 == 0))
         let eq_x0_x1 = self.is_emulated_var_equal(&p0.0, &p1.0)?;
         let neq_x0_x1 = self.logic_neg(eq_x0_x1)?;
         let eq_y0_y1 = self.is_emulated_var_equal(&p0.1, &p1.1)?;
+        let neq_y0_y1 = self.logic_neg(eq_y0_y1)?;
         let is_y0_zero = self.is_emulated_var_zero(&p0.1)?;
         let not_y0_zero = self.logic_neg(is_y0_zero)?;
-        let t = self.logic_and(eq_y0_y1, not_y0_zero)?;
-        let t = self.logic_or(neq_x0_x1, t)?;
-        let t = self.logic_or(non_inf_p2, t)?;
-        self.logic_or_gate(infinity_mark, t)?;
+        let t = self.logic_or(neq_y0_y1, is_y0_zero)?;
+        let expected_inf2 = self.logic_and(eq_x0_x1, t)?;
+        let inf2_diff = self.sub(p2.2 .0, expected_inf2.0)?;
+        let zero = self.zero();
+        self.mul_gate(non_infinity_mark.0, inf2_diff, zero)?;
+        // if inf2 == 1, then x2 == y2 == 0
+        for &limb in p2.0 .0.iter().chain(p2.1 .0.iter()) {
+            self.mul_gate(limb, p2.2 .0, zero)?;
+        }
 
         // Case 3: point doubling
         // doubling mark is 1 iff x0 == x1 and y0 == y1
@@ -327,6 +342,259 @@ impl<F: PrimeField> PlonkCircuit<F> {
         self.emulated_sw_ecc_add_gate(p0, p1, &p2, a)?;
         Ok(p2)
     }
+
+    /// Constrain variable `p1` to be the point doubling of `p0` over an
+    /// elliptic curve with coefficient `a`.
+    /// Let p0 = (x0, y0, inf0), p1 = (x1, y1, inf1)
+    /// The doubling formula for affine points of sw curve is
+    ///   If p0 is infinity or y0 == 0, then p1 is infinity.
+    ///   Otherwise
+    ///     - Calculate s = (3 * x0^2 + a) / (2 * y0)
+    ///     - x1 = s^2 - 2 * x0
+    ///     - y1 = s(x0 - x1) - y0
+    /// This is equivalent to the following:
+    /// - inf1 == inf0 || y0 == 0
+    /// - if inf1 == 1, then x1 == y1 == 0
+    /// - (x1 + 2 * x0) * (y0 + y0)^2 == (3 * x0^2 + a)^2
+    /// - (y1 + y0) * (y0 + y0) == (3 * x0^2 + a) (x0 - x1)
+    ///
+    /// Both LHS and RHS of the last two equalities are multiplied with !inf1,
+    /// so that they trivially pass when p1 is infinity.
+    pub fn emulated_sw_ecc_double_gate<E: EmulationConfig<F>>(
+        &mut self,
+        p0: &EmulatedSWPointVariable<E>,
+        p1: &EmulatedSWPointVariable<E>,
+        a: E,
+    ) -> Result<(), CircuitError> {
+        let is_y0_zero = self.is_emulated_var_zero(&p0.1)?;
+        let infinity = self.logic_or(p0.2, is_y0_zero)?;
+        self.enforce_equal(p1.2 .0, infinity.0)?;
+        let zero = self.zero();
+        for &limb in p1.0 .0.iter().chain(p1.1 .0.iter()) {
+            self.mul_gate(limb, p1.2 .0, zero)?;
+        }
+
+        let non_infinity = self.logic_neg(p1.2)?;
+        // forcefully convert Variable into EmulatedVariable
+        // safe because it's boolean
+        let mut v = vec![self.zero(); E::NUM_LIMBS];
+        v[0] = non_infinity.0;
+        let coef = EmulatedVariable::<E>(v, core::marker::PhantomData);
+
+        // first equality (x1 + 2 * x0) * (y0 + y0)^2 == (3 * x0^2 + a)^2
+        let y0_times_2 = self.emulated_add(&p0.1, &p0.1)?;
+        let x0_times_2 = self.emulated_add(&p0.0, &p0.0)?;
+        let x1_plus_2x0 = self.emulated_add(&p1.0, &x0_times_2)?;
+        let lhs = self.emulated_mul(&x1_plus_2x0, &y0_times_2)?;
+        let lhs = self.emulated_mul(&lhs, &y0_times_2)?;
+        // s = 3 * x0^2 + a
+        let s = self.emulated_mul(&p0.0, &p0.0)?;
+        let s = self.emulated_mul_constant(&s, E::from(3u64))?;
+        let s = self.emulated_add_constant(&s, a)?;
+        let rhs = self.emulated_mul(&s, &s)?;
+
+        let lhs = self.emulated_mul(&lhs, &coef)?;
+        let rhs = self.emulated_mul(&rhs, &coef)?;
+        self.enforce_emulated_var_equal(&lhs, &rhs)?;
+
+        // second equality (y1 + y0) * (y0 + y0) == (3 * x0^2 + a) (x0 - x1)
+        let y1_plus_y0 = self.emulated_add(&p1.1, &p0.1)?;
+        let lhs = self.emulated_mul(&y1_plus_y0, &y0_times_2)?;
+        let x0_minus_x1 = self.emulated_sub(&p0.0, &p1.0)?;
+        let rhs = self.emulated_mul(&s, &x0_minus_x1)?;
+
+        let lhs = self.emulated_mul(&lhs, &coef)?;
+        let rhs = self.emulated_mul(&rhs, &coef)?;
+        self.enforce_emulated_var_equal(&lhs, &rhs)
+    }
+
+    /// Obtain a variable to the point doubling result of `p0`.
+    pub fn emulated_sw_ecc_double<E: EmulationConfig<F>>(
+        &mut self,
+        p0: &EmulatedSWPointVariable<E>,
+        a: E,
+    ) -> Result<EmulatedSWPointVariable<E>, CircuitError> {
+        let x0 = self.emulated_witness(&p0.0)?;
+        let y0 = self.emulated_witness(&p0.1)?;
+        let infinity0 = self.witness(p0.2 .0)? == F::one();
+        let p1 = if infinity0 || y0.is_zero() {
+            SWPoint::default()
+        } else {
+            let s = (x0 * x0 * E::from(3u64) + a) / (y0 + y0);
+            let x1 = s * s - x0 - x0;
+            let y1 = s * (x0 - x1) - y0;
+            SWPoint(x1, y1, false)
+        };
+        let p1 = self.create_emulated_sw_point_variable(p1)?;
+        self.emulated_sw_ecc_double_gate(p0, &p1, a)?;
+        Ok(p1)
+    }
+
+    /// Constrain that the point `p` is on the curve `y^2 = x^3 + a * x + b`,
+    /// unless it is infinity.
+    /// Return error if the input point variable is invalid.
+    pub fn enforce_emulated_sw_on_curve<E: EmulationConfig<F>>(
+        &mut self,
+        p: &EmulatedSWPointVariable<E>,
+        a: E,
+        b: E,
+    ) -> Result<(), CircuitError> {
+        let non_infinity = self.logic_neg(p.2)?;
+        // forcefully convert Variable into EmulatedVariable
+        // safe because it's boolean
+        let mut v = vec![self.zero(); E::NUM_LIMBS];
+        v[0] = non_infinity.0;
+        let coef = EmulatedVariable::<E>(v, core::marker::PhantomData);
+
+        let lhs = self.emulated_mul(&p.1, &p.1)?;
+        let x_square = self.emulated_mul(&p.0, &p.0)?;
+        let rhs = self.emulated_add_constant(&x_square, a)?;
+        let rhs = self.emulated_mul(&rhs, &p.0)?;
+        let rhs = self.emulated_add_constant(&rhs, b)?;
+
+        let lhs = self.emulated_mul(&lhs, &coef)?;
+        let rhs = self.emulated_mul(&rhs, &coef)?;
+        self.enforce_emulated_var_equal(&lhs, &rhs)
+    }
+
+    /// Obtain a variable to the scalar multiplication `scalar * base` for a
+    /// constant `base`, whose coordinates are emulated by `P::BaseField`.
+    ///
+    /// The scalar is split into windows of [`EMULATED_MSM_WINDOW_SIZE`] bits,
+    /// and the multiples of `base` for each window are computed natively, so
+    /// that only one point addition per window is needed.
+    pub fn emulated_sw_fixed_base_scalar_mul<P>(
+        &mut self,
+        base: &Affine<P>,
+        scalar: &EmulatedVariable<P::ScalarField>,
+    ) -> Result<EmulatedSWPointVariable<P::BaseField>, CircuitError>
+    where
+        P: SWCurveConfig,
+        P::BaseField: EmulationConfig<F>,
+        P::ScalarField: EmulationConfig<F>,
+    {
+        self.profile("emulated_sw_fixed_base_scalar_mul", |cs| {
+            let bits = cs.emulated_var_to_bits(scalar)?;
+            let mut acc = cs.create_constant_emulated_sw_point_variable(SWPoint::default())?;
+            let mut window_base = base.into_group();
+            for window in bits.chunks(EMULATED_MSM_WINDOW_SIZE) {
+                let mut table = vec![];
+                let mut multiple = Projective::<P>::zero();
+                for _ in 0..1 << window.len() {
+                    table.push(cs.create_constant_emulated_sw_point_variable(
+                        multiple.into_affine().into(),
+                    )?);
+                    multiple += window_base;
+                }
+                let selected = cs.emulated_sw_point_lookup(&table, window)?;
+                acc = cs.emulated_sw_ecc_add(&acc, &selected, P::COEFF_A)?;
+                window_base = multiple;
+            }
+            Ok(acc)
+        })
+    }
+
+    /// Obtain a variable to the scalar multiplication `scalar * base`, see
+    /// [`Self::emulated_sw_msm`].
+    pub fn emulated_sw_variable_base_scalar_mul<E, S>(
+        &mut self,
+        base: &EmulatedSWPointVariable<E>,
+        scalar: &EmulatedVariable<S>,
+        a: E,
+    ) -> Result<EmulatedSWPointVariable<E>, CircuitError>
+    where
+        E: EmulationConfig<F>,
+        S: EmulationConfig<F>,
+    {
+        self.emulated_sw_msm(
+            ark_std::slice::from_ref(base),
+            ark_std::slice::from_ref(scalar),
+            a,
+        )
+    }
+
+    /// Obtain a variable to the multi-scalar multiplication `sum_i scalars[i]
+    /// * bases[i]` over a curve with coefficient `a` whose base field is
+    /// emulated by `E`, and whose scalar field is emulated by `S`.
+    ///
+    /// The bases should be points in a subgroup of order `S::MODULUS`, so that
+    /// scalars do not need to be in canonical form. We use the
+    /// Straus-Shamir trick with windows of [`EMULATED_MSM_WINDOW_SIZE`] bits:
+    /// each base has a table of its `2^w` first multiples, and the result is
+    /// accumulated from the most significant window.
+    /// Return error if the number of bases does not match the number of
+    /// scalars.
+    pub fn emulated_sw_msm<E, S>(
+        &mut self,
+        bases: &[EmulatedSWPointVariable<E>],
+        scalars: &[EmulatedVariable<S>],
+        a: E,
+    ) -> Result<EmulatedSWPointVariable<E>, CircuitError>
+    where
+        E: EmulationConfig<F>,
+        S: EmulationConfig<F>,
+    {
+        if bases.len() != scalars.len() {
+            return Err(CircuitError::ParameterError(format!(
+                "bases length ({}) does not match scalar length ({})",
+                bases.len(),
+                scalars.len()
+            )));
+        }
+        self.profile("emulated_sw_msm", |cs| {
+            let num_windows = (S::T + EMULATED_MSM_WINDOW_SIZE - 1) / EMULATED_MSM_WINDOW_SIZE;
+            let mut scalars_bits = vec![];
+            for scalar in scalars {
+                let mut bits = cs.emulated_var_to_bits(scalar)?;
+                bits.resize(num_windows * EMULATED_MSM_WINDOW_SIZE, BoolVar(cs.zero()));
+                scalars_bits.push(bits);
+            }
+            let infinity = cs.create_constant_emulated_sw_point_variable(SWPoint::default())?;
+            let mut tables = vec![];
+            for base in bases {
+                let double = cs.emulated_sw_ecc_double(base, a)?;
+                let mut table = vec![infinity.clone(), base.clone(), double];
+                for _ in 3..1 << EMULATED_MSM_WINDOW_SIZE {
+                    let next = cs.emulated_sw_ecc_add(table.last().unwrap(), base, a)?;
+                    table.push(next);
+                }
+                tables.push(table);
+            }
+
+            let mut acc = infinity;
+            for i in (0..num_windows).rev() {
+                if i + 1 != num_windows {
+                    for _ in 0..EMULATED_MSM_WINDOW_SIZE {
+                        acc = cs.emulated_sw_ecc_double(&acc, a)?;
+                    }
+                }
+                for (table, bits) in tables.iter().zip(scalars_bits.iter()) {
+                    let window =
+                        &bits[i * EMULATED_MSM_WINDOW_SIZE..(i + 1) * EMULATED_MSM_WINDOW_SIZE];
+                    let selected = cs.emulated_sw_point_lookup(table, window)?;
+                    acc = cs.emulated_sw_ecc_add(&acc, &selected, a)?;
+                }
+            }
+            Ok(acc)
+        })
+    }
+
+    // Select `table[i]` where `bits` is the little-endian binary
+    // representation of `i`.
+    fn emulated_sw_point_lookup<E: EmulationConfig<F>>(
+        &mut self,
+        table: &[EmulatedSWPointVariable<E>],
+        bits: &[BoolVar],
+    ) -> Result<EmulatedSWPointVariable<E>, CircuitError> {
+        let mut table = table.to_vec();
+        for &bit in bits {
+            table = table
+                .chunks(2)
+                .map(|pair| self.binary_emulated_sw_point_vars_select(bit, &pair[0], &pair[1]))
+                .collect::<Result<Vec<_>, CircuitError>>()?;
+        }
+        Ok(table[0].clone())
+    }
 }
 
 #[cfg(test)]
@@ -425,4 +693,290 @@ mod tests {
             .check_circuit_satisfiability(&public_inputs)
             .is_err());
     }
+
+    #[test]
+    fn test_emulated_sw_point_addition_infinity() {
+        test_emulated_sw_point_addition_infinity_helper::<_, Fr254, Param377>();
+        test_emulated_sw_point_addition_infinity_helper::<_, Fr254, Param254>();
+    }
+
+    fn test_emulated_sw_point_addition_infinity_helper<E, F, P>()
+    where
+        E: EmulationConfig<F>,
+        F: PrimeField,
+        P: SWCurveConfig<BaseField = E>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let p = Projective::<P>::rand(&mut rng).into_affine();
+        let q = Projective::<P>::rand(&mut rng).into_affine();
+        let neg_p = -p;
+        let sum = (p + q).into_affine();
+
+        // P + (-P) = O
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let var_p = circuit.create_emulated_sw_point_variable(p.into()).unwrap();
+        let var_neg_p = circuit
+            .create_emulated_sw_point_variable(neg_p.into())
+            .unwrap();
+        let var_sum = circuit
+            .emulated_sw_ecc_add(&var_p, &var_neg_p, P::COEFF_A)
+            .unwrap();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_sum).unwrap(),
+            SWPoint::default()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // forged infinity flags
+        let forged = [
+            // P + (-P) is not infinity
+            (neg_p, p.into()),
+            (neg_p, SWPoint(E::one(), E::one(), false)),
+            // P + (-P) is infinity with nonzero coordinates
+            (neg_p, SWPoint(p.x, p.y, true)),
+            // P + Q is infinity
+            (q, SWPoint(sum.x, sum.y, true)),
+            (q, SWPoint::default()),
+            // P + P is infinity
+            (p, SWPoint::default()),
+        ];
+        for (other, wrong_result) in forged {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let var_p = circuit.create_emulated_sw_point_variable(p.into()).unwrap();
+            let var_other = circuit
+                .create_emulated_sw_point_variable(other.into())
+                .unwrap();
+            let var_wrong_result = circuit
+                .create_emulated_sw_point_variable(wrong_result)
+                .unwrap();
+            circuit
+                .emulated_sw_ecc_add_gate(&var_p, &var_other, &var_wrong_result, P::COEFF_A)
+                .unwrap();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_emulated_sw_point_doubling() {
+        test_emulated_sw_point_doubling_helper::<_, Fr254, Param377>();
+        #[cfg(feature = "secp256")]
+        test_emulated_sw_point_doubling_helper::<_, Fr254, ark_secp256r1::Config>();
+    }
+
+    fn test_emulated_sw_point_doubling_helper<E, F, P>()
+    where
+        E: EmulationConfig<F>,
+        F: PrimeField,
+        P: SWCurveConfig<BaseField = E>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let p = Projective::<P>::rand(&mut rng).into_affine();
+        let neutral = Projective::<P>::zero().into_affine();
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let var_p = circuit.create_emulated_sw_point_variable(p.into()).unwrap();
+        let var_neutral = circuit
+            .create_emulated_sw_point_variable(neutral.into())
+            .unwrap();
+        let var_double = circuit.emulated_sw_ecc_double(&var_p, P::COEFF_A).unwrap();
+        let expected: SWPoint<E> = (p + p).into_affine().into();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_double).unwrap(),
+            expected
+        );
+        let var_double_neutral = circuit
+            .emulated_sw_ecc_double(&var_neutral, P::COEFF_A)
+            .unwrap();
+        assert_eq!(
+            circuit
+                .emulated_sw_point_witness(&var_double_neutral)
+                .unwrap(),
+            SWPoint::default()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong results
+        let wrong_results = [
+            (p + p + P::GENERATOR).into_affine().into(),
+            p.into(),
+            SWPoint::default(),
+            SWPoint(expected.0, expected.1, true),
+        ];
+        for wrong_result in wrong_results {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let var_p = circuit.create_emulated_sw_point_variable(p.into()).unwrap();
+            let var_wrong_result = circuit
+                .create_emulated_sw_point_variable(wrong_result)
+                .unwrap();
+            circuit
+                .emulated_sw_ecc_double_gate(&var_p, &var_wrong_result, P::COEFF_A)
+                .unwrap();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_emulated_sw_on_curve() {
+        test_emulated_sw_on_curve_helper::<_, Fr254, Param254>();
+        #[cfg(feature = "secp256")]
+        test_emulated_sw_on_curve_helper::<_, Fr254, ark_secp256r1::Config>();
+    }
+
+    fn test_emulated_sw_on_curve_helper<E, F, P>()
+    where
+        E: EmulationConfig<F>,
+        F: PrimeField,
+        P: SWCurveConfig<BaseField = E>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let p = Projective::<P>::rand(&mut rng).into_affine();
+        let points = [
+            (SWPoint::from(p), true),
+            (SWPoint::default(), true),
+            (SWPoint(p.x, p.y + E::one(), false), false),
+            (SWPoint(E::zero(), E::zero(), false), P::COEFF_B.is_zero()),
+        ];
+        for (point, expected) in points {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let var_p = circuit.create_emulated_sw_point_variable(point).unwrap();
+            circuit
+                .enforce_emulated_sw_on_curve(&var_p, P::COEFF_A, P::COEFF_B)
+                .unwrap();
+            assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), expected);
+        }
+    }
+
+    #[test]
+    fn test_emulated_sw_fixed_base_scalar_mul() {
+        test_emulated_sw_fixed_base_scalar_mul_helper::<Fr254, ark_bls12_381::g1::Config>();
+        #[cfg(feature = "secp256")]
+        test_emulated_sw_fixed_base_scalar_mul_helper::<Fr254, ark_secp256k1::Config>();
+    }
+
+    fn test_emulated_sw_fixed_base_scalar_mul_helper<F, P>()
+    where
+        F: PrimeField,
+        P: SWCurveConfig,
+        P::BaseField: EmulationConfig<F>,
+        P::ScalarField: EmulationConfig<F>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let base = Projective::<P>::rand(&mut rng).into_affine();
+        let s = P::ScalarField::rand(&mut rng);
+
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(16);
+        let var_s = circuit.create_emulated_variable(s).unwrap();
+        let var_zero = circuit
+            .create_emulated_variable(P::ScalarField::zero())
+            .unwrap();
+        let var_mul = circuit
+            .emulated_sw_fixed_base_scalar_mul(&base, &var_s)
+            .unwrap();
+        let expected: SWPoint<P::BaseField> = (base * s).into_affine().into();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_mul).unwrap(),
+            expected
+        );
+        let var_mul_zero = circuit
+            .emulated_sw_fixed_base_scalar_mul(&base, &var_zero)
+            .unwrap();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_mul_zero).unwrap(),
+            SWPoint::default()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong scalar
+        *circuit.witness_mut(var_s.0[0]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
+    #[test]
+    fn test_emulated_sw_msm() {
+        test_emulated_sw_msm_helper::<_, _, Fr254, ark_bls12_381::g1::Config>();
+        #[cfg(feature = "secp256")]
+        {
+            test_emulated_sw_msm_helper::<_, _, Fr254, ark_secp256k1::Config>();
+            test_emulated_sw_msm_helper::<_, _, Fr254, ark_secp256r1::Config>();
+        }
+    }
+
+    fn test_emulated_sw_msm_helper<E, S, F, P>()
+    where
+        E: EmulationConfig<F>,
+        S: EmulationConfig<F>,
+        F: PrimeField,
+        P: SWCurveConfig<BaseField = E, ScalarField = S>,
+    {
+        let mut rng = jf_utils::test_rng();
+        let p1 = Projective::<P>::rand(&mut rng);
+        let p2 = Projective::<P>::rand(&mut rng);
+        let s1 = S::rand(&mut rng);
+        let s2 = S::rand(&mut rng);
+
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(16);
+        let var_p1 = circuit
+            .create_emulated_sw_point_variable(p1.into_affine().into())
+            .unwrap();
+        let var_p2 = circuit
+            .create_emulated_sw_point_variable(p2.into_affine().into())
+            .unwrap();
+        let var_s1 = circuit.create_emulated_variable(s1).unwrap();
+        let var_s2 = circuit.create_emulated_variable(s2).unwrap();
+        let var_zero = circuit.create_emulated_variable(S::zero()).unwrap();
+
+        let var_msm = circuit
+            .emulated_sw_msm(
+                &[var_p1.clone(), var_p2.clone()],
+                &[var_s1.clone(), var_s2],
+                P::COEFF_A,
+            )
+            .unwrap();
+        let expected: SWPoint<E> = (p1 * s1 + p2 * s2).into_affine().into();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_msm).unwrap(),
+            expected
+        );
+        let var_mul = circuit
+            .emulated_sw_variable_base_scalar_mul(&var_p1, &var_s1, P::COEFF_A)
+            .unwrap();
+        let expected: SWPoint<E> = (p1 * s1).into_affine().into();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_mul).unwrap(),
+            expected
+        );
+        let var_mul = circuit
+            .emulated_sw_variable_base_scalar_mul(&var_p1, &var_zero, P::COEFF_A)
+            .unwrap();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_mul).unwrap(),
+            SWPoint::default()
+        );
+        // the terms cancel out, so that the sum of the first window of each
+        // base is infinity
+        let var_neg_p1 = circuit
+            .create_emulated_sw_point_variable((-p1).into_affine().into())
+            .unwrap();
+        let var_msm_zero = circuit
+            .emulated_sw_msm(
+                &[var_p1.clone(), var_neg_p1],
+                &[var_s1.clone(), var_s1.clone()],
+                P::COEFF_A,
+            )
+            .unwrap();
+        assert_eq!(
+            circuit.emulated_sw_point_witness(&var_msm_zero).unwrap(),
+            SWPoint::default()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong result
+        *circuit.witness_mut(var_msm.0 .0[0]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // mismatched lengths
+        assert!(circuit
+            .emulated_sw_msm(&[var_p1], &[var_s1.clone(), var_s1], P::COEFF_A)
+            .is_err());
+    }
 }
@@ -67,19 +67,21 @@ where
         scalars: &[Variable],
         scalar_bit_length: usize,
     ) -> Result<PointVariable, CircuitError> {
-        if bases.len() != scalars.len() {
-            return Err(CircuitError::ParameterError(format!(
-                "bases length ({}) does not match scalar length ({})",
-                bases.len(),
-                scalars.len()
-            )));
-        }
+        self.profile("msm", |cs| {
+            if bases.len() != scalars.len() {
+                return Err(CircuitError::ParameterError(format!(
+                    "bases length ({}) does not match scalar length ({})",
+                    bases.len(),
+                    scalars.len()
+                )));
+            }
 
-        if self.support_lookup() {
-            msm_pippenger::<F, P>(self, bases, scalars, scalar_bit_length)
-        } else {
-            msm_naive::<F, P>(self, bases, scalars, scalar_bit_length)
-        }
+            if cs.support_lookup() {
+                msm_pippenger::<F, P>(cs, bases, scalars, scalar_bit_length)
+            } else {
+                msm_naive::<F, P>(cs, bases, scalars, scalar_bit_length)
+            }
+        })
     }
 }
 
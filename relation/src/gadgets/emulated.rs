@@ -20,6 +20,9 @@ use itertools::izip;
 use num_bigint::BigUint;
 
 /// Parameters needed for emulating field operations over [`PrimeField`].
+///
+/// Safe parameters can be obtained from [`derive_emulation_params`], and
+/// checked with [`check_emulation_config`].
 pub trait EmulationConfig<F: PrimeField>: PrimeField {
     /// Log2 of the other CRT modulus is 2^T.
     const T: usize;
@@ -29,6 +32,92 @@ pub trait EmulationConfig<F: PrimeField>: PrimeField {
     const NUM_LIMBS: usize;
 }
 
+/// Derive `(T, B, NUM_LIMBS)` for emulating a field whose modulus has
+/// `emulated_bits` bits over a native field whose modulus has `native_bits`
+/// bits. The smallest number of limbs is chosen, and then the smallest limb
+/// size.
+///
+/// Writing `q < 2^emulated_bits` and `p >= 2^(native_bits - 1)` for the two
+/// moduli, the parameters satisfy:
+/// - `T >= emulated_bits`, so that every emulated element fits in `T` bits;
+/// - `T >= 2 * emulated_bits - native_bits + 1`, so that `p * 2^T > q^2 + q`
+///   and `a * b - k * q = c` holds over the integers once it holds modulo `p`
+///   and `2^T`;
+/// - `2 * B + L + 3 <= native_bits` where `L` is the bit length of `2 *
+///   NUM_LIMBS`, so that the sum of limb products, the carries and their
+///   shifts in each limb of the multiplication are less than `p`.
+///
+/// Panics (at compile time when used in a constant) if the native field is
+/// too small.
+pub const fn derive_emulation_params(
+    emulated_bits: u32,
+    native_bits: u32,
+) -> (usize, usize, usize) {
+    let emulated_bits = emulated_bits as usize;
+    let native_bits = native_bits as usize;
+    let min_t = if 2 * emulated_bits + 1 > native_bits + emulated_bits {
+        2 * emulated_bits + 1 - native_bits
+    } else {
+        emulated_bits
+    };
+    let mut num_limbs: usize = 1;
+    loop {
+        let log_num_vals = (usize::BITS - (2 * num_limbs).leading_zeros()) as usize;
+        if native_bits < 2 + log_num_vals + 3 {
+            panic!("native field is too small for field emulation");
+        }
+        let max_b = (native_bits - log_num_vals - 3) / 2;
+        let b = (min_t + num_limbs - 1) / num_limbs;
+        if b <= max_b {
+            return (b * num_limbs, b, num_limbs);
+        }
+        num_limbs += 1;
+    }
+}
+
+/// Check that the parameters of an [`EmulationConfig`] are safe, i.e. that
+/// none of the integer relations enforced by [`PlonkCircuit::emulated_mul_gate`]
+/// overflows in the native field:
+/// - `B * NUM_LIMBS = T` and `q < 2^T`;
+/// - `p * 2^T > q^2 + q`;
+/// - for every limb, the sum of `2 * NUM_LIMBS` limb products, the incoming
+///   carry, the output limb and the outgoing carry shifted by `B` bits is less
+///   than `p`.
+///
+/// Return error with the violated condition otherwise.
+pub fn check_emulation_config<E, F>() -> Result<(), CircuitError>
+where
+    E: EmulationConfig<F>,
+    F: PrimeField,
+{
+    let err = |msg: &str| Err(CircuitError::ParameterError(msg.to_string()));
+    if E::B == 0 || E::B.checked_mul(E::NUM_LIMBS) != Some(E::T) {
+        return err("B * NUM_LIMBS should equal to T");
+    }
+    let p: BigUint = F::MODULUS.into();
+    let q: BigUint = E::MODULUS.into();
+    let two_to_t = BigUint::one() << E::T;
+    if q >= two_to_t {
+        return err("emulated modulus does not fit in T bits");
+    }
+    if &p * &two_to_t <= &q * &q + &q {
+        return err("p * 2^T should be larger than q^2 + q");
+    }
+    // same bounds as the range checks on the carries in `emulated_mul_gate`
+    let num_vals = 2u64 * (E::NUM_LIMBS as u64);
+    let log_num_vals = (u64::BITS - num_vals.leading_zeros()) as usize;
+    let limb_max = (BigUint::one() << E::B) - 1u32;
+    let max_carry = BigUint::one() << (E::B + log_num_vals);
+    let max_limb_sum = BigUint::from(num_vals) * &limb_max * &limb_max
+        + &max_carry
+        + &limb_max
+        + (&max_carry << E::B);
+    if max_limb_sum >= p {
+        return err("limb computation overflows the native field");
+    }
+    Ok(())
+}
+
 /// A struct that can be serialized into `Vec` of field elements.
 pub trait SerializableEmulatedStruct<F: PrimeField> {
     /// Serialize into a `Vec` of field elements.
@@ -295,10 +384,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: &EmulatedVariable<E>,
         b: &EmulatedVariable<E>,
     ) -> Result<EmulatedVariable<E>, CircuitError> {
-        let c = self.emulated_witness(a)? * self.emulated_witness(b)?;
-        let c = self.create_emulated_variable(c)?;
-        self.emulated_mul_gate(a, b, &c)?;
-        Ok(c)
+        self.profile("emulated_mul", |cs| {
+            let c = cs.emulated_witness(a)? * cs.emulated_witness(b)?;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_mul_gate(a, b, &c)?;
+            Ok(c)
+        })
     }
 
     /// Constrain that a*b=c in the emulated field for a constant b.
@@ -412,10 +503,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: &EmulatedVariable<E>,
         b: E,
     ) -> Result<EmulatedVariable<E>, CircuitError> {
-        let c = self.emulated_witness(a)? * b;
-        let c = self.create_emulated_variable(c)?;
-        self.emulated_mul_constant_gate(a, b, &c)?;
-        Ok(c)
+        self.profile("emulated_mul_constant", |cs| {
+            let c = cs.emulated_witness(a)? * b;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_mul_constant_gate(a, b, &c)?;
+            Ok(c)
+        })
     }
 
     /// Constrain that a+b=c in the emulated field.
@@ -484,10 +577,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: &EmulatedVariable<E>,
         b: &EmulatedVariable<E>,
     ) -> Result<EmulatedVariable<E>, CircuitError> {
-        let c = self.emulated_witness(a)? + self.emulated_witness(b)?;
-        let c = self.create_emulated_variable(c)?;
-        self.emulated_add_gate(a, b, &c)?;
-        Ok(c)
+        self.profile("emulated_add", |cs| {
+            let c = cs.emulated_witness(a)? + cs.emulated_witness(b)?;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_add_gate(a, b, &c)?;
+            Ok(c)
+        })
     }
 
     /// Return an [`EmulatedVariable`] which equals to a-b.
@@ -496,10 +591,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: &EmulatedVariable<E>,
         b: &EmulatedVariable<E>,
     ) -> Result<EmulatedVariable<E>, CircuitError> {
-        let c = self.emulated_witness(a)? - self.emulated_witness(b)?;
-        let c = self.create_emulated_variable(c)?;
-        self.emulated_add_gate(&c, b, a)?;
-        Ok(c)
+        self.profile("emulated_sub", |cs| {
+            let c = cs.emulated_witness(a)? - cs.emulated_witness(b)?;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_add_gate(&c, b, a)?;
+            Ok(c)
+        })
     }
 
     /// Constrain that a+b=c in the emulated field.
@@ -568,10 +665,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: &EmulatedVariable<E>,
         b: E,
     ) -> Result<EmulatedVariable<E>, CircuitError> {
-        let c = self.emulated_witness(a)? + b;
-        let c = self.create_emulated_variable(c)?;
-        self.emulated_add_constant_gate(a, b, &c)?;
-        Ok(c)
+        self.profile("emulated_add_constant", |cs| {
+            let c = cs.emulated_witness(a)? + b;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_add_constant_gate(a, b, &c)?;
+            Ok(c)
+        })
     }
 
     /// Return an [`EmulatedVariable`] which equals to a - b where b is a
@@ -581,10 +680,12 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: &EmulatedVariable<E>,
         b: E,
     ) -> Result<EmulatedVariable<E>, CircuitError> {
-        let c = self.emulated_witness(a)? - b;
-        let c = self.create_emulated_variable(c)?;
-        self.emulated_add_constant_gate(&c, b, a)?;
-        Ok(c)
+        self.profile("emulated_sub_constant", |cs| {
+            let c = cs.emulated_witness(a)? - b;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_add_constant_gate(&c, b, a)?;
+            Ok(c)
+        })
     }
     /// Obtain an emulated variable of the conditional selection from 2 emulated
     /// variables. `b` is a boolean variable that indicates selection of P_b
@@ -655,6 +756,218 @@ impl<F: PrimeField> PlonkCircuit<F> {
         self.logic_and_all(&c)
     }
 
+    /// Return an [`EmulatedVariable`] which equals to a^{-1}.
+    /// The circuit will be unsatisfiable if `a` is zero.
+    pub fn emulated_inverse<E: EmulationConfig<F>>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+    ) -> Result<EmulatedVariable<E>, CircuitError> {
+        self.profile("emulated_inverse", |cs| {
+            let a_inv = cs.emulated_witness(a)?.inverse().unwrap_or_else(E::zero);
+            let a_inv = cs.create_emulated_variable(a_inv)?;
+            let one = cs.create_constant_emulated_variable(E::one())?;
+            cs.emulated_mul_gate(a, &a_inv, &one)?;
+            Ok(a_inv)
+        })
+    }
+
+    /// Return an [`EmulatedVariable`] which equals to a/b.
+    /// The circuit will be unsatisfiable if `b` is zero.
+    pub fn emulated_div<E: EmulationConfig<F>>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+        b: &EmulatedVariable<E>,
+    ) -> Result<EmulatedVariable<E>, CircuitError> {
+        self.profile("emulated_div", |cs| {
+            let b_inv = cs.emulated_witness(b)?.inverse().unwrap_or_else(E::zero);
+            let c = cs.emulated_witness(a)? * b_inv;
+            let c = cs.create_emulated_variable(c)?;
+            cs.emulated_mul_gate(b, &c, a)?;
+            Ok(c)
+        })
+    }
+
+    /// Return a pair `(is_square, root)` for an emulated field element `a`.
+    /// If `a` is a square, `is_square` is true and `root^2 = a`; otherwise
+    /// `is_square` is false and `root^2 = g * a` where `g` is the fixed
+    /// quadratic non-residue `E::GENERATOR`, which proves that `a` is not a
+    /// square. Which of the two square roots is returned is up to the prover.
+    pub fn emulated_sqrt<E: EmulationConfig<F>>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+    ) -> Result<(BoolVar, EmulatedVariable<E>), CircuitError> {
+        self.profile("emulated_sqrt", |cs| {
+            let non_residue = E::GENERATOR;
+            let a_val = cs.emulated_witness(a)?;
+            let (is_square, root) = match a_val.sqrt() {
+                Some(root) => (true, root),
+                None => (
+                    false,
+                    (a_val * non_residue).sqrt().ok_or_else(|| {
+                        CircuitError::FieldAlgebraError(
+                            "E::GENERATOR is not a quadratic non-residue".to_string(),
+                        )
+                    })?,
+                ),
+            };
+            let is_square = cs.create_boolean_variable(is_square)?;
+            let root = cs.create_emulated_variable(root)?;
+
+            let shifted_a = cs.emulated_mul_constant(a, non_residue)?;
+            let square = cs.conditional_select_emulated(is_square, &shifted_a, a)?;
+            cs.emulated_mul_gate(&root, &root, &square)?;
+            // zero is a square, and `g * 0` is a square as well, so we need to
+            // rule out claiming that zero is not a square.
+            let is_zero = cs.is_emulated_var_zero(a)?;
+            let is_non_zero = cs.logic_neg(is_zero)?;
+            cs.logic_or_gate(is_square, is_non_zero)?;
+            Ok((is_square, root))
+        })
+    }
+
+    /// Obtain a bool variable representing whether the input emulated variable
+    /// is a square in the emulated field, i.e. whether its Legendre symbol is
+    /// not -1. Return error if variables are invalid.
+    pub fn is_emulated_square<E: EmulationConfig<F>>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+    ) -> Result<BoolVar, CircuitError> {
+        self.emulated_sqrt(a).map(|(is_square, _)| is_square)
+    }
+
+    /// Return the little-endian bit decomposition of the integer represented
+    /// by the limbs of `a`, which has `E::T` bits. Note that the integer is
+    /// only guaranteed to be congruent to `a` modulo `E::MODULUS` unless
+    /// [`Self::enforce_emulated_canonical`] is applied.
+    pub fn emulated_var_to_bits<E: EmulationConfig<F>>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+    ) -> Result<Vec<BoolVar>, CircuitError> {
+        self.check_vars_bound(&a.0)?;
+        let mut bits = Vec::with_capacity(E::T);
+        for &limb in a.0.iter() {
+            bits.extend(self.unpack(limb, E::B)?);
+        }
+        Ok(bits)
+    }
+
+    /// Constrain that the integer represented by the limbs of `a` is less than
+    /// `E::MODULUS`, i.e. `a` is in canonical form.
+    /// Return error if the input variable is invalid.
+    pub fn enforce_emulated_canonical<E: EmulationConfig<F>>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+    ) -> Result<(), CircuitError> {
+        self.check_vars_bound(&a.0)?;
+        // a + d = E::MODULUS - 1 for some non-negative d
+        let max: BigUint = E::MODULUS.into() - 1u32;
+        let a_val = self.emulated_limbs_witness(&a.0, E::B)?.min(max.clone());
+        let d = self.create_emulated_variable(E::from(&max - a_val))?;
+        let max = biguint_to_limbs::<F>(&max, E::B, E::NUM_LIMBS)
+            .into_iter()
+            .map(|v| self.create_constant_variable(v))
+            .collect::<Result<Vec<_>, CircuitError>>()?;
+        self.enforce_limbs_sum(&a.0, &d.0, &max, E::B)
+    }
+
+    /// Constrain that the integers represented by the limbs of `a` and `b`
+    /// satisfy `a = b + wrap * S::MODULUS` for a boolean `wrap`, which is
+    /// returned. When both `a` and `b` are canonical and `E::MODULUS < 2 *
+    /// S::MODULUS`, this is equivalent to `a mod S::MODULUS = b`.
+    /// Return error if `E` and `S` have different limb layouts, or if the
+    /// input variables are invalid.
+    pub fn enforce_emulated_mod_reduce<E, S>(
+        &mut self,
+        a: &EmulatedVariable<E>,
+        b: &EmulatedVariable<S>,
+    ) -> Result<BoolVar, CircuitError>
+    where
+        E: EmulationConfig<F>,
+        S: EmulationConfig<F>,
+    {
+        if E::B != S::B || E::NUM_LIMBS != S::NUM_LIMBS {
+            return Err(CircuitError::ParameterError(
+                "enforce_emulated_mod_reduce: mismatched limb layouts".to_string(),
+            ));
+        }
+        self.check_vars_bound(&a.0)?;
+        self.check_vars_bound(&b.0)?;
+        let modulus: BigUint = S::MODULUS.into();
+        let wrap = self.emulated_limbs_witness(&a.0, E::B)?
+            >= self.emulated_limbs_witness(&b.0, S::B)? + &modulus;
+        let wrap = self.create_boolean_variable(wrap)?;
+        let modulus = biguint_to_limbs::<F>(&modulus, S::B, S::NUM_LIMBS);
+        let b_pow = F::from(2u32).pow([S::B as u64]);
+        let limb_bound = BigUint::one() << S::B;
+        let mut carry = self.zero();
+        for (i, (&a, &b, &m)) in izip!(&a.0, &b.0, &modulus).enumerate() {
+            // b + wrap * m + carry < 2^(B + 1), so that each carry is boolean
+            let next_carry = if i + 1 == S::NUM_LIMBS {
+                self.zero()
+            } else {
+                let sum = <F as Into<BigUint>>::into(
+                    self.witness(b)? + self.witness(wrap.0)? * m + self.witness(carry)?,
+                );
+                self.create_boolean_variable(sum >= limb_bound)?.into()
+            };
+            self.lc_gate(
+                &[b, wrap.0, carry, next_carry, a],
+                &[F::one(), m, F::one(), -b_pow],
+            )?;
+            carry = next_carry;
+        }
+        Ok(wrap)
+    }
+
+    /// Constrain that the integers represented by `a_limbs`, `b_limbs` and
+    /// `c_limbs` satisfy `a + b = c`, where all limbs have `limb_bits` bits.
+    /// This function doesn't range check the limbs, we assume that they are
+    /// already performed elsewhere.
+    pub(crate) fn enforce_limbs_sum(
+        &mut self,
+        a_limbs: &[Variable],
+        b_limbs: &[Variable],
+        c_limbs: &[Variable],
+        limb_bits: usize,
+    ) -> Result<(), CircuitError> {
+        if a_limbs.len() != b_limbs.len() || a_limbs.len() != c_limbs.len() {
+            return Err(CircuitError::ParameterError(
+                "enforce_limbs_sum: mismatched number of limbs".to_string(),
+            ));
+        }
+        let b_pow = F::from(2u32).pow([limb_bits as u64]);
+        let limb_bound = BigUint::one() << limb_bits;
+        let mut carry = self.zero();
+        for (i, (&a, &b, &c)) in izip!(a_limbs, b_limbs, c_limbs).enumerate() {
+            // the carry out of the last limb should be zero
+            let next_carry = if i + 1 == a_limbs.len() {
+                self.zero()
+            } else {
+                let sum = <F as Into<BigUint>>::into(self.witness(a)?)
+                    + <F as Into<BigUint>>::into(self.witness(b)?)
+                    + <F as Into<BigUint>>::into(self.witness(carry)?);
+                self.create_boolean_variable(sum >= limb_bound)?.into()
+            };
+            self.lc_gate(
+                &[a, b, carry, next_carry, c],
+                &[F::one(), F::one(), F::one(), -b_pow],
+            )?;
+            carry = next_carry;
+        }
+        Ok(())
+    }
+
+    // The integer represented by `limbs` of `limb_bits` bits each.
+    fn emulated_limbs_witness(
+        &self,
+        limbs: &[Variable],
+        limb_bits: usize,
+    ) -> Result<BigUint, CircuitError> {
+        limbs.iter().rev().try_fold(BigUint::zero(), |acc, &limb| {
+            Ok((acc << limb_bits) + <F as Into<BigUint>>::into(self.witness(limb)?))
+        })
+    }
+
     /// Given an emulated field element `a`, return `a mod F::MODULUS` in the
     /// native field.
     fn mod_to_native_field<E: EmulationConfig<F>>(
@@ -704,13 +1017,98 @@ impl EmulationConfig<ark_bn254::Fr> for ark_bn254::Fq {
     const NUM_LIMBS: usize = 3;
 }
 
+// Implement `EmulationConfig<$native>` for each `$emulated` with parameters
+// from `derive_emulation_params`.
+macro_rules! impl_emulation_config {
+    ($native:ty; $($emulated:ty),+ $(,)?) => {
+        $(
+            impl EmulationConfig<$native> for $emulated {
+                const T: usize = derive_emulation_params(
+                    <$emulated as PrimeField>::MODULUS_BIT_SIZE,
+                    <$native as PrimeField>::MODULUS_BIT_SIZE,
+                )
+                .0;
+                const B: usize = derive_emulation_params(
+                    <$emulated as PrimeField>::MODULUS_BIT_SIZE,
+                    <$native as PrimeField>::MODULUS_BIT_SIZE,
+                )
+                .1;
+                const NUM_LIMBS: usize = derive_emulation_params(
+                    <$emulated as PrimeField>::MODULUS_BIT_SIZE,
+                    <$native as PrimeField>::MODULUS_BIT_SIZE,
+                )
+                .2;
+            }
+        )+
+    };
+}
+
+// `ark_bw6_761::Fr` is `ark_bls12_377::Fq`.
+impl_emulation_config!(
+    ark_bn254::Fr;
+    ark_bls12_381::Fq,
+    ark_bls12_381::Fr,
+    ark_bw6_761::Fq,
+);
+impl_emulation_config!(
+    ark_bls12_381::Fr;
+    ark_bls12_381::Fq,
+    ark_bls12_377::Fq,
+    ark_bw6_761::Fq,
+    ark_bn254::Fq,
+    ark_bn254::Fr,
+);
+impl_emulation_config!(
+    ark_bls12_377::Fr;
+    ark_bls12_381::Fq,
+    ark_bls12_381::Fr,
+    ark_bls12_377::Fq,
+    ark_bw6_761::Fq,
+    ark_bn254::Fq,
+    ark_bn254::Fr,
+);
+
+// Fields of the ECDSA curves secp256k1 and P-256.
+#[cfg(feature = "secp256")]
+impl_emulation_config!(
+    ark_bn254::Fr;
+    ark_secp256k1::Fq,
+    ark_secp256k1::Fr,
+    ark_secp256r1::Fq,
+    ark_secp256r1::Fr,
+);
+#[cfg(feature = "secp256")]
+impl_emulation_config!(
+    ark_bls12_381::Fr;
+    ark_secp256k1::Fq,
+    ark_secp256k1::Fr,
+    ark_secp256r1::Fq,
+    ark_secp256r1::Fr,
+);
+#[cfg(feature = "secp256")]
+impl_emulation_config!(
+    ark_bls12_377::Fr;
+    ark_secp256k1::Fq,
+    ark_secp256k1::Fr,
+    ark_secp256r1::Fq,
+    ark_secp256r1::Fr,
+);
+
+// Fields of ed25519, which are also those of curve25519.
+#[cfg(feature = "ed25519")]
+impl_emulation_config!(ark_bn254::Fr; ark_ed25519::Fq, ark_ed25519::Fr);
+#[cfg(feature = "ed25519")]
+impl_emulation_config!(ark_bls12_381::Fr; ark_ed25519::Fq, ark_ed25519::Fr);
+#[cfg(feature = "ed25519")]
+impl_emulation_config!(ark_bls12_377::Fr; ark_ed25519::Fq, ark_ed25519::Fr);
+
 #[cfg(test)]
 mod tests {
-    use super::EmulationConfig;
+    use super::{check_emulation_config, derive_emulation_params, EmulationConfig};
     use crate::{gadgets::from_emulated_field, Circuit, PlonkCircuit};
     use ark_bls12_377::Fq as Fq377;
     use ark_bn254::{Fq as Fq254, Fr as Fr254};
-    use ark_ff::{MontFp, PrimeField};
+    use ark_ff::{BigInteger, MontFp, PrimeField};
 
     #[test]
     fn test_basics() {
@@ -732,10 +1130,74 @@ mod tests {
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
     }
 
+    // Unsafe parameters for testing `check_emulation_config`
+    impl EmulationConfig<ark_bls12_381::Fr> for ark_bls12_377::Fr {
+        const T: usize = 256;
+        const B: usize = 128;
+        const NUM_LIMBS: usize = 2;
+    }
+    impl EmulationConfig<Fr254> for ark_bls12_377::Fr {
+        const T: usize = 250;
+        const B: usize = 125;
+        const NUM_LIMBS: usize = 2;
+    }
+
+    #[test]
+    fn test_emulation_params() {
+        // Fq377 and Fq254 over Fr254 are hand-tuned
+        check_emulation_config::<Fq377, Fr254>().unwrap();
+        check_emulation_config::<Fq254, Fr254>().unwrap();
+        assert_eq!(derive_emulation_params(377, 254), (505, 101, 5));
+        assert_eq!(derive_emulation_params(254, 254), (255, 85, 3));
+
+        macro_rules! check_configs {
+            ($native:ty; $($emulated:ty),+) => {
+                $(
+                    check_emulation_config::<$emulated, $native>().unwrap();
+                )+
+            };
+        }
+        #[allow(unused_macros)]
+        macro_rules! for_all_natives {
+            ($($emulated:ty),+) => {
+                check_configs!(Fr254; $($emulated),+);
+                check_configs!(ark_bls12_381::Fr; $($emulated),+);
+                check_configs!(ark_bls12_377::Fr; $($emulated),+);
+            };
+        }
+        check_configs!(Fr254; ark_bls12_381::Fq, ark_bls12_381::Fr, ark_bw6_761::Fq);
+        check_configs!(
+            ark_bls12_381::Fr;
+            ark_bls12_381::Fq, Fq377, ark_bw6_761::Fq, Fq254, Fr254
+        );
+        check_configs!(
+            ark_bls12_377::Fr;
+            ark_bls12_381::Fq, ark_bls12_381::Fr, Fq377, ark_bw6_761::Fq, Fq254, Fr254
+        );
+        #[cfg(feature = "secp256")]
+        for_all_natives!(
+            ark_secp256k1::Fq,
+            ark_secp256k1::Fr,
+            ark_secp256r1::Fq,
+            ark_secp256r1::Fr
+        );
+        #[cfg(feature = "ed25519")]
+        for_all_natives!(ark_ed25519::Fq, ark_ed25519::Fr);
+
+        // bad parameters
+        assert!(check_emulation_config::<ark_bls12_377::Fr, ark_bls12_381::Fr>().is_err());
+        assert!(check_emulation_config::<ark_bls12_377::Fr, Fr254>().is_err());
+    }
+
     #[test]
     fn test_emulated_add() {
         test_emulated_add_helper::<Fq377, Fr254>();
         test_emulated_add_helper::<Fq254, Fr254>();
+        #[cfg(feature = "secp256")]
+        test_emulated_add_helper::<ark_secp256k1::Fq, Fr254>();
+        #[cfg(feature = "ed25519")]
+        test_emulated_add_helper::<ark_ed25519::Fq, Fr254>();
+        test_emulated_add_helper::<ark_bw6_761::Fq, ark_bls12_381::Fr>();
     }
 
     fn test_emulated_add_helper<E, F>()
@@ -767,6 +1229,17 @@ mod tests {
     fn test_emulated_mul() {
         test_emulated_mul_helper::<Fq377, Fr254>();
         test_emulated_mul_helper::<Fq254, Fr254>();
+        #[cfg(feature = "secp256")]
+        {
+            test_emulated_mul_helper::<ark_secp256k1::Fq, Fr254>();
+            test_emulated_mul_helper::<ark_secp256r1::Fr, ark_bls12_377::Fr>();
+        }
+        #[cfg(feature = "ed25519")]
+        {
+            test_emulated_mul_helper::<ark_ed25519::Fq, Fr254>();
+            test_emulated_mul_helper::<ark_ed25519::Fr, ark_bls12_381::Fr>();
+        }
+        test_emulated_mul_helper::<ark_bw6_761::Fq, ark_bls12_381::Fr>();
 
         // test for issue (https://github.com/EspressoSystems/jellyfish/issues/306)
         let x : Fq377= MontFp!("218393408942992446968589193493746660101651787560689350338764189588519393175121782177906966561079408675464506489966");
@@ -865,4 +1338,192 @@ mod tests {
         circuit.enforce_emulated_var_equal(&var_x, &var_y).unwrap();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
     }
+
+    #[test]
+    fn test_emulated_inverse_and_div() {
+        test_emulated_inverse_and_div_helper::<Fq377, Fr254>();
+        test_emulated_inverse_and_div_helper::<Fq254, Fr254>();
+    }
+
+    fn test_emulated_inverse_and_div_helper<E, F>()
+    where
+        E: EmulationConfig<F>,
+        F: PrimeField,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let x = E::from(E::MODULUS.into() - 7u64);
+        let y = E::from(3u64);
+        let var_x = circuit.create_emulated_variable(x).unwrap();
+        let var_y = circuit.create_emulated_variable(y).unwrap();
+        let var_x_inv = circuit.emulated_inverse(&var_x).unwrap();
+        assert_eq!(
+            circuit.emulated_witness(&var_x_inv).unwrap(),
+            x.inverse().unwrap()
+        );
+        let var_z = circuit.emulated_div(&var_x, &var_y).unwrap();
+        assert_eq!(circuit.emulated_witness(&var_z).unwrap(), x / y);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong witness
+        *circuit.witness_mut(var_z.0[0]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // zero has no inverse
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let var_zero = circuit.create_emulated_variable(E::zero()).unwrap();
+        circuit.emulated_inverse(&var_zero).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let var_x = circuit.create_emulated_variable(x).unwrap();
+        let var_zero = circuit.create_emulated_variable(E::zero()).unwrap();
+        circuit.emulated_div(&var_x, &var_zero).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
+    #[test]
+    fn test_emulated_sqrt() {
+        test_emulated_sqrt_helper::<Fq377, Fr254>();
+        test_emulated_sqrt_helper::<Fq254, Fr254>();
+    }
+
+    fn test_emulated_sqrt_helper<E, F>()
+    where
+        E: EmulationConfig<F>,
+        F: PrimeField,
+    {
+        assert!(E::GENERATOR.legendre().is_qnr());
+        let square = E::from(E::MODULUS.into() - 12387u64).square();
+        let non_square = square * E::GENERATOR;
+        for (x, expected) in [
+            (square, true),
+            (non_square, false),
+            (E::zero(), true),
+            (E::one(), true),
+        ] {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let var_x = circuit.create_emulated_variable(x).unwrap();
+            let (is_square, root) = circuit.emulated_sqrt(&var_x).unwrap();
+            assert_eq!(
+                circuit.witness(is_square.into()).unwrap(),
+                F::from(expected)
+            );
+            let root = circuit.emulated_witness(&root).unwrap();
+            if expected {
+                assert_eq!(root.square(), x);
+            } else {
+                assert_eq!(root.square(), x * E::GENERATOR);
+            }
+            let is_square_2 = circuit.is_emulated_square(&var_x).unwrap();
+            assert_eq!(
+                circuit.witness(is_square_2.into()).unwrap(),
+                F::from(expected)
+            );
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // flipping the claim breaks the circuit
+            *circuit.witness_mut(is_square.into()) = F::from(!expected);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // a dishonest prover cannot claim that zero is not a square
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let var_x = circuit.create_emulated_variable(E::zero()).unwrap();
+        let (is_square, _) = circuit.emulated_sqrt(&var_x).unwrap();
+        *circuit.witness_mut(is_square.into()) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
+    #[test]
+    fn test_canonical_and_bits() {
+        test_canonical_and_bits_helper::<Fq377, Fr254>();
+        #[cfg(feature = "secp256")]
+        test_canonical_and_bits_helper::<ark_secp256k1::Fr, Fr254>();
+        #[cfg(feature = "ed25519")]
+        test_canonical_and_bits_helper::<ark_ed25519::Fq, ark_bls12_377::Fr>();
+    }
+
+    fn test_canonical_and_bits_helper<E, F>()
+    where
+        E: EmulationConfig<F>,
+        F: PrimeField,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let x = E::from(E::MODULUS.into() - 1u64);
+        let var_x = circuit.create_emulated_variable(x).unwrap();
+        circuit.enforce_emulated_canonical(&var_x).unwrap();
+        let bits = circuit.emulated_var_to_bits(&var_x).unwrap();
+        assert_eq!(bits.len(), E::T);
+        let expected = x.into_bigint().to_bits_le();
+        for (i, bit) in bits.iter().enumerate() {
+            assert_eq!(
+                circuit.witness(bit.0).unwrap(),
+                F::from(expected.get(i).copied().unwrap_or(false))
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // `x + MODULUS` has the same value but is not canonical
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let var_x = circuit.create_emulated_variable(x).unwrap();
+        circuit.enforce_emulated_canonical(&var_x).unwrap();
+        let non_canonical: num_bigint::BigUint = x.into();
+        let non_canonical: ark_std::vec::Vec<F> =
+            super::biguint_to_limbs(&(non_canonical + E::MODULUS.into()), E::B, E::NUM_LIMBS);
+        for (&var, val) in var_x.0.iter().zip(non_canonical) {
+            *circuit.witness_mut(var) = val;
+        }
+        assert_eq!(circuit.emulated_witness(&var_x).unwrap(), x);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
+    #[test]
+    fn test_emulated_mod_reduce() {
+        #[cfg(feature = "secp256")]
+        {
+            test_emulated_mod_reduce_helper::<ark_secp256k1::Fq, ark_secp256k1::Fr, Fr254>();
+            test_emulated_mod_reduce_helper::<ark_secp256r1::Fq, ark_secp256r1::Fr, Fr254>();
+        }
+
+        // mismatched limb layouts
+        let mut circuit = PlonkCircuit::<Fr254>::new_turbo_plonk();
+        let var_x = circuit.create_emulated_variable(Fq377::from(1u64)).unwrap();
+        let var_y = circuit
+            .create_emulated_variable(ark_bls12_381::Fr::from(1u64))
+            .unwrap();
+        assert!(circuit.enforce_emulated_mod_reduce(&var_x, &var_y).is_err());
+    }
+
+    fn test_emulated_mod_reduce_helper<E, S, F>()
+    where
+        E: EmulationConfig<F>,
+        S: EmulationConfig<F>,
+        F: PrimeField,
+    {
+        let mut rng = jf_utils::test_rng();
+        let large = E::from(E::MODULUS.into() - 1u64);
+        for (x, expected_wrap) in [(E::rand(&mut rng), None), (large, Some(true))] {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let reduced = S::from(x.into_bigint().into());
+            let var_x = circuit.create_emulated_variable(x).unwrap();
+            let var_reduced = circuit.create_emulated_variable(reduced).unwrap();
+            let wrap = circuit
+                .enforce_emulated_mod_reduce(&var_x, &var_reduced)
+                .unwrap();
+            if let Some(expected_wrap) = expected_wrap {
+                assert_eq!(circuit.witness(wrap.0).unwrap(), F::from(expected_wrap));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // wrong reduction
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let var_x = circuit.create_emulated_variable(x).unwrap();
+            let var_reduced = circuit
+                .create_emulated_variable(reduced + S::one())
+                .unwrap();
+            circuit
+                .enforce_emulated_mod_reduce(&var_x, &var_reduced)
+                .unwrap();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+    }
 }
@@ -0,0 +1,513 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Signed fixed-point arithmetic gadgets.
+//!
+//! A fixed-point number `x` with `FRAC_BITS` fractional bits is held as the
+//! field element of the signed integer `x * 2^FRAC_BITS` (i.e. `p - |x| *
+//! 2^FRAC_BITS` if `x` is negative), which is range checked to fit in
+//! `INT_BITS + FRAC_BITS` bits in two's complement. The arithmetic gadgets
+//! constrain their results to the same range, so that the circuit is not
+//! satisfiable if an operation overflows.
+
+use super::uint::pow2;
+use crate::{
+    errors::{CircuitError, CircuitError::*},
+    BoolVar, Circuit, PlonkCircuit, Variable,
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+/// A variable holding a signed fixed-point number with `INT_BITS` integer bits
+/// (including the sign bit) and `FRAC_BITS` fractional bits, i.e. a multiple of
+/// `2^-FRAC_BITS` in the range [-2^(`INT_BITS` - 1), 2^(`INT_BITS` - 1)).
+///
+/// `INT_BITS` is at least 2 and `INT_BITS + FRAC_BITS` at most 64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPointVar<const INT_BITS: usize, const FRAC_BITS: usize>(pub(crate) Variable);
+
+impl<const INT_BITS: usize, const FRAC_BITS: usize> FixedPointVar<INT_BITS, FRAC_BITS> {
+    /// The underlying native variable, holding the number scaled by
+    /// 2^`FRAC_BITS`.
+    pub fn var(&self) -> Variable {
+        self.0
+    }
+}
+
+impl<const INT_BITS: usize, const FRAC_BITS: usize> From<FixedPointVar<INT_BITS, FRAC_BITS>>
+    for Variable
+{
+    fn from(a: FixedPointVar<INT_BITS, FRAC_BITS>) -> Self {
+        a.0
+    }
+}
+
+/// How the results of fixed-point multiplications and divisions are rounded
+/// to a multiple of `2^-FRAC_BITS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceil,
+    /// Round to the nearest value, with ties rounded towards positive
+    /// infinity.
+    Nearest,
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for the fixed-point number `raw * 2^-FRAC_BITS`, with
+    /// a range check.
+    /// Return error if the number is out of range, or if the parameters are
+    /// not supported.
+    pub fn create_fixed_point_variable<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        raw: i64,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        check_fixed_point_value::<F, INT_BITS, FRAC_BITS>(raw)?;
+        let var = self.create_variable(field_from_i128::<F>(raw as i128))?;
+        self.fixed_point_from_variable(var)
+    }
+
+    /// Create a constant variable for the fixed-point number
+    /// `raw * 2^-FRAC_BITS`.
+    /// Return error if the number is out of range, or if the parameters are
+    /// not supported.
+    pub fn create_constant_fixed_point_variable<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        raw: i64,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        check_fixed_point_value::<F, INT_BITS, FRAC_BITS>(raw)?;
+        Ok(FixedPointVar(self.create_constant_variable(
+            field_from_i128::<F>(raw as i128),
+        )?))
+    }
+
+    /// Create a public input variable for the fixed-point number
+    /// `raw * 2^-FRAC_BITS`, with a range check. The public input is the field
+    /// element of `raw`.
+    /// Return error if the number is out of range, or if the parameters are
+    /// not supported.
+    pub fn create_public_fixed_point_variable<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        raw: i64,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        check_fixed_point_value::<F, INT_BITS, FRAC_BITS>(raw)?;
+        let var = self.create_public_variable(field_from_i128::<F>(raw as i128))?;
+        self.fixed_point_from_variable(var)
+    }
+
+    /// Constrain the existing variable `a` to hold a fixed-point number scaled
+    /// by 2^`FRAC_BITS` in range, and return it as a fixed-point variable.
+    /// Return error if `a` is invalid or if the parameters are not supported.
+    pub fn fixed_point_from_variable<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: Variable,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        check_fixed_point_bits::<F>(INT_BITS, FRAC_BITS)?;
+        self.check_var_bound(a)?;
+        let bits = INT_BITS + FRAC_BITS;
+        let shifted = self.add_constant(a, &pow2(bits - 1))?;
+        self.enforce_in_range(shifted, bits)?;
+        Ok(FixedPointVar(a))
+    }
+
+    /// The value of a fixed-point variable scaled by 2^`FRAC_BITS`. Only the
+    /// lowest 64 bits of its absolute value are considered if the witness has
+    /// been tampered with.
+    pub fn fixed_point_witness<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+    ) -> Result<i64, CircuitError> {
+        let val = self.witness(a.0)?;
+        let negative = val.into_bigint() > F::MODULUS_MINUS_ONE_DIV_TWO;
+        let abs = if negative { -val } else { val };
+        let abs = abs.into_bigint().as_ref()[0] as i64;
+        Ok(if negative { abs.wrapping_neg() } else { abs })
+    }
+
+    /// Obtain a variable for `a + b`.
+    /// The circuit is not satisfiable if the sum overflows.
+    pub fn fixed_point_add<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+        b: FixedPointVar<INT_BITS, FRAC_BITS>,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        let c = self.add(a.0, b.0)?;
+        self.fixed_point_from_variable(c)
+    }
+
+    /// Obtain a variable for `a - b`.
+    /// The circuit is not satisfiable if the difference overflows.
+    pub fn fixed_point_sub<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+        b: FixedPointVar<INT_BITS, FRAC_BITS>,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        let c = self.sub(a.0, b.0)?;
+        self.fixed_point_from_variable(c)
+    }
+
+    /// Obtain a variable for `a * b`, rounded with `mode`.
+    /// The circuit is not satisfiable if the rounded product overflows.
+    pub fn fixed_point_mul<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+        b: FixedPointVar<INT_BITS, FRAC_BITS>,
+        mode: RoundingMode,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        check_fixed_point_bits::<F>(INT_BITS, FRAC_BITS)?;
+        self.check_var_bound(a.0)?;
+        self.check_var_bound(b.0)?;
+        if FRAC_BITS == 0 {
+            let c = self.mul(a.0, b.0)?;
+            return self.fixed_point_from_variable(c);
+        }
+        // a * b + adj = c * 2^FRAC_BITS + rem, with rem in [0, 2^FRAC_BITS)
+        let adj: i128 = match mode {
+            RoundingMode::Floor => 0,
+            RoundingMode::Ceil => (1 << FRAC_BITS) - 1,
+            RoundingMode::Nearest => 1 << (FRAC_BITS - 1),
+        };
+        let num = self.fixed_point_witness(a)? as i128 * self.fixed_point_witness(b)? as i128 + adj;
+        let c_val = num >> FRAC_BITS;
+        let rem_val = num - (c_val << FRAC_BITS);
+        let c = self.create_variable(field_from_i128::<F>(c_val))?;
+        let rem = self.create_variable(field_from_i128::<F>(rem_val))?;
+        let zero = self.zero();
+        self.quad_poly_gate(
+            &[a.0, b.0, c, rem, zero],
+            &[F::zero(), F::zero(), -pow2::<F>(FRAC_BITS), -F::one()],
+            &[F::one(), F::zero()],
+            F::zero(),
+            field_from_i128::<F>(adj),
+        )?;
+        self.enforce_in_range(rem, FRAC_BITS)?;
+        self.fixed_point_from_variable(c)
+    }
+
+    /// Obtain a variable for `a / b`, rounded with `mode`.
+    /// The circuit is not satisfiable if `b` is zero or if the rounded
+    /// quotient overflows.
+    pub fn fixed_point_div<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+        b: FixedPointVar<INT_BITS, FRAC_BITS>,
+        mode: RoundingMode,
+    ) -> Result<FixedPointVar<INT_BITS, FRAC_BITS>, CircuitError> {
+        check_fixed_point_bits::<F>(INT_BITS, FRAC_BITS)?;
+        self.check_var_bound(a.0)?;
+        self.check_var_bound(b.0)?;
+        let bits = INT_BITS + FRAC_BITS;
+        // a / b = (a * sign(b)) / |b|, where sign(b) = 2 * is_nonneg - 1
+        let shifted = self.add_constant(b.0, &pow2(bits - 1))?;
+        let (_, is_nonneg) = self.uint_split(shifted, bits, bits - 1)?;
+        let zero = self.zero();
+        let b_abs = self.gen_quad_poly(
+            &[b.0, is_nonneg, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::from(2u32), F::zero()],
+            F::zero(),
+        )?;
+        let a_signed = self.gen_quad_poly(
+            &[a.0, is_nonneg, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::from(2u32), F::zero()],
+            F::zero(),
+        )?;
+        // k * a_signed * 2^FRAC_BITS + adj_b * |b| + adj_c = c * k * |b| + rem,
+        // with rem in [0, k * |b|)
+        let (k, adj_b, adj_c): (i128, i128, i128) = match mode {
+            RoundingMode::Floor => (1, 0, 0),
+            RoundingMode::Ceil => (1, 1, -1),
+            RoundingMode::Nearest => (2, 1, 0),
+        };
+        let a_raw = self.fixed_point_witness(a)? as i128;
+        let b_raw = self.fixed_point_witness(b)? as i128;
+        let (a_val, b_val) = if b_raw < 0 {
+            (-a_raw, -b_raw)
+        } else {
+            (a_raw, b_raw)
+        };
+        let num = k * (a_val << FRAC_BITS) + adj_b * b_val + adj_c;
+        let den = k * b_val;
+        let (c_val, rem_val) = if den > 0 {
+            let c_val = num.div_euclid(den);
+            (c_val, num - c_val * den)
+        } else {
+            (0, 0)
+        };
+        let c = self.create_variable(field_from_i128::<F>(c_val))?;
+        let rem = self.create_variable(field_from_i128::<F>(rem_val))?;
+        self.quad_poly_gate(
+            &[a_signed, b_abs, c, b_abs, rem],
+            &[
+                field_from_i128::<F>(k) * pow2::<F>(FRAC_BITS),
+                field_from_i128::<F>(adj_b),
+                F::zero(),
+                F::zero(),
+            ],
+            &[F::zero(), -field_from_i128::<F>(k)],
+            F::one(),
+            field_from_i128::<F>(adj_c),
+        )?;
+        // rem in [0, 2^bits) and k * |b| - 1 - rem in [0, 2^bits), as
+        // k * |b| <= 2^bits
+        self.enforce_in_range(rem, bits)?;
+        let gap = self.gen_quad_poly(
+            &[b_abs, rem, zero, zero],
+            &[field_from_i128::<F>(k), -F::one(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            -F::one(),
+        )?;
+        self.enforce_in_range(gap, bits)?;
+        self.fixed_point_from_variable(c)
+    }
+
+    /// Obtain a boolean variable indicating whether `a < b`.
+    pub fn fixed_point_is_lt<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+        b: FixedPointVar<INT_BITS, FRAC_BITS>,
+    ) -> Result<BoolVar, CircuitError> {
+        check_fixed_point_bits::<F>(INT_BITS, FRAC_BITS)?;
+        self.check_var_bound(a.0)?;
+        self.check_var_bound(b.0)?;
+        let bits = INT_BITS + FRAC_BITS;
+        // b - a is in (-2^bits, 2^bits), so `a < b` iff the bit `bits` of
+        // `b - a - 1 + 2^bits` is set
+        let zero = self.zero();
+        let diff = self.gen_quad_poly(
+            &[b.0, a.0, zero, zero],
+            &[F::one(), -F::one(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            pow2::<F>(bits) - F::one(),
+        )?;
+        let (_, lt) = self.uint_split(diff, bits + 1, bits)?;
+        Ok(BoolVar::new_unchecked(lt))
+    }
+
+    /// Obtain a boolean variable indicating whether `a <= b`.
+    pub fn fixed_point_is_leq<const INT_BITS: usize, const FRAC_BITS: usize>(
+        &mut self,
+        a: FixedPointVar<INT_BITS, FRAC_BITS>,
+        b: FixedPointVar<INT_BITS, FRAC_BITS>,
+    ) -> Result<BoolVar, CircuitError> {
+        let gt = self.fixed_point_is_lt(b, a)?;
+        let one = self.one();
+        let leq = self.sub(one, gt.into())?;
+        Ok(BoolVar::new_unchecked(leq))
+    }
+}
+
+// Check that the fixed-point parameters are supported and that products of
+// fixed-point numbers fit in the field.
+fn check_fixed_point_bits<F: PrimeField>(
+    int_bits: usize,
+    frac_bits: usize,
+) -> Result<(), CircuitError> {
+    if int_bits < 2 || int_bits + frac_bits > 64 {
+        return Err(ParameterError(format!(
+            "unsupported fixed-point format: {int_bits} integer and {frac_bits} fractional bits"
+        )));
+    }
+    if 2 * (int_bits + frac_bits) + 2 >= F::MODULUS_BIT_SIZE as usize {
+        return Err(ParameterError(format!(
+            "field too small for {}-bit fixed-point arithmetic",
+            int_bits + frac_bits
+        )));
+    }
+    Ok(())
+}
+
+#[inline]
+fn field_from_i128<F: PrimeField>(val: i128) -> F {
+    if val < 0 {
+        -F::from(val.unsigned_abs())
+    } else {
+        F::from(val as u128)
+    }
+}
+
+fn check_fixed_point_value<F: PrimeField, const INT_BITS: usize, const FRAC_BITS: usize>(
+    raw: i64,
+) -> Result<(), CircuitError> {
+    check_fixed_point_bits::<F>(INT_BITS, FRAC_BITS)?;
+    let shift = 64 - INT_BITS - FRAC_BITS;
+    if (raw << shift) >> shift != raw {
+        return Err(ParameterError(format!(
+            "{raw} does not fit in {} bits",
+            INT_BITS + FRAC_BITS
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{vec, UniformRand};
+    use jf_utils::test_rng;
+
+    const MODES: [RoundingMode; 3] = [
+        RoundingMode::Floor,
+        RoundingMode::Ceil,
+        RoundingMode::Nearest,
+    ];
+
+    // `n / d` rounded with `mode`, `d` being non-zero.
+    fn round_div(n: i128, d: i128, mode: RoundingMode) -> i128 {
+        let (n, d) = if d < 0 { (-n, -d) } else { (n, d) };
+        match mode {
+            RoundingMode::Floor => n.div_euclid(d),
+            RoundingMode::Ceil => -(-n).div_euclid(d),
+            RoundingMode::Nearest => (2 * n + d).div_euclid(2 * d),
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_gadgets() -> Result<(), CircuitError> {
+        test_fixed_point_gadgets_helper::<FqEd254, 8, 8>()?;
+        test_fixed_point_gadgets_helper::<FqEd254, 2, 6>()?;
+        test_fixed_point_gadgets_helper::<FqEd377, 16, 0>()?;
+        test_fixed_point_gadgets_helper::<Fq377, 32, 32>()
+    }
+
+    fn test_fixed_point_gadgets_helper<F: PrimeField, const I: usize, const FR: usize>(
+    ) -> Result<(), CircuitError> {
+        let rng = &mut test_rng();
+        let bits = I + FR;
+        let min = -1i64 << (bits - 1);
+        let max = !min;
+        let one = 1i64 << FR;
+        let mut vals = vec![(0, 0), (1, one / 2), (-1, one / 2), (one, 3 * one / 2)];
+        vals.extend([(min, max), (max, max), (min, -one), (max, 1), (-one, min)]);
+        vals.extend([(one / 2, 3), (-one / 2, 3), (5, 0), (min, 1)]);
+        for _ in 0..4 {
+            let a = (i64::rand(rng) << (64 - bits)) >> (64 - bits);
+            let b = (i64::rand(rng) << (64 - bits)) >> (64 - bits);
+            vals.extend([(a, b), (a >> (bits / 2), b >> (bits / 2))]);
+        }
+        let in_range = |val: i128| val >= min as i128 && val <= max as i128;
+
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            for &(a_val, b_val) in vals.iter() {
+                let mut circuit = circuit.clone();
+                let a: FixedPointVar<I, FR> = circuit.create_fixed_point_variable(a_val)?;
+                let b: FixedPointVar<I, FR> = circuit.create_public_fixed_point_variable(b_val)?;
+                let pub_input = [field_from_i128::<F>(b_val as i128)];
+                assert_eq!(circuit.fixed_point_witness(a)?, a_val);
+                let lt = circuit.fixed_point_is_lt(a, b)?;
+                let leq = circuit.fixed_point_is_leq(a, b)?;
+                assert_eq!(circuit.witness(lt.into())?, F::from(a_val < b_val));
+                assert_eq!(circuit.witness(leq.into())?, F::from(a_val <= b_val));
+                circuit.check_circuit_satisfiability(&pub_input)?;
+                for res in [lt.0, leq.0] {
+                    let mut bad_circuit = circuit.clone();
+                    *bad_circuit.witness_mut(res) += F::one();
+                    assert!(bad_circuit
+                        .check_circuit_satisfiability(&pub_input)
+                        .is_err());
+                }
+
+                let (a_val, b_val) = (a_val as i128, b_val as i128);
+                // (operation, mode, expected result)
+                let mut ops = vec![
+                    (0, MODES[0], Some(a_val + b_val)),
+                    (1, MODES[0], Some(a_val - b_val)),
+                ];
+                for mode in MODES {
+                    let prod = round_div(a_val * b_val, 1 << FR, mode);
+                    let quot = (b_val != 0).then(|| round_div(a_val << FR, b_val, mode));
+                    ops.extend([(2, mode, Some(prod)), (3, mode, quot)]);
+                }
+                for (op, mode, expected) in ops {
+                    let mut circuit = circuit.clone();
+                    let res = match op {
+                        0 => circuit.fixed_point_add(a, b)?,
+                        1 => circuit.fixed_point_sub(a, b)?,
+                        2 => circuit.fixed_point_mul(a, b, mode)?,
+                        _ => circuit.fixed_point_div(a, b, mode)?,
+                    };
+                    let satisfied = expected.map_or(false, in_range);
+                    assert_eq!(
+                        circuit.check_circuit_satisfiability(&pub_input).is_ok(),
+                        satisfied
+                    );
+                    if satisfied {
+                        assert_eq!(Some(circuit.fixed_point_witness(res)? as i128), expected);
+                        // any other result is rejected
+                        *circuit.witness_mut(res.0) += F::one();
+                        assert!(circuit.check_circuit_satisfiability(&pub_input).is_err());
+                    }
+                }
+            }
+        }
+
+        // error paths
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        if bits < 64 {
+            assert!(circuit
+                .create_fixed_point_variable::<I, FR>(max + 1)
+                .is_err());
+            assert!(circuit
+                .create_fixed_point_variable::<I, FR>(min - 1)
+                .is_err());
+        }
+        assert!(circuit.create_fixed_point_variable::<1, 8>(0).is_err());
+        assert!(circuit.create_fixed_point_variable::<32, 33>(0).is_err());
+        let a = circuit.create_fixed_point_variable::<I, FR>(0)?;
+        let bad = FixedPointVar::<I, FR>(circuit.num_vars());
+        assert!(circuit.fixed_point_add(a, bad).is_err());
+        assert!(circuit
+            .fixed_point_mul(bad, a, RoundingMode::Floor)
+            .is_err());
+        assert!(circuit
+            .fixed_point_div(a, bad, RoundingMode::Floor)
+            .is_err());
+        assert!(circuit.fixed_point_is_lt(a, bad).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_point_rounding() -> Result<(), CircuitError> {
+        let mut circuit = PlonkCircuit::<FqEd254>::new_ultra_plonk(8);
+        // 8 fractional bits: 1 is 256
+        let x = |circuit: &mut PlonkCircuit<FqEd254>, raw| {
+            circuit.create_fixed_point_variable::<8, 8>(raw)
+        };
+        let eps = x(&mut circuit, 1)?;
+        let neg_eps = x(&mut circuit, -1)?;
+        let half = x(&mut circuit, 128)?;
+        let one = x(&mut circuit, 256)?;
+        let neg_one = x(&mut circuit, -256)?;
+        let three = x(&mut circuit, 768)?;
+        // (a, b, [a * b], [a / b]) with the floor, ceil and nearest roundings
+        let cases = [
+            (eps, half, [0, 1, 1], [2, 2, 2]),
+            (neg_eps, half, [-1, 0, 0], [-2, -2, -2]),
+            (one, three, [768, 768, 768], [85, 86, 85]),
+            (neg_one, three, [-768, -768, -768], [-86, -85, -85]),
+            (one, neg_one, [-256, -256, -256], [-256, -256, -256]),
+        ];
+        for (a, b, prods, quots) in cases {
+            for (mode, (prod, quot)) in MODES.into_iter().zip(prods.into_iter().zip(quots)) {
+                let c = circuit.fixed_point_mul(a, b, mode)?;
+                assert_eq!(circuit.fixed_point_witness(c)?, prod);
+                let c = circuit.fixed_point_div(a, b, mode)?;
+                assert_eq!(circuit.fixed_point_witness(c)?, quot);
+            }
+        }
+        circuit.check_circuit_satisfiability(&[])?;
+        Ok(())
+    }
+}
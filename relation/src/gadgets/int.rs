@@ -0,0 +1,429 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Fixed-width signed integer variables and gadgets.
+//!
+//! A signed integer `x` of `BITS` bits is held in two's complement, i.e. as
+//! the unsigned integer `x mod 2^BITS`, so that the wrapping arithmetic and
+//! bitwise gadgets of [`UIntVar`] apply to it unchanged, see
+//! [`IntVar::to_uint()`].
+
+use super::uint::{check_uint_bits, pow2, UIntVar};
+use crate::{errors::CircuitError, BoolVar, Circuit, PlonkCircuit, Variable};
+use ark_ff::PrimeField;
+use ark_std::format;
+
+/// A variable holding a signed integer of `BITS` bits in two's complement,
+/// i.e. whose value is constrained to the range [0, 2^`BITS`).
+///
+/// `BITS` is a multiple of 8 between 8 and 64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntVar<const BITS: usize>(pub(crate) Variable);
+
+/// An 8-bit signed integer variable.
+pub type Int8Var = IntVar<8>;
+/// A 32-bit signed integer variable.
+pub type Int32Var = IntVar<32>;
+/// A 64-bit signed integer variable.
+pub type Int64Var = IntVar<64>;
+
+impl<const BITS: usize> IntVar<BITS> {
+    /// The underlying native variable.
+    pub fn var(&self) -> Variable {
+        self.0
+    }
+
+    /// The same variable as an unsigned integer, i.e. `self mod 2^BITS`.
+    pub fn to_uint(&self) -> UIntVar<BITS> {
+        UIntVar(self.0)
+    }
+}
+
+impl<const BITS: usize> From<IntVar<BITS>> for Variable {
+    fn from(a: IntVar<BITS>) -> Self {
+        a.0
+    }
+}
+
+impl<const BITS: usize> From<UIntVar<BITS>> for IntVar<BITS> {
+    fn from(a: UIntVar<BITS>) -> Self {
+        Self(a.0)
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for the `BITS`-bit signed integer `val`, with a
+    /// range check.
+    /// Return error if `val` does not fit in `BITS` bits, or if `BITS` is not
+    /// supported.
+    pub fn create_int_variable<const BITS: usize>(
+        &mut self,
+        val: i64,
+    ) -> Result<IntVar<BITS>, CircuitError> {
+        let val = int_to_twos_complement::<BITS>(val)?;
+        Ok(self.create_uint_variable::<BITS>(val)?.into())
+    }
+
+    /// Create a constant variable for the `BITS`-bit signed integer `val`.
+    /// Return error if `val` does not fit in `BITS` bits, or if `BITS` is not
+    /// supported.
+    pub fn create_constant_int_variable<const BITS: usize>(
+        &mut self,
+        val: i64,
+    ) -> Result<IntVar<BITS>, CircuitError> {
+        let val = int_to_twos_complement::<BITS>(val)?;
+        Ok(self.create_constant_uint_variable::<BITS>(val)?.into())
+    }
+
+    /// Create a public input variable for the `BITS`-bit signed integer `val`,
+    /// with a range check. The public input is the two's complement
+    /// `val mod 2^BITS`.
+    /// Return error if `val` does not fit in `BITS` bits, or if `BITS` is not
+    /// supported.
+    pub fn create_public_int_variable<const BITS: usize>(
+        &mut self,
+        val: i64,
+    ) -> Result<IntVar<BITS>, CircuitError> {
+        let val = int_to_twos_complement::<BITS>(val)?;
+        Ok(self.create_public_uint_variable::<BITS>(val)?.into())
+    }
+
+    /// The value of a signed integer variable. Only the lowest 64 bits of
+    /// the witness are considered if it has been tampered with.
+    pub fn int_witness<const BITS: usize>(&self, a: IntVar<BITS>) -> Result<i64, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        let val = self.uint_witness(a.to_uint())?;
+        Ok(((val << (64 - BITS)) as i64) >> (64 - BITS))
+    }
+
+    /// Obtain a boolean variable indicating whether `a` is negative.
+    pub fn int_is_negative<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+    ) -> Result<BoolVar, CircuitError> {
+        Ok(self.int_sign_split(a)?.1)
+    }
+
+    /// Obtain a variable for the field element `x` (i.e. `p - |x|` if `x` is
+    /// negative) of the signed integer `x` held by `a`.
+    pub fn int_to_field<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+    ) -> Result<Variable, CircuitError> {
+        let (_, sign) = self.int_sign_split(a)?;
+        self.int_to_field_with_sign(a, sign)
+    }
+
+    /// Obtain a boolean variable indicating whether `a < b`.
+    pub fn int_is_lt<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        b: IntVar<BITS>,
+    ) -> Result<BoolVar, CircuitError> {
+        check_uint_bits::<F>(BITS, 1)?;
+        let (lo_a, sign_a) = self.int_sign_split(a)?;
+        let (lo_b, sign_b) = self.int_sign_split(b)?;
+        // With the offset binary encodings `x + 2^(BITS-1) = lo + 2^(BITS-1) *
+        // (1 - sign)`, `a < b` iff the bit `BITS` of
+        // `(b + 2^(BITS-1)) - (a + 2^(BITS-1)) - 1 + 2^BITS` is set.
+        let half = pow2::<F>(BITS - 1);
+        let diff = self.gen_quad_poly(
+            &[lo_b, lo_a, sign_b.into(), sign_a.into()],
+            &[F::one(), -F::one(), -half, half],
+            &[F::zero(), F::zero()],
+            pow2::<F>(BITS) - F::one(),
+        )?;
+        let (_, lt) = self.uint_split(diff, BITS + 1, BITS)?;
+        Ok(BoolVar::new_unchecked(lt))
+    }
+
+    /// Obtain a boolean variable indicating whether `a <= b`.
+    pub fn int_is_leq<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        b: IntVar<BITS>,
+    ) -> Result<BoolVar, CircuitError> {
+        let gt = self.int_is_lt(b, a)?;
+        let one = self.one();
+        let leq = self.sub(one, gt.into())?;
+        Ok(BoolVar::new_unchecked(leq))
+    }
+
+    /// Obtain a variable for the minimum of `a` and `b`.
+    pub fn int_min<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        b: IntVar<BITS>,
+    ) -> Result<IntVar<BITS>, CircuitError> {
+        let lt = self.int_is_lt(a, b)?;
+        Ok(IntVar(self.conditional_select(lt, b.0, a.0)?))
+    }
+
+    /// Obtain a variable for the maximum of `a` and `b`.
+    pub fn int_max<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        b: IntVar<BITS>,
+    ) -> Result<IntVar<BITS>, CircuitError> {
+        let lt = self.int_is_lt(a, b)?;
+        Ok(IntVar(self.conditional_select(lt, a.0, b.0)?))
+    }
+
+    /// Obtain a variable for the absolute value of `a`, as an unsigned integer
+    /// so that the absolute value of `-2^(BITS-1)` does not overflow.
+    pub fn int_abs<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        let (_, sign) = self.int_sign_split(a)?;
+        self.int_abs_with_sign(a, sign)
+    }
+
+    /// Obtain variables for the quotient and the remainder of `a / b`, rounded
+    /// towards zero as Rust's `/` and `%`: `a = q * b + r` with `|r| < |b|`
+    /// and `r` of the sign of `a`.
+    /// The circuit is not satisfiable if `b` is zero or if the quotient
+    /// overflows, i.e. for `-2^(BITS-1) / -1`.
+    pub fn int_div_rem<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        b: IntVar<BITS>,
+    ) -> Result<(IntVar<BITS>, IntVar<BITS>), CircuitError> {
+        check_uint_bits::<F>(BITS, BITS + 2)?;
+        let a_val = self.int_witness(a)? as i128;
+        let b_val = self.int_witness(b)? as i128;
+        let (q_val, r_val) = if b_val == 0 {
+            (0, a_val)
+        } else {
+            (a_val / b_val, a_val % b_val)
+        };
+        let mask = u64::MAX >> (64 - BITS);
+        let q: IntVar<BITS> = self
+            .create_uint_variable::<BITS>(q_val as u64 & mask)?
+            .into();
+        let r: IntVar<BITS> = self
+            .create_uint_variable::<BITS>(r_val as u64 & mask)?
+            .into();
+
+        let (_, sign_a) = self.int_sign_split(a)?;
+        let (_, sign_b) = self.int_sign_split(b)?;
+        let (_, sign_q) = self.int_sign_split(q)?;
+        let (_, sign_r) = self.int_sign_split(r)?;
+        let a_field = self.int_to_field_with_sign(a, sign_a)?;
+        let b_field = self.int_to_field_with_sign(b, sign_b)?;
+        let q_field = self.int_to_field_with_sign(q, sign_q)?;
+        let r_field = self.int_to_field_with_sign(r, sign_r)?;
+        // a = q * b + r, which does not wrap around the modulus
+        let one = self.one();
+        self.mul_add_gate(
+            &[q_field, b_field, r_field, one, a_field],
+            &[F::one(), F::one()],
+        )?;
+        // r is zero or of the sign of a: (sign_r - sign_a) * r = 0
+        let zero = self.zero();
+        self.mul_add_gate(
+            &[sign_r.into(), r_field, sign_a.into(), r_field, zero],
+            &[F::one(), -F::one()],
+        )?;
+        // |r| < |b|, with |r|, |b| <= 2^(BITS-1)
+        let abs_b = self.int_abs_with_sign(b, sign_b)?;
+        let abs_r = self.int_abs_with_sign(r, sign_r)?;
+        let diff = self.gen_quad_poly(
+            &[abs_b.0, abs_r.0, zero, zero],
+            &[F::one(), -F::one(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            -F::one(),
+        )?;
+        self.enforce_in_range(diff, BITS)?;
+        Ok((q, r))
+    }
+}
+
+/// Private helper functions for signed integer gadgets.
+impl<F: PrimeField> PlonkCircuit<F> {
+    // Split `a` into its low `BITS - 1` bits and its sign bit.
+    fn int_sign_split<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+    ) -> Result<(Variable, BoolVar), CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        self.check_var_bound(a.0)?;
+        let (lo, sign) = self.uint_split(a.0, BITS, BITS - 1)?;
+        Ok((lo, BoolVar::new_unchecked(sign)))
+    }
+
+    // a - 2^BITS * sign
+    fn int_to_field_with_sign<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        sign: BoolVar,
+    ) -> Result<Variable, CircuitError> {
+        let zero = self.zero();
+        self.lc(
+            &[a.0, sign.into(), zero, zero],
+            &[F::one(), -pow2::<F>(BITS), F::zero(), F::zero()],
+        )
+    }
+
+    // a + sign * (2^BITS - 2 * a)
+    fn int_abs_with_sign<const BITS: usize>(
+        &mut self,
+        a: IntVar<BITS>,
+        sign: BoolVar,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        let zero = self.zero();
+        let abs = self.gen_quad_poly(
+            &[a.0, sign.into(), zero, zero],
+            &[F::one(), pow2::<F>(BITS), F::zero(), F::zero()],
+            &[-F::from(2u32), F::zero()],
+            F::zero(),
+        )?;
+        Ok(UIntVar(abs))
+    }
+}
+
+// The two's complement of `val` on `BITS` bits.
+fn int_to_twos_complement<const BITS: usize>(val: i64) -> Result<u64, CircuitError> {
+    let shift = 64 - BITS.clamp(1, 64);
+    if (val << shift) >> shift != val {
+        return Err(CircuitError::ParameterError(format!(
+            "{val} does not fit in {BITS} bits"
+        )));
+    }
+    Ok(val as u64 & (u64::MAX >> shift))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{vec, UniformRand};
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_int_gadgets() -> Result<(), CircuitError> {
+        test_int_gadgets_helper::<FqEd254, 8>()?;
+        test_int_gadgets_helper::<FqEd254, 64>()?;
+        test_int_gadgets_helper::<FqEd377, 32>()?;
+        test_int_gadgets_helper::<Fq377, 64>()
+    }
+
+    fn test_int_gadgets_helper<F: PrimeField, const BITS: usize>() -> Result<(), CircuitError> {
+        let rng = &mut test_rng();
+        let min = -1i64 << (BITS - 1);
+        let max = !min;
+        let mut vals = vec![
+            (min, -1),
+            (min, 1),
+            (min, max),
+            (max, min),
+            (-7, 2),
+            (7, -2),
+        ];
+        vals.extend([(0, 0), (5, 0), (-1, -1), (min, min), (3, 3)]);
+        for _ in 0..4 {
+            let a = (i64::rand(rng) << (64 - BITS)) >> (64 - BITS);
+            let b = (i64::rand(rng) << (64 - BITS)) >> (64 - BITS);
+            vals.extend([(a, b), (a, b >> (BITS / 2))]);
+        }
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            for &(a_val, b_val) in vals.iter() {
+                let mut circuit = circuit.clone();
+                let a: IntVar<BITS> = circuit.create_int_variable(a_val)?;
+                let b: IntVar<BITS> = circuit.create_public_int_variable(b_val)?;
+                let pub_input = [F::from(int_to_twos_complement::<BITS>(b_val)?)];
+                assert_eq!(circuit.int_witness(a)?, a_val);
+
+                let neg = circuit.int_is_negative(a)?;
+                let a_field = circuit.int_to_field(a)?;
+                let lt = circuit.int_is_lt(a, b)?;
+                let leq = circuit.int_is_leq(a, b)?;
+                let min_ab = circuit.int_min(a, b)?;
+                let max_ab = circuit.int_max(a, b)?;
+                let abs = circuit.int_abs(a)?;
+                assert_eq!(circuit.witness(neg.into())?, F::from(a_val < 0));
+                let expected_field = if a_val < 0 {
+                    -F::from(a_val.unsigned_abs())
+                } else {
+                    F::from(a_val as u64)
+                };
+                assert_eq!(circuit.witness(a_field)?, expected_field);
+                assert_eq!(circuit.witness(lt.into())?, F::from(a_val < b_val));
+                assert_eq!(circuit.witness(leq.into())?, F::from(a_val <= b_val));
+                assert_eq!(circuit.int_witness(min_ab)?, a_val.min(b_val));
+                assert_eq!(circuit.int_witness(max_ab)?, a_val.max(b_val));
+                assert_eq!(circuit.uint_witness(abs)?, a_val.unsigned_abs());
+                circuit.check_circuit_satisfiability(&pub_input)?;
+
+                // tampering with any result breaks the circuit
+                for res in [neg.0, a_field, lt.0, leq.0, min_ab.0, max_ab.0, abs.0] {
+                    let mut bad_circuit = circuit.clone();
+                    *bad_circuit.witness_mut(res) += F::one();
+                    assert!(bad_circuit
+                        .check_circuit_satisfiability(&pub_input)
+                        .is_err());
+                }
+
+                let (q, r) = circuit.int_div_rem(a, b)?;
+                let defined = b_val != 0 && !(a_val == min && b_val == -1);
+                assert_eq!(
+                    circuit.check_circuit_satisfiability(&pub_input).is_ok(),
+                    defined
+                );
+                if defined {
+                    assert_eq!(circuit.int_witness(q)?, a_val / b_val);
+                    assert_eq!(circuit.int_witness(r)?, a_val % b_val);
+                    // another decomposition a = q * b + r is rejected
+                    let q_other = if b_val > 0 {
+                        a_val / b_val - 1
+                    } else {
+                        a_val / b_val + 1
+                    };
+                    let r_other = (a_val as i128 - q_other as i128 * b_val as i128) as i64;
+                    if let (Ok(q_other), Ok(r_other)) = (
+                        int_to_twos_complement::<BITS>(q_other),
+                        int_to_twos_complement::<BITS>(r_other),
+                    ) {
+                        let mut bad_circuit = circuit.clone();
+                        *bad_circuit.witness_mut(q.0) = F::from(q_other);
+                        *bad_circuit.witness_mut(r.0) = F::from(r_other);
+                        assert!(bad_circuit
+                            .check_circuit_satisfiability(&pub_input)
+                            .is_err());
+                    }
+                }
+            }
+        }
+
+        // wrapping arithmetic through the unsigned integer gadgets
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let a: IntVar<BITS> = circuit.create_int_variable(-3)?;
+        let b: IntVar<BITS> = circuit.create_constant_int_variable(max)?;
+        let sum = circuit.uint_wrapping_add(a.to_uint(), b.to_uint())?;
+        assert_eq!(circuit.int_witness(sum.into())?, max - 3);
+        let prod = circuit.uint_wrapping_mul(a.to_uint(), a.to_uint())?;
+        assert_eq!(circuit.int_witness(prod.into())?, 9);
+        circuit.check_circuit_satisfiability(&[])?;
+
+        // error paths
+        assert!(circuit.create_int_variable::<BITS>(max).is_ok());
+        assert!(circuit.create_int_variable::<BITS>(min).is_ok());
+        if BITS < 64 {
+            assert!(circuit.create_int_variable::<BITS>(max + 1).is_err());
+            assert!(circuit.create_int_variable::<BITS>(min - 1).is_err());
+        }
+        assert!(circuit.create_int_variable::<12>(0).is_err());
+        let bad_var = IntVar::<BITS>(circuit.num_vars());
+        assert!(circuit.int_is_negative(bad_var).is_err());
+        Ok(())
+    }
+}
@@ -12,8 +12,12 @@ pub mod ultraplonk;
 mod arithmetic;
 mod cmp;
 mod emulated;
+mod fixed_point;
+mod int;
 mod logic;
 mod range;
+mod sort;
+mod uint;
 #[allow(unused_imports)]
 pub use arithmetic::*;
 #[allow(unused_imports)]
@@ -21,9 +25,17 @@ pub use cmp::*;
 #[allow(unused_imports)]
 pub use emulated::*;
 #[allow(unused_imports)]
+pub use fixed_point::*;
+#[allow(unused_imports)]
+pub use int::*;
+#[allow(unused_imports)]
 pub use logic::*;
 #[allow(unused_imports)]
 pub use range::*;
+#[allow(unused_imports)]
+pub use sort::*;
+#[allow(unused_imports)]
+pub use uint::*;
 
 // Helper functions
 mod utils;
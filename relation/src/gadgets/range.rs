@@ -37,6 +37,45 @@ impl<F: PrimeField> PlonkCircuit<F> {
         self.is_zero(higher_bit_sum)
     }
 
+    /// Constrain a variable to be within the [`lower`, `upper`) range, with
+    /// two range checks of `ceil(log2(upper - lower))` bits, using lookups in
+    /// UltraPlonk.
+    /// Return error if the range is empty, if `upper - lower` does not fit
+    /// in `MODULUS_BIT_SIZE - 2` bits, or if the variable is invalid.
+    pub fn enforce_in_interval(
+        &mut self,
+        a: Variable,
+        lower: F,
+        upper: F,
+    ) -> Result<(), CircuitError> {
+        self.check_var_bound(a)?;
+        if upper.into_bigint() <= lower.into_bigint() {
+            return Err(CircuitError::ParameterError(format!(
+                "empty range [{lower}, {upper})"
+            )));
+        }
+        let width = upper - lower;
+        let bit_len = (width - F::one()).into_bigint().num_bits() as usize;
+        if bit_len + 2 > F::MODULUS_BIT_SIZE as usize {
+            return Err(CircuitError::ParameterError(format!(
+                "range [{lower}, {upper}) too large"
+            )));
+        }
+        if bit_len == 0 {
+            return self.enforce_constant(a, lower);
+        }
+        // a - lower in [0, 2^bit_len) and a - lower + 2^bit_len - width in [0,
+        // 2^bit_len), i.e. a - lower < width, since no sum wraps around the modulus
+        let low = self.add_constant(a, &-lower)?;
+        self.enforce_in_range(low, bit_len)?;
+        let pow = F::from(2u32).pow([bit_len as u64]);
+        if width != pow {
+            let high = self.add_constant(low, &(pow - width))?;
+            self.enforce_in_range(high, bit_len)?;
+        }
+        Ok(())
+    }
+
     /// Obtain the `bit_len`-long binary representation of variable `a`
     /// Return a list of variables [b0, ..., b_`bit_len`] which is the binary
     /// representation of `a`.
@@ -266,4 +305,55 @@ mod test {
         circuit.finalize_for_arithmetization()?;
         Ok(circuit)
     }
+
+    #[test]
+    fn test_enforce_in_interval() -> Result<(), CircuitError> {
+        test_enforce_in_interval_helper::<FqEd254>()?;
+        test_enforce_in_interval_helper::<FqEd377>()?;
+        test_enforce_in_interval_helper::<FqEd381>()?;
+        test_enforce_in_interval_helper::<Fq377>()
+    }
+    fn test_enforce_in_interval_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            // (lower, upper, values in range, values out of range)
+            let cases = [
+                (F::from(100u32), F::from(1100u32), [100, 1099], [99, 1100]),
+                (F::from(7u32), F::from(8u32), [7, 7], [6, 8]),
+                (F::from(0u32), F::from(1024u32), [0, 1023], [1024, 1 << 20]),
+            ];
+            for (lower, upper, good, bad) in cases {
+                for (vals, satisfied) in [(good, true), (bad, false)] {
+                    for val in vals {
+                        let mut circuit = circuit.clone();
+                        let a = circuit.create_variable(F::from(val as u32))?;
+                        circuit.enforce_in_interval(a, lower, upper)?;
+                        assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), satisfied);
+                    }
+                }
+            }
+            // a range across the modulus is empty
+            let mut circuit = circuit.clone();
+            let a = circuit.create_variable(-F::one())?;
+            assert!(circuit.enforce_in_interval(a, -F::one(), F::one()).is_err());
+            let a = circuit.create_variable(-F::from(5u32))?;
+            circuit.enforce_in_interval(a, -F::from(10u32), -F::one())?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            *circuit.witness_mut(a) = F::zero();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // error paths
+            let a = circuit.zero();
+            assert!(circuit.enforce_in_interval(a, F::one(), F::one()).is_err());
+            assert!(circuit
+                .enforce_in_interval(a, F::zero(), -F::one())
+                .is_err());
+            assert!(circuit
+                .enforce_in_interval(circuit.num_vars(), F::zero(), F::one())
+                .is_err());
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,463 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Permutation, sorting and swap network gadgets.
+//!
+//! Multiset equality is checked with a grand product: `a` and `b` are
+//! permutations of each other if `prod_i (a_i - c) = prod_i (b_i - c)` for a
+//! random challenge `c`, except with probability `a.len() / |F|`. The
+//! challenge must be unpredictable once `a` and `b` are fixed: either a public
+//! input derived by the verifier (e.g. by Fiat-Shamir) from a commitment to
+//! the vectors, or the output of an in-circuit hash of both vectors. Callers
+//! are responsible for this: a challenge the prover can choose, or one that
+//! does not depend on both vectors, makes the check unsound. The
+//! `MultisetGadget` of `jf-primitives` derives the challenges with the Rescue
+//! sponge over both vectors.
+//!
+//! Sorting is checked without any challenge, with range checks on the
+//! differences of consecutive elements, which use lookups in UltraPlonk.
+
+use crate::{
+    errors::{CircuitError, CircuitError::*},
+    BoolVar, Circuit, PlonkCircuit, Variable,
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec::Vec};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Constrain `a` to be a permutation of `b`, with the grand product
+    /// argument at `challenge`, see the module documentation.
+    /// Return error if the lengths differ or if a variable is invalid.
+    pub fn enforce_multiset_equal(
+        &mut self,
+        a: &[Variable],
+        b: &[Variable],
+        challenge: Variable,
+    ) -> Result<(), CircuitError> {
+        if a.len() != b.len() {
+            return Err(ParameterError(format!(
+                "multisets of different sizes: {} != {}",
+                a.len(),
+                b.len()
+            )));
+        }
+        self.check_vars_bound(a)?;
+        self.check_vars_bound(b)?;
+        self.check_var_bound(challenge)?;
+        let prod_a = self.grand_product(a, challenge)?;
+        let prod_b = self.grand_product(b, challenge)?;
+        self.enforce_equal(prod_a, prod_b)
+    }
+
+    /// Constrain the tuples of `a` to be a permutation of the tuples of `b`.
+    /// Each tuple `(x_0, ..., x_{N-1})` is folded into
+    /// `x_0 + x_1 * fold_challenge + ... + x_{N-1} * fold_challenge^{N-1}`
+    /// and the folded values are checked with
+    /// [`Self::enforce_multiset_equal()`] at `challenge`. Both challenges
+    /// must be unpredictable once `a` and `b` are fixed.
+    /// Return error if the lengths differ, if `N` is zero or if a variable is
+    /// invalid.
+    pub fn enforce_multiset_equal_tuples<const N: usize>(
+        &mut self,
+        a: &[[Variable; N]],
+        b: &[[Variable; N]],
+        challenge: Variable,
+        fold_challenge: Variable,
+    ) -> Result<(), CircuitError> {
+        if N == 0 {
+            return Err(ParameterError("tuples cannot be empty".to_string()));
+        }
+        if a.len() != b.len() {
+            return Err(ParameterError(format!(
+                "multisets of different sizes: {} != {}",
+                a.len(),
+                b.len()
+            )));
+        }
+        self.check_var_bound(fold_challenge)?;
+        let folded_a = a
+            .iter()
+            .map(|tuple| self.fold_tuple(tuple, fold_challenge))
+            .collect::<Result<Vec<_>, _>>()?;
+        let folded_b = b
+            .iter()
+            .map(|tuple| self.fold_tuple(tuple, fold_challenge))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.enforce_multiset_equal(&folded_a, &folded_b, challenge)
+    }
+
+    /// Constrain `a` to be sorted in non-decreasing order, with elements in
+    /// the range [0, 2^`bit_len`).
+    /// Only the first and last elements and the differences of consecutive
+    /// elements are range checked.
+    /// Return error if `(a.len() + 1) * 2^bit_len` may exceed the field
+    /// modulus, or if a variable is invalid.
+    pub fn enforce_sorted(&mut self, a: &[Variable], bit_len: usize) -> Result<(), CircuitError> {
+        self.sorted_gate(a, bit_len, false)
+    }
+
+    /// Constrain `a` to be sorted in increasing order, i.e. sorted and
+    /// without duplicates, with elements in the range [0, 2^`bit_len`).
+    /// Return error if `(a.len() + 1) * 2^bit_len` may exceed the field
+    /// modulus, or if a variable is invalid.
+    pub fn enforce_strictly_sorted(
+        &mut self,
+        a: &[Variable],
+        bit_len: usize,
+    ) -> Result<(), CircuitError> {
+        self.sorted_gate(a, bit_len, true)
+    }
+
+    /// Obtain `(b, a)` if `swap` is true, `(a, b)` otherwise.
+    /// Return error if a variable is invalid.
+    pub fn conditional_swap(
+        &mut self,
+        swap: BoolVar,
+        a: Variable,
+        b: Variable,
+    ) -> Result<(Variable, Variable), CircuitError> {
+        let first = self.conditional_select(swap, a, b)?;
+        // second = a + b - first
+        let second = self.lc(
+            &[a, b, first, self.zero()],
+            &[F::one(), F::one(), -F::one(), F::zero()],
+        )?;
+        Ok((first, second))
+    }
+
+    /// Obtain the elements of `a` sorted in non-decreasing order, with a
+    /// sorting network of conditional swaps (Batcher's odd-even merge sort,
+    /// `O(n log^2 n)` comparators for `n` elements).
+    /// The elements of `a` are constrained to be in the range
+    /// [0, 2^`bit_len`). Return error if `bit_len` is zero or not smaller
+    /// than the field size, or if a variable is invalid.
+    pub fn sorting_network(
+        &mut self,
+        a: &[Variable],
+        bit_len: usize,
+    ) -> Result<Vec<Variable>, CircuitError> {
+        if bit_len == 0 || bit_len >= F::MODULUS_BIT_SIZE as usize {
+            return Err(ParameterError(format!(
+                "sorted elements must have between 1 and {} bits, got {bit_len}",
+                F::MODULUS_BIT_SIZE - 1
+            )));
+        }
+        self.check_vars_bound(a)?;
+        for &var in a.iter() {
+            self.enforce_in_range(var, bit_len)?;
+        }
+        let mut sorted = a.to_vec();
+        let n = sorted.len();
+        let mut p = 1;
+        while p < n {
+            let mut k = p;
+            while k > 0 {
+                let mut j = k % p;
+                while j + k < n {
+                    for i in 0..k.min(n - j - k) {
+                        if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                            let (lo, hi) =
+                                self.compare_and_swap(sorted[i + j], sorted[i + j + k], bit_len)?;
+                            sorted[i + j] = lo;
+                            sorted[i + j + k] = hi;
+                        }
+                    }
+                    j += 2 * k;
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+        Ok(sorted)
+    }
+}
+
+/// Private helper functions for sorting and permutation gadgets.
+impl<F: PrimeField> PlonkCircuit<F> {
+    // prod_i (a_i - challenge), with a gate per element.
+    fn grand_product(
+        &mut self,
+        a: &[Variable],
+        challenge: Variable,
+    ) -> Result<Variable, CircuitError> {
+        let mut prod = self.one();
+        for &var in a.iter() {
+            // prod' = prod * var - prod * challenge
+            prod = self.mul_add(&[prod, var, prod, challenge], &[F::one(), -F::one()])?;
+        }
+        Ok(prod)
+    }
+
+    // x_0 + x_1 * challenge + ... + x_{N-1} * challenge^{N-1}, by Horner's rule.
+    fn fold_tuple(
+        &mut self,
+        tuple: &[Variable],
+        challenge: Variable,
+    ) -> Result<Variable, CircuitError> {
+        self.check_vars_bound(tuple)?;
+        let one = self.one();
+        let mut folded = tuple[tuple.len() - 1];
+        for &var in tuple.iter().rev().skip(1) {
+            folded = self.mul_add(&[folded, challenge, var, one], &[F::one(), F::one()])?;
+        }
+        Ok(folded)
+    }
+
+    // The sorting check of `enforce_sorted` and `enforce_strictly_sorted`: all
+    // the elements lie between the first and the last ones, which are in range,
+    // unless the differences wrap around the modulus.
+    fn sorted_gate(
+        &mut self,
+        a: &[Variable],
+        bit_len: usize,
+        strict: bool,
+    ) -> Result<(), CircuitError> {
+        let len_bits = (usize::BITS - a.len().leading_zeros()) as usize;
+        if bit_len == 0 || bit_len + len_bits >= F::MODULUS_BIT_SIZE as usize {
+            return Err(ParameterError(format!(
+                "cannot check the order of {} elements of {bit_len} bits",
+                a.len()
+            )));
+        }
+        self.check_vars_bound(a)?;
+        let (first, last) = match (a.first(), a.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return Ok(()),
+        };
+        self.enforce_in_range(first, bit_len)?;
+        if a.len() > 1 {
+            self.enforce_in_range(last, bit_len)?;
+        }
+        let zero = self.zero();
+        let offset = if strict { -F::one() } else { F::zero() };
+        for pair in a.windows(2) {
+            let diff = self.gen_quad_poly(
+                &[pair[1], pair[0], zero, zero],
+                &[F::one(), -F::one(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                offset,
+            )?;
+            self.enforce_in_range(diff, bit_len)?;
+        }
+        Ok(())
+    }
+
+    // Order `a` and `b`, both in the range [0, 2^`bit_len`).
+    fn compare_and_swap(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<(Variable, Variable), CircuitError> {
+        let swap = self.witness(a)?.into_bigint() > self.witness(b)?.into_bigint();
+        let swap = self.create_boolean_variable(swap)?;
+        let (lo, hi) = self.conditional_swap(swap, a, b)?;
+        let diff = self.sub(hi, lo)?;
+        self.enforce_in_range(diff, bit_len)?;
+        Ok((lo, hi))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::UniformRand;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_multiset_equal() -> Result<(), CircuitError> {
+        test_multiset_equal_helper::<FqEd254>()?;
+        test_multiset_equal_helper::<FqEd377>()?;
+        test_multiset_equal_helper::<FqEd381>()?;
+        test_multiset_equal_helper::<Fq377>()
+    }
+    fn test_multiset_equal_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let rng = &mut test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let mut vals: Vec<F> = (0..9).map(|_| F::rand(rng)).collect();
+        vals[3] = vals[5];
+        let a: Vec<_> = vals
+            .iter()
+            .map(|&val| circuit.create_variable(val))
+            .collect::<Result<_, _>>()?;
+        let b: Vec<_> = vals
+            .iter()
+            .rev()
+            .cycle()
+            .skip(4)
+            .take(vals.len())
+            .map(|&val| circuit.create_variable(val))
+            .collect::<Result<_, _>>()?;
+        let challenge = circuit.create_public_variable(F::rand(rng))?;
+        circuit.enforce_multiset_equal(&a, &b, challenge)?;
+        let pub_input = [circuit.witness(challenge)?];
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+        // a different element
+        *circuit.witness_mut(b[2]) = F::rand(rng);
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_err());
+        // same sets, different multiplicities
+        *circuit.witness_mut(b[2]) = circuit.witness(a[0])?;
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_err());
+
+        // tuples
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(8);
+        let a: Vec<[Variable; 3]> = (0..6)
+            .map(|_| {
+                Ok([
+                    circuit.create_variable(F::rand(rng))?,
+                    circuit.create_variable(F::rand(rng))?,
+                    circuit.create_variable(F::rand(rng))?,
+                ])
+            })
+            .collect::<Result<_, CircuitError>>()?;
+        let b: Vec<_> = a.iter().rev().copied().collect();
+        let challenge = circuit.create_variable(F::rand(rng))?;
+        let fold_challenge = circuit.create_variable(F::rand(rng))?;
+        circuit.enforce_multiset_equal_tuples(&a, &b, challenge, fold_challenge)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // elements moved across tuples
+        let mut bad_b = b.clone();
+        bad_b[0].swap(0, 1);
+        circuit.enforce_multiset_equal_tuples(&a, &bad_b, challenge, fold_challenge)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // error paths
+        assert!(circuit
+            .enforce_multiset_equal(&[challenge], &[], challenge)
+            .is_err());
+        assert!(circuit
+            .enforce_multiset_equal(&[challenge], &[circuit.num_vars()], challenge)
+            .is_err());
+        assert!(circuit
+            .enforce_multiset_equal(&[challenge], &[challenge], circuit.num_vars())
+            .is_err());
+        assert!(circuit
+            .enforce_multiset_equal_tuples(&a, &b[1..], challenge, fold_challenge)
+            .is_err());
+        assert!(circuit
+            .enforce_multiset_equal_tuples::<0>(&[], &[], challenge, fold_challenge)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sorted() -> Result<(), CircuitError> {
+        test_sorted_helper::<FqEd254>()?;
+        test_sorted_helper::<FqEd377>()?;
+        test_sorted_helper::<FqEd381>()?;
+        test_sorted_helper::<Fq377>()
+    }
+    fn test_sorted_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            let check = |vals: &[F], bit_len: usize, strict: bool| {
+                let mut circuit = circuit.clone();
+                let vars: Vec<_> = vals
+                    .iter()
+                    .map(|&val| circuit.create_variable(val))
+                    .collect::<Result<_, _>>()?;
+                if strict {
+                    circuit.enforce_strictly_sorted(&vars, bit_len)?;
+                } else {
+                    circuit.enforce_sorted(&vars, bit_len)?;
+                }
+                Ok::<_, CircuitError>(circuit.check_circuit_satisfiability(&[]).is_ok())
+            };
+            let vals: Vec<F> = [0u32, 3, 3, 7, 200, 255].map(F::from).to_vec();
+            assert!(check(&vals, 8, false)?);
+            assert!(!check(&vals, 8, true)?);
+            assert!(check(&[vals[0], vals[1], vals[3]], 8, true)?);
+            assert!(check(&[], 8, true)?);
+            assert!(check(&vals[4..5], 8, true)?);
+            // unsorted
+            assert!(!check(&[vals[1], vals[0]], 8, false)?);
+            // out of range
+            assert!(!check(&vals, 7, false)?);
+            assert!(!check(&[F::from(256u32)], 8, false)?);
+            // wrapping around the modulus
+            assert!(!check(&[-F::one(), F::zero(), F::one()], 8, false)?);
+
+            // error paths
+            let mut circuit = circuit.clone();
+            let bits = F::MODULUS_BIT_SIZE as usize;
+            assert!(circuit.enforce_sorted(&[circuit.zero()], 0).is_err());
+            assert!(circuit.enforce_sorted(&[circuit.zero()], bits - 1).is_err());
+            assert!(circuit.enforce_sorted(&[circuit.zero()], bits - 2).is_ok());
+            assert!(circuit
+                .enforce_sorted(&[circuit.zero(), circuit.num_vars()], 8)
+                .is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sorting_network() -> Result<(), CircuitError> {
+        test_sorting_network_helper::<FqEd254>()?;
+        test_sorting_network_helper::<FqEd377>()?;
+        test_sorting_network_helper::<FqEd381>()?;
+        test_sorting_network_helper::<Fq377>()
+    }
+    fn test_sorting_network_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let rng = &mut test_rng();
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            for n in 0..12 {
+                let mut circuit = circuit.clone();
+                let mut vals: Vec<u32> = (0..n).map(|_| u32::rand(rng) % 16).collect();
+                let a: Vec<_> = vals
+                    .iter()
+                    .map(|&val| circuit.create_variable(F::from(val)))
+                    .collect::<Result<_, _>>()?;
+                let sorted = circuit.sorting_network(&a, 4)?;
+                vals.sort_unstable();
+                let witness: Vec<_> = sorted
+                    .iter()
+                    .map(|&var| circuit.witness(var))
+                    .collect::<Result<_, _>>()?;
+                assert_eq!(
+                    witness,
+                    vals.iter().map(|&val| F::from(val)).collect::<Vec<_>>()
+                );
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+                if n > 0 {
+                    // an input out of range
+                    let mut bad_circuit = circuit.clone();
+                    *bad_circuit.witness_mut(a[0]) = F::from(16u32);
+                    assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+                }
+            }
+
+            // a wrongly swapped pair
+            let mut circuit = circuit.clone();
+            let a = circuit.create_variable(F::from(2u32))?;
+            let b = circuit.create_variable(F::from(1u32))?;
+            let swap = circuit.create_boolean_variable(false)?;
+            let (lo, hi) = circuit.conditional_swap(swap, a, b)?;
+            assert_eq!(circuit.witness(lo)?, F::from(2u32));
+            assert_eq!(circuit.witness(hi)?, F::from(1u32));
+            let diff = circuit.sub(hi, lo)?;
+            circuit.enforce_in_range(diff, 4)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // error paths
+            let bits = F::MODULUS_BIT_SIZE as usize;
+            assert!(circuit.sorting_network(&[a, b], 0).is_err());
+            assert!(circuit.sorting_network(&[a, b], bits).is_err());
+            assert!(circuit
+                .sorting_network(&[a, circuit.num_vars()], 4)
+                .is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,746 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Fixed-width unsigned integer variables and gadgets.
+//!
+//! Bitwise operations are proven with bit decompositions on TurboPlonk
+//! circuits, and with lookups into built-in 4-bit nibble tables on UltraPlonk
+//! circuits.
+
+use crate::{errors::CircuitError, Circuit, PlonkCircuit, Variable};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{format, vec, vec::Vec};
+
+/// A variable holding an unsigned integer of `BITS` bits, i.e. whose value is
+/// constrained to the range [0, 2^`BITS`).
+///
+/// `BITS` is a multiple of 8 between 8 and 64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UIntVar<const BITS: usize>(pub(crate) Variable);
+
+/// An 8-bit unsigned integer variable.
+pub type UInt8Var = UIntVar<8>;
+/// A 32-bit unsigned integer variable.
+pub type UInt32Var = UIntVar<32>;
+/// A 64-bit unsigned integer variable.
+pub type UInt64Var = UIntVar<64>;
+
+impl<const BITS: usize> UIntVar<BITS> {
+    /// The underlying native variable.
+    pub fn var(&self) -> Variable {
+        self.0
+    }
+}
+
+impl<const BITS: usize> From<UIntVar<BITS>> for Variable {
+    fn from(a: UIntVar<BITS>) -> Self {
+        a.0
+    }
+}
+
+/// The built-in lookup tables of bitwise operations over 4-bit nibbles. The
+/// entry with key `a + 16 * b` holds the values `(a, op(a, b))`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NibbleTable {
+    Xor,
+    And,
+}
+
+impl NibbleTable {
+    fn apply(self, a: u8, b: u8) -> u8 {
+        match self {
+            Self::Xor => a ^ b,
+            Self::And => a & b,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Xor => "XOR Nibble Lookup",
+            Self::And => "AND Nibble Lookup",
+        }
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for the `BITS`-bit unsigned integer `val`, with a
+    /// range check.
+    /// Return error if `val` does not fit in `BITS` bits, or if `BITS` is not
+    /// supported.
+    pub fn create_uint_variable<const BITS: usize>(
+        &mut self,
+        val: u64,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_value::<F, BITS>(val)?;
+        let var = self.create_variable(F::from(val))?;
+        self.enforce_in_range(var, BITS)?;
+        Ok(UIntVar(var))
+    }
+
+    /// Create a constant variable for the `BITS`-bit unsigned integer `val`.
+    /// Return error if `val` does not fit in `BITS` bits, or if `BITS` is not
+    /// supported.
+    pub fn create_constant_uint_variable<const BITS: usize>(
+        &mut self,
+        val: u64,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_value::<F, BITS>(val)?;
+        Ok(UIntVar(self.create_constant_variable(F::from(val))?))
+    }
+
+    /// Create a public input variable for the `BITS`-bit unsigned integer
+    /// `val`, with a range check.
+    /// Return error if `val` does not fit in `BITS` bits, or if `BITS` is not
+    /// supported.
+    pub fn create_public_uint_variable<const BITS: usize>(
+        &mut self,
+        val: u64,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_value::<F, BITS>(val)?;
+        let var = self.create_public_variable(F::from(val))?;
+        self.enforce_in_range(var, BITS)?;
+        Ok(UIntVar(var))
+    }
+
+    /// Constrain the existing variable `a` to be in the range [0, 2^`BITS`)
+    /// and return it as a `BITS`-bit unsigned integer variable.
+    /// Return error if `a` is invalid or if `BITS` is not supported.
+    pub fn uint_from_variable<const BITS: usize>(
+        &mut self,
+        a: Variable,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        self.enforce_in_range(a, BITS)?;
+        Ok(UIntVar(a))
+    }
+
+    /// The value of an unsigned integer variable. Only the lowest 64 bits of
+    /// the witness are returned if it has been tampered with.
+    pub fn uint_witness<const BITS: usize>(&self, a: UIntVar<BITS>) -> Result<u64, CircuitError> {
+        Ok(self.witness(a.0)?.into_bigint().as_ref()[0])
+    }
+
+    /// Obtain a variable for `a + b`.
+    /// The circuit is not satisfiable if the sum overflows `BITS` bits.
+    pub fn uint_add<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 1)?;
+        let c = self.add(a.0, b.0)?;
+        self.enforce_in_range(c, BITS)?;
+        Ok(UIntVar(c))
+    }
+
+    /// Obtain a variable for `a + b mod 2^BITS`.
+    pub fn uint_wrapping_add<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        self.uint_wrapping_sum(&[a, b])
+    }
+
+    /// Obtain a variable for the sum of `elems` modulo 2^`BITS`.
+    /// Return error if `elems` is empty or if the unreduced sum may not fit in
+    /// the field.
+    pub fn uint_wrapping_sum<const BITS: usize>(
+        &mut self,
+        elems: &[UIntVar<BITS>],
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        if elems.len() == 1 {
+            return Ok(elems[0]);
+        }
+        let vars: Vec<Variable> = elems.iter().map(|a| a.0).collect();
+        let sum = self.sum(&vars)?;
+        let carry_bits = (usize::BITS - (elems.len() - 1).leading_zeros()) as usize;
+        check_uint_bits::<F>(BITS, carry_bits)?;
+        let (lo, _) = self.uint_split(sum, BITS + carry_bits, BITS)?;
+        Ok(UIntVar(lo))
+    }
+
+    /// Obtain a variable for `a * b`.
+    /// The circuit is not satisfiable if the product overflows `BITS` bits.
+    pub fn uint_mul<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, BITS)?;
+        let c = self.mul(a.0, b.0)?;
+        self.enforce_in_range(c, BITS)?;
+        Ok(UIntVar(c))
+    }
+
+    /// Obtain a variable for `a * b mod 2^BITS`.
+    pub fn uint_wrapping_mul<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, BITS)?;
+        let c = self.mul(a.0, b.0)?;
+        let (lo, _) = self.uint_split(c, 2 * BITS, BITS)?;
+        Ok(UIntVar(lo))
+    }
+
+    /// Obtain a variable for `a << shift`, discarding the bits shifted out.
+    pub fn uint_shl<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        shift: usize,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        if shift == 0 {
+            return Ok(a);
+        }
+        if shift >= BITS {
+            return Ok(UIntVar(self.zero()));
+        }
+        let (lo, _) = self.uint_split(a.0, BITS, BITS - shift)?;
+        Ok(UIntVar(self.mul_constant(lo, &pow2(shift))?))
+    }
+
+    /// Obtain a variable for `a >> shift`.
+    pub fn uint_shr<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        shift: usize,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        if shift == 0 {
+            return Ok(a);
+        }
+        if shift >= BITS {
+            return Ok(UIntVar(self.zero()));
+        }
+        let (_, hi) = self.uint_split(a.0, BITS, shift)?;
+        Ok(UIntVar(hi))
+    }
+
+    /// Obtain a variable for `a` rotated to the left by `shift` bits.
+    pub fn uint_rotl<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        shift: usize,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        self.uint_rotr(a, BITS - shift % BITS)
+    }
+
+    /// Obtain a variable for `a` rotated to the right by `shift` bits.
+    pub fn uint_rotr<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        shift: usize,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        let shift = shift % BITS;
+        if shift == 0 {
+            return Ok(a);
+        }
+        let (lo, hi) = self.uint_split(a.0, BITS, shift)?;
+        let c = self.lc(
+            &[lo, hi, self.zero(), self.zero()],
+            &[pow2(BITS - shift), F::one(), F::zero(), F::zero()],
+        )?;
+        Ok(UIntVar(c))
+    }
+
+    /// Obtain a variable for the bitwise negation of `a`.
+    pub fn uint_not<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        let zero = self.zero();
+        let c = self.gen_quad_poly(
+            &[a.0, zero, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            pow2::<F>(BITS) - F::one(),
+        )?;
+        Ok(UIntVar(c))
+    }
+
+    /// Obtain a variable for the bitwise XOR of `a` and `b`.
+    pub fn uint_xor<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        self.uint_bitwise(a, b, NibbleTable::Xor, 0)
+    }
+
+    /// Obtain a variable for the bitwise XOR of `a` and `b` rotated to the
+    /// right by `shift` bits. The rotation comes for free if `shift` is a
+    /// multiple of 4.
+    pub fn uint_xor_rotr<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+        shift: usize,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        self.uint_bitwise(a, b, NibbleTable::Xor, shift)
+    }
+
+    /// Obtain a variable for the bitwise AND of `a` and `b`.
+    pub fn uint_and<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        self.uint_bitwise(a, b, NibbleTable::And, 0)
+    }
+
+    /// Obtain a variable for the bitwise OR of `a` and `b`, computed as
+    /// `a + b - (a & b)`.
+    pub fn uint_or<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        let and = self.uint_and(a, b)?;
+        let zero = self.zero();
+        let c = self.lc(
+            &[a.0, b.0, and.0, zero],
+            &[F::one(), F::one(), -F::one(), F::zero()],
+        )?;
+        Ok(UIntVar(c))
+    }
+
+    /// Decompose `a` into its `BITS / 8` bytes, in little-endian order.
+    /// Return error if `BITS` is not a multiple of 8.
+    pub fn uint_to_bytes<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+    ) -> Result<Vec<UInt8Var>, CircuitError> {
+        check_uint_bytes::<F>(BITS)?;
+        if BITS == 8 {
+            return Ok(vec![UIntVar(a.0)]);
+        }
+        let val = self.uint_witness(a)?;
+        let bytes = (0..BITS / 8)
+            .map(|i| self.create_uint_variable((val >> (8 * i)) & 0xff))
+            .collect::<Result<Vec<UInt8Var>, CircuitError>>()?;
+        self.decomposition_gate(bytes.iter().map(|b| b.0).collect(), a.0, F::from(256u32))?;
+        Ok(bytes)
+    }
+
+    /// Pack `BITS / 8` bytes, given in little-endian order, into a `BITS`-bit
+    /// unsigned integer.
+    /// Return error if `BITS` is not a multiple of 8 or if the number of bytes
+    /// is wrong.
+    pub fn uint_from_bytes<const BITS: usize>(
+        &mut self,
+        bytes: &[UInt8Var],
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bytes::<F>(BITS)?;
+        if bytes.len() != BITS / 8 {
+            return Err(CircuitError::ParameterError(format!(
+                "expecting {} bytes for a {}-bit integer, got {}",
+                BITS / 8,
+                BITS,
+                bytes.len()
+            )));
+        }
+        if BITS == 8 {
+            return Ok(UIntVar(bytes[0].0));
+        }
+        let val = bytes
+            .iter()
+            .rev()
+            .map(|&b| self.uint_witness(b))
+            .try_fold(0u64, |acc, b| b.map(|b| (acc << 8) | b))?;
+        let c = self.create_variable(F::from(val))?;
+        self.decomposition_gate(bytes.iter().map(|b| b.0).collect(), c, F::from(256u32))?;
+        Ok(UIntVar(c))
+    }
+}
+
+/// Private helper functions for unsigned integer gadgets.
+impl<F: PrimeField> PlonkCircuit<F> {
+    // Split `a` into `lo + 2^low_bits * hi` with range checked `lo` and `hi`,
+    // where `a` is expected to fit in `total_bits` bits and
+    // `0 < low_bits < total_bits`.
+    pub(crate) fn uint_split(
+        &mut self,
+        a: Variable,
+        total_bits: usize,
+        low_bits: usize,
+    ) -> Result<(Variable, Variable), CircuitError> {
+        let bits = self.witness(a)?.into_bigint().to_bits_le();
+        let lo = self.create_variable(field_from_bits_le(&bits[..low_bits]))?;
+        let hi = self.create_variable(field_from_bits_le(&bits[low_bits..]))?;
+        self.enforce_in_range(lo, low_bits)?;
+        self.enforce_in_range(hi, total_bits - low_bits)?;
+        let zero = self.zero();
+        self.lc_gate(
+            &[lo, hi, zero, zero, a],
+            &[F::one(), pow2(low_bits), F::zero(), F::zero()],
+        )?;
+        Ok((lo, hi))
+    }
+
+    // Bitwise operation `op` over `a` and `b`, with the result rotated to the
+    // right by `shift` bits. The rotation is free if it is aligned with the
+    // chunks the operation is proven over: nibbles on UltraPlonk, bits on
+    // TurboPlonk.
+    fn uint_bitwise<const BITS: usize>(
+        &mut self,
+        a: UIntVar<BITS>,
+        b: UIntVar<BITS>,
+        op: NibbleTable,
+        shift: usize,
+    ) -> Result<UIntVar<BITS>, CircuitError> {
+        check_uint_bits::<F>(BITS, 0)?;
+        let a_val = self.uint_witness(a)?;
+        let b_val = self.uint_witness(b)?;
+        let c_val = match op {
+            NibbleTable::Xor => a_val ^ b_val,
+            NibbleTable::And => a_val & b_val,
+        };
+        let (mut c_chunks, chunk_bits) = if self.support_lookup() {
+            // look up each pair of nibbles, the lookup range checks both
+            // nibbles of `a` and `b` and the nibbles of the result
+            let nibble = |val: u64, i: usize| (val >> (4 * i)) & 0xf;
+            let mut a_nibbles = vec![];
+            let mut b_nibbles = vec![];
+            let mut c_nibbles = vec![];
+            for i in 0..BITS / 4 {
+                let a_i = self.create_variable(F::from(nibble(a_val, i)))?;
+                let b_i = self.create_variable(F::from(nibble(b_val, i)))?;
+                let c_i = self.create_variable(F::from(nibble(c_val, i)))?;
+                let zero = self.zero();
+                let key = self.lc(
+                    &[a_i, b_i, zero, zero],
+                    &[F::one(), F::from(16u32), F::zero(), F::zero()],
+                )?;
+                let table = self.nibble_table(op)?;
+                self.deferred_tables_mut()[table]
+                    .lookups
+                    .push([key, a_i, c_i]);
+                a_nibbles.push(a_i);
+                b_nibbles.push(b_i);
+                c_nibbles.push(c_i);
+            }
+            let sixteen = F::from(16u32);
+            self.decomposition_gate(a_nibbles, a.0, sixteen)?;
+            self.decomposition_gate(b_nibbles, b.0, sixteen)?;
+            (c_nibbles, 4)
+        } else {
+            let a_bits = self.range_gate_internal(a.0, BITS)?;
+            let b_bits = self.range_gate_internal(b.0, BITS)?;
+            let (q_lc, q_mul) = match op {
+                NibbleTable::Xor => (F::one(), -F::from(2u32)),
+                NibbleTable::And => (F::zero(), F::one()),
+            };
+            let zero = self.zero();
+            let c_bits = a_bits
+                .into_iter()
+                .zip(b_bits)
+                .map(|(a_i, b_i)| {
+                    self.gen_quad_poly(
+                        &[a_i.into(), b_i.into(), zero, zero],
+                        &[q_lc, q_lc, F::zero(), F::zero()],
+                        &[q_mul, F::zero()],
+                        F::zero(),
+                    )
+                })
+                .collect::<Result<Vec<_>, CircuitError>>()?;
+            (c_bits, 1)
+        };
+        let shift = shift % BITS;
+        let aligned_shift = shift - shift % chunk_bits;
+        c_chunks.rotate_left(aligned_shift / chunk_bits);
+        let c = self.create_variable(F::from(rotr_u64::<BITS>(c_val, aligned_shift)))?;
+        self.decomposition_gate(c_chunks, c, pow2(chunk_bits))?;
+        self.uint_rotr(UIntVar(c), shift - aligned_shift)
+    }
+
+    // The index of the deferred nibble table of `op`, created at first use.
+    fn nibble_table(&mut self, op: NibbleTable) -> Result<usize, CircuitError> {
+        if let Some(table) = self
+            .deferred_tables()
+            .iter()
+            .position(|table| table.name == op.name())
+        {
+            return Ok(table);
+        }
+        let mut nibbles = vec![self.zero(), self.one()];
+        for i in 2..16u32 {
+            nibbles.push(self.create_constant_variable(F::from(i))?);
+        }
+        let entries = (0..256u16)
+            .map(|key| {
+                let (a, b) = ((key % 16) as u8, (key / 16) as u8);
+                (nibbles[a as usize], nibbles[op.apply(a, b) as usize])
+            })
+            .collect();
+        Ok(self.create_deferred_table(op.name(), entries))
+    }
+}
+
+// Check that `BITS` is supported and that integers of `BITS + extra_bits` bits
+// fit in the field.
+pub(crate) fn check_uint_bits<F: PrimeField>(
+    bits: usize,
+    extra_bits: usize,
+) -> Result<(), CircuitError> {
+    if bits == 0 || bits > 64 || bits % 8 != 0 {
+        return Err(CircuitError::ParameterError(format!(
+            "unsupported integer width: {bits} bits"
+        )));
+    }
+    if bits + extra_bits >= F::MODULUS_BIT_SIZE as usize {
+        return Err(CircuitError::ParameterError(format!(
+            "field too small for {bits}-bit integer arithmetic"
+        )));
+    }
+    Ok(())
+}
+
+// Check that `BITS` is supported and is a whole number of bytes.
+fn check_uint_bytes<F: PrimeField>(bits: usize) -> Result<(), CircuitError> {
+    if bits % 8 != 0 {
+        return Err(CircuitError::ParameterError(format!(
+            "a {bits}-bit integer is not a whole number of bytes"
+        )));
+    }
+    check_uint_bits::<F>(bits, 0)
+}
+
+fn check_uint_value<F: PrimeField, const BITS: usize>(val: u64) -> Result<(), CircuitError> {
+    check_uint_bits::<F>(BITS, 0)?;
+    if BITS < 64 && val >> BITS != 0 {
+        return Err(CircuitError::ParameterError(format!(
+            "{val} does not fit in {BITS} bits"
+        )));
+    }
+    Ok(())
+}
+
+// Rotate the `BITS`-bit integer `val` to the right by `shift < BITS` bits.
+#[inline]
+fn rotr_u64<const BITS: usize>(val: u64, shift: usize) -> u64 {
+    if shift == 0 {
+        val
+    } else {
+        ((val >> shift) | (val << (BITS - shift))) & (u64::MAX >> (64 - BITS))
+    }
+}
+
+#[inline]
+pub(crate) fn pow2<F: PrimeField>(exp: usize) -> F {
+    F::from(2u32).pow([exp as u64])
+}
+
+#[inline]
+fn field_from_bits_le<F: PrimeField>(bits: &[bool]) -> F {
+    F::from_bigint(F::BigInt::from_bits_le(bits)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        constraint_system::test::test_arithmetization_for_lookup_circuit, Circuit, ConstraintKind,
+    };
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::UniformRand;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_uint_gadgets() -> Result<(), CircuitError> {
+        test_uint_gadgets_helper::<FqEd254>()?;
+        test_uint_gadgets_helper::<FqEd377>()?;
+        test_uint_gadgets_helper::<Fq377>()
+    }
+
+    fn test_uint_gadgets_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let rng = &mut test_rng();
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            let vals = [
+                (0, 0),
+                (u64::MAX, u64::MAX),
+                (u64::MAX, 1),
+                (u64::rand(rng), u64::rand(rng)),
+            ];
+            for (a, b) in vals {
+                check_uint_ops::<F, 8>(circuit.clone(), a, b)?;
+                check_uint_ops::<F, 32>(circuit.clone(), a, b)?;
+                check_uint_ops::<F, 64>(circuit.clone(), a, b)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_uint_ops<F: PrimeField, const BITS: usize>(
+        mut circuit: PlonkCircuit<F>,
+        a_val: u64,
+        b_val: u64,
+    ) -> Result<(), CircuitError> {
+        let mask = u64::MAX >> (64 - BITS);
+        let (a_val, b_val) = (a_val & mask, b_val & mask);
+        let a: UIntVar<BITS> = circuit.create_uint_variable(a_val)?;
+        let b: UIntVar<BITS> = circuit.create_public_uint_variable(b_val)?;
+        let c: UIntVar<BITS> = circuit.create_constant_uint_variable(mask / 3)?;
+        let mut expected = vec![];
+
+        let res = circuit.uint_wrapping_add(a, b)?;
+        expected.push((res, a_val.wrapping_add(b_val) & mask));
+        let res = circuit.uint_wrapping_sum(&[a, b, c, a, b])?;
+        let sum = [a_val, b_val, mask / 3, a_val, b_val]
+            .iter()
+            .fold(0u64, |acc, v| acc.wrapping_add(*v));
+        expected.push((res, sum & mask));
+        let res = circuit.uint_wrapping_mul(a, b)?;
+        expected.push((res, a_val.wrapping_mul(b_val) & mask));
+        for shift in [0, 1, 4, 7, 8, BITS - 1, BITS, BITS + 3] {
+            let res = circuit.uint_shl(a, shift)?;
+            expected.push((res, a_val.checked_shl(shift as u32).unwrap_or(0) & mask));
+            let res = circuit.uint_shr(a, shift)?;
+            expected.push((res, a_val.checked_shr(shift as u32).unwrap_or(0)));
+            let r = (shift % BITS) as u32;
+            let rotr = if r == 0 {
+                a_val
+            } else {
+                ((a_val >> r) | (a_val << (BITS as u32 - r))) & mask
+            };
+            let res = circuit.uint_rotr(a, shift)?;
+            expected.push((res, rotr));
+            let res = circuit.uint_rotl(res, shift)?;
+            expected.push((res, a_val));
+            let res = circuit.uint_xor_rotr(a, b, shift)?;
+            expected.push((res, rotr_u64::<BITS>(a_val ^ b_val, r as usize)));
+        }
+        let res = circuit.uint_not(a)?;
+        expected.push((res, !a_val & mask));
+        let res = circuit.uint_xor(a, b)?;
+        expected.push((res, a_val ^ b_val));
+        let res = circuit.uint_and(a, c)?;
+        expected.push((res, a_val & (mask / 3)));
+        let res = circuit.uint_or(b, c)?;
+        expected.push((res, b_val | (mask / 3)));
+        let bytes = circuit.uint_to_bytes(a)?;
+        assert_eq!(bytes.len(), BITS / 8);
+        for (i, byte) in bytes.iter().enumerate() {
+            assert_eq!(circuit.uint_witness(*byte)?, (a_val >> (8 * i)) & 0xff);
+        }
+        let res = circuit.uint_from_bytes(&bytes)?;
+        expected.push((res, a_val));
+        assert!(circuit.uint_from_bytes::<BITS>(&bytes[1..]).is_err());
+
+        for (res, val) in expected.iter() {
+            assert_eq!(circuit.uint_witness(*res)?, *val);
+        }
+        let pub_input = [F::from(b_val)];
+        circuit.check_circuit_satisfiability(&pub_input)?;
+
+        // overflow-checked arithmetic
+        let mut bad_circuit = circuit.clone();
+        let sum = circuit.uint_add(a, b)?;
+        let prod = circuit.uint_mul(a, b)?;
+        let no_overflow = (a_val as u128 + b_val as u128) <= mask as u128
+            && (a_val as u128 * b_val as u128) <= mask as u128;
+        assert_eq!(
+            circuit.check_circuit_satisfiability(&pub_input).is_ok(),
+            no_overflow
+        );
+        if no_overflow {
+            assert_eq!(circuit.uint_witness(sum)?, a_val + b_val);
+            assert_eq!(circuit.uint_witness(prod)?, a_val * b_val);
+        }
+
+        // tampering with any result breaks the circuit
+        for (res, _) in expected.iter() {
+            if res.0 < 2 {
+                continue;
+            }
+            let mut circuit = bad_circuit.clone();
+            *circuit.witness_mut(res.0) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&pub_input).is_err());
+        }
+
+        // lookups are checked before and after the tables are inserted
+        bad_circuit.finalize_for_arithmetization()?;
+        bad_circuit.check_circuit_satisfiability(&pub_input)?;
+        if bad_circuit.support_lookup() {
+            test_arithmetization_for_lookup_circuit(&bad_circuit)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_uint_bitwise_lookups() -> Result<(), CircuitError> {
+        let mut circuit = PlonkCircuit::<FqEd254>::new_ultra_plonk(8);
+        let a: UInt32Var = circuit.create_uint_variable(0xdead_beef)?;
+        let b: UInt32Var = circuit.create_uint_variable(0x1234_5678)?;
+        let xor = circuit.uint_xor(a, b)?;
+        let and = circuit.uint_and(a, b)?;
+        assert_eq!(circuit.uint_witness(xor)?, 0xdead_beef ^ 0x1234_5678);
+        assert_eq!(circuit.uint_witness(and)?, 0xdead_beef & 0x1234_5678);
+        // one table per operation is inserted, whatever the number of lookups
+        for _ in 0..100 {
+            circuit.uint_xor(a, b)?;
+        }
+        let num_table_elems = circuit.num_table_elems();
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.num_table_elems(), num_table_elems + 808 + 256);
+        circuit.check_circuit_satisfiability(&[])?;
+
+        // a wrong nibble is reported as a lookup failure
+        let mut circuit = PlonkCircuit::<FqEd254>::new_ultra_plonk(8);
+        let a: UInt8Var = circuit.create_uint_variable(0x5a)?;
+        let b: UInt8Var = circuit.create_uint_variable(0xc3)?;
+        circuit.uint_xor(a, b)?;
+        let [_, _, val1] = circuit.deferred_tables()[0].lookups[0];
+        *circuit.witness_mut(val1) = FqEd254::from(3u32);
+        let reports = circuit.unsatisfied_gates(&[], 10)?;
+        assert!(reports
+            .iter()
+            .any(|r| r.kind == ConstraintKind::Lookup && r.gate_name == "XOR Nibble Lookup"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_uint_bad_parameters() {
+        let mut circuit = PlonkCircuit::<FqEd254>::new_turbo_plonk();
+        assert!(circuit.create_uint_variable::<8>(256).is_err());
+        assert!(circuit.create_uint_variable::<12>(1).is_err());
+        assert!(circuit.create_uint_variable::<128>(1).is_err());
+        assert!(circuit
+            .create_constant_uint_variable::<32>(1 << 32)
+            .is_err());
+        assert!(circuit.uint_wrapping_sum::<32>(&[]).is_err());
+
+        // byte conversions need a whole number of bytes
+        let a = UIntVar::<12>(circuit.create_variable(FqEd254::from(1u32)).unwrap());
+        assert!(matches!(
+            circuit.uint_to_bytes(a),
+            Err(CircuitError::ParameterError(_))
+        ));
+        let byte = circuit.create_uint_variable::<8>(1).unwrap();
+        assert!(matches!(
+            circuit.uint_from_bytes::<12>(&[byte, byte]),
+            Err(CircuitError::ParameterError(_))
+        ));
+        assert!(matches!(
+            circuit.uint_from_bytes::<4>(&[]),
+            Err(CircuitError::ParameterError(_))
+        ));
+        assert!(matches!(
+            circuit.uint_from_bytes::<16>(&[byte]),
+            Err(CircuitError::ParameterError(_))
+        ));
+    }
+}
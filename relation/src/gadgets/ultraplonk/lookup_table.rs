@@ -8,7 +8,19 @@
 
 use crate::{errors::CircuitError, gates::LookupGate, Circuit, PlonkCircuit, Variable};
 use ark_ff::PrimeField;
-use ark_std::{boxed::Box, cmp::max};
+use ark_std::{boxed::Box, cmp::max, mem, vec, vec::Vec};
+
+/// A lookup table whose lookups are collected until the circuit is
+/// finalized, when the table and the lookups are inserted together.
+#[derive(Debug, Clone)]
+pub(crate) struct DeferredTable {
+    /// The gate name reported for unsatisfied lookups.
+    pub(crate) name: &'static str,
+    /// The values `(val0, val1)` of the entry with key `i`.
+    pub(crate) entries: Vec<(Variable, Variable)>,
+    /// The looked up tuples `(key, val0, val1)`.
+    pub(crate) lookups: Vec<[Variable; 3]>,
+}
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Create a table with keys/values
@@ -69,6 +81,36 @@ impl<F: PrimeField> PlonkCircuit<F> {
         *self.num_table_elems_mut() += n;
         Ok(())
     }
+
+    // Add a deferred table and return its index.
+    pub(crate) fn create_deferred_table(
+        &mut self,
+        name: &'static str,
+        entries: Vec<(Variable, Variable)>,
+    ) -> usize {
+        self.deferred_tables_mut().push(DeferredTable {
+            name,
+            entries,
+            lookups: vec![],
+        });
+        self.deferred_tables_mut().len() - 1
+    }
+
+    // Insert every deferred table together with the lookups into it.
+    pub(crate) fn insert_deferred_tables(&mut self) -> Result<(), CircuitError> {
+        for table in mem::take(self.deferred_tables_mut()) {
+            if table.lookups.is_empty() {
+                continue;
+            }
+            let lookups: Vec<_> = table
+                .lookups
+                .iter()
+                .map(|&[key, val0, val1]| (key, val0, val1))
+                .collect();
+            self.create_table_and_lookup_variables(&lookups, &table.entries)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,429 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Random access to arrays of variables at witness indices.
+//!
+//! A read-only memory (ROM) is a lookup table whose keys are the indices of
+//! its values, so that a read is a single lookup.
+//!
+//! A read-write memory (RAM) records every access in a lookup table keyed by
+//! the time of the access. When the circuit is finalized, the accesses are
+//! sorted by address then time, each sorted access is looked up in the trace,
+//! and consecutive sorted accesses are checked to be consistent: an address
+//! starts with a write, and a read returns the value of the previous access.
+//! Since the sorted accesses are strictly increasing, distinct and as many as
+//! the accesses of the trace, they are a permutation of the trace.
+
+use crate::{
+    errors::{CircuitError, CircuitError::*},
+    Circuit, PlonkCircuit, Variable,
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{cmp::max, mem, string::ToString, vec, vec::Vec};
+
+/// A read-only memory of variables, read at witness indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomVar(pub(crate) usize);
+
+/// A read-write memory of variables, accessed at witness addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RamVar(pub(crate) usize);
+
+/// The access trace of a RAM. The entry with key `t` of the trace table holds
+/// `(2 * address + is_write, value)` of the access at time `t`.
+#[derive(Debug, Clone)]
+pub(crate) struct Memory {
+    /// The index of the trace table among the deferred tables.
+    pub(crate) table: usize,
+    /// Whether each access is a write.
+    pub(crate) writes: Vec<bool>,
+    /// The last variable written at each address.
+    pub(crate) contents: Vec<Variable>,
+    /// The bit length of the addresses.
+    pub(crate) addr_bits: usize,
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a ROM holding `values`.
+    /// Return error if the circuit does not support lookup or if a variable
+    /// is invalid.
+    pub fn create_rom(&mut self, values: &[Variable]) -> Result<RomVar, CircuitError> {
+        if !self.support_lookup() {
+            return Err(WrongPlonkType);
+        }
+        self.check_vars_bound(values)?;
+        let zero = self.zero();
+        let entries = values.iter().map(|&val| (val, zero)).collect();
+        Ok(RomVar(self.create_deferred_table("ROM Read", entries)))
+    }
+
+    /// Obtain a variable for the value at `index` of a ROM, with a single
+    /// lookup. The circuit is not satisfiable if `index` is out of bounds.
+    /// Return error if `rom` or `index` is invalid.
+    pub fn rom_read(&mut self, rom: RomVar, index: Variable) -> Result<Variable, CircuitError> {
+        self.check_var_bound(index)?;
+        let index_val = self.witness_index(index)?;
+        let table = self.deferred_tables().get(rom.0).ok_or(IndexError)?;
+        let entry = index_val
+            .and_then(|i| table.entries.get(i))
+            .map(|entry| entry.0);
+        let val = match entry {
+            Some(var) => self.witness(var)?,
+            None => F::zero(),
+        };
+        let var = self.create_variable(val)?;
+        let zero = self.zero();
+        self.deferred_tables_mut()[rom.0]
+            .lookups
+            .push([index, var, zero]);
+        Ok(var)
+    }
+
+    /// Create a RAM of `init.len()` cells initialized with `init`.
+    /// Return error if the circuit does not support lookup, if `init` is empty
+    /// or if a variable is invalid.
+    pub fn create_ram(&mut self, init: &[Variable]) -> Result<RamVar, CircuitError> {
+        if !self.support_lookup() {
+            return Err(WrongPlonkType);
+        }
+        if init.is_empty() {
+            return Err(ParameterError("a RAM has at least one cell".to_string()));
+        }
+        self.check_vars_bound(init)?;
+        let mut entries = vec![];
+        for (addr, &val) in init.iter().enumerate() {
+            let key = self.create_constant_variable(F::from(2 * addr as u64 + 1))?;
+            entries.push((key, val));
+        }
+        let table = self.create_deferred_table("RAM Access", entries);
+        let addr_bits = max(1, ceil_log2(init.len()));
+        let memory = Memory {
+            table,
+            writes: vec![true; init.len()],
+            contents: init.to_vec(),
+            addr_bits,
+        };
+        self.memories_mut().push(memory);
+        Ok(RamVar(self.memories_mut().len() - 1))
+    }
+
+    /// Obtain a variable for the value at `addr` of a RAM.
+    /// The circuit is not satisfiable if `addr` is out of bounds.
+    /// Return error if `ram` or `addr` is invalid.
+    pub fn ram_read(&mut self, ram: RamVar, addr: Variable) -> Result<Variable, CircuitError> {
+        self.check_var_bound(addr)?;
+        let addr_val = self.witness_index(addr)?;
+        let memory = self.memories().get(ram.0).ok_or(IndexError)?;
+        let cell = addr_val.and_then(|i| memory.contents.get(i)).copied();
+        let val = match cell {
+            Some(var) => self.witness(var)?,
+            None => F::zero(),
+        };
+        let var = self.create_variable(val)?;
+        self.ram_access(ram, addr, var, false)?;
+        Ok(var)
+    }
+
+    /// Write `val` at `addr` of a RAM.
+    /// The circuit is not satisfiable if `addr` is out of bounds.
+    /// Return error if `ram`, `addr` or `val` is invalid.
+    pub fn ram_write(
+        &mut self,
+        ram: RamVar,
+        addr: Variable,
+        val: Variable,
+    ) -> Result<(), CircuitError> {
+        self.check_var_bound(val)?;
+        self.ram_access(ram, addr, val, true)?;
+        if let Some(i) = self.witness_index(addr)? {
+            if let Some(cell) = self.memories_mut()[ram.0].contents.get_mut(i) {
+                *cell = val;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Private helper functions for ROM/RAM gadgets.
+impl<F: PrimeField> PlonkCircuit<F> {
+    // The index held by `var`, `None` if it does not fit in a `usize`.
+    fn witness_index(&self, var: Variable) -> Result<Option<usize>, CircuitError> {
+        let val = self.witness(var)?.into_bigint();
+        if val.num_bits() > usize::BITS {
+            return Ok(None);
+        }
+        Ok(Some(val.as_ref()[0] as usize))
+    }
+
+    // Append an access to the trace of a RAM.
+    fn ram_access(
+        &mut self,
+        ram: RamVar,
+        addr: Variable,
+        val: Variable,
+        is_write: bool,
+    ) -> Result<(), CircuitError> {
+        self.check_var_bound(addr)?;
+        let memory = self.memories().get(ram.0).ok_or(IndexError)?;
+        let (table, addr_bits) = (memory.table, memory.addr_bits);
+        let size = memory.contents.len();
+        self.enforce_in_range(addr, addr_bits)?;
+        let zero = self.zero();
+        if !size.is_power_of_two() {
+            // `size - 1 - addr` is in range too
+            let gap = self.gen_quad_poly(
+                &[addr, zero, zero, zero],
+                &[-F::one(), F::zero(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                F::from(size as u64 - 1),
+            )?;
+            self.enforce_in_range(gap, addr_bits)?;
+        }
+        let key = self.gen_quad_poly(
+            &[addr, zero, zero, zero],
+            &[F::from(2u32), F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            F::from(is_write as u32),
+        )?;
+        self.deferred_tables_mut()[table].entries.push((key, val));
+        self.memories_mut()[ram.0].writes.push(is_write);
+        Ok(())
+    }
+
+    // Prove the consistency of the accesses of every RAM, see the module
+    // documentation. The lookups are inserted with the deferred tables.
+    pub(crate) fn finalize_memories(&mut self) -> Result<(), CircuitError> {
+        for memory in mem::take(self.memories_mut()) {
+            let trace = self.deferred_tables()[memory.table].entries.clone();
+            let num_accesses = trace.len();
+            let time_bits = max(1, ceil_log2(num_accesses));
+            let key_bits = memory.addr_bits + time_bits;
+            if key_bits >= F::MODULUS_BIT_SIZE as usize {
+                return Err(ParameterError("too many RAM accesses".to_string()));
+            }
+            let two_inv = F::from(2u32).inverse().ok_or(FieldAlgebraError(
+                "the field has characteristic two".to_string(),
+            ))?;
+            let mut sorted = vec![];
+            for (time, (&(key, val), &is_write)) in
+                trace.iter().zip(memory.writes.iter()).enumerate()
+            {
+                let addr = (self.witness(key)? - F::from(is_write as u32)) * two_inv;
+                sorted.push((addr.into_bigint(), time, addr, is_write, self.witness(val)?));
+            }
+            // stable, so that the accesses to an address stay in time order
+            sorted.sort_by_key(|access| access.0);
+
+            let zero = self.zero();
+            let num_accesses_f = F::from(num_accesses as u64);
+            let mut prev: Option<(Variable, Variable, Variable)> = None;
+            for (_, time, addr, is_write, val) in sorted {
+                let time = self.create_variable(F::from(time as u64))?;
+                let addr = self.create_variable(addr)?;
+                let is_write = self.create_boolean_variable(is_write)?;
+                let val = self.create_variable(val)?;
+                self.enforce_in_range(addr, memory.addr_bits)?;
+                let key = self.lc(
+                    &[addr, is_write.into(), zero, zero],
+                    &[F::from(2u32), F::one(), F::zero(), F::zero()],
+                )?;
+                self.deferred_tables_mut()[memory.table]
+                    .lookups
+                    .push([time, key, val]);
+                // the sorting key `addr * num_accesses + time`
+                let sort_key = self.lc(
+                    &[addr, time, zero, zero],
+                    &[num_accesses_f, F::one(), F::zero(), F::zero()],
+                )?;
+                match prev {
+                    None => self.enforce_true(is_write.into())?,
+                    Some((prev_sort_key, prev_addr, prev_val)) => {
+                        let diff = self.gen_quad_poly(
+                            &[sort_key, prev_sort_key, zero, zero],
+                            &[F::one(), -F::one(), F::zero(), F::zero()],
+                            &[F::zero(), F::zero()],
+                            -F::one(),
+                        )?;
+                        self.enforce_in_range(diff, key_bits)?;
+                        let same_addr = self.is_equal(addr, prev_addr)?;
+                        // a new address starts with a write:
+                        // (1 - same_addr) * (1 - is_write) = 0
+                        self.quad_poly_gate(
+                            &[same_addr.into(), is_write.into(), zero, zero, zero],
+                            &[-F::one(), -F::one(), F::zero(), F::zero()],
+                            &[F::one(), F::zero()],
+                            F::one(),
+                            F::one(),
+                        )?;
+                        // a read returns the previous value:
+                        // same_addr * (1 - is_write) * (val - prev_val) = 0
+                        let is_read_again = self.gen_quad_poly(
+                            &[same_addr.into(), is_write.into(), zero, zero],
+                            &[F::one(), F::zero(), F::zero(), F::zero()],
+                            &[-F::one(), F::zero()],
+                            F::zero(),
+                        )?;
+                        self.mul_add_gate(
+                            &[is_read_again, val, is_read_again, prev_val, zero],
+                            &[F::one(), -F::one()],
+                        )?;
+                    },
+                }
+                prev = Some((sort_key, addr, val));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The number of bits of `n - 1`, i.e. the smallest `b` with `n <= 2^b`.
+fn ceil_log2(n: usize) -> usize {
+    (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{constraint_system::test::test_arithmetization_for_lookup_circuit, ConstraintKind};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use jf_utils::test_rng;
+
+    // Check that `circuit` is satisfied before and after finalization.
+    fn check_finalized<F: PrimeField>(
+        circuit: &PlonkCircuit<F>,
+        satisfied: bool,
+    ) -> Result<(), CircuitError> {
+        assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), satisfied);
+        let mut circuit = circuit.clone();
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), satisfied);
+        if satisfied {
+            test_arithmetization_for_lookup_circuit(&circuit)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rom() -> Result<(), CircuitError> {
+        test_rom_helper::<FqEd254>()?;
+        test_rom_helper::<FqEd377>()?;
+        test_rom_helper::<FqEd381>()?;
+        test_rom_helper::<Fq377>()
+    }
+    fn test_rom_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let mut rng = test_rng();
+        let values: Vec<_> = (0..10)
+            .map(|_| circuit.create_variable(F::rand(&mut rng)))
+            .collect::<Result<_, _>>()?;
+        let rom = circuit.create_rom(&values)?;
+        for i in [3u32, 0, 9, 3] {
+            let index = circuit.create_variable(F::from(i))?;
+            let val = circuit.rom_read(rom, index)?;
+            assert_eq!(circuit.witness(val)?, circuit.witness(values[i as usize])?);
+        }
+        check_finalized(&circuit, true)?;
+
+        // a wrong value
+        let mut bad_circuit = circuit.clone();
+        let [_, val, _] = bad_circuit.deferred_tables()[rom.0].lookups[0];
+        *bad_circuit.witness_mut(val) = F::one();
+        let reports = bad_circuit.unsatisfied_gates(&[], 10)?;
+        assert!(reports
+            .iter()
+            .any(|r| r.kind == ConstraintKind::Lookup && r.gate_name == "ROM Read"));
+        check_finalized(&bad_circuit, false)?;
+
+        // an index out of bounds
+        let mut bad_circuit = circuit.clone();
+        let index = bad_circuit.create_variable(F::from(10u32))?;
+        bad_circuit.rom_read(rom, index)?;
+        check_finalized(&bad_circuit, false)?;
+
+        // error paths
+        assert!(circuit.rom_read(RomVar(1), circuit.zero()).is_err());
+        assert!(circuit.rom_read(rom, circuit.num_vars()).is_err());
+        assert!(circuit.create_rom(&[circuit.num_vars()]).is_err());
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        assert!(circuit.create_rom(&[circuit.zero()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ram() -> Result<(), CircuitError> {
+        test_ram_helper::<FqEd254>()?;
+        test_ram_helper::<FqEd377>()?;
+        test_ram_helper::<FqEd381>()?;
+        test_ram_helper::<Fq377>()
+    }
+    fn test_ram_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let size = 5;
+        let init: Vec<_> = (0..size)
+            .map(|i| circuit.create_variable(F::from(100 + i as u32)))
+            .collect::<Result<_, _>>()?;
+        let ram = circuit.create_ram(&init)?;
+        let mut expected: Vec<_> = (0..size).map(|i| F::from(100 + i as u32)).collect();
+        // interleaved reads and writes, with repeated addresses
+        for (i, addr) in [2usize, 4, 2, 0, 4, 4, 1].into_iter().enumerate() {
+            let addr_var = circuit.create_variable(F::from(addr as u32))?;
+            let val = circuit.ram_read(ram, addr_var)?;
+            assert_eq!(circuit.witness(val)?, expected[addr]);
+            if i % 2 == 0 {
+                let new_val = circuit.create_variable(F::from(i as u32))?;
+                circuit.ram_write(ram, addr_var, new_val)?;
+                expected[addr] = F::from(i as u32);
+            }
+        }
+        check_finalized(&circuit, true)?;
+
+        // a read of a wrong value
+        let mut bad_circuit = circuit.clone();
+        let table = bad_circuit.memories()[ram.0].table;
+        let (_, val) = bad_circuit.deferred_tables()[table].entries[size + 2];
+        *bad_circuit.witness_mut(val) += F::one();
+        let reports = bad_circuit.unsatisfied_gates(&[], 10)?;
+        assert!(reports
+            .iter()
+            .any(|r| r.kind == ConstraintKind::Memory && r.index == size + 2));
+        check_finalized(&bad_circuit, false)?;
+
+        // accesses out of bounds, including below the next power of two
+        for addr in [size, 7, 8] {
+            for is_write in [false, true] {
+                let mut bad_circuit = circuit.clone();
+                let addr = bad_circuit.create_variable(F::from(addr as u32))?;
+                if is_write {
+                    bad_circuit.ram_write(ram, addr, bad_circuit.one())?;
+                } else {
+                    bad_circuit.ram_read(ram, addr)?;
+                }
+                check_finalized(&bad_circuit, false)?;
+            }
+        }
+
+        // a single cell RAM
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let one = circuit.one();
+        let ram = circuit.create_ram(&[one])?;
+        let zero = circuit.zero();
+        let val = circuit.ram_read(ram, zero)?;
+        assert_eq!(circuit.witness(val)?, F::one());
+        check_finalized(&circuit, true)?;
+
+        // error paths
+        assert!(circuit.ram_read(RamVar(1), zero).is_err());
+        assert!(circuit.ram_read(ram, circuit.num_vars()).is_err());
+        assert!(circuit.ram_write(ram, zero, circuit.num_vars()).is_err());
+        assert!(circuit.create_ram(&[]).is_err());
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        assert!(circuit.create_ram(&[circuit.zero()]).is_err());
+        Ok(())
+    }
+}
@@ -7,6 +7,11 @@
 //! Implements ultra-plonk related circuits.
 
 mod lookup_table;
+mod memory;
 pub mod mod_arith;
 mod non_native_gates;
 mod range;
+
+pub(crate) use lookup_table::DeferredTable;
+pub(crate) use memory::Memory;
+pub use memory::{RamVar, RomVar};
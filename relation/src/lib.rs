@@ -12,6 +12,7 @@ pub mod constants;
 pub mod errors;
 pub mod gadgets;
 pub mod gates;
+pub mod r1cs;
 
 pub mod constraint_system;
 pub use constraint_system::*;
@@ -0,0 +1,703 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Import of rank-1 constraint systems (R1CS) into Plonk circuits.
+//!
+//! An R1CS over wires `w` (where `w[0] = 1`) is a list of constraints
+//! `<A, w> * <B, w> = <C, w>`. Each constraint is compiled into one
+//! `QuadPolyGate`, `MulAddGate` or `LinCombGate`, after linear combinations
+//! too long to fit in a single gate have been split into `LinCombGate`
+//! chains. When the circuit supports lookups, bit decompositions are
+//! replaced by range checks.
+//!
+//! Constraint systems can be read from circom files with
+//! [`R1cs::from_circom()`] or, with the `arkworks` feature, produced by an
+//! arkworks constraint synthesizer with `R1cs::from_arkworks()`.
+
+use crate::{
+    constants::{GATE_WIDTH, N_MUL_SELECTORS},
+    errors::{CircuitError, CircuitError::*},
+    gates::{Gate, LinCombGate, MulAddGate, QuadPolyGate},
+    Circuit, PlonkCircuit, Variable,
+};
+use ark_ff::PrimeField;
+use ark_std::{boxed::Box, format, string::ToString, vec, vec::Vec};
+use hashbrown::{HashMap, HashSet};
+
+#[cfg(feature = "arkworks")]
+mod arkworks;
+mod circom;
+#[cfg(feature = "arkworks")]
+pub use arkworks::synthesize_arkworks;
+pub use circom::read_circom_witness;
+
+/// A linear combination of R1CS wires: a list of `(wire index, coefficient)`.
+pub type LinearCombination<F> = Vec<(usize, F)>;
+
+/// An R1CS constraint `<a, w> * <b, w> = <c, w>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csConstraint<F: PrimeField> {
+    /// The left factor.
+    pub a: LinearCombination<F>,
+    /// The right factor.
+    pub b: LinearCombination<F>,
+    /// The product.
+    pub c: LinearCombination<F>,
+}
+
+/// A rank-1 constraint system.
+///
+/// Wire `0` is the constant one, wires `1..=num_public_inputs` are the public
+/// inputs, all other wires are private.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1cs<F: PrimeField> {
+    /// The total number of wires, including the constant wire `0`.
+    pub num_wires: usize,
+    /// The number of public inputs.
+    pub num_public_inputs: usize,
+    /// The constraints.
+    pub constraints: Vec<R1csConstraint<F>>,
+}
+
+impl<F: PrimeField> R1cs<F> {
+    /// Add the constraints of the R1CS to `circuit` given the values of all
+    /// the wires. The public input wires become public variables of
+    /// `circuit`, in order. Return the circuit variable of each wire.
+    ///
+    /// Return an error if the witness has the wrong length, if `witness[0]`
+    /// is not one, or if a constraint refers to a non-existing wire.
+    pub fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        witness: &[F],
+    ) -> Result<Vec<Variable>, CircuitError> {
+        self.check_wires()?;
+        if witness.len() != self.num_wires {
+            return Err(ParameterError(format!(
+                "witness length {} does not match the number of wires {}",
+                witness.len(),
+                self.num_wires
+            )));
+        }
+        if !witness[0].is_one() {
+            return Err(ParameterError(
+                "the constant wire must have value one".to_string(),
+            ));
+        }
+        let mut vars = vec![circuit.one()];
+        for (wire, &val) in witness.iter().enumerate().skip(1) {
+            vars.push(if wire <= self.num_public_inputs {
+                circuit.create_public_variable(val)?
+            } else {
+                circuit.create_variable(val)?
+            });
+        }
+        // with lookups, bit decompositions are replaced by range checks
+        let decompositions = if circuit.support_lookup() {
+            self.bit_decompositions()
+        } else {
+            vec![]
+        };
+        let mut skip = vec![false; self.constraints.len()];
+        for decomposition in decompositions.iter() {
+            for &idx in decomposition.constraints.iter() {
+                skip[idx] = true;
+            }
+        }
+        for (constraint, _) in self.constraints.iter().zip(skip).filter(|(_, skip)| !skip) {
+            synthesize_constraint(circuit, &vars, constraint)?;
+        }
+        for decomposition in decompositions {
+            circuit.enforce_in_range(vars[decomposition.wire], decomposition.bit_len)?;
+        }
+        Ok(vars)
+    }
+
+    /// Check that there are fewer public inputs than wires, and that the
+    /// constraints only refer to existing wires.
+    fn check_wires(&self) -> Result<(), CircuitError> {
+        if self.num_public_inputs >= self.num_wires {
+            return Err(ParameterError(format!(
+                "{} public inputs for {} wires",
+                self.num_public_inputs, self.num_wires
+            )));
+        }
+        for (idx, constraint) in self.constraints.iter().enumerate() {
+            let terms = [&constraint.a, &constraint.b, &constraint.c]
+                .into_iter()
+                .flatten();
+            for &(wire, _) in terms {
+                if wire >= self.num_wires {
+                    return Err(ParameterError(format!(
+                        "constraint {idx} refers to wire {wire} out of {} wires",
+                        self.num_wires
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the constraints decomposing a wire into private bits used
+    /// nowhere else, that is, range checks.
+    fn bit_decompositions(&self) -> Vec<BitDecomposition> {
+        let mut uses = vec![0usize; self.num_wires];
+        let mut boolean_constraints: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (idx, constraint) in self.constraints.iter().enumerate() {
+            let wires: HashSet<usize> = [&constraint.a, &constraint.b, &constraint.c]
+                .into_iter()
+                .flatten()
+                .map(|&(wire, _)| wire)
+                .collect();
+            for wire in wires {
+                if let Some(count) = uses.get_mut(wire) {
+                    *count += 1;
+                }
+            }
+            if let Some(wire) = constraint.boolean_wire() {
+                boolean_constraints.entry(wire).or_default().push(idx);
+            }
+        }
+        // a bit is private, boolean, and only used in one other constraint
+        let is_bit = |wire: usize| {
+            wire > self.num_public_inputs
+                && boolean_constraints
+                    .get(&wire)
+                    .map_or(false, |c| uses[wire] == c.len() + 1)
+        };
+
+        let mut res = vec![];
+        for (idx, constraint) in self.constraints.iter().enumerate() {
+            let (terms, constant) = match constraint.linear_terms() {
+                Some(res) => res,
+                None => continue,
+            };
+            let (bits, others): (Vec<_>, Vec<_>) =
+                terms.into_iter().partition(|&(wire, _)| is_bit(wire));
+            if !constant.is_zero()
+                || others.len() != 1
+                || bits.is_empty()
+                || bits.len() >= F::MODULUS_BIT_SIZE as usize
+            {
+                continue;
+            }
+            // the bits must have weights 2^0, ..., 2^(n-1) relative to the
+            // decomposed wire
+            let (wire, coeff) = others[0];
+            let scale = -coeff.inverse().unwrap(); // safe unwrap
+            let mut powers: HashMap<F, bool> = HashMap::new();
+            let mut power = F::one();
+            for _ in 0..bits.len() {
+                powers.insert(power, false);
+                power.double_in_place();
+            }
+            let valid = bits
+                .iter()
+                .all(|&(_, c)| match powers.get_mut(&(c * scale)) {
+                    Some(used) if !*used => {
+                        *used = true;
+                        true
+                    },
+                    _ => false,
+                });
+            if valid {
+                let mut constraints = vec![idx];
+                for (bit, _) in bits.iter() {
+                    constraints.extend_from_slice(&boolean_constraints[bit]);
+                }
+                res.push(BitDecomposition {
+                    wire,
+                    bit_len: bits.len(),
+                    constraints,
+                });
+            }
+        }
+        res
+    }
+}
+
+impl<F: PrimeField> R1csConstraint<F> {
+    /// If the constraint is `b * (b - 1) = 0` up to a scalar for some wire
+    /// `b`, return `b`.
+    fn boolean_wire(&self) -> Option<usize> {
+        // with a = alpha * b + a0, b = beta * b + b0, c = gamma * b + c0, the
+        // constraint must be alpha * beta * (b^2 - b)
+        let wire = [&self.a, &self.b]
+            .into_iter()
+            .flatten()
+            .map(|&(wire, _)| wire)
+            .find(|&wire| wire != 0)?;
+        let split = |lc: &LinearCombination<F>| -> Option<(F, F)> {
+            let mut res = (F::zero(), F::zero());
+            for &(w, coeff) in lc.iter() {
+                match w {
+                    0 => res.1 += coeff,
+                    w if w == wire => res.0 += coeff,
+                    _ => return None,
+                }
+            }
+            Some(res)
+        };
+        let (alpha, a0) = split(&self.a)?;
+        let (beta, b0) = split(&self.b)?;
+        let (gamma, c0) = split(&self.c)?;
+        let quad = alpha * beta;
+        (!quad.is_zero() && alpha * b0 + beta * a0 - gamma == -quad && a0 * b0 == c0)
+            .then_some(wire)
+    }
+
+    /// If one of the factors is a constant, return the constraint as a
+    /// linear combination of wires plus a constant equal to zero.
+    fn linear_terms(&self) -> Option<(LinearCombination<F>, F)> {
+        let constant = |lc: &LinearCombination<F>| -> Option<F> {
+            lc.iter().try_fold(F::zero(), |acc, &(wire, coeff)| {
+                (wire == 0).then_some(acc + coeff)
+            })
+        };
+        let (factor, lc) = match (constant(&self.a), constant(&self.b)) {
+            (Some(k), _) => (k, &self.b),
+            (None, Some(k)) => (k, &self.a),
+            (None, None) => return None,
+        };
+        let mut terms: LinearCombination<F> = vec![];
+        let mut res_constant = F::zero();
+        let all_terms = lc
+            .iter()
+            .map(|&(wire, coeff)| (wire, factor * coeff))
+            .chain(self.c.iter().map(|&(wire, coeff)| (wire, -coeff)));
+        for (wire, coeff) in all_terms {
+            if wire == 0 {
+                res_constant += coeff;
+            } else {
+                match terms.iter_mut().find(|(w, _)| *w == wire) {
+                    Some((_, acc)) => *acc += coeff,
+                    None => terms.push((wire, coeff)),
+                }
+            }
+        }
+        terms.retain(|(_, coeff)| !coeff.is_zero());
+        Some((terms, res_constant))
+    }
+}
+
+// A wire decomposed into `bit_len` bits by the R1CS constraints `constraints`.
+struct BitDecomposition {
+    wire: usize,
+    bit_len: usize,
+    constraints: Vec<usize>,
+}
+
+// A linear combination of circuit variables plus a constant.
+struct LinearTerms<F: PrimeField> {
+    terms: Vec<(Variable, F)>,
+    constant: F,
+}
+
+impl<F: PrimeField> LinearTerms<F> {
+    fn new(lc: &LinearCombination<F>, vars: &[Variable]) -> Result<Self, CircuitError> {
+        let mut res = Self {
+            terms: vec![],
+            constant: F::zero(),
+        };
+        for &(wire, coeff) in lc.iter() {
+            let var = *vars
+                .get(wire)
+                .ok_or_else(|| ParameterError(format!("wire {wire} out of bound")))?;
+            if wire == 0 {
+                res.constant += coeff;
+            } else {
+                res.push(var, coeff);
+            }
+        }
+        res.terms.retain(|(_, coeff)| !coeff.is_zero());
+        Ok(res)
+    }
+
+    fn push(&mut self, var: Variable, coeff: F) {
+        match self.terms.iter_mut().find(|(v, _)| *v == var) {
+            Some((_, acc)) => *acc += coeff,
+            None => self.terms.push((var, coeff)),
+        }
+    }
+
+    // Replace the terms by a single variable holding their sum.
+    fn fold(&mut self, circuit: &mut PlonkCircuit<F>) -> Result<(), CircuitError> {
+        if self.terms.len() > 1 {
+            let var = sum_terms(circuit, &self.terms)?;
+            self.terms = vec![(var, F::one())];
+        }
+        Ok(())
+    }
+}
+
+// Return a variable equal to the sum of `terms`.
+fn sum_terms<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    terms: &[(Variable, F)],
+) -> Result<Variable, CircuitError> {
+    let mut acc: Option<Variable> = None;
+    let mut rest = terms;
+    while !rest.is_empty() || acc.is_none() {
+        let mut wires = [circuit.zero(); GATE_WIDTH];
+        let mut coeffs = [F::zero(); GATE_WIDTH];
+        let mut slot = 0;
+        if let Some(var) = acc {
+            wires[0] = var;
+            coeffs[0] = F::one();
+            slot = 1;
+        }
+        let n = rest.len().min(GATE_WIDTH - slot);
+        for (i, &(var, coeff)) in rest[..n].iter().enumerate() {
+            wires[slot + i] = var;
+            coeffs[slot + i] = coeff;
+        }
+        rest = &rest[n..];
+        acc = Some(circuit.lc(&wires, &coeffs)?);
+    }
+    Ok(acc.unwrap()) // safe unwrap
+}
+
+fn synthesize_constraint<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    vars: &[Variable],
+    constraint: &R1csConstraint<F>,
+) -> Result<(), CircuitError> {
+    let mut a = LinearTerms::new(&constraint.a, vars)?;
+    let mut b = LinearTerms::new(&constraint.b, vars)?;
+    let c = LinearTerms::new(&constraint.c, vars)?;
+
+    // expand a * b - c into products, linear terms and a constant
+    let mut muls = vec![];
+    let mut linear = LinearTerms {
+        terms: vec![],
+        constant: a.constant * b.constant - c.constant,
+    };
+    if a.terms.is_empty() || b.terms.is_empty() {
+        for &(var, coeff) in a.terms.iter() {
+            linear.push(var, coeff * b.constant);
+        }
+        for &(var, coeff) in b.terms.iter() {
+            linear.push(var, coeff * a.constant);
+        }
+    } else {
+        // one side must be a single variable, the other at most two
+        if a.terms.len() > 1 && b.terms.len() > 1 {
+            a.fold(circuit)?;
+        }
+        if a.terms.len() > 1 {
+            core::mem::swap(&mut a, &mut b);
+        }
+        if b.terms.len() > N_MUL_SELECTORS {
+            b.fold(circuit)?;
+        }
+        let (x, x_coeff) = a.terms[0];
+        linear.push(x, x_coeff * b.constant);
+        for &(y, y_coeff) in b.terms.iter() {
+            muls.push((x, y, x_coeff * y_coeff));
+            linear.push(y, y_coeff * a.constant);
+        }
+    }
+    for &(var, coeff) in c.terms.iter() {
+        linear.push(var, -coeff);
+    }
+    linear.terms.retain(|(_, coeff)| !coeff.is_zero());
+    if muls.is_empty() && linear.terms.is_empty() && linear.constant.is_zero() {
+        // trivially satisfied
+        return Ok(());
+    }
+    enforce_quad_poly(circuit, &muls, linear)
+}
+
+// Enforce `sum(coeff * x * y for (x, y, coeff) in muls) + linear = 0` with a
+// single gate, after folding the linear terms that do not fit.
+fn enforce_quad_poly<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    muls: &[(Variable, Variable, F)],
+    mut linear: LinearTerms<F>,
+) -> Result<(), CircuitError> {
+    let mut wires = [circuit.zero(); GATE_WIDTH + 1];
+    let mut pinned = [false; GATE_WIDTH];
+    let mut q_mul = [F::zero(); N_MUL_SELECTORS];
+    for (i, &(x, y, coeff)) in muls.iter().enumerate() {
+        wires[2 * i] = x;
+        wires[2 * i + 1] = y;
+        pinned[2 * i] = true;
+        pinned[2 * i + 1] = true;
+        q_mul[i] = coeff;
+    }
+
+    // the linear terms go to the input wire holding the same variable, to a
+    // free input wire, or to the output wire
+    let (matched, mut unmatched): (Vec<_>, Vec<_>) = linear
+        .terms
+        .drain(..)
+        .partition(|(var, _)| (0..GATE_WIDTH).any(|i| pinned[i] && wires[i] == *var));
+    let capacity = pinned.iter().filter(|&&p| !p).count() + 1;
+    if unmatched.len() > capacity {
+        let overflow = unmatched.split_off(capacity - 1);
+        let var = sum_terms(circuit, &overflow)?;
+        unmatched.push((var, F::one()));
+    }
+
+    let mut q_lc = [F::zero(); GATE_WIDTH];
+    for (var, coeff) in matched {
+        let slot = (0..GATE_WIDTH)
+            .find(|&i| pinned[i] && wires[i] == var)
+            .unwrap(); // safe unwrap
+        q_lc[slot] += coeff;
+    }
+    let mut q_o = F::zero();
+    let mut free_slots = (0..GATE_WIDTH).filter(|&i| !pinned[i]);
+    for (var, coeff) in unmatched {
+        match free_slots.next() {
+            Some(slot) => {
+                wires[slot] = var;
+                q_lc[slot] = coeff;
+            },
+            None => {
+                wires[GATE_WIDTH] = var;
+                q_o = -coeff;
+            },
+        }
+    }
+    let q_c = linear.constant;
+
+    let no_lc = q_lc.iter().all(|q| q.is_zero());
+    let no_mul = q_mul.iter().all(|q| q.is_zero());
+    let gate: Box<dyn Gate<F>> = if q_o.is_one() && q_c.is_zero() && no_mul {
+        Box::new(LinCombGate { coeffs: q_lc })
+    } else if q_o.is_one() && q_c.is_zero() && no_lc && muls.len() == N_MUL_SELECTORS {
+        Box::new(MulAddGate { coeffs: q_mul })
+    } else {
+        Box::new(QuadPolyGate {
+            q_lc,
+            q_mul,
+            q_o,
+            q_c,
+        })
+    };
+    circuit.insert_gate(&wires, gate)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{R1cs, R1csConstraint};
+    use crate::{errors::CircuitError, Circuit, PlonkCircuit};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::PrimeField;
+    use ark_std::{vec, vec::Vec};
+
+    // Wires: 1, out, x, y, z, s, t
+    //   x * y = z
+    //   (x + y + z + 2) * (z + 1) = s
+    //   (x + 3) * (2y + z + 1) = t
+    //   (s - t + x + y + z + 5) * 1 = out
+    //   0 * x = 0
+    pub(super) fn test_r1cs<F: PrimeField>() -> (R1cs<F>, Vec<F>) {
+        let f = |v: i64| {
+            if v < 0 {
+                -F::from((-v) as u64)
+            } else {
+                F::from(v as u64)
+            }
+        };
+        let constraints = vec![
+            R1csConstraint {
+                a: vec![(2, f(1))],
+                b: vec![(3, f(1))],
+                c: vec![(4, f(1))],
+            },
+            R1csConstraint {
+                a: vec![(2, f(1)), (3, f(1)), (4, f(1)), (0, f(2))],
+                b: vec![(4, f(1)), (0, f(1))],
+                c: vec![(5, f(1))],
+            },
+            R1csConstraint {
+                a: vec![(2, f(1)), (0, f(3))],
+                b: vec![(3, f(2)), (4, f(1)), (0, f(1))],
+                c: vec![(6, f(1))],
+            },
+            R1csConstraint {
+                a: vec![
+                    (5, f(1)),
+                    (6, f(-1)),
+                    (2, f(1)),
+                    (3, f(1)),
+                    (4, f(1)),
+                    (0, f(5)),
+                ],
+                b: vec![(0, f(1))],
+                c: vec![(1, f(1))],
+            },
+            R1csConstraint {
+                a: vec![],
+                b: vec![(2, f(1))],
+                c: vec![],
+            },
+        ];
+        let (x, y) = (3, 4);
+        let z = x * y;
+        let s = (x + y + z + 2) * (z + 1);
+        let t = (x + 3) * (2 * y + z + 1);
+        let out = s - t + x + y + z + 5;
+        let witness = [1, out, x, y, z, s, t].into_iter().map(f).collect();
+        let r1cs = R1cs {
+            num_wires: 7,
+            num_public_inputs: 1,
+            constraints,
+        };
+        (r1cs, witness)
+    }
+
+    #[test]
+    fn test_synthesize() -> Result<(), CircuitError> {
+        test_synthesize_helper::<FqEd254>()?;
+        test_synthesize_helper::<FqEd377>()?;
+        test_synthesize_helper::<FqEd381>()?;
+        test_synthesize_helper::<Fq377>()
+    }
+
+    fn test_synthesize_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let (r1cs, witness) = test_r1cs::<F>();
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let vars = r1cs.synthesize(&mut circuit, &witness)?;
+        assert_eq!(vars.len(), r1cs.num_wires);
+        assert_eq!(circuit.public_input()?, vec![witness[1]]);
+        circuit.check_circuit_satisfiability(&[witness[1]])?;
+        for (&var, val) in vars.iter().zip(witness.iter()) {
+            assert_eq!(circuit.witness(var)?, *val);
+        }
+
+        // every constraint is checked
+        for &var in vars.iter().skip(2) {
+            let mut bad_circuit = circuit.clone();
+            *bad_circuit.witness_mut(var) += F::one();
+            assert!(bad_circuit
+                .check_circuit_satisfiability(&[witness[1]])
+                .is_err());
+        }
+        assert!(circuit
+            .check_circuit_satisfiability(&[witness[1] + F::one()])
+            .is_err());
+
+        // bad inputs
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(r1cs.synthesize(&mut circuit, &witness[1..]).is_err());
+        let mut bad_witness = witness.clone();
+        bad_witness[0] = F::zero();
+        assert!(r1cs.synthesize(&mut circuit, &bad_witness).is_err());
+        let mut bad_r1cs = r1cs.clone();
+        bad_r1cs.constraints[0].a[0].0 = 7;
+        assert!(bad_r1cs.synthesize(&mut circuit, &witness).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wire_out_of_bound() -> Result<(), CircuitError> {
+        test_wire_out_of_bound_helper::<FqEd254>()?;
+        test_wire_out_of_bound_helper::<Fq377>()
+    }
+
+    fn test_wire_out_of_bound_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        // a boolean constraint on a non-existing wire, which would be
+        // mistaken for a bit when looking for bit decompositions
+        let (mut r1cs, witness) = range_r1cs::<F>(200, false);
+        let bit = r1cs.num_wires;
+        r1cs.constraints.push(R1csConstraint {
+            a: vec![(0, F::one()), (bit, -F::one())],
+            b: vec![(bit, F::one())],
+            c: vec![],
+        });
+        r1cs.constraints[8].a.push((bit, F::from(256u32)));
+        for mut circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(4),
+        ] {
+            assert!(r1cs.synthesize(&mut circuit, &witness).is_err());
+        }
+        Ok(())
+    }
+
+    // Wires: 1, y, x, b_0, ..., b_7 where x = sum(b_i * 2^i) and y = x * x.
+    // If `reuse_bit`, the bit b_7 is also used in y = x * x + b_7.
+    fn range_r1cs<F: PrimeField>(x: u64, reuse_bit: bool) -> (R1cs<F>, Vec<F>) {
+        let mut constraints = vec![];
+        let mut packing = vec![];
+        for i in 0..8 {
+            let bit = i + 3;
+            // (1 - b) * b = 0
+            constraints.push(R1csConstraint {
+                a: vec![(0, F::one()), (bit, -F::one())],
+                b: vec![(bit, F::one())],
+                c: vec![],
+            });
+            packing.push((bit, F::from(1u64 << i)));
+        }
+        constraints.push(R1csConstraint {
+            a: packing,
+            b: vec![(0, F::one())],
+            c: vec![(2, F::one())],
+        });
+        let mut y = F::from(x * x);
+        let mut c = vec![(1, F::one())];
+        if reuse_bit {
+            c.push((10, -F::one()));
+            y += F::from((x >> 7) & 1);
+        }
+        constraints.push(R1csConstraint {
+            a: vec![(2, F::one())],
+            b: vec![(2, F::one())],
+            c,
+        });
+        let mut witness = vec![F::one(), y, F::from(x)];
+        witness.extend((0..8).map(|i| F::from((x >> i) & 1)));
+        let r1cs = R1cs {
+            num_wires: 11,
+            num_public_inputs: 1,
+            constraints,
+        };
+        (r1cs, witness)
+    }
+
+    #[test]
+    fn test_range_check_with_lookup() -> Result<(), CircuitError> {
+        test_range_check_with_lookup_helper::<FqEd254>()?;
+        test_range_check_with_lookup_helper::<FqEd377>()?;
+        test_range_check_with_lookup_helper::<FqEd381>()?;
+        test_range_check_with_lookup_helper::<Fq377>()
+    }
+
+    fn test_range_check_with_lookup_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let (r1cs, witness) = range_r1cs::<F>(200, false);
+        let mut turbo = PlonkCircuit::<F>::new_turbo_plonk();
+        r1cs.synthesize(&mut turbo, &witness)?;
+        turbo.check_circuit_satisfiability(&[witness[1]])?;
+
+        // the bit decomposition is replaced by a range check
+        let mut ultra = PlonkCircuit::<F>::new_ultra_plonk(4);
+        let vars = r1cs.synthesize(&mut ultra, &witness)?;
+        ultra.check_circuit_satisfiability(&[witness[1]])?;
+        assert!(ultra.num_gates() < turbo.num_gates());
+        let mut bad_ultra = ultra.clone();
+        *bad_ultra.witness_mut(vars[2]) = F::from(256u32);
+        assert!(bad_ultra
+            .check_circuit_satisfiability(&[witness[1]])
+            .is_err());
+
+        // unless a bit is used elsewhere
+        let (r1cs, witness) = range_r1cs::<F>(200, true);
+        let mut turbo = PlonkCircuit::<F>::new_turbo_plonk();
+        r1cs.synthesize(&mut turbo, &witness)?;
+        let mut ultra = PlonkCircuit::<F>::new_ultra_plonk(4);
+        r1cs.synthesize(&mut ultra, &witness)?;
+        ultra.check_circuit_satisfiability(&[witness[1]])?;
+        assert_eq!(ultra.num_gates(), turbo.num_gates());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Adapter running `ark-relations` constraint synthesizers into Plonk
+//! circuits.
+
+use super::{R1cs, R1csConstraint};
+use crate::{
+    errors::{CircuitError, CircuitError::*},
+    PlonkCircuit, Variable,
+};
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use ark_std::{format, string::ToString, vec::Vec};
+
+fn synthesis_error(e: SynthesisError) -> CircuitError {
+    ParameterError(format!("arkworks synthesis error: {e}"))
+}
+
+impl<F: PrimeField> R1cs<F> {
+    /// Run an arkworks constraint synthesizer and return the resulting R1CS
+    /// together with the values of all its wires.
+    ///
+    /// The instance variables of the arkworks constraint system become the
+    /// public inputs of the R1CS, in order. Return an error if the
+    /// synthesizer fails.
+    pub fn from_arkworks<C: ConstraintSynthesizer<F>>(
+        synthesizer: C,
+    ) -> Result<(Self, Vec<F>), CircuitError> {
+        let cs = ConstraintSystem::<F>::new_ref();
+        synthesizer
+            .generate_constraints(cs.clone())
+            .map_err(synthesis_error)?;
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or_else(|| {
+            InternalError("arkworks constraint matrices were not constructed".to_string())
+        })?;
+        let cs = cs
+            .into_inner()
+            .ok_or_else(|| InternalError("arkworks constraint system is shared".to_string()))?;
+
+        // arkworks indexes the constant one and the instance variables first,
+        // then the witness variables, which matches the R1CS wire layout.
+        let swap = |row: Vec<(F, usize)>| row.into_iter().map(|(c, i)| (i, c)).collect();
+        let constraints = matrices
+            .a
+            .into_iter()
+            .zip(matrices.b)
+            .zip(matrices.c)
+            .map(|((a, b), c)| R1csConstraint {
+                a: swap(a),
+                b: swap(b),
+                c: swap(c),
+            })
+            .collect();
+        let mut witness = cs.instance_assignment;
+        witness.extend(cs.witness_assignment);
+        let r1cs = Self {
+            num_wires: matrices.num_instance_variables + matrices.num_witness_variables,
+            num_public_inputs: matrices.num_instance_variables - 1,
+            constraints,
+        };
+        if witness.len() != r1cs.num_wires {
+            return Err(ParameterError(
+                "arkworks constraint system has no assignment".to_string(),
+            ));
+        }
+        Ok((r1cs, witness))
+    }
+}
+
+/// Run an arkworks constraint synthesizer and add its constraints to
+/// `circuit`, see [`R1cs::from_arkworks()`] and [`R1cs::synthesize()`].
+/// Return the circuit variables of the arkworks instance variables followed
+/// by those of its witness variables.
+pub fn synthesize_arkworks<F, C>(
+    circuit: &mut PlonkCircuit<F>,
+    synthesizer: C,
+) -> Result<Vec<Variable>, CircuitError>
+where
+    F: PrimeField,
+    C: ConstraintSynthesizer<F>,
+{
+    let (r1cs, witness) = R1cs::from_arkworks(synthesizer)?;
+    r1cs.synthesize(circuit, &witness)
+}
+
+#[cfg(test)]
+mod test {
+    use super::synthesize_arkworks;
+    use crate::{errors::CircuitError, r1cs::R1cs, Circuit, PlonkCircuit};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, prelude::Boolean};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_std::vec::Vec;
+
+    // Proves knowledge of a `num_bits`-bit `x` such that `x^3 + x + 5 = y`
+    // for the public `y`.
+    struct CubicCircuit<F: PrimeField> {
+        x: F,
+        y: F,
+        num_bits: usize,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for CubicCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let y = FpVar::new_input(cs.clone(), || Ok(self.y))?;
+            let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+            // `x` is the packing of `num_bits` boolean witnesses
+            let x_bits = self.x.into_bigint().to_bits_le();
+            let bits = x_bits[..self.num_bits]
+                .iter()
+                .map(|&bit| Boolean::new_witness(cs.clone(), || Ok(bit)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&x)?;
+            let res = &x * &x * &x + &x + FpVar::Constant(F::from(5u32));
+            res.enforce_equal(&y)
+        }
+    }
+
+    #[test]
+    fn test_arkworks_adapter() -> Result<(), CircuitError> {
+        test_arkworks_adapter_helper::<FqEd254>()?;
+        test_arkworks_adapter_helper::<FqEd377>()?;
+        test_arkworks_adapter_helper::<FqEd381>()?;
+        test_arkworks_adapter_helper::<Fq377>()
+    }
+
+    fn test_arkworks_adapter_helper<F: PrimeField>() -> Result<(), CircuitError> {
+        let x = F::from(200u32);
+        let y = x * x * x + x + F::from(5u32);
+        let synthesizer = || CubicCircuit { x, y, num_bits: 8 };
+
+        let (r1cs, witness) = R1cs::from_arkworks(synthesizer())?;
+        assert_eq!(r1cs.num_public_inputs, 1);
+        assert_eq!(witness[1], y);
+
+        let mut turbo = PlonkCircuit::<F>::new_turbo_plonk();
+        let vars = synthesize_arkworks(&mut turbo, synthesizer())?;
+        assert_eq!(vars.len(), r1cs.num_wires);
+        assert_eq!(turbo.num_inputs(), 1);
+        turbo.check_circuit_satisfiability(&[y])?;
+        assert!(turbo.check_circuit_satisfiability(&[x]).is_err());
+        assert_eq!(turbo.profile_report().total.num_range_checks, 0);
+
+        // with lookups, the bit decomposition of `x` becomes a range check
+        let mut ultra = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let vars = synthesize_arkworks(&mut ultra, synthesizer())?;
+        ultra.check_circuit_satisfiability(&[y])?;
+        assert_eq!(ultra.profile_report().total.num_range_checks, 1);
+        assert!(ultra.num_gates() < turbo.num_gates());
+        let mut bad_ultra = ultra.clone();
+        *bad_ultra.witness_mut(vars[2]) = F::from(256u32);
+        assert!(bad_ultra.check_circuit_satisfiability(&[y]).is_err());
+
+        // an unsatisfied arkworks constraint system yields an unsatisfied
+        // circuit
+        let bad = CubicCircuit {
+            x,
+            y: y + F::one(),
+            num_bits: 8,
+        };
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        synthesize_arkworks(&mut circuit, bad)?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[y + F::one()])
+            .is_err());
+        Ok(())
+    }
+}
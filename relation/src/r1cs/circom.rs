@@ -0,0 +1,290 @@
+// Copyright (c) 2024 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Readers for the binary `.r1cs` and `.wtns` files produced by circom, see
+//! <https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md>.
+
+use super::{LinearCombination, R1cs, R1csConstraint};
+use crate::errors::{CircuitError, CircuitError::*};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec::Vec};
+use num_bigint::BigUint;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const R1CS_HEADER_SECTION: u32 = 1;
+const R1CS_CONSTRAINTS_SECTION: u32 = 2;
+const R1CS_CUSTOM_GATES_LIST_SECTION: u32 = 4;
+const R1CS_CUSTOM_GATES_USES_SECTION: u32 = 5;
+const WTNS_HEADER_SECTION: u32 = 1;
+const WTNS_DATA_SECTION: u32 = 2;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], CircuitError> {
+        if self.bytes.len() < n {
+            return Err(ParameterError("unexpected end of circom file".to_string()));
+        }
+        let (res, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(res)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, CircuitError> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, CircuitError> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn read_usize(&mut self) -> Result<usize, CircuitError> {
+        usize::try_from(self.read_u32()?).map_err(|_| ParameterError("size overflow".to_string()))
+    }
+
+    // A field element of `n8` bytes in little-endian, must be canonical.
+    fn read_field<F: PrimeField>(&mut self, n8: usize) -> Result<F, CircuitError> {
+        let bytes = self.read_bytes(n8)?;
+        if BigUint::from_bytes_le(bytes) >= F::MODULUS.into() {
+            return Err(ParameterError(
+                "non-canonical field element in circom file".to_string(),
+            ));
+        }
+        Ok(F::from_le_bytes_mod_order(bytes))
+    }
+
+    // The field header shared by both formats: the byte size of field
+    // elements followed by the prime, which must be the modulus of `F`.
+    fn read_prime<F: PrimeField>(&mut self) -> Result<usize, CircuitError> {
+        let n8 = self.read_usize()?;
+        let prime = BigUint::from_bytes_le(self.read_bytes(n8)?);
+        if prime != F::MODULUS.into() {
+            return Err(ParameterError(format!(
+                "circom file is over the prime field {prime}, which does not match the circuit \
+                 field"
+            )));
+        }
+        Ok(n8)
+    }
+}
+
+// Split a circom binary file into its `(section type, section content)`.
+fn read_sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+) -> Result<Vec<(u32, Reader<'a>)>, CircuitError> {
+    let mut reader = Reader { bytes };
+    if reader.read_bytes(4)? != magic {
+        return Err(ParameterError(format!(
+            "not a circom `{}` file",
+            core::str::from_utf8(magic).unwrap_or_default()
+        )));
+    }
+    let _version = reader.read_u32()?;
+    let num_sections = reader.read_u32()?;
+    let mut sections = Vec::new();
+    for _ in 0..num_sections {
+        let section_type = reader.read_u32()?;
+        let size = usize::try_from(reader.read_u64()?)
+            .map_err(|_| ParameterError("section size overflow".to_string()))?;
+        sections.push((
+            section_type,
+            Reader {
+                bytes: reader.read_bytes(size)?,
+            },
+        ));
+    }
+    Ok(sections)
+}
+
+fn take_section<'a>(
+    sections: &mut Vec<(u32, Reader<'a>)>,
+    section_type: u32,
+) -> Result<Reader<'a>, CircuitError> {
+    let pos = sections
+        .iter()
+        .position(|(t, _)| *t == section_type)
+        .ok_or_else(|| ParameterError(format!("missing section {section_type} in circom file")))?;
+    Ok(sections.remove(pos).1)
+}
+
+impl<F: PrimeField> R1cs<F> {
+    /// Read a constraint system from the content of a circom `.r1cs` file.
+    ///
+    /// Return an error if the file is malformed, if it is over another field
+    /// than `F`, if it uses custom gates, or if a constraint refers to a
+    /// non-existing wire.
+    pub fn from_circom(bytes: &[u8]) -> Result<Self, CircuitError> {
+        let mut sections = read_sections(bytes, R1CS_MAGIC)?;
+        if sections.iter().any(|(t, _)| {
+            *t == R1CS_CUSTOM_GATES_LIST_SECTION || *t == R1CS_CUSTOM_GATES_USES_SECTION
+        }) {
+            return Err(NotSupported("circom custom gates".to_string()));
+        }
+
+        let mut header = take_section(&mut sections, R1CS_HEADER_SECTION)?;
+        let n8 = header.read_prime::<F>()?;
+        let num_wires = header.read_usize()?;
+        let num_public_outputs = header.read_usize()?;
+        let num_public_inputs = header.read_usize()?;
+        let _num_private_inputs = header.read_usize()?;
+        let _num_labels = header.read_u64()?;
+        let num_constraints = header.read_usize()?;
+
+        let mut reader = take_section(&mut sections, R1CS_CONSTRAINTS_SECTION)?;
+        let mut read_lc = || -> Result<LinearCombination<F>, CircuitError> {
+            let num_terms = reader.read_usize()?;
+            (0..num_terms)
+                .map(|_| Ok((reader.read_usize()?, reader.read_field(n8)?)))
+                .collect()
+        };
+        let constraints = (0..num_constraints)
+            .map(|_| {
+                Ok(R1csConstraint {
+                    a: read_lc()?,
+                    b: read_lc()?,
+                    c: read_lc()?,
+                })
+            })
+            .collect::<Result<Vec<_>, CircuitError>>()?;
+
+        let r1cs = Self {
+            num_wires,
+            num_public_inputs: num_public_outputs + num_public_inputs,
+            constraints,
+        };
+        r1cs.check_wires()?;
+        Ok(r1cs)
+    }
+}
+
+/// Read the values of all the wires from the content of a circom `.wtns`
+/// file.
+///
+/// Return an error if the file is malformed or if it is over another field
+/// than `F`.
+pub fn read_circom_witness<F: PrimeField>(bytes: &[u8]) -> Result<Vec<F>, CircuitError> {
+    let mut sections = read_sections(bytes, WTNS_MAGIC)?;
+    let mut header = take_section(&mut sections, WTNS_HEADER_SECTION)?;
+    let n8 = header.read_prime::<F>()?;
+    let num_wires = header.read_usize()?;
+    let mut reader = take_section(&mut sections, WTNS_DATA_SECTION)?;
+    (0..num_wires).map(|_| reader.read_field(n8)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{super::test::test_r1cs, read_circom_witness, R1CS_MAGIC, WTNS_MAGIC};
+    use crate::{errors::CircuitError, r1cs::R1cs, Circuit, PlonkCircuit};
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_std::vec::Vec;
+
+    fn write_field<F: PrimeField>(buf: &mut Vec<u8>, val: &F) {
+        buf.extend_from_slice(&val.into_bigint().to_bytes_le());
+    }
+
+    fn write_prime<F: PrimeField>(buf: &mut Vec<u8>) {
+        let prime = F::MODULUS.to_bytes_le();
+        buf.extend_from_slice(&(prime.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&prime);
+    }
+
+    fn write_file(magic: &[u8; 4], version: u32, sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut buf = magic.to_vec();
+        buf.extend_from_slice(&version.to_le_bytes());
+        buf.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+        for (t, content) in sections {
+            buf.extend_from_slice(&t.to_le_bytes());
+            buf.extend_from_slice(&(content.len() as u64).to_le_bytes());
+            buf.extend_from_slice(content);
+        }
+        buf
+    }
+
+    fn write_r1cs<F: PrimeField>(r1cs: &R1cs<F>) -> Vec<u8> {
+        let mut header = Vec::new();
+        write_prime::<F>(&mut header);
+        header.extend_from_slice(&(r1cs.num_wires as u32).to_le_bytes());
+        // all the public inputs are written as outputs
+        header.extend_from_slice(&(r1cs.num_public_inputs as u32).to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&(r1cs.num_wires as u64).to_le_bytes());
+        header.extend_from_slice(&(r1cs.constraints.len() as u32).to_le_bytes());
+
+        let mut constraints = Vec::new();
+        for constraint in r1cs.constraints.iter() {
+            for lc in [&constraint.a, &constraint.b, &constraint.c] {
+                constraints.extend_from_slice(&(lc.len() as u32).to_le_bytes());
+                for (wire, coeff) in lc.iter() {
+                    constraints.extend_from_slice(&(*wire as u32).to_le_bytes());
+                    write_field(&mut constraints, coeff);
+                }
+            }
+        }
+        // sections may come in any order
+        write_file(R1CS_MAGIC, 1, &[(2, constraints), (1, header)])
+    }
+
+    fn write_witness<F: PrimeField>(witness: &[F]) -> Vec<u8> {
+        let mut header = Vec::new();
+        write_prime::<F>(&mut header);
+        header.extend_from_slice(&(witness.len() as u32).to_le_bytes());
+        let mut data = Vec::new();
+        for val in witness {
+            write_field(&mut data, val);
+        }
+        write_file(WTNS_MAGIC, 2, &[(1, header), (2, data)])
+    }
+
+    #[test]
+    fn test_circom_import() -> Result<(), CircuitError> {
+        let (r1cs, witness) = test_r1cs::<Fr>();
+        let r1cs_bytes = write_r1cs(&r1cs);
+        let wtns_bytes = write_witness(&witness);
+        let parsed = R1cs::<Fr>::from_circom(&r1cs_bytes)?;
+        assert_eq!(parsed, r1cs);
+        let parsed_witness = read_circom_witness::<Fr>(&wtns_bytes)?;
+        assert_eq!(parsed_witness, witness);
+
+        let mut circuit = PlonkCircuit::<Fr>::new_turbo_plonk();
+        parsed.synthesize(&mut circuit, &parsed_witness)?;
+        circuit.check_circuit_satisfiability(&[witness[1]])?;
+
+        // malformed files
+        assert!(R1cs::<Fr>::from_circom(&wtns_bytes).is_err());
+        assert!(read_circom_witness::<Fr>(&r1cs_bytes).is_err());
+        assert!(R1cs::<Fr>::from_circom(&r1cs_bytes[..r1cs_bytes.len() - 1]).is_err());
+        assert!(read_circom_witness::<Fr>(&wtns_bytes[..wtns_bytes.len() - 1]).is_err());
+        // wrong field
+        assert!(R1cs::<ark_bn254::Fq>::from_circom(&r1cs_bytes).is_err());
+        assert!(read_circom_witness::<ark_bn254::Fq>(&wtns_bytes).is_err());
+        // non-canonical field element
+        let mut bad_wtns = wtns_bytes.clone();
+        let len = bad_wtns.len();
+        bad_wtns[len - 32..].copy_from_slice(&[0xff; 32]);
+        assert!(read_circom_witness::<Fr>(&bad_wtns).is_err());
+        // custom gates
+        let mut custom = write_r1cs(&r1cs);
+        custom[8] += 1;
+        custom.extend_from_slice(&4u32.to_le_bytes());
+        custom.extend_from_slice(&0u64.to_le_bytes());
+        assert!(R1cs::<Fr>::from_circom(&custom).is_err());
+        // wire out of bound
+        let mut bad_r1cs = r1cs.clone();
+        bad_r1cs.constraints[0].c[0].0 = r1cs.num_wires;
+        assert!(R1cs::<Fr>::from_circom(&write_r1cs(&bad_r1cs)).is_err());
+        Ok(())
+    }
+}
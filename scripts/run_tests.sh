@@ -7,4 +7,4 @@ export RUSTFLAGS="-C overflow-checks=on"
 cargo test --release -p jf-utils #-- -Zunstable-options --report-time
 cargo test --release -p jf-plonk --lib --bins #-- -Zunstable-options --report-time
 cargo test --release -p jf-primitives --features test-srs #-- -Zunstable-options --report-time # enable test-srs feature for gen_srs_for_testing
-cargo test --release -p jf-relation #-- -Zunstable-options --report-time
+cargo test --release -p jf-relation --all-features #-- -Zunstable-options --report-time